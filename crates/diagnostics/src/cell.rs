@@ -1,12 +1,113 @@
 use crate::display::{dump_kv, dump_kvi};
-use holochain_types::{app::CellNick, cell::CellId};
-use holochain_lmdb::{db, env::EnvironmentWrite, prelude::*};
-
-pub async fn dump_cell_state(
-    env: EnvironmentWrite,
-    _cell_id: CellId,
-    cell_nick: &CellNick,
-) -> anyhow::Result<()> {
+use fallible_iterator::FallibleIterator;
+use holo_hash::{DhtOpHash, EntryHash};
+use holochain_lmdb::{db, env::EnvironmentRead, fresh_reader, prelude::*};
+use holochain_state::{
+    dht_op_integration::{
+        AuthoredDhtOpsStore, AuthoredDhtOpsValue, IntegratedDhtOpsBuf, IntegratedDhtOpsValue,
+        IntegrationLimboStore, IntegrationLimboValue,
+    },
+    metadata::{LinkMetaKey, LinkMetaVal, MetadataBuf, MetadataBufT},
+    source_chain::{SourceChainBuf, SourceChainJsonDump},
+    validation_db::{ValidationLimboStore, ValidationLimboValue},
+};
+use holochain_types::cell::CellId;
+
+/// Where an op is in the workflows of a cell
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "status", content = "value", rename_all = "snake_case")]
+pub enum OpStatus {
+    /// The cell has not received the op, or has not kept it
+    Missing,
+    /// The op is waiting to be validated
+    ValidationLimbo(ValidationLimboValue),
+    /// The op has been validated and is waiting to be integrated
+    IntegrationLimbo(IntegrationLimboValue),
+    /// The op has been integrated, whether it was valid or not
+    Integrated(IntegratedDhtOpsValue),
+}
+
+/// Everything a cell knows about an op
+#[derive(Debug, serde::Serialize)]
+pub struct OpReport {
+    pub op_hash: DhtOpHash,
+    /// If the cell authored the op, how far it has been published
+    pub authored: Option<AuthoredDhtOpsValue>,
+    /// Where the op is as a DHT authority
+    pub status: OpStatus,
+}
+
+/// The ops which are waiting on validation or integration
+#[derive(Debug, serde::Serialize)]
+pub struct Limbo {
+    pub validation_limbo: Vec<(DhtOpHash, ValidationLimboValue)>,
+    pub integration_limbo: Vec<(DhtOpHash, IntegrationLimboValue)>,
+}
+
+/// The source chain of a cell, from its head back to genesis.
+/// The entries of private entry headers are left out.
+pub async fn chain(env: &EnvironmentRead) -> anyhow::Result<SourceChainJsonDump> {
+    let chain = SourceChainBuf::public_only(env.clone())?;
+    Ok(chain.dump_state().await?)
+}
+
+/// Find an op in the authored ops, limbos and integrated ops of a cell
+pub fn op_status(env: &EnvironmentRead, op_hash: &DhtOpHash) -> anyhow::Result<OpReport> {
+    use db::*;
+    let authored = AuthoredDhtOpsStore::new(env.clone(), env.get_db(&AUTHORED_DHT_OPS)?);
+    let validation_limbo = ValidationLimboStore::new(env.clone())?;
+    let integration_limbo =
+        IntegrationLimboStore::new(env.clone(), env.get_db(&INTEGRATION_LIMBO)?);
+    let integrated = IntegratedDhtOpsBuf::new(env.clone())?;
+
+    let status = if let Some(v) = integrated.get(op_hash)? {
+        OpStatus::Integrated(v)
+    } else if let Some(v) = integration_limbo.get(op_hash)? {
+        OpStatus::IntegrationLimbo(v)
+    } else if let Some(v) = validation_limbo.get(op_hash)? {
+        OpStatus::ValidationLimbo(v)
+    } else {
+        OpStatus::Missing
+    };
+    Ok(OpReport {
+        op_hash: op_hash.clone(),
+        authored: authored.get(op_hash)?,
+        status,
+    })
+}
+
+/// The live links on a base held by a cell as an authority
+pub fn links(env: &EnvironmentRead, base: &EntryHash) -> anyhow::Result<Vec<LinkMetaVal>> {
+    let meta_vault = MetadataBuf::vault(env.clone())?;
+    let links = fresh_reader!(env, |r| meta_vault
+        .get_links_all(&r, &LinkMetaKey::Base(base))?
+        .collect::<Vec<_>>())?;
+    Ok(links)
+}
+
+/// The contents of the validation and integration limbos of a cell
+pub fn limbo(env: &EnvironmentRead) -> anyhow::Result<Limbo> {
+    let validation_limbo = ValidationLimboStore::new(env.clone())?;
+    let integration_limbo =
+        IntegrationLimboStore::new(env.clone(), env.get_db(&db::INTEGRATION_LIMBO)?);
+    let limbo = fresh_reader!(env, |r| {
+        let validation_limbo = validation_limbo
+            .iter(&r)?
+            .map(|(k, v)| Ok((DhtOpHash::from_raw_39_panicky(k.to_vec()), v)))
+            .collect()?;
+        let integration_limbo = integration_limbo
+            .iter(&r)?
+            .map(|(k, v)| Ok((DhtOpHash::from_raw_39_panicky(k.to_vec()), v)))
+            .collect()?;
+        DatabaseResult::Ok(Limbo {
+            validation_limbo,
+            integration_limbo,
+        })
+    })?;
+    Ok(limbo)
+}
+
+pub async fn dump_cell_state(env: EnvironmentRead, cell_id: &CellId) -> anyhow::Result<()> {
     use db::*;
     let g = env.guard();
     let r = g.reader()?;
@@ -27,8 +128,8 @@ pub async fn dump_cell_state(
 
     println!();
     println!(
-        "+++++++++++++++++++++++++  cell \"{}\"  +++++++++++++++++++++++++",
-        cell_nick
+        "+++++++++++++++++++++++++  cell {}  +++++++++++++++++++++++++",
+        cell_id
     );
     println!();
 
@@ -50,9 +151,11 @@ pub async fn dump_cell_state(
     kv!("metadata cache - links", CACHE_LINKS_META);
     kv!("metadata cache - status", CACHE_STATUS_META);
 
+    kv!("validation limbo", VALIDATION_LIMBO);
     kv!("integration queue", INTEGRATION_LIMBO);
     kv!("integrated dht ops", INTEGRATED_DHT_OPS);
     kv!("authored dht ops", AUTHORED_DHT_OPS);
+    kv!("chain forks", CHAIN_FORKS);
 
     Ok(())
 }
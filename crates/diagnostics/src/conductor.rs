@@ -1,13 +1,50 @@
 use crate::display::human_size;
-use crate::holochain::conductor::{state::ConductorState, ConductorStateDb};
-use holochain_lmdb::{db::CONDUCTOR_STATE, env::EnvironmentWrite, prelude::*};
+use holochain::conductor::{state::ConductorState, ConductorStateDb};
+use holochain_lmdb::{db::CONDUCTOR_STATE, env::EnvironmentRead, prelude::*};
+use holochain_types::{app::InstalledAppId, cell::CellId};
 
-pub async fn dump_conductor_state(env: EnvironmentWrite) -> anyhow::Result<ConductorState> {
+/// A cell of an installed app
+#[derive(Debug, serde::Serialize)]
+pub struct CellInfo {
+    pub installed_app_id: InstalledAppId,
+    pub cell_id: CellId,
+    pub active: bool,
+}
+
+/// The persisted state of the conductor
+pub fn conductor_state(env: &EnvironmentRead) -> anyhow::Result<ConductorState> {
+    let g = env.guard();
+    let r = g.reader()?;
+    let db = ConductorStateDb::new(env.get_db(&CONDUCTOR_STATE)?);
+    db.get(&r, &().into())?
+        .ok_or_else(|| anyhow::anyhow!("The conductor has no persisted state"))
+}
+
+/// The cells of every installed app, whether active or not
+pub fn list_cells(state: &ConductorState) -> Vec<CellInfo> {
+    let active = state.active_apps.iter().flat_map(|(id, app)| {
+        app.all_cells().map(move |cell_id| CellInfo {
+            installed_app_id: id.clone(),
+            cell_id: cell_id.clone(),
+            active: true,
+        })
+    });
+    let inactive = state.inactive_apps.iter().flat_map(|(id, app)| {
+        app.all_cells().map(move |cell_id| CellInfo {
+            installed_app_id: id.clone(),
+            cell_id: cell_id.clone(),
+            active: false,
+        })
+    });
+    active.chain(inactive).collect()
+}
+
+pub async fn dump_conductor_state(env: EnvironmentRead) -> anyhow::Result<ConductorState> {
     let g = env.guard();
     let r = g.reader()?;
     let db = ConductorStateDb::new(env.get_db(&CONDUCTOR_STATE)?);
     let bytes = db.get_bytes(&r, &().into())?.unwrap();
-    let state = db.get(&r, &().into())?.unwrap();
+    let state = conductor_state(&env)?;
 
     println!("Size: {}", human_size(bytes.len()));
     println!("Data: {:#?}", state);
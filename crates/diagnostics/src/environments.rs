@@ -0,0 +1,103 @@
+//! Opening the environments of a stopped conductor, read-only
+
+use crate::conductor::conductor_state;
+use holochain_keystore::test_keystore::spawn_test_keystore;
+use holochain_keystore::KeystoreSender;
+use holochain_lmdb::env::{EnvironmentKind, EnvironmentRead};
+use holochain_types::cell::CellId;
+use std::path::{Path, PathBuf};
+
+/// The environments under a conductor's environment path
+pub struct Environments {
+    lmdb_path: PathBuf,
+    /// Throwaway keystore that we'll never use.
+    /// Private entries can't be decrypted without the agent's key, so the
+    /// queries never read them.
+    keystore: KeystoreSender,
+}
+
+impl Environments {
+    /// Open the environments under the conductor's environment path
+    pub async fn open(lmdb_path: &Path) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            lmdb_path.is_dir(),
+            "There are no environments at {}",
+            lmdb_path.display()
+        );
+        Ok(Self {
+            lmdb_path: lmdb_path.to_path_buf(),
+            keystore: spawn_test_keystore().await?,
+        })
+    }
+
+    /// The conductor environment
+    pub fn conductor(&self) -> anyhow::Result<EnvironmentRead> {
+        self.open_kind(EnvironmentKind::Conductor)
+    }
+
+    /// The wasm environment
+    pub fn wasm(&self) -> anyhow::Result<EnvironmentRead> {
+        self.open_kind(EnvironmentKind::Wasm)
+    }
+
+    /// The environment of a cell of one of the installed apps
+    pub fn cell(&self, cell_id: &CellId) -> anyhow::Result<EnvironmentRead> {
+        let state = conductor_state(&self.conductor()?)?;
+        anyhow::ensure!(
+            state
+                .active_apps
+                .values()
+                .flat_map(|app| app.all_cells())
+                .chain(state.inactive_apps.values().flat_map(|app| app.all_cells()))
+                .any(|id| id == cell_id),
+            "The cell {} is not in any installed app",
+            cell_id
+        );
+        self.open_kind(EnvironmentKind::Cell(cell_id.clone()))
+    }
+
+    fn open_kind(&self, kind: EnvironmentKind) -> anyhow::Result<EnvironmentRead> {
+        Ok(EnvironmentRead::open_read_only(
+            self.lmdb_path.as_ref(),
+            kind,
+            self.keystore.clone(),
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain::conductor::state::ConductorState;
+    use holochain::conductor::ConductorStateDb;
+    use holochain_lmdb::prelude::*;
+    use holochain_types::prelude::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn environments_are_opened_read_only() -> anyhow::Result<()> {
+        let tmpdir = tempdir::TempDir::new("diagnostics")?;
+        let keystore = spawn_test_keystore().await?;
+        let env = EnvironmentWrite::new(tmpdir.path(), EnvironmentKind::Conductor, keystore)?;
+        let state_db: ConductorStateDb = KvStore::new(env.get_db(&*CONDUCTOR_STATE)?);
+        env.guard().with_commit(|txn| {
+            state_db.put(txn, &UnitDbKey, &ConductorState::default())?;
+            DatabaseResult::Ok(())
+        })?;
+
+        let envs = Environments::open(tmpdir.path()).await?;
+        let state = conductor_state(&envs.conductor()?)?;
+        assert!(state.active_apps.is_empty());
+
+        // Nothing is created for a cell which isn't installed
+        let cell_id = CellId::new(
+            DnaHash::from_raw_36(vec![0xdb; 36]),
+            AgentPubKey::from_raw_36(vec![0xdc; 36]),
+        );
+        assert!(envs.cell(&cell_id).is_err());
+        assert!(envs
+            .open_kind(EnvironmentKind::Cell(cell_id.clone()))
+            .is_err());
+        assert!(!tmpdir.path().join(cell_id.to_string()).exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+//! Printing the results of queries as JSON
+
+use holochain_conductor_api::json::extern_io_to_json;
+use holochain_types::prelude::*;
+
+/// The result of a query as JSON, with holo hashes in their base64 form and
+/// every map keyed by a string.
+///
+/// Serializing most holochain types straight to JSON either fails, as maps
+/// like the superseded agent keys of the conductor state are keyed by hashes,
+/// or shows hashes as arrays of numbers. The value is transcoded through
+/// msgpack instead, as the HTTP app interface does.
+#[derive(Debug, serde::Serialize)]
+#[serde(transparent)]
+pub struct JsonView(serde_json::Value);
+
+impl JsonView {
+    /// The JSON view of any serializable value
+    pub fn new<T: serde::Serialize>(value: &T) -> anyhow::Result<Self> {
+        let bytes = rmp_serde::to_vec_named(value)?;
+        Ok(Self(extern_io_to_json(&ExternIO::from(bytes))?))
+    }
+
+    /// The JSON value
+    pub fn into_inner(self) -> serde_json::Value {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain::conductor::state::AppInterfaceConfig;
+    use holochain::conductor::state::AppInterfaceId;
+    use holochain::conductor::state::ConductorState;
+    use holochain::conductor::state::SupersededAgentKey;
+
+    #[test]
+    fn conductor_state_as_json() {
+        let old_key = AgentPubKey::from_raw_36(vec![0xdb; 36]);
+        let new_key = AgentPubKey::from_raw_36(vec![0xdc; 36]);
+        let mut state = ConductorState::default();
+        state.superseded_agent_keys.insert(
+            old_key.clone(),
+            SupersededAgentKey {
+                by: new_key.clone(),
+                at: timestamp::now(),
+            },
+        );
+        state.app_interfaces.insert(
+            AppInterfaceId::new(1234),
+            AppInterfaceConfig::websocket(1234),
+        );
+
+        // Maps keyed by hashes can't be serialized as JSON directly
+        assert!(serde_json::to_string(&state).is_err());
+
+        let json = JsonView::new(&state).unwrap().into_inner();
+        assert_eq!(
+            json["superseded_agent_keys"][old_key.to_string()]["by"],
+            serde_json::json!(new_key.to_string())
+        );
+        assert_eq!(json["app_interfaces"].as_object().unwrap().len(), 1);
+    }
+}
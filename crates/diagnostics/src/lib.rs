@@ -0,0 +1,20 @@
+//! Holochain Diagnostics
+//!
+//! A library of read-only queries over the LMDB environments of a conductor,
+//! for debugging a conductor which has been stopped, e.g. to find out why an
+//! op is stuck. Every query returns plain serializable data, which the
+//! `diagnostics` binary prints as a [JsonView].
+//!
+//! The environments are opened read-only, so the queries never change them,
+//! but the conductor should still be stopped first, as a running conductor
+//! may be part of the way through its workflows.
+
+pub mod cell;
+pub mod conductor;
+pub mod display;
+pub mod environments;
+pub mod json;
+pub mod wasm;
+
+pub use environments::Environments;
+pub use json::JsonView;
@@ -1,63 +1,103 @@
 //! Holochain Diagnostics
 //!
-//! This is a simple program that spits out some info about LMDB databases.
-//! It is written as a separate binary so that builds can be fast for quick
-//! feedback loops when debugging. In the future, this could be reorganized
-//! as a library of helper functions alongside a binary that calls into the lib,
-//! so that the binary can be freely modifiable while still accumulating a
-//! useful set of tools for querying LMDB state.
+//! This is a simple program that spits out some info about the LMDB databases
+//! of a stopped conductor. It is written as a separate binary so that builds
+//! can be fast for quick feedback loops when debugging. The queries live in
+//! the library alongside it, so that the binary can be freely modifiable while
+//! still accumulating a useful set of tools for querying LMDB state.
+//!
+//! Every subcommand except `summary` prints JSON.
 
-use cell::dump_cell_state;
-use conductor::dump_conductor_state;
-use holochain_keystore::test_keystore::spawn_test_keystore;
-use holochain_lmdb::env::{EnvironmentKind, EnvironmentWrite};
+use diagnostics::cell::{chain, dump_cell_state, limbo, links, op_status};
+use diagnostics::conductor::{conductor_state, dump_conductor_state, list_cells};
+use diagnostics::wasm::dump_wasm_state;
+use diagnostics::Environments;
+use diagnostics::JsonView;
+use holo_hash::{AgentPubKey, DhtOpHash, DnaHash, EntryHash};
+use holochain_types::cell::CellId;
+use std::convert::TryFrom;
 use std::path::PathBuf;
 use structopt::StructOpt;
-use wasm::dump_wasm_state;
-
-mod cell;
-mod conductor;
-mod display;
-mod wasm;
 
 #[derive(Debug, StructOpt)]
 struct Opt {
+    /// The environment path of the conductor
     lmdb_path: PathBuf,
+    #[structopt(subcommand)]
+    cmd: Option<Cmd>,
 }
 
-async fn run() -> anyhow::Result<()> {
-    let opt = Opt::from_args();
+#[derive(Debug, StructOpt)]
+enum Cmd {
+    /// Print the size of every database. This is the default.
+    Summary,
+    /// Print the persisted conductor state.
+    ConductorState,
+    /// List the cells of every installed app.
+    Cells,
+    /// Print the source chain of a cell, from its head back to genesis.
+    Chain(CellArgs),
+    /// Find where an op is in the workflows of a cell.
+    OpStatus {
+        #[structopt(flatten)]
+        cell: CellArgs,
+        #[structopt(parse(try_from_str = parse_hash))]
+        op_hash: DhtOpHash,
+    },
+    /// List the links on a base held by a cell.
+    Links {
+        #[structopt(flatten)]
+        cell: CellArgs,
+        #[structopt(parse(try_from_str = parse_hash))]
+        base: EntryHash,
+    },
+    /// List the ops waiting on validation or integration in a cell.
+    Limbo(CellArgs),
+}
+
+#[derive(Debug, StructOpt)]
+struct CellArgs {
+    #[structopt(long, parse(try_from_str = parse_hash))]
+    /// The dna hash half of the cell id.
+    dna: DnaHash,
+    #[structopt(long, parse(try_from_str = parse_hash))]
+    /// The agent half of the cell id.
+    agent: AgentPubKey,
+}
+
+impl From<CellArgs> for CellId {
+    fn from(args: CellArgs) -> Self {
+        CellId::new(args.dna, args.agent)
+    }
+}
 
-    // throwaway keystore that we'll never use.
-    let keystore = spawn_test_keystore().await.unwrap();
+fn parse_hash<H>(arg: &str) -> anyhow::Result<H>
+where
+    H: for<'a> TryFrom<&'a str>,
+    for<'a> <H as TryFrom<&'a str>>::Error: std::fmt::Debug,
+{
+    H::try_from(arg).map_err(|e| anyhow::anyhow!("{:?}", e))
+}
 
-    // set up the various environments
-    let wasm_env = EnvironmentWrite::new(
-        opt.lmdb_path.as_ref(),
-        EnvironmentKind::Wasm,
-        keystore.clone(),
-    )?;
+fn print_json<T: serde::Serialize>(value: &T) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(&JsonView::new(value)?)?);
+    Ok(())
+}
 
+async fn summary(envs: &Environments) -> anyhow::Result<()> {
     println!();
     println!("        +++++++++++++++++++++++++++++++++");
     println!("        ++++++++   WASM  STATE   ++++++++");
     println!("        +++++++++++++++++++++++++++++++++");
     println!();
-    dump_wasm_state(wasm_env).await?;
-
-    // set up the various environments
-    let conductor_env = EnvironmentWrite::new(
-        opt.lmdb_path.as_ref(),
-        EnvironmentKind::Conductor,
-        keystore.clone(),
-    )?;
+    dump_wasm_state(envs.wasm()?).await?;
 
     println!();
     println!("        +++++++++++++++++++++++++++++++++");
     println!("        +++++++  CONDUCTOR STATE  +++++++");
     println!("        +++++++++++++++++++++++++++++++++");
     println!();
-    let conductor_state = dump_conductor_state(conductor_env).await?;
+    let conductor_state = dump_conductor_state(envs.conductor()?).await?;
 
     println!();
     println!("        +++++++++++++++++++++++++++++++++");
@@ -65,21 +105,30 @@ async fn run() -> anyhow::Result<()> {
     println!("        +++++++++++++++++++++++++++++++++");
     println!();
 
-    for (_app_id, cells) in conductor_state.active_apps {
-        for cell in cells {
-            let (cell_id, cell_nick) = cell.into_inner();
-            let cell_env = EnvironmentWrite::new(
-                opt.lmdb_path.as_ref(),
-                EnvironmentKind::Cell(cell_id.clone()),
-                keystore.clone(),
-            )?;
-            dump_cell_state(cell_env, cell_id, &cell_nick).await?;
-        }
+    for cell in list_cells(&conductor_state) {
+        dump_cell_state(envs.cell(&cell.cell_id)?, &cell.cell_id).await?;
     }
 
     Ok(())
 }
 
+async fn run() -> anyhow::Result<()> {
+    let opt = Opt::from_args();
+    let envs = Environments::open(&opt.lmdb_path).await?;
+
+    match opt.cmd.unwrap_or(Cmd::Summary) {
+        Cmd::Summary => summary(&envs).await,
+        Cmd::ConductorState => print_json(&conductor_state(&envs.conductor()?)?),
+        Cmd::Cells => print_json(&list_cells(&conductor_state(&envs.conductor()?)?)),
+        Cmd::Chain(cell) => print_json(&chain(&envs.cell(&cell.into())?).await?),
+        Cmd::OpStatus { cell, op_hash } => {
+            print_json(&op_status(&envs.cell(&cell.into())?, &op_hash)?)
+        }
+        Cmd::Links { cell, base } => print_json(&links(&envs.cell(&cell.into())?, &base)?),
+        Cmd::Limbo(cell) => print_json(&limbo(&envs.cell(&cell.into())?)?),
+    }
+}
+
 #[tokio::main(flavor = "multi_thread")]
 async fn main() -> anyhow::Result<()> {
     run().await
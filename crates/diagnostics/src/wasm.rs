@@ -1,7 +1,7 @@
 use crate::display::dump_kv;
-use holochain_lmdb::{db, env::EnvironmentWrite, prelude::*};
+use holochain_lmdb::{db, env::EnvironmentRead, prelude::*};
 
-pub async fn dump_wasm_state(env: EnvironmentWrite) -> anyhow::Result<()> {
+pub async fn dump_wasm_state(env: EnvironmentRead) -> anyhow::Result<()> {
     use db::*;
     let g = env.guard();
     let r = g.reader()?;
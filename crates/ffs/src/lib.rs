@@ -65,5 +65,7 @@ impl_ffs! {
     fn create_dir_all(path) -> ();
     fn read(path) -> Vec<u8>;
     fn read_to_string(path) -> String;
+    fn remove_file(path) -> ();
+    fn rename(path, to: &std::path::Path) -> ();
     fn write(path, data: &[u8]) -> ();
 }
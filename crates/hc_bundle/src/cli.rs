@@ -1,7 +1,7 @@
 #![forbid(missing_docs)]
 //! Binary `hc-dna` command executable.
 
-use holochain_types::prelude::{AppManifest, DnaManifest};
+use holochain_types::prelude::{AppBundle, AppManifest, DnaBundle, DnaManifest};
 use mr_bundle::Manifest;
 use std::path::Path;
 use std::path::PathBuf;
@@ -46,6 +46,12 @@ pub enum HcDnaBundle {
         /// provided working directory.
         #[structopt(short = "o", long)]
         output: Option<PathBuf>,
+
+        /// Before packing, fetch any resources referenced by URL which are not
+        /// yet pinned, and record their hashes in the lockfile next to the
+        /// manifest. Pinned resources are verified whenever they are fetched.
+        #[structopt(long)]
+        lock: bool,
     },
 
     /// Unpack parts of the `.dna` bundle file into a specific directory.
@@ -72,6 +78,41 @@ pub enum HcDnaBundle {
         #[structopt(short = "f", long)]
         force: bool,
     },
+
+    /// Print a summary of a `.dna` bundle as YAML: the manifest, the size of
+    /// each bundled resource, the DNA hash, and the wasm hashes and exports
+    /// of each zome, as well as its entry defs if built with the `entry_defs`
+    /// feature.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc dna inspect ./some/dir/my-dna.dna
+    Inspect {
+        /// The path to the bundle to inspect
+        path: std::path::PathBuf,
+
+        /// Only print the DNA hash
+        #[structopt(long)]
+        hash: bool,
+    },
+
+    /// Check that a `.dna` bundle is well formed and that every zome compiles
+    /// and exports the functions holochain needs.
+    ///
+    /// Exits with an error if any problems are found.
+    Validate {
+        /// The path to the bundle to validate
+        path: std::path::PathBuf,
+    },
+
+    /// Show the differences between two `.dna` bundles.
+    Diff {
+        /// The path to the original bundle
+        original: std::path::PathBuf,
+
+        /// The path to the bundle to compare it with
+        other: std::path::PathBuf,
+    },
 }
 
 /// Work with Holochain hApp bundles
@@ -104,6 +145,12 @@ pub enum HcAppBundle {
         /// provided working directory.
         #[structopt(short = "o", long)]
         output: Option<PathBuf>,
+
+        /// Before packing, fetch any resources referenced by URL which are not
+        /// yet pinned, and record their hashes in the lockfile next to the
+        /// manifest. Pinned resources are verified whenever they are fetched.
+        #[structopt(long)]
+        lock: bool,
     },
 
     /// Unpack parts of the `.happ` bundle file into a specific directory.
@@ -130,6 +177,40 @@ pub enum HcAppBundle {
         #[structopt(short = "f", long)]
         force: bool,
     },
+
+    /// Print a summary of a `.happ` bundle as YAML: the manifest, the size of
+    /// each bundled resource, the DNA hash of each slot, and the wasm hashes and exports
+    /// of each zome, as well as its entry defs if built with the `entry_defs` feature.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc app inspect ./some/dir/my-app.happ
+    Inspect {
+        /// The path to the bundle to inspect
+        path: std::path::PathBuf,
+
+        /// Only print the DNA hashes
+        #[structopt(long)]
+        hash: bool,
+    },
+
+    /// Check that a `.happ` bundle is well formed and that every zome compiles
+    /// and exports the functions holochain needs.
+    ///
+    /// Exits with an error if any problems are found.
+    Validate {
+        /// The path to the bundle to validate
+        path: std::path::PathBuf,
+    },
+
+    /// Show the differences between two `.happ` bundles.
+    Diff {
+        /// The path to the original bundle
+        original: std::path::PathBuf,
+
+        /// The path to the bundle to compare it with
+        other: std::path::PathBuf,
+    },
 }
 
 impl HcDnaBundle {
@@ -139,7 +220,11 @@ impl HcDnaBundle {
             Self::Init { path } => {
                 crate::init::init_dna(path).await?;
             }
-            Self::Pack { path, output } => {
+            Self::Pack { path, output, lock } => {
+                if lock {
+                    let lock_path = crate::packing::lock::<DnaManifest>(&path).await?;
+                    println!("Wrote lockfile {}", lock_path.to_string_lossy());
+                }
                 let name = get_dna_name(&path).await?;
                 let (bundle_path, _) =
                     crate::packing::pack::<DnaManifest>(&path, output, name).await?;
@@ -155,6 +240,26 @@ impl HcDnaBundle {
                         .await?;
                 println!("Unpacked to directory {}", dir_path.to_string_lossy());
             }
+            Self::Inspect { path, hash } => {
+                let bundle = DnaBundle::decode(&ffs::read(&path).await?)?;
+                let inspection = crate::inspect::inspect_dna(bundle, None, None).await?;
+                if hash {
+                    println!("{}", inspection.dna_hash);
+                } else {
+                    print!("{}", serde_yaml::to_string(&inspection)?);
+                }
+            }
+            Self::Validate { path } => {
+                let bundle = DnaBundle::decode(&ffs::read(&path).await?)?;
+                report_problems(&path, crate::inspect::validate_dna(bundle).await)?;
+            }
+            Self::Diff { original, other } => {
+                let a = DnaBundle::decode(&ffs::read(&original).await?)?;
+                let b = DnaBundle::decode(&ffs::read(&other).await?)?;
+                let a = crate::inspect::inspect_dna(a, None, None).await?;
+                let b = crate::inspect::inspect_dna(b, None, None).await?;
+                report_diffs(crate::inspect::diff_dna(&a, &b));
+            }
         }
         Ok(())
     }
@@ -167,7 +272,11 @@ impl HcAppBundle {
             Self::Init { path } => {
                 crate::init::init_app(path).await?;
             }
-            Self::Pack { path, output } => {
+            Self::Pack { path, output, lock } => {
+                if lock {
+                    let lock_path = crate::packing::lock::<AppManifest>(&path).await?;
+                    println!("Wrote lockfile {}", lock_path.to_string_lossy());
+                }
                 let name = get_app_name(&path).await?;
                 let (bundle_path, _) =
                     crate::packing::pack::<AppManifest>(&path, output, name).await?;
@@ -183,11 +292,57 @@ impl HcAppBundle {
                         .await?;
                 println!("Unpacked to directory {}", dir_path.to_string_lossy());
             }
+            Self::Inspect { path, hash } => {
+                let bundle = AppBundle::decode(&ffs::read(&path).await?)?;
+                let inspection = crate::inspect::inspect_app(bundle).await?;
+                if hash {
+                    for (slot_id, dna) in inspection.dnas.iter() {
+                        println!("{}: {}", slot_id, dna.dna_hash);
+                    }
+                } else {
+                    print!("{}", serde_yaml::to_string(&inspection)?);
+                }
+            }
+            Self::Validate { path } => {
+                let bundle = AppBundle::decode(&ffs::read(&path).await?)?;
+                report_problems(&path, crate::inspect::validate_app(bundle).await)?;
+            }
+            Self::Diff { original, other } => {
+                let a = AppBundle::decode(&ffs::read(&original).await?)?;
+                let b = AppBundle::decode(&ffs::read(&other).await?)?;
+                let a = crate::inspect::inspect_app(a).await?;
+                let b = crate::inspect::inspect_app(b).await?;
+                report_diffs(crate::inspect::diff_app(&a, &b));
+            }
         }
         Ok(())
     }
 }
 
+fn report_problems(path: &Path, problems: Vec<String>) -> anyhow::Result<()> {
+    if problems.is_empty() {
+        println!("{} is valid", path.to_string_lossy());
+        return Ok(());
+    }
+    for problem in problems.iter() {
+        println!("- {}", problem);
+    }
+    anyhow::bail!(
+        "Found {} problem(s) in {}",
+        problems.len(),
+        path.to_string_lossy()
+    )
+}
+
+fn report_diffs(diffs: Vec<String>) {
+    if diffs.is_empty() {
+        println!("The bundles are the same");
+    }
+    for diff in diffs {
+        println!("- {}", diff);
+    }
+}
+
 async fn get_dna_name(manifest_path: &Path) -> HcBundleResult<String> {
     let manifest_path = manifest_path.to_path_buf();
     let manifest_path = manifest_path.join(&DnaManifest::path());
@@ -32,6 +32,10 @@ pub enum HcBundleError {
     #[error("Unknown error: {0}")]
     MiscError(#[from] Box<dyn std::error::Error + Send + Sync>),
 
+    /// A DNA has an inline zome, which has no wasm to bundle
+    #[error("Zome {0} is an inline zome, which can't be bundled")]
+    InlineZome(holochain_types::prelude::ZomeName),
+
     #[error("This file should have a '.{0}' extension: {1}")]
     FileExtensionMissing(&'static str, PathBuf),
 }
@@ -0,0 +1,422 @@
+#![forbid(missing_docs)]
+
+//! Inspect, validate and compare DNA and hApp bundles
+
+use crate::error::HcBundleError;
+use crate::error::HcBundleResult;
+use holochain_types::prelude::*;
+use mr_bundle::ResourceBytes;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+
+/// The exports every zome needs so the host can pass data in and out of it
+pub const REQUIRED_WASM_EXPORTS: &[&str] = &["__allocate", "__deallocate"];
+
+/// Everything we can learn about a DNA bundle without installing it
+#[derive(Debug, serde::Serialize)]
+pub struct DnaInspection {
+    /// The hash this DNA will have when installed without overrides
+    pub dna_hash: DnaHashB64,
+    /// The manifest, as found in the bundle
+    pub manifest: DnaManifest,
+    /// The size in bytes of each bundled resource
+    pub resources: BTreeMap<PathBuf, usize>,
+    /// Details of each zome, in manifest order
+    pub zomes: Vec<ZomeInspection>,
+    /// Problems found while inspecting the wasms
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// The details of a single zome in a DNA
+#[derive(Debug, serde::Serialize)]
+pub struct ZomeInspection {
+    /// The name of the zome
+    pub name: ZomeName,
+    /// The hash of the zome's wasm
+    pub wasm_hash: WasmHashB64,
+    /// The size of the wasm in bytes
+    pub wasm_size: usize,
+    /// The functions exported by the wasm, or `None` if it failed to compile
+    pub exports: Option<Vec<String>>,
+    /// The entry defs returned by the `entry_defs` callback, or `None` if the
+    /// callback could not be run. Running it needs the whole ribosome, so
+    /// this is always `None` unless the `entry_defs` feature is enabled.
+    pub entry_defs: Option<Vec<EntryDef>>,
+}
+
+/// Everything we can learn about a hApp bundle without installing it
+#[derive(Debug, serde::Serialize)]
+pub struct AppInspection {
+    /// The manifest, as found in the bundle
+    pub manifest: AppManifest,
+    /// The size in bytes of each bundled resource
+    pub resources: BTreeMap<PathBuf, usize>,
+    /// The DNA used by each slot which has a location,
+    /// with the slot's uid and properties applied
+    pub dnas: BTreeMap<SlotId, DnaInspection>,
+}
+
+/// Inspect a DNA bundle, applying these overrides when computing the hash.
+pub async fn inspect_dna(
+    bundle: DnaBundle,
+    uid: Option<Uid>,
+    properties: Option<YamlProperties>,
+) -> HcBundleResult<DnaInspection> {
+    let manifest = bundle.manifest().clone();
+    let resources = resource_sizes(bundle.bundled_resources());
+    let (dna_file, _) = bundle.into_dna_file(uid, properties).await?;
+    let dna_hash = dna_file.dna_hash().clone().into();
+    let mut errors = Vec::new();
+
+    let mut zomes = Vec::new();
+    for (name, zome_def) in dna_file.dna_def().zomes.iter() {
+        let wasm_hash = match zome_def {
+            ZomeDef::Wasm(WasmZome { wasm_hash, .. }) => wasm_hash.clone(),
+            ZomeDef::Inline(_) => return Err(HcBundleError::InlineZome(name.clone())),
+        };
+        let wasm = dna_file.get_wasm_for_zome(name)?.code();
+        let wasm_size = wasm.len();
+        let module =
+            holochain_wasmer_host::instantiate::module(wasm_hash.get_raw_39(), &wasm, None);
+        let exports = match module {
+            Ok(module) => {
+                let mut exports: Vec<String> = module.info().exports.keys().cloned().collect();
+                exports.sort();
+                Some(exports)
+            }
+            Err(e) => {
+                errors.push(format!("Zome {} failed to compile: {}", name, e));
+                None
+            }
+        };
+        zomes.push(ZomeInspection {
+            name: name.clone(),
+            wasm_hash: wasm_hash.into(),
+            wasm_size,
+            exports,
+            entry_defs: None,
+        });
+    }
+
+    // Entry defs can only be collected once every zome compiles.
+    #[cfg(feature = "entry_defs")]
+    if errors.is_empty() {
+        inspect_entry_defs(dna_file, &mut zomes, &mut errors);
+    }
+
+    Ok(DnaInspection {
+        dna_hash,
+        manifest,
+        resources,
+        zomes,
+        errors,
+    })
+}
+
+/// Run the `entry_defs` callback of every zome and record the results
+#[cfg(feature = "entry_defs")]
+fn inspect_entry_defs(dna_file: DnaFile, zomes: &mut [ZomeInspection], errors: &mut Vec<String>) {
+    use holochain::core::ribosome::guest_callback::entry_defs::{
+        EntryDefsHostAccess, EntryDefsInvocation, EntryDefsResult,
+    };
+    use holochain::core::ribosome::real_ribosome::RealRibosome;
+    use holochain::core::ribosome::RibosomeT;
+
+    let ribosome = RealRibosome::new(dna_file);
+    match ribosome.run_entry_defs(EntryDefsHostAccess, EntryDefsInvocation::new()) {
+        Ok(EntryDefsResult::Defs(mut defs)) => {
+            for zome in zomes.iter_mut() {
+                zome.entry_defs = Some(
+                    defs.remove(&zome.name)
+                        .map(|defs| defs.into_iter().collect())
+                        .unwrap_or_default(),
+                );
+            }
+        }
+        Ok(EntryDefsResult::Err(zome_name, msg)) => {
+            errors.push(format!(
+                "The entry_defs callback failed in zome {}: {}",
+                zome_name, msg
+            ));
+        }
+        Err(e) => errors.push(format!("Failed to run the entry_defs callback: {}", e)),
+    }
+}
+
+/// Inspect a hApp bundle and every DNA it refers to.
+pub async fn inspect_app(bundle: AppBundle) -> HcBundleResult<AppInspection> {
+    let manifest = bundle.manifest().clone();
+    let resources = resource_sizes(bundle.bundled_resources());
+    let mut dnas = BTreeMap::new();
+    match &manifest {
+        AppManifest::V1(m) => {
+            for slot in m.slots.iter() {
+                if let Some(location) = &slot.dna.location {
+                    let bytes = bundle.resolve(location).await?;
+                    let dna_bundle = DnaBundle::decode(&bytes)?;
+                    let dna = inspect_dna(
+                        dna_bundle,
+                        slot.dna.uid.clone(),
+                        slot.dna.properties.clone(),
+                    )
+                    .await?;
+                    dnas.insert(slot.id.clone(), dna);
+                }
+            }
+        }
+    }
+    Ok(AppInspection {
+        manifest,
+        resources,
+        dnas,
+    })
+}
+
+/// Check a DNA bundle for problems, returning a description of each one found.
+pub async fn validate_dna(bundle: DnaBundle) -> Vec<String> {
+    let mut problems = Vec::new();
+    if bundle.manifest().name().trim().is_empty() {
+        problems.push("The DNA name is empty".to_string());
+    }
+    match inspect_dna(bundle, None, None).await {
+        Ok(inspection) => {
+            problems.extend(inspection.errors);
+            let mut names = HashSet::new();
+            for zome in inspection.zomes.iter() {
+                if !names.insert(zome.name.clone()) {
+                    problems.push(format!("Zome {} is defined more than once", zome.name));
+                }
+                if let Some(exports) = &zome.exports {
+                    for required in REQUIRED_WASM_EXPORTS {
+                        if !exports.iter().any(|e| e == required) {
+                            problems.push(format!(
+                                "Zome {} does not export {}. Was it built with the hdk?",
+                                zome.name, required
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        Err(e) => problems.push(e.to_string()),
+    }
+    problems
+}
+
+/// Check a hApp bundle and every DNA it refers to for problems,
+/// returning a description of each one found.
+pub async fn validate_app(bundle: AppBundle) -> Vec<String> {
+    let mut problems = Vec::new();
+    let manifest = bundle.manifest().clone();
+    if let Err(e) = manifest.clone().validate() {
+        problems.push(e.to_string());
+    }
+    match &manifest {
+        AppManifest::V1(m) => {
+            let mut ids = HashSet::new();
+            for slot in m.slots.iter() {
+                if !ids.insert(slot.id.clone()) {
+                    problems.push(format!("Slot {} is defined more than once", slot.id));
+                }
+                if let Some(location) = &slot.dna.location {
+                    let dna_bundle = match bundle.resolve(location).await {
+                        Ok(bytes) => DnaBundle::decode(&bytes),
+                        Err(e) => {
+                            problems.push(format!("Slot {}: {}", slot.id, e));
+                            continue;
+                        }
+                    };
+                    match dna_bundle {
+                        Ok(dna_bundle) => problems.extend(
+                            validate_dna(dna_bundle)
+                                .await
+                                .into_iter()
+                                .map(|p| format!("Slot {}: {}", slot.id, p)),
+                        ),
+                        Err(e) => problems.push(format!("Slot {}: {}", slot.id, e)),
+                    }
+                }
+            }
+        }
+    }
+    problems
+}
+
+/// Describe the differences between two inspected DNAs.
+/// An empty list means they are the same.
+pub fn diff_dna(a: &DnaInspection, b: &DnaInspection) -> Vec<String> {
+    let mut diffs = Vec::new();
+    if a.dna_hash != b.dna_hash {
+        diffs.push(format!("DNA hash: {} -> {}", a.dna_hash, b.dna_hash));
+    }
+    if a.manifest.name() != b.manifest.name() {
+        diffs.push(format!(
+            "name: {} -> {}",
+            a.manifest.name(),
+            b.manifest.name()
+        ));
+    }
+    if a.manifest.uid() != b.manifest.uid() {
+        diffs.push(format!(
+            "uid: {:?} -> {:?}",
+            a.manifest.uid(),
+            b.manifest.uid()
+        ));
+    }
+    if a.manifest.properties() != b.manifest.properties() {
+        diffs.push(format!(
+            "properties: {:?} -> {:?}",
+            a.manifest.properties(),
+            b.manifest.properties()
+        ));
+    }
+    let a_names: Vec<_> = a.zomes.iter().map(|z| &z.name).collect();
+    let b_names: Vec<_> = b.zomes.iter().map(|z| &z.name).collect();
+    for zome in a.zomes.iter() {
+        match b.zomes.iter().find(|z| z.name == zome.name) {
+            Some(other) => diffs.extend(
+                diff_zome(zome, other)
+                    .into_iter()
+                    .map(|d| format!("zome {}: {}", zome.name, d)),
+            ),
+            None => diffs.push(format!("zome {}: removed", zome.name)),
+        }
+    }
+    for zome in b.zomes.iter() {
+        if !a_names.contains(&&zome.name) {
+            diffs.push(format!("zome {}: added", zome.name));
+        }
+    }
+    let a_common: Vec<_> = a_names.iter().filter(|n| b_names.contains(n)).collect();
+    let b_common: Vec<_> = b_names.iter().filter(|n| a_names.contains(n)).collect();
+    if a_common != b_common {
+        diffs.push("zome order changed".to_string());
+    }
+    diffs
+}
+
+fn diff_zome(a: &ZomeInspection, b: &ZomeInspection) -> Vec<String> {
+    let mut diffs = Vec::new();
+    if a.wasm_hash != b.wasm_hash {
+        diffs.push(format!(
+            "wasm hash: {} -> {} ({} -> {} bytes)",
+            a.wasm_hash, b.wasm_hash, a.wasm_size, b.wasm_size
+        ));
+    }
+    if let (Some(a_exports), Some(b_exports)) = (&a.exports, &b.exports) {
+        for e in a_exports.iter().filter(|e| !b_exports.contains(e)) {
+            diffs.push(format!("export {} removed", e));
+        }
+        for e in b_exports.iter().filter(|e| !a_exports.contains(e)) {
+            diffs.push(format!("export {} added", e));
+        }
+    }
+    if let (Some(a_defs), Some(b_defs)) = (&a.entry_defs, &b.entry_defs) {
+        if a_defs != b_defs {
+            diffs.push(format!("entry defs: {:?} -> {:?}", a_defs, b_defs));
+        }
+    }
+    diffs
+}
+
+/// Describe the differences between two inspected hApps.
+/// An empty list means they are the same.
+pub fn diff_app(a: &AppInspection, b: &AppInspection) -> Vec<String> {
+    let mut diffs = Vec::new();
+    if a.manifest.app_name() != b.manifest.app_name() {
+        diffs.push(format!(
+            "name: {} -> {}",
+            a.manifest.app_name(),
+            b.manifest.app_name()
+        ));
+    }
+    for (slot_id, dna) in a.dnas.iter() {
+        match b.dnas.get(slot_id) {
+            Some(other) => diffs.extend(
+                diff_dna(dna, other)
+                    .into_iter()
+                    .map(|d| format!("slot {}: {}", slot_id, d)),
+            ),
+            None => diffs.push(format!("slot {}: removed", slot_id)),
+        }
+    }
+    for slot_id in b.dnas.keys().filter(|id| !a.dnas.contains_key(*id)) {
+        diffs.push(format!("slot {}: added", slot_id));
+    }
+    if a.manifest != b.manifest && diffs.is_empty() {
+        diffs.push("manifests differ".to_string());
+    }
+    diffs
+}
+
+fn resource_sizes(resources: &HashMap<PathBuf, ResourceBytes>) -> BTreeMap<PathBuf, usize> {
+    resources
+        .iter()
+        .map(|(path, bytes)| (path.clone(), bytes.len()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn fake_dna(uid: &str, wasm: Vec<u8>) -> DnaBundle {
+        let manifest: DnaManifest = serde_yaml::from_str(&format!(
+            r#"
+---
+manifest_version: "1"
+name: test_dna
+uid: {}
+zomes:
+  - name: zome1
+    bundled: zome1.wasm
+"#,
+            uid
+        ))
+        .unwrap();
+        DnaBundle::new(manifest, vec![("zome1.wasm".into(), wasm)], ".".into()).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn inspect_reports_hash_and_compile_errors() {
+        let bundle = fake_dna("a", vec![1, 2, 3]).await;
+        let (dna_file, _) = fake_dna("a", vec![1, 2, 3])
+            .await
+            .into_dna_file(None, None)
+            .await
+            .unwrap();
+        let inspection = inspect_dna(bundle, None, None).await.unwrap();
+        assert_eq!(inspection.dna_hash, dna_file.dna_hash().clone().into());
+        assert_eq!(
+            inspection.resources.get(&PathBuf::from("zome1.wasm")),
+            Some(&3)
+        );
+        assert_eq!(inspection.zomes[0].wasm_size, 3);
+        assert_eq!(inspection.zomes[0].exports, None);
+        assert_eq!(inspection.errors.len(), 1);
+
+        assert!(!validate_dna(fake_dna("a", vec![1, 2, 3]).await)
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn diff_shows_changes() {
+        let a = inspect_dna(fake_dna("a", vec![1, 2, 3]).await, None, None)
+            .await
+            .unwrap();
+        let b = inspect_dna(fake_dna("a", vec![1, 2, 3]).await, None, None)
+            .await
+            .unwrap();
+        assert!(diff_dna(&a, &b).is_empty());
+
+        let c = inspect_dna(fake_dna("b", vec![4, 5, 6]).await, None, None)
+            .await
+            .unwrap();
+        let diffs = diff_dna(&a, &c);
+        assert!(diffs.iter().any(|d| d.starts_with("DNA hash")));
+        assert!(diffs.iter().any(|d| d.starts_with("uid")));
+        assert!(diffs.iter().any(|d| d.starts_with("zome zome1: wasm hash")));
+    }
+}
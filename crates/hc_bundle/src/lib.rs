@@ -1,6 +1,7 @@
 mod cli;
 mod error;
 mod init;
+pub mod inspect;
 mod packing;
 
 pub use cli::{HcAppBundle, HcDnaBundle};
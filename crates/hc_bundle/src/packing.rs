@@ -68,6 +68,19 @@ pub async fn pack<M: Manifest>(
     Ok((target_path, bundle))
 }
 
+/// Fetch every remote resource referenced by the manifest in a working
+/// directory, and pin its hash in the lockfile next to the manifest.
+/// Resources which are already pinned are not fetched again.
+/// Returns the path to the lockfile.
+pub async fn lock<M: Manifest>(dir_path: &std::path::Path) -> HcBundleResult<PathBuf> {
+    let dir_path = ffs::canonicalize(dir_path).await?;
+    let mut bundle: Bundle<M> = Bundle::pack_yaml(&dir_path.join(&M::path())).await?;
+    bundle.lock_remote_resources().await?;
+    let lock_path = dir_path.join(Bundle::<M>::lockfile_path());
+    ffs::write(&lock_path, serde_yaml::to_string(bundle.lock())?.as_bytes()).await?;
+    Ok(lock_path)
+}
+
 fn dir_to_bundle_path(dir_path: &Path, name: String, extension: &str) -> HcBundleResult<PathBuf> {
     Ok(dir_path.join(format!("{}.{}", name, extension)))
 }
@@ -38,4 +38,27 @@ async fn roundtrip() {
     let _original_happ = read_app(&app_path).unwrap();
     let _original_dna1 = read_dna(&dna1_path).unwrap();
     let _original_dna2 = read_dna(&dna2_path).unwrap();
+
+    {
+        let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+        let cmd = cmd.args(&["inspect", "--hash", dna1_path.to_str().unwrap()]);
+        cmd.assert().success();
+    }
+    {
+        // The fixture wasms are not real wasm so they fail validation
+        let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+        let cmd = cmd.args(&["validate", dna1_path.to_str().unwrap()]);
+        cmd.assert().failure();
+    }
+    {
+        let mut cmd = Command::cargo_bin("hc-app").unwrap();
+        let cmd = cmd.args(&[
+            "diff",
+            app_path.to_str().unwrap(),
+            app_path.to_str().unwrap(),
+        ]);
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains("The bundles are the same"));
+    }
 }
@@ -13,18 +13,23 @@ use anyhow::bail;
 use anyhow::ensure;
 use holochain_conductor_api::AdminRequest;
 use holochain_conductor_api::AdminResponse;
+use holochain_conductor_api::AgentKeyInfo;
+use holochain_conductor_api::AppAuthenticationToken;
 use holochain_conductor_api::InterfaceDriver;
 use holochain_conductor_api::{AdminInterfaceConfig, InstalledAppInfo};
+use holochain_keystore::backup::KeystoreBackup;
 use holochain_p2p::kitsune_p2p;
 use holochain_p2p::kitsune_p2p::agent_store::AgentInfoSigned;
+use holochain_state::source_chain::SignedSourceChainArchive;
 use holochain_types::prelude::DnaHash;
 use holochain_types::prelude::InstallAppDnaPayload;
 use holochain_types::prelude::InstallAppPayload;
 use holochain_types::prelude::RegisterDnaPayload;
 use holochain_types::prelude::YamlProperties;
 use holochain_types::prelude::{AgentPubKey, AppBundleSource};
-use holochain_types::prelude::{CellId, InstallAppBundlePayload};
-use holochain_types::prelude::{DnaSource, Uid};
+use holochain_types::prelude::{CellHealth, CellId, InstallAppBundlePayload};
+use holochain_types::prelude::{DnaSource, Timestamp, Uid};
+use holochain_types::prelude::{ZomeExports, ZomeName};
 use std::convert::TryFrom;
 
 use crate::cmds::Existing;
@@ -61,8 +66,10 @@ pub enum AdminRequestCli {
     InstallAppBundle(InstallAppBundle),
     /// Calls AdminRequest::ListAppInterfaces.
     ListAppWs,
+    IssueAppToken(IssueAppToken),
     /// Calls AdminRequest::ListDnas.
     ListDnas,
+    ListZomeFns(ListZomeFns),
     /// Calls AdminRequest::GenerateAgentPubKey.
     NewAgent,
     /// Calls AdminRequest::ListCellIds.
@@ -72,10 +79,18 @@ pub enum AdminRequestCli {
     ActivateApp(ActivateApp),
     DeactivateApp(DeactivateApp),
     DumpState(DumpState),
+    CheckHealth(CheckHealth),
     /// Calls AdminRequest::AddAgentInfo.
     /// [Unimplemented].
     AddAgents,
     ListAgents(ListAgents),
+    /// Calls AdminRequest::ListAgentKeys.
+    ListAgentKeys,
+    RotateAgentKey(RotateAgentKey),
+    ExportSourceChain(ExportSourceChain),
+    ImportSourceChain(ImportSourceChain),
+    ExportKeystore(ExportKeystore),
+    ImportKeystore(ImportKeystore),
 }
 #[derive(Debug, StructOpt, Clone)]
 /// Calls AdminRequest::AddAdminInterfaces
@@ -95,6 +110,19 @@ pub struct AddAppWs {
     pub port: Option<u16>,
 }
 
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::IssueAppAuthenticationToken
+/// and prints a token which app interface connections
+/// must authenticate with to access the app.
+pub struct IssueAppToken {
+    /// The app the token gives access to.
+    pub app_id: String,
+    #[structopt(short, long)]
+    /// How many seconds until the token expires.
+    /// Defaults to one day.
+    pub expiry_seconds: Option<u64>,
+}
+
 #[derive(Debug, StructOpt, Clone)]
 /// Calls AdminRequest::RegisterDna
 /// and registers a Dna. You can only use a path or a hash not both.
@@ -190,6 +218,29 @@ pub struct DumpState {
     pub agent_key: AgentPubKey,
 }
 
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::ListZomeFunctions
+/// and lists the zome functions and callbacks
+/// exported by each zome of a dna.
+pub struct ListZomeFns {
+    #[structopt(parse(try_from_str = parse_dna_hash))]
+    /// The hash of the dna to inspect.
+    pub dna: DnaHash,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::CheckCellHealth
+/// and lists the cell's authored ops which are held
+/// by fewer authorities than the DNA's redundancy target.
+pub struct CheckHealth {
+    #[structopt(parse(try_from_str = parse_dna_hash))]
+    /// The dna hash half of the cell id to check.
+    pub dna: DnaHash,
+    #[structopt(parse(try_from_str = parse_agent_key))]
+    /// The agent half of the cell id to check.
+    pub agent_key: AgentPubKey,
+}
+
 #[derive(Debug, StructOpt, Clone)]
 /// Calls AdminRequest::RequestAgentInfo
 /// and pretty prints the agent info on
@@ -203,6 +254,64 @@ pub struct ListAgents {
     pub dna: Option<DnaHash>,
 }
 
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::RotateAgentKey
+/// and replaces an agent key with a new one.
+pub struct RotateAgentKey {
+    #[structopt(parse(try_from_str = parse_agent_key))]
+    /// The agent key to rotate.
+    pub agent_key: AgentPubKey,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::ExportSourceChain
+/// and writes the signed source chain of a cell,
+/// private entries included, to a file.
+pub struct ExportSourceChain {
+    #[structopt(parse(try_from_str = parse_dna_hash))]
+    /// The dna hash half of the cell id to export.
+    pub dna: DnaHash,
+    #[structopt(parse(try_from_str = parse_agent_key))]
+    /// The agent half of the cell id to export.
+    pub agent_key: AgentPubKey,
+    /// The file to write the archive to.
+    pub output: PathBuf,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::ImportSourceChain
+/// and imports a source chain written by `export-source-chain`.
+/// The agent key must be in the keystore, and the app must be
+/// installed with that agent key afterwards.
+pub struct ImportSourceChain {
+    /// The file to read the archive from.
+    pub input: PathBuf,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::ExportKeystore
+/// and writes an encrypted backup of the keystore to a file.
+pub struct ExportKeystore {
+    /// The file to write the backup to.
+    pub output: PathBuf,
+    #[structopt(long, env = "HC_KEYSTORE_BACKUP_PASSPHRASE", hide_env_values = true)]
+    /// The passphrase to encrypt the backup with.
+    pub passphrase: String,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::ImportKeystore
+/// and stages a backup written by `export-keystore` to replace
+/// the keystore the next time the conductor starts.
+/// The keystore must not hold any agent keys yet.
+pub struct ImportKeystore {
+    /// The file to read the backup from.
+    pub input: PathBuf,
+    #[structopt(long, env = "HC_KEYSTORE_BACKUP_PASSPHRASE", hide_env_values = true)]
+    /// The passphrase the backup was encrypted with.
+    pub passphrase: String,
+}
+
 #[doc(hidden)]
 pub async fn call(holochain_path: &Path, req: Call) -> anyhow::Result<()> {
     let Call {
@@ -261,8 +370,13 @@ async fn call_inner(cmd: &mut CmdRunner, call: AdminRequestCli) -> anyhow::Resul
             msg!("Added App port {}", port);
         }
         AdminRequestCli::ListAppWs => {
-            let ports = list_app_ws(cmd).await?;
-            msg!("Attached App Interfaces {:?}", ports);
+            let interfaces = list_app_interfaces(cmd).await?;
+            msg!("Attached App Interfaces {:?}", interfaces);
+        }
+        AdminRequestCli::IssueAppToken(args) => {
+            let (token, expires_at) = issue_app_token(cmd, args).await?;
+            msg!("Issued app token, which expires at {}:", expires_at);
+            msg!("{}", String::from(token));
         }
         AdminRequestCli::RegisterDna(args) => {
             let dnas = register_dna(cmd, args).await?;
@@ -281,6 +395,13 @@ async fn call_inner(cmd: &mut CmdRunner, call: AdminRequestCli) -> anyhow::Resul
             let dnas = list_dnas(cmd).await?;
             msg!("Dnas: {:?}", dnas);
         }
+        AdminRequestCli::ListZomeFns(args) => {
+            for (zome_name, exports) in list_zome_functions(cmd, args).await? {
+                msg!("Zome {}:", zome_name);
+                msg!("  Functions: {:?}", exports.zome_fns);
+                msg!("  Callbacks: {:?}", exports.callbacks);
+            }
+        }
         AdminRequestCli::NewAgent => {
             let agent = generate_agent_pub_key(cmd).await?;
             msg!("Added agent {}", agent);
@@ -307,6 +428,18 @@ async fn call_inner(cmd: &mut CmdRunner, call: AdminRequestCli) -> anyhow::Resul
             let state = dump_state(cmd, args).await?;
             msg!("DUMP STATE \n{}", state);
         }
+        AdminRequestCli::CheckHealth(args) => {
+            let health = check_cell_health(cmd, args).await?;
+            msg!(
+                "{} of {} authored ops are held by fewer than {} authorities",
+                health.ops_below_target.len(),
+                health.authored_ops,
+                health.redundancy_target
+            );
+            for (op_hash, count) in health.ops_below_target {
+                msg!("{} held by {}", op_hash, count);
+            }
+        }
         AdminRequestCli::AddAgents => todo!("Adding agent info via cli is coming soon"),
         AdminRequestCli::ListAgents(args) => {
             use std::fmt::Write;
@@ -355,6 +488,41 @@ async fn call_inner(cmd: &mut CmdRunner, call: AdminRequestCli) -> anyhow::Resul
                 msg!("{}\n", out);
             }
         }
+        AdminRequestCli::ListAgentKeys => {
+            for key in list_agent_keys(cmd).await? {
+                msg!("Agent {}:", key.agent_key);
+                msg!("  Cells: {:?}", key.cells);
+                if let Some(old) = key.supersedes {
+                    msg!("  Supersedes: {}", old);
+                }
+                if let Some(new) = key.superseded_by {
+                    msg!("  Superseded by: {}", new);
+                }
+            }
+        }
+        AdminRequestCli::RotateAgentKey(args) => {
+            let old = args.agent_key.clone();
+            let new = rotate_agent_key(cmd, args).await?;
+            msg!("Rotated agent {} to {}", old, new);
+        }
+        AdminRequestCli::ExportSourceChain(args) => {
+            let output = args.output.clone();
+            export_source_chain(cmd, args).await?;
+            msg!("Exported the source chain to {}", output.display());
+        }
+        AdminRequestCli::ImportSourceChain(args) => {
+            let cell_id = import_source_chain(cmd, args).await?;
+            msg!("Imported the source chain of cell {}", cell_id);
+        }
+        AdminRequestCli::ExportKeystore(args) => {
+            let output = args.output.clone();
+            export_keystore(cmd, args).await?;
+            msg!("Exported the keystore to {}", output.display());
+        }
+        AdminRequestCli::ImportKeystore(args) => {
+            import_keystore(cmd, args).await?;
+            msg!("Imported the keystore. It will replace the keystore when the conductor restarts");
+        }
     }
     Ok(())
 }
@@ -365,7 +533,7 @@ pub async fn add_admin_interface(cmd: &mut CmdRunner, args: AddAdminWs) -> anyho
     let resp = cmd
         .command(AdminRequest::AddAdminInterfaces(vec![
             AdminInterfaceConfig {
-                driver: InterfaceDriver::Websocket { port },
+                driver: InterfaceDriver::websocket(port),
             },
         ]))
         .await?;
@@ -495,17 +663,42 @@ pub async fn install_app_bundle(
 }
 
 /// Calls [`AdminRequest::ListAppInterfaces`].
-pub async fn list_app_ws(cmd: &mut CmdRunner) -> anyhow::Result<Vec<u16>> {
+pub async fn list_app_interfaces(cmd: &mut CmdRunner) -> anyhow::Result<Vec<InterfaceDriver>> {
     let resp = cmd.command(AdminRequest::ListAppInterfaces).await?;
     Ok(expect_match!(resp => AdminResponse::AppInterfacesListed, "Failed to list app interfaces"))
 }
 
+/// Calls [`AdminRequest::ListAppInterfaces`] and returns the ports of the
+/// app interfaces served over a websocket, leaving out HTTP and unix socket
+/// interfaces.
+pub async fn list_app_ws(cmd: &mut CmdRunner) -> anyhow::Result<Vec<u16>> {
+    Ok(list_app_interfaces(cmd)
+        .await?
+        .into_iter()
+        .filter_map(|driver| match driver {
+            InterfaceDriver::Websocket { port, .. } => Some(port),
+            _ => None,
+        })
+        .collect())
+}
+
 /// Calls [`AdminRequest::ListCellIds`].
 pub async fn list_dnas(cmd: &mut CmdRunner) -> anyhow::Result<Vec<DnaHash>> {
     let resp = cmd.command(AdminRequest::ListDnas).await?;
     Ok(expect_match!(resp => AdminResponse::DnasListed, "Failed to list dnas"))
 }
 
+/// Calls [`AdminRequest::ListZomeFunctions`].
+pub async fn list_zome_functions(
+    cmd: &mut CmdRunner,
+    args: ListZomeFns,
+) -> anyhow::Result<Vec<(ZomeName, ZomeExports)>> {
+    let resp = cmd
+        .command(AdminRequest::ListZomeFunctions { dna_hash: args.dna })
+        .await?;
+    Ok(expect_match!(resp => AdminResponse::ZomeFunctionsListed, "Failed to list zome functions"))
+}
+
 /// Calls [`AdminRequest::GenerateAgentPubKey`].
 pub async fn generate_agent_pub_key(cmd: &mut CmdRunner) -> anyhow::Result<AgentPubKey> {
     let resp = cmd.command(AdminRequest::GenerateAgentPubKey).await?;
@@ -559,11 +752,16 @@ pub async fn deactivate_app(cmd: &mut CmdRunner, args: DeactivateApp) -> anyhow:
 /// Calls [`AdminRequest::AttachAppInterface`] and adds another app interface.
 pub async fn attach_app_interface(cmd: &mut CmdRunner, args: AddAppWs) -> anyhow::Result<u16> {
     let resp = cmd
-        .command(AdminRequest::AttachAppInterface { port: args.port })
+        .command(AdminRequest::AttachAppInterface {
+            port: args.port,
+            driver: None,
+        })
         .await?;
     tracing::debug!(?resp);
     match resp {
-        AdminResponse::AppInterfaceAttached { port } => Ok(port),
+        AdminResponse::AppInterfaceAttached {
+            port: Some(port), ..
+        } => Ok(port),
         _ => Err(anyhow!(
             "Failed to attach app interface {:?}, got: {:?}",
             args.port,
@@ -572,6 +770,29 @@ pub async fn attach_app_interface(cmd: &mut CmdRunner, args: AddAppWs) -> anyhow
     }
 }
 
+/// Calls [`AdminRequest::IssueAppAuthenticationToken`].
+pub async fn issue_app_token(
+    cmd: &mut CmdRunner,
+    args: IssueAppToken,
+) -> anyhow::Result<(AppAuthenticationToken, Timestamp)> {
+    let resp = cmd
+        .command(AdminRequest::IssueAppAuthenticationToken {
+            installed_app_id: args.app_id.clone(),
+            expiry_seconds: args.expiry_seconds,
+        })
+        .await?;
+    match resp {
+        AdminResponse::AppAuthenticationTokenIssued { token, expires_at } => {
+            Ok((token, expires_at))
+        }
+        _ => Err(anyhow!(
+            "Failed to issue app token for {}, got: {:?}",
+            args.app_id,
+            resp
+        )),
+    }
+}
+
 /// Calls [`AdminRequest::DumpState`] and dumps the current cell's state.
 // TODO: Add pretty print.
 // TODO: Default to dumping all cell state.
@@ -584,6 +805,19 @@ pub async fn dump_state(cmd: &mut CmdRunner, args: DumpState) -> anyhow::Result<
     Ok(expect_match!(resp => AdminResponse::StateDumped, "Failed to dump state"))
 }
 
+/// Calls [`AdminRequest::CheckCellHealth`] and returns the cell's health.
+pub async fn check_cell_health(
+    cmd: &mut CmdRunner,
+    args: CheckHealth,
+) -> anyhow::Result<CellHealth> {
+    let resp = cmd
+        .command(AdminRequest::CheckCellHealth {
+            cell_id: Box::new(CellId::new(args.dna, args.agent_key)),
+        })
+        .await?;
+    Ok(expect_match!(resp => AdminResponse::CellHealthChecked, "Failed to check cell health"))
+}
+
 /// Calls [`AdminRequest::AddAgentInfo`] with and adds the list of agent info.
 pub async fn add_agent_info(cmd: &mut CmdRunner, args: Vec<AgentInfoSigned>) -> anyhow::Result<()> {
     let resp = cmd
@@ -610,6 +844,84 @@ pub async fn request_agent_info(
     Ok(expect_match!(resp => AdminResponse::AgentInfoRequested, "Failed to request agent info"))
 }
 
+/// Calls [`AdminRequest::ListAgentKeys`] and lists the agent keys in the keystore.
+pub async fn list_agent_keys(cmd: &mut CmdRunner) -> anyhow::Result<Vec<AgentKeyInfo>> {
+    let resp = cmd.command(AdminRequest::ListAgentKeys).await?;
+    Ok(expect_match!(resp => AdminResponse::AgentKeysListed, "Failed to list agent keys"))
+}
+
+/// Calls [`AdminRequest::RotateAgentKey`] and returns the new agent key.
+pub async fn rotate_agent_key(
+    cmd: &mut CmdRunner,
+    args: RotateAgentKey,
+) -> anyhow::Result<AgentPubKey> {
+    let resp = cmd
+        .command(AdminRequest::RotateAgentKey {
+            agent_key: args.agent_key,
+        })
+        .await?;
+    Ok(expect_match!(resp => AdminResponse::AgentKeyRotated, "Failed to rotate agent key"))
+}
+
+/// Calls [`AdminRequest::ExportSourceChain`] and writes the archive to a file.
+pub async fn export_source_chain(
+    cmd: &mut CmdRunner,
+    args: ExportSourceChain,
+) -> anyhow::Result<()> {
+    let resp = cmd
+        .command(AdminRequest::ExportSourceChain {
+            cell_id: CellId::new(args.dna, args.agent_key),
+        })
+        .await?;
+    let archive =
+        expect_match!(resp => AdminResponse::SourceChainExported, "Failed to export source chain");
+    std::fs::write(args.output, archive.to_bytes()?)?;
+    Ok(())
+}
+
+/// Calls [`AdminRequest::ImportSourceChain`] with an archive read from a file
+/// and returns the cell id it was imported for.
+pub async fn import_source_chain(
+    cmd: &mut CmdRunner,
+    args: ImportSourceChain,
+) -> anyhow::Result<CellId> {
+    let archive = SignedSourceChainArchive::from_bytes(&std::fs::read(args.input)?)?;
+    let resp = cmd
+        .command(AdminRequest::ImportSourceChain { archive })
+        .await?;
+    Ok(expect_match!(resp => AdminResponse::SourceChainImported, "Failed to import source chain"))
+}
+
+/// Calls [`AdminRequest::ExportKeystore`] and writes the backup to a file.
+pub async fn export_keystore(cmd: &mut CmdRunner, args: ExportKeystore) -> anyhow::Result<()> {
+    let resp = cmd
+        .command(AdminRequest::ExportKeystore {
+            passphrase: args.passphrase,
+        })
+        .await?;
+    let backup =
+        expect_match!(resp => AdminResponse::KeystoreExported, "Failed to export keystore");
+    std::fs::write(args.output, backup.to_bytes()?)?;
+    Ok(())
+}
+
+/// Calls [`AdminRequest::ImportKeystore`] with a backup read from a file.
+pub async fn import_keystore(cmd: &mut CmdRunner, args: ImportKeystore) -> anyhow::Result<()> {
+    let archive = KeystoreBackup::from_bytes(&std::fs::read(args.input)?)?;
+    let resp = cmd
+        .command(AdminRequest::ImportKeystore {
+            archive,
+            passphrase: args.passphrase,
+        })
+        .await?;
+    ensure!(
+        matches!(resp, AdminResponse::KeystoreImported),
+        "Failed to import keystore, got: {:?}",
+        resp
+    );
+    Ok(())
+}
+
 fn parse_agent_key(arg: &str) -> anyhow::Result<AgentPubKey> {
     AgentPubKey::try_from(arg).map_err(|e| anyhow::anyhow!("{:?}", e))
 }
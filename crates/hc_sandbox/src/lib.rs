@@ -73,6 +73,20 @@
 //! ```shell
 //! hc sandbox call list-cells
 //! ```
+//! #### Zome Call
+//! Calls a zome function over an app interface.
+//! The payload can be JSON or YAML and the response is
+//! printed as JSON.
+//!
+//! ```shell
+//! hc sandbox zome-call my-app/my-cell my_zome my_fn --payload '{"title": "hi"}'
+//! ```
+//! Add `-i` to keep the connection open, make calls from stdin
+//! and print any signals that come in.
+//!
+//! ```shell
+//! hc sandbox zome-call my-app -i
+//! ```
 //! #### List and Clean
 //! These commands allow you to list the persisted sandboxes
 //! in the current directory (from the`.hcXXX`) file.
@@ -140,6 +154,7 @@ pub mod generate;
 pub mod run;
 pub mod sandbox;
 pub mod save;
+pub mod zome_call;
 pub use cli::HcSandbox;
 
 mod ports;
@@ -165,6 +180,18 @@ impl CmdRunner {
         Ok(Self { client })
     }
 
+    /// Create a new connection for calling admin interface commands
+    /// over the unix socket admin interface at `path`.
+    #[cfg(unix)]
+    pub async fn try_new_unix(path: PathBuf) -> WebsocketResult<Self> {
+        let (client, _) = holochain_websocket::connect_unix(
+            path,
+            std::sync::Arc::new(holochain_websocket::WebsocketConfig::default()),
+        )
+        .await?;
+        Ok(Self { client })
+    }
+
     /// Create a command runner from a sandbox path.
     /// This expects holochain to be on the path.
     pub async fn from_sandbox(
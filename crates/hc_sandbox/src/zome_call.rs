@@ -0,0 +1,347 @@
+//! Helpers for making zome calls over an app interface.
+//!
+//! Payloads are written as JSON or YAML on the command line and
+//! transcoded to the msgpack [`ExternIO`] that the conductor expects.
+//! Responses and signals are decoded back into JSON for printing.
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use anyhow::bail;
+use futures::stream::StreamExt;
+use holochain_conductor_api::AppAuthenticationToken;
+use holochain_conductor_api::AppRequest;
+use holochain_conductor_api::AppResponse;
+use holochain_conductor_api::InstalledAppInfo;
+use holochain_conductor_api::ZomeCall;
+use holochain_types::prelude::*;
+use holochain_websocket::{self as ws, WebsocketConfig, WebsocketReceiver, WebsocketSender};
+use structopt::StructOpt;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::BufReader;
+use url2::prelude::*;
+
+use crate::calls::attach_app_interface;
+use crate::calls::issue_app_token;
+use crate::calls::list_app_ws;
+use crate::calls::AddAppWs;
+use crate::calls::IssueAppToken;
+use crate::ports::get_admin_ports;
+use crate::run::run_async;
+use crate::CmdRunner;
+
+#[derive(Debug, StructOpt, Clone)]
+/// Call a zome function on a running conductor.
+///
+/// For example `hc sandbox zome-call my-app/my-cell my_zome my_fn --payload '{"title": "hi"}'`
+/// or `hc sandbox zome-call my-app -i` to start an interactive session
+/// which also prints any signals emitted by the app.
+pub struct ZomeCallCmd {
+    #[structopt(short, long, conflicts_with = "index")]
+    /// Port of a running conductor's admin interface.
+    /// If this is not set an existing sandbox will be used.
+    pub running: Option<u16>,
+    #[structopt(long)]
+    /// Index of the existing sandbox to use (see `hc sandbox list`).
+    /// Can be omitted if there is only one sandbox.
+    pub index: Option<usize>,
+    #[structopt(short, long)]
+    /// Port of the app interface to attach to.
+    /// Defaults to the first app interface served over a websocket,
+    /// or a new one if there are none.
+    pub app_port: Option<u16>,
+    #[structopt(parse(try_from_str = parse_cell_target))]
+    /// The cell to call, as `<installed_app_id>/<cell_nick>`.
+    /// The cell nick can be left out if the app only has one cell.
+    pub target: CellTarget,
+    #[structopt(required_unless = "interactive")]
+    /// The zome containing the function.
+    pub zome: Option<String>,
+    #[structopt(required_unless = "interactive")]
+    /// The name of the function to call.
+    pub function: Option<String>,
+    #[structopt(short, long, default_value = "null")]
+    /// The input to the function as JSON or YAML.
+    pub payload: String,
+    #[structopt(short, long)]
+    /// Keep the connection open and read calls from stdin
+    /// in the form `<zome> <fn> [payload]`.
+    /// Signals are printed as they arrive.
+    pub interactive: bool,
+}
+
+/// The cell a zome call is addressed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellTarget {
+    /// The app the cell belongs to.
+    pub installed_app_id: InstalledAppId,
+    /// The nick of the cell within the app.
+    pub cell_nick: Option<CellNick>,
+}
+
+/// An open connection to a conductor's app interface.
+pub struct AppClient {
+    client: WebsocketSender,
+    cell_id: CellId,
+}
+
+impl AppClient {
+    /// Connect to the app interface on this port, authenticate
+    /// with the token and find the cell for the target.
+    pub async fn connect(
+        port: u16,
+        token: AppAuthenticationToken,
+        target: &CellTarget,
+    ) -> anyhow::Result<(Self, WebsocketReceiver)> {
+        let (mut client, rx) = ws::connect(
+            url2!("ws://127.0.0.1:{}", port),
+            Arc::new(WebsocketConfig::default()),
+        )
+        .await?;
+        let resp: AppResponse = client.request(AppRequest::Authenticate { token }).await?;
+        if !matches!(resp, AppResponse::Authenticated { .. }) {
+            bail!(
+                "Failed to authenticate with the app interface, got: {:?}",
+                resp
+            );
+        }
+        let resp: AppResponse = client
+            .request(AppRequest::AppInfo {
+                installed_app_id: target.installed_app_id.clone(),
+            })
+            .await?;
+        let app = match resp {
+            AppResponse::AppInfo(Some(app)) => app,
+            AppResponse::AppInfo(None) => bail!("App {} is not installed", target.installed_app_id),
+            _ => bail!("Failed to get app info, got: {:?}", resp),
+        };
+        let cell_id = find_cell(&app, target)?;
+        Ok((Self { client, cell_id }, rx))
+    }
+
+    /// The cell this client is calling.
+    pub fn cell_id(&self) -> &CellId {
+        &self.cell_id
+    }
+
+    /// Call a zome function with a JSON or YAML payload and
+    /// return the decoded response as JSON.
+    pub async fn call(
+        &mut self,
+        zome: &str,
+        function: &str,
+        payload: &str,
+    ) -> anyhow::Result<serde_json::Value> {
+        let call = ZomeCall {
+            cell_id: self.cell_id.clone(),
+            zome_name: zome.into(),
+            fn_name: function.into(),
+            payload: encode_payload(payload)?,
+            cap: None,
+            provenance: self.cell_id.agent_pubkey().clone(),
+        };
+        let resp: AppResponse = self
+            .client
+            .request(AppRequest::ZomeCall(Box::new(call)))
+            .await?;
+        match resp {
+            AppResponse::ZomeCall(output) => decode_output(&output),
+            AppResponse::Error(e) => Err(anyhow!("Zome call failed: {:?}", e)),
+            _ => Err(anyhow!("Unexpected response to zome call: {:?}", resp)),
+        }
+    }
+}
+
+#[doc(hidden)]
+pub async fn zome_call(holochain_path: &Path, args: ZomeCallCmd) -> anyhow::Result<()> {
+    // Hold on to the conductor if we had to start one.
+    let (mut cmd, _holochain) = admin_runner(holochain_path, args.running, args.index).await?;
+    let app_port = match args.app_port {
+        Some(port) => port,
+        None => match list_app_ws(&mut cmd).await?.first() {
+            Some(port) => *port,
+            None => attach_app_interface(&mut cmd, AddAppWs { port: None }).await?,
+        },
+    };
+    let (token, _) = issue_app_token(
+        &mut cmd,
+        IssueAppToken {
+            app_id: args.target.installed_app_id.clone(),
+            expiry_seconds: None,
+        },
+    )
+    .await?;
+    let (mut client, rx) = AppClient::connect(app_port, token, &args.target).await?;
+    if args.interactive {
+        msg!(
+            "Connected to {:?} on app port {}",
+            client.cell_id(),
+            app_port
+        );
+        return repl(client, rx).await;
+    }
+    let zome = args.zome.expect("Required unless interactive");
+    let function = args.function.expect("Required unless interactive");
+    let output = client.call(&zome, &function, &args.payload).await?;
+    msg!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+async fn repl(mut client: AppClient, mut rx: WebsocketReceiver) -> anyhow::Result<()> {
+    tokio::task::spawn(async move {
+        while let Some((msg, _)) = rx.next().await {
+            match Signal::try_from(msg) {
+                Ok(Signal::App(cell_id, signal)) => match decode_output(&signal.into_inner()) {
+                    Ok(signal) => msg!("Signal from {:?}:\n{:#}", cell_id, signal),
+                    Err(e) => msg!("Failed to decode signal from {:?}: {:?}", cell_id, e),
+                },
+                Ok(Signal::System(signal)) => msg!("System signal: {:?}", signal),
+                Err(e) => tracing::warn!(failed_to_decode_signal = ?e),
+            }
+        }
+        msg!("App interface closed the connection");
+    });
+    msg!("Enter `<zome> <fn> [payload]` to make a call or `exit` to quit");
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "exit" || line == "quit" {
+            break;
+        }
+        let mut parts = line.splitn(3, char::is_whitespace);
+        let (zome, function) = match (parts.next(), parts.next()) {
+            (Some(zome), Some(function)) => (zome, function),
+            _ => {
+                msg!("Expected `<zome> <fn> [payload]`");
+                continue;
+            }
+        };
+        let payload = parts.next().map(str::trim).unwrap_or("null");
+        match client.call(zome, function, payload).await {
+            Ok(output) => msg!("{}", serde_json::to_string_pretty(&output)?),
+            Err(e) => msg!("{:?}", e),
+        }
+    }
+    Ok(())
+}
+
+/// Connect to the admin interface of a running conductor
+/// or start the sandbox's conductor if it isn't running.
+async fn admin_runner(
+    holochain_path: &Path,
+    running: Option<u16>,
+    index: Option<usize>,
+) -> anyhow::Result<(CmdRunner, Option<tokio::process::Child>)> {
+    if let Some(port) = running {
+        return Ok((CmdRunner::try_new(port).await?, None));
+    }
+    let sandboxes = crate::save::load(std::env::current_dir()?)?;
+    let path = match index {
+        Some(i) => sandboxes
+            .get(i)
+            .cloned()
+            .ok_or_else(|| anyhow!("No sandbox at index {}", i))?,
+        None if sandboxes.len() == 1 => sandboxes[0].clone(),
+        None if sandboxes.is_empty() => bail!("No sandboxes found, use `hc sandbox generate`"),
+        None => bail!("There are multiple sandboxes, choose one with `--index`"),
+    };
+    let port = get_admin_ports(vec![path.clone()])
+        .await?
+        .first()
+        .cloned()
+        .ok_or_else(|| anyhow!("No admin port found for sandbox {}", path.display()))?;
+    match CmdRunner::try_new(port).await {
+        Ok(cmd) => Ok((cmd, None)),
+        Err(holochain_websocket::WebsocketError::Io(e))
+            if e.kind() == std::io::ErrorKind::ConnectionRefused =>
+        {
+            let (port, holochain) = run_async(holochain_path, path, None).await?;
+            Ok((CmdRunner::try_new(port).await?, Some(holochain)))
+        }
+        Err(e) => bail!(
+            "Failed to connect to running conductor or start one {:?}",
+            e
+        ),
+    }
+}
+
+fn find_cell(app: &InstalledAppInfo, target: &CellTarget) -> anyhow::Result<CellId> {
+    match &target.cell_nick {
+        Some(nick) => app
+            .cell_data
+            .iter()
+            .find(|c| c.as_nick() == nick)
+            .map(|c| c.as_id().clone())
+            .ok_or_else(|| anyhow!("App {} has no cell {}", app.installed_app_id, nick)),
+        None => match &app.cell_data[..] {
+            [cell] => Ok(cell.as_id().clone()),
+            _ => bail!(
+                "App {} has {} cells, use `<app>/<cell>` to choose one",
+                app.installed_app_id,
+                app.cell_data.len()
+            ),
+        },
+    }
+}
+
+fn parse_cell_target(arg: &str) -> anyhow::Result<CellTarget> {
+    let mut parts = arg.splitn(2, '/');
+    let installed_app_id = parts
+        .next()
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| anyhow!("Expected `<installed_app_id>/<cell_nick>`"))?
+        .to_string();
+    let cell_nick = parts.next().map(|n| n.to_string());
+    Ok(CellTarget {
+        installed_app_id,
+        cell_nick,
+    })
+}
+
+/// Transcode a JSON or YAML payload to msgpack.
+pub fn encode_payload(payload: &str) -> anyhow::Result<ExternIO> {
+    let value: serde_yaml::Value = serde_yaml::from_str(payload)?;
+    Ok(ExternIO::encode(value)?)
+}
+
+/// Transcode msgpack output to JSON.
+/// Holo hashes are shown in their base64 form and
+/// other bytes are shown as arrays of numbers.
+pub fn decode_output(output: &ExternIO) -> anyhow::Result<serde_json::Value> {
+    Ok(holochain_conductor_api::json::extern_io_to_json(output)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_round_trip() {
+        let payload = encode_payload(r#"{"title": "hi", "count": 3, "tags": ["a", "b"]}"#).unwrap();
+        assert_eq!(
+            decode_output(&payload).unwrap(),
+            serde_json::json!({"title": "hi", "count": 3, "tags": ["a", "b"]})
+        );
+        let payload = encode_payload("title: hi\ncount: 3").unwrap();
+        assert_eq!(
+            decode_output(&payload).unwrap(),
+            serde_json::json!({"title": "hi", "count": 3})
+        );
+    }
+
+    #[test]
+    fn parses_cell_target() {
+        assert_eq!(
+            parse_cell_target("my-app/my-cell").unwrap(),
+            CellTarget {
+                installed_app_id: "my-app".into(),
+                cell_nick: Some("my-cell".into()),
+            }
+        );
+        assert_eq!(parse_cell_target("my-app").unwrap().cell_nick, None);
+        assert!(parse_cell_target("/my-cell").is_err());
+    }
+}
@@ -9,6 +9,7 @@ use std::sync::Arc;
 use assert_cmd::prelude::*;
 use holochain_conductor_api::AppRequest;
 use holochain_conductor_api::AppResponse;
+use holochain_conductor_api::ExternalApiWireError;
 use holochain_websocket::{self as ws, WebsocketConfig, WebsocketReceiver, WebsocketSender};
 use matches::assert_matches;
 use portpicker::pick_unused_port;
@@ -37,7 +38,11 @@ async fn call_app_interface(port: u16) {
     };
     let response = app_tx.request(request);
     let r: AppResponse = check_timeout(response).await;
-    assert_matches!(r, AppResponse::AppInfo(None));
+    // The interface is running, but refuses requests until authenticated
+    assert_matches!(
+        r,
+        AppResponse::Error(ExternalApiWireError::AppAuthenticationFailed(_))
+    );
 }
 
 async fn check_timeout<T>(response: impl Future<Output = Result<T, ws::WebsocketError>>) -> T {
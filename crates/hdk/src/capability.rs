@@ -60,6 +60,15 @@ pub fn create_cap_claim(cap_claim_entry: CapClaimEntry) -> ExternResult<HeaderHa
 /// - [ `CapAccess::Transferable` ] access means any external agent with a valid secret can call the extern
 /// - [ `CapAccess::Assigned` ] access means only explicitly approved agents with a valid secret can call the extern
 ///
+/// A grant can also carry an `expires_at` timestamp after which it no longer gives access, and a
+/// `max_uses` count of the calls it gives access to. Once either limit is reached the grant stays on
+/// the chain, but calls made with it are unauthorized.
+///
+/// The `curry_payloads` of a grant pin some or all of the fields of the input of the granted
+/// functions, e.g. to give access to posting to a single channel rather than any channel. A call
+/// may leave pinned fields out, to have them filled in, but is unauthorized if it gives them
+/// different values.
+///
 /// The authoring agent itself always has an implicit capability which grants access to its own externs,
 /// and needs no special capability grant.
 ///
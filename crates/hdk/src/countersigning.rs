@@ -0,0 +1,59 @@
+use crate::prelude::*;
+
+/// Accept a request to take part in a countersigning session.
+///
+/// If the request is accepted the agent's source chain is locked until the session ends,
+/// or until the countersigned entry is committed, whichever comes first.
+/// Nothing else can be committed to the chain in the meantime, not even later in the same
+/// zome call.
+///
+/// The [ `PreflightResponse` ] in an accepted request must be sent back to whoever is
+/// collecting the responses, e.g. as the return value of a [ `call_remote` ].
+pub fn accept_countersigning_preflight_request(
+    preflight_request: PreflightRequest,
+) -> ExternResult<PreflightRequestAcceptance> {
+    HDK.with(|h| {
+        h.borrow()
+            .accept_countersigning_preflight_request(preflight_request)
+    })
+}
+
+/// Commit an app entry countersigned by every agent in a countersigning session.
+///
+/// Every signing agent commits the same entry, with the same session data, to their own
+/// source chain.
+/// The session data can be built from all the agents' responses with
+/// [ `CounterSigningSessionData::try_from_responses` ].
+///
+/// The commit fails unless the agent's chain is locked for this session, and it must be
+/// made before the session ends.
+///
+/// e.g.
+/// ```ignore
+/// #[hdk_entry(id = "transaction")]
+/// pub struct Transaction { .. }
+/// create_countersigned_entry(session_data, transaction)?;
+/// ```
+pub fn create_countersigned_entry<I, E>(
+    session_data: CounterSigningSessionData,
+    input: I,
+) -> ExternResult<HeaderHash>
+where
+    EntryWithDefId: TryFrom<I, Error = E>,
+    WasmError: From<E>,
+{
+    let entry_with_def_id = EntryWithDefId::try_from(input)?;
+    let app_entry_bytes = match AsRef::<Entry>::as_ref(&entry_with_def_id) {
+        Entry::App(app_entry_bytes) => app_entry_bytes.clone(),
+        entry => {
+            return Err(WasmError::Guest(format!(
+                "Only app entries can be countersigned, not {:?}",
+                entry
+            )))
+        }
+    };
+    create(EntryWithDefId::new(
+        AsRef::<EntryDefId>::as_ref(&entry_with_def_id).clone(),
+        Entry::CounterSign(Box::new(session_data), app_entry_bytes),
+    ))
+}
@@ -294,7 +294,8 @@ macro_rules! app_entry {
             type Error = $crate::prelude::WasmError;
             fn try_from(entry: &$crate::prelude::Entry) -> Result<Self, Self::Error> {
                 match entry {
-                    $crate::prelude::Entry::App(eb) => Ok(Self::try_from(
+                    $crate::prelude::Entry::App(eb)
+                    | $crate::prelude::Entry::CounterSign(_, eb) => Ok(Self::try_from(
                         $crate::prelude::SerializedBytes::from(eb.to_owned()),
                     )?),
                     _ => Err($crate::prelude::SerializedBytesError::Deserialize(format!(
@@ -23,6 +23,11 @@ pub trait HdkT: Send + Sync {
         get_agent_activity_input: GetAgentActivityInput,
     ) -> ExternResult<AgentActivity>;
     fn query(&self, filter: ChainQueryFilter) -> ExternResult<Vec<Element>>;
+    // CounterSigning
+    fn accept_countersigning_preflight_request(
+        &self,
+        preflight_request: PreflightRequest,
+    ) -> ExternResult<PreflightRequestAcceptance>;
     // Ed25519
     fn sign(&self, sign: Sign) -> ExternResult<Signature>;
     fn sign_ephemeral(&self, sign_ephemeral: SignEphemeral) -> ExternResult<EphemeralSignatures>;
@@ -94,6 +99,12 @@ impl HdkT for ErrHdk {
     fn query(&self, _: ChainQueryFilter) -> ExternResult<Vec<Element>> {
         Self::err()
     }
+    fn accept_countersigning_preflight_request(
+        &self,
+        _: PreflightRequest,
+    ) -> ExternResult<PreflightRequestAcceptance> {
+        Self::err()
+    }
     fn sign(&self, _: Sign) -> ExternResult<Signature> {
         Self::err()
     }
@@ -227,6 +238,15 @@ impl HdkT for HostHdk {
     fn query(&self, filter: ChainQueryFilter) -> ExternResult<Vec<Element>> {
         host_call::<ChainQueryFilter, Vec<Element>>(__query, filter)
     }
+    fn accept_countersigning_preflight_request(
+        &self,
+        preflight_request: PreflightRequest,
+    ) -> ExternResult<PreflightRequestAcceptance> {
+        host_call::<PreflightRequest, PreflightRequestAcceptance>(
+            __accept_countersigning_preflight_request,
+            preflight_request,
+        )
+    }
 
     fn sign(&self, sign: Sign) -> ExternResult<Signature> {
         host_call::<Sign, Signature>(__sign, sign)
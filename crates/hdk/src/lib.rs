@@ -70,6 +70,7 @@
 //! - Create, read, update, delete (CRUD) operations on the above
 //! - Libsodium compatible symmetric/secret (secretbox) and asymmetric/keypair (box) encryption: x_salsa20_poly1305 module
 //! - Ed25519 signing and verification of data: ed25519 module
+//! - Atomic commits of entries countersigned by several agents: countersigning module
 //! - Exposing information about the current execution context such as zome name: info module
 //! - Other utility functions provided by the host such as generating randomness and timestamps that are impossible in wasm: utility module
 //! - Exposing functions to external processes and callbacks to the host: `#[hdk_extern]` and `map_extern!` macros
@@ -399,6 +400,19 @@ pub use paste;
 /// The same query DSL for local chain queries is used to filter remote agent activity headers.
 pub mod chain;
 
+/// Countersigning lets several agents commit the same app entry atomically, each to their own source chain.
+///
+/// This is how agreements between agents, such as both sides of a transaction, can be recorded with cryptographic integrity.
+///
+/// 0. One agent builds a [ `crate::prelude::PreflightRequest` ] naming the entry, the signing agents and a short session window, and sends it to every other agent.
+/// 0. Every agent accepts the request with `accept_countersigning_preflight_request`, which locks their source chain for the session and signs its current state.
+/// 0. The responses are collected into [ `crate::prelude::CounterSigningSessionData` ] and sent back to every agent.
+/// 0. Every agent commits the entry along with the session data with `create_countersigned_entry`, which unlocks their chain.
+///
+/// Sys validation checks every agent's signature, and that each agent committed the entry at the chain position they signed for, within the session window.
+/// If the session window passes before an agent commits the entry their chain unlocks and the entry can no longer be committed.
+pub mod countersigning;
+
 /// Create and verify signatures for serializable Rust structures and raw binary data.
 ///
 /// The signatures are always created with the [Ed25519](https://en.wikipedia.org/wiki/EdDSA) algorithm by the secure keystore (lair).
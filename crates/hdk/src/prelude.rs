@@ -6,6 +6,8 @@ pub use crate::capability::generate_cap_secret;
 pub use crate::capability::update_cap_grant;
 pub use crate::chain::get_agent_activity;
 pub use crate::chain::query;
+pub use crate::countersigning::accept_countersigning_preflight_request;
+pub use crate::countersigning::create_countersigned_entry;
 pub use crate::ed25519::sign;
 pub use crate::ed25519::sign_ephemeral;
 pub use crate::ed25519::sign_ephemeral_raw;
@@ -118,6 +120,7 @@ macro_rules! holochain_externs {
             __update,
             __delete,
             __schedule,
+            __accept_countersigning_preflight_request,
             __sleep,
             __x_salsa20_poly1305_encrypt,
             __x_salsa20_poly1305_decrypt,
@@ -1,10 +1,16 @@
 use holochain::conductor::config::ConductorConfig;
+use holochain::conductor::error::ConductorError;
 use holochain::conductor::interactive;
 use holochain::conductor::manager::handle_shutdown;
 use holochain::conductor::paths::ConfigFilePath;
 use holochain::conductor::Conductor;
 use holochain::conductor::ConductorHandle;
 use holochain_conductor_api::conductor::ConductorConfigError;
+use holochain_keystore::backup::export_lair_dir;
+use holochain_keystore::backup::prompt_backup_passphrase;
+use holochain_keystore::backup::restore_lair_dir;
+use holochain_keystore::backup::KeystoreBackup;
+use holochain_keystore::KeystoreError;
 use observability::Output;
 #[cfg(unix)]
 use sd_notify::{notify, NotifyState};
@@ -44,6 +50,22 @@ struct Opt {
     useful when running a conductor for the first time"
     )]
     interactive: bool,
+
+    #[structopt(
+        long,
+        help = "Write an encrypted backup of the keystore to this file and exit,
+    without starting the conductor. Lair must not be running.
+    Prompts for the backup passphrase"
+    )]
+    export_keystore: Option<PathBuf>,
+
+    #[structopt(
+        long,
+        help = "Restore the keystore from a backup made with --export-keystore
+    or the ExportKeystore admin request before starting the conductor. The keystore directory in the configuration
+    must be empty. Prompts for the backup passphrase"
+    )]
+    restore_keystore: Option<PathBuf>,
 }
 
 fn main() {
@@ -63,8 +85,13 @@ async fn async_main() {
 
     kitsune_p2p_types::metrics::init_sys_info_poll();
 
-    let conductor =
-        conductor_handle_from_config_path(opt.config_path.clone(), opt.interactive).await;
+    let conductor = conductor_handle_from_config_path(
+        opt.config_path.clone(),
+        opt.interactive,
+        opt.export_keystore.clone(),
+        opt.restore_keystore.clone(),
+    )
+    .await;
 
     info!("Conductor successfully initialized.");
 
@@ -96,6 +123,8 @@ async fn async_main() {
 async fn conductor_handle_from_config_path(
     config_path: Option<PathBuf>,
     interactive: bool,
+    export_keystore: Option<PathBuf>,
+    restore_keystore: Option<PathBuf>,
 ) -> ConductorHandle {
     let config_path_default = config_path.is_none();
     let config_path: ConfigFilePath = config_path.map(Into::into).unwrap_or_default();
@@ -113,6 +142,19 @@ async fn conductor_handle_from_config_path(
         load_config(&config_path, config_path_default)
     };
 
+    if let Some(backup_path) = export_keystore {
+        match export_keystore_backup(&backup_path, &config) {
+            Ok(()) => {
+                println!("Exported the keystore to {}.", backup_path.display());
+                std::process::exit(0);
+            }
+            Err(e) => {
+                println!("Couldn't export the keystore: {}", e);
+                std::process::exit(ERROR_CODE);
+            }
+        }
+    }
+
     // Check if LMDB env dir is present
     // In interactive mode give the user a chance to create it, otherwise create it automatically
     let env_path = PathBuf::from(config.environment_path.clone());
@@ -131,12 +173,51 @@ async fn conductor_handle_from_config_path(
         }
     }
 
-    // Initialize the Conductor
-    Conductor::builder()
-        .config(config)
-        .build()
-        .await
-        .expect("Could not initialize Conductor from configuration")
+    if let Some(backup_path) = restore_keystore {
+        match restore_keystore_backup(&backup_path, &config) {
+            Ok(()) => println!("Restored the keystore from {}.", backup_path.display()),
+            Err(e) => {
+                println!("Couldn't restore the keystore: {}", e);
+                std::process::exit(ERROR_CODE);
+            }
+        }
+    }
+
+    // Initialize the Conductor, unlocking the keystore with the configured passphrase service
+    match Conductor::builder().config(config).build().await {
+        Ok(conductor) => conductor,
+        Err(ConductorError::KeystoreError(e))
+            if matches!(
+                e,
+                KeystoreError::IncorrectPassphrase(_) | KeystoreError::PassphraseUnavailable(_)
+            ) =>
+        {
+            display_friendly_keystore_unlock_message(e);
+            std::process::exit(ERROR_CODE);
+        }
+        Err(e) => panic!("Could not initialize Conductor from configuration: {:?}", e),
+    }
+}
+
+/// Back up the keystore directory of the config into a file
+fn export_keystore_backup(
+    backup_path: &std::path::Path,
+    config: &ConductorConfig,
+) -> Result<(), KeystoreError> {
+    let passphrase = prompt_backup_passphrase()?;
+    let backup = export_lair_dir(config.keystore_path.as_deref(), &passphrase)?;
+    std::fs::write(backup_path, backup.to_bytes()?).map_err(|e| KeystoreError::Other(e.to_string()))
+}
+
+/// Restore a keystore backup into the keystore directory of the config
+fn restore_keystore_backup(
+    backup_path: &std::path::Path,
+    config: &ConductorConfig,
+) -> Result<(), KeystoreError> {
+    let bytes = std::fs::read(backup_path).map_err(|e| KeystoreError::Other(e.to_string()))?;
+    let backup = KeystoreBackup::from_bytes(&bytes)?;
+    let passphrase = prompt_backup_passphrase()?;
+    restore_lair_dir(&backup, &passphrase, config.keystore_path.as_deref())
 }
 
 /// Load config, throw friendly error on failure
@@ -205,3 +286,16 @@ a valid default configuration. Details:
         config_path, error
     )
 }
+
+fn display_friendly_keystore_unlock_message(error: KeystoreError) {
+    println!(
+        "
+Error: The keystore could not be unlocked. Please check the passphrase, and the
+passphrase_service section of the conductor config. Details:
+
+    {}
+
+    ",
+        error
+    )
+}
@@ -4,6 +4,8 @@ use std::sync::Arc;
 
 use super::error::ConductorApiError;
 use super::error::ConductorApiResult;
+use crate::conductor::config::WasmConfig;
+use crate::conductor::dpki::DpkiImpl;
 use crate::conductor::interface::SignalBroadcaster;
 use crate::conductor::ConductorHandle;
 use crate::core::workflow::call_zome_workflow::call_zome_workspace_lock::CallZomeWorkspaceLock;
@@ -13,7 +15,6 @@ use holo_hash::DnaHash;
 use holochain_conductor_api::ZomeCall;
 use holochain_keystore::KeystoreSender;
 use holochain_types::prelude::*;
-use tracing::*;
 
 /// The concrete implementation of [CellConductorApiT], which is used to give
 /// Cells an API for calling back to their [Conductor].
@@ -62,9 +63,12 @@ impl CellConductorApiT for CellConductorApi {
         }
     }
 
-    async fn dpki_request(&self, _method: String, _args: String) -> ConductorApiResult<String> {
-        warn!("Using placeholder dpki");
-        Ok("TODO".to_string())
+    fn dpki(&self) -> Option<DpkiImpl> {
+        self.conductor_handle.dpki()
+    }
+
+    async fn wasm_config(&self) -> Arc<WasmConfig> {
+        self.conductor_handle.wasm_config().await
     }
 
     fn keystore(&self) -> &KeystoreSender {
@@ -119,9 +123,11 @@ pub trait CellConductorApiT: Clone + Send + Sync + Sized {
         call: ZomeCall,
     ) -> ConductorApiResult<ZomeCallResult>;
 
-    /// Make a request to the DPKI service running for this Conductor.
-    /// TODO: decide on actual signature
-    async fn dpki_request(&self, method: String, args: String) -> ConductorApiResult<String>;
+    /// The DPKI service of this Conductor, if it has one
+    fn dpki(&self) -> Option<DpkiImpl>;
+
+    /// How the wasm of this Conductor's zomes is compiled and pooled
+    async fn wasm_config(&self) -> Arc<WasmConfig>;
 
     /// Request access to this conductor's keystore
     fn keystore(&self) -> &KeystoreSender;
@@ -162,6 +168,17 @@ pub trait CellConductorReadHandleT: Send + Sync {
 
     /// Get a zome from this cell's Dna
     async fn get_zome(&self, dna_hash: &DnaHash, zome_name: &ZomeName) -> ConductorApiResult<Zome>;
+
+    /// Persist the countersigning session this cell's chain has been locked for
+    async fn persist_chain_lock(&self, request: PreflightRequest) -> ConductorApiResult<()>;
+
+    /// Count a use of the use-limited cap grant committed by this header,
+    /// returning false if the grant has no uses left
+    async fn use_cap_grant(
+        &self,
+        grant_header: HeaderHash,
+        max_uses: u32,
+    ) -> ConductorApiResult<bool>;
 }
 
 #[async_trait]
@@ -187,4 +204,20 @@ impl CellConductorReadHandleT for CellConductorApi {
     async fn get_zome(&self, dna_hash: &DnaHash, zome_name: &ZomeName) -> ConductorApiResult<Zome> {
         CellConductorApiT::get_zome(self, dna_hash, zome_name).await
     }
+
+    async fn persist_chain_lock(&self, request: PreflightRequest) -> ConductorApiResult<()> {
+        self.conductor_handle
+            .persist_chain_lock(&self.cell_id, request)
+            .await
+    }
+
+    async fn use_cap_grant(
+        &self,
+        grant_header: HeaderHash,
+        max_uses: u32,
+    ) -> ConductorApiResult<bool> {
+        self.conductor_handle
+            .use_cap_grant(&self.cell_id, grant_header, max_uses)
+            .await
+    }
 }
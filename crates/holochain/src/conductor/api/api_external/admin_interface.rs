@@ -201,6 +201,10 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                 let dna_list = self.conductor_handle.list_dnas().await?;
                 Ok(AdminResponse::DnasListed(dna_list))
             }
+            ListZomeFunctions { dna_hash } => {
+                let zomes = self.conductor_handle.list_zome_functions(&dna_hash).await?;
+                Ok(AdminResponse::ZomeFunctionsListed(zomes))
+            }
             GenerateAgentPubKey => {
                 let agent_pub_key = self
                     .conductor_handle
@@ -249,23 +253,48 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                     .await?;
                 Ok(AdminResponse::AppDeactivated)
             }
-            AttachAppInterface { port } => {
-                let port = port.unwrap_or(0);
+            AttachAppInterface { port, driver } => {
+                let driver =
+                    driver.unwrap_or_else(|| InterfaceDriver::websocket(port.unwrap_or(0)));
+                let path = match &driver {
+                    InterfaceDriver::UnixSocket { path, .. } => Some(path.clone()),
+                    _ => None,
+                };
                 let port = self
                     .conductor_handle
                     .clone()
-                    .add_app_interface(port)
+                    .add_app_interface(driver)
                     .await?;
-                Ok(AdminResponse::AppInterfaceAttached { port })
+                Ok(AdminResponse::AppInterfaceAttached {
+                    port: path.is_none().then(|| port),
+                    path,
+                })
             }
             ListAppInterfaces => {
                 let interfaces = self.conductor_handle.list_app_interfaces().await?;
                 Ok(AdminResponse::AppInterfacesListed(interfaces))
             }
+            IssueAppAuthenticationToken {
+                installed_app_id,
+                expiry_seconds,
+            } => {
+                let (token, expires_at) = self
+                    .conductor_handle
+                    .issue_app_authentication_token(
+                        installed_app_id,
+                        expiry_seconds.unwrap_or(DEFAULT_APP_AUTHENTICATION_TOKEN_EXPIRY_SECONDS),
+                    )
+                    .await?;
+                Ok(AdminResponse::AppAuthenticationTokenIssued { token, expires_at })
+            }
             DumpState { cell_id } => {
                 let state = self.conductor_handle.dump_cell_state(&cell_id).await?;
                 Ok(AdminResponse::StateDumped(state))
             }
+            CheckCellHealth { cell_id } => {
+                let health = self.conductor_handle.check_cell_health(&cell_id).await?;
+                Ok(AdminResponse::CellHealthChecked(health))
+            }
             AddAgentInfo { agent_infos } => {
                 self.conductor_handle.add_agent_infos(agent_infos).await?;
                 Ok(AdminResponse::AgentInfoAdded)
@@ -274,6 +303,35 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                 let r = self.conductor_handle.get_agent_infos(cell_id).await?;
                 Ok(AdminResponse::AgentInfoRequested(r))
             }
+            ListAgentKeys => {
+                let keys = self.conductor_handle.list_agent_keys().await?;
+                Ok(AdminResponse::AgentKeysListed(keys))
+            }
+            RotateAgentKey { agent_key } => {
+                let new_key = self.conductor_handle.rotate_agent_key(&agent_key).await?;
+                Ok(AdminResponse::AgentKeyRotated(new_key))
+            }
+            ExportSourceChain { cell_id } => {
+                let archive = self.conductor_handle.export_source_chain(&cell_id).await?;
+                Ok(AdminResponse::SourceChainExported(archive))
+            }
+            ImportSourceChain { archive } => {
+                let cell_id = self.conductor_handle.import_source_chain(archive).await?;
+                Ok(AdminResponse::SourceChainImported(cell_id))
+            }
+            ExportKeystore { passphrase } => {
+                let backup = self.conductor_handle.export_keystore(passphrase).await?;
+                Ok(AdminResponse::KeystoreExported(backup))
+            }
+            ImportKeystore {
+                archive,
+                passphrase,
+            } => {
+                self.conductor_handle
+                    .import_keystore(archive, passphrase)
+                    .await?;
+                Ok(AdminResponse::KeystoreImported)
+            }
         }
     }
 }
@@ -327,8 +385,14 @@ impl InterfaceApi for RealAdminInterfaceApi {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::conductor::config::ConductorConfig;
     use crate::conductor::Conductor;
+    use ::fixt::prelude::*;
     use anyhow::Result;
+    use holo_hash::fixt::AgentPubKeyFixturator;
+    use holochain_keystore::backup::restore_lair_dir;
+    use holochain_keystore::backup::staged_restore_path;
+    use holochain_keystore::test_keystore::spawn_test_keystore;
     use holochain_lmdb::test_utils::test_environments;
     use holochain_types::app::InstallAppDnaPayload;
     use holochain_types::test_utils::fake_agent_pubkey_1;
@@ -384,6 +448,20 @@ mod test {
         let expects = vec![dna_hash.clone()];
         assert_matches!(dna_list, AdminResponse::DnasListed(a) if a == expects);
 
+        let zome_fns = admin_api
+            .handle_admin_request(AdminRequest::ListZomeFunctions {
+                dna_hash: dna_hash.clone(),
+            })
+            .await;
+        assert_matches!(
+            zome_fns,
+            AdminResponse::ZomeFunctionsListed(zomes)
+            if zomes.len() == 1
+                && zomes[0].1.zome_fns.contains(&"foo".into())
+                && zomes[0].1.callbacks.contains(&"init".into())
+                && !zomes[0].1.zome_fns.contains(&"init".into())
+        );
+
         // register by hash
         let hash_payload = RegisterDnaPayload {
             uid: None,
@@ -584,4 +662,276 @@ mod test {
             .ok();
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn list_and_rotate_agent_keys() -> Result<()> {
+        observability::test_run().ok();
+        let envs = test_environments();
+        let handle = Conductor::builder().test(&envs).await?;
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        let admin_api = RealAdminInterfaceApi::new(handle.clone());
+
+        let old_key = match admin_api
+            .handle_admin_request(AdminRequest::GenerateAgentPubKey)
+            .await
+        {
+            AdminResponse::AgentPubKeyGenerated(key) => key,
+            r => panic!("Unexpected response {:?}", r),
+        };
+        let new_key = match admin_api
+            .handle_admin_request(AdminRequest::RotateAgentKey {
+                agent_key: old_key.clone(),
+            })
+            .await
+        {
+            AdminResponse::AgentKeyRotated(key) => key,
+            r => panic!("Unexpected response {:?}", r),
+        };
+
+        let keys = match admin_api
+            .handle_admin_request(AdminRequest::ListAgentKeys)
+            .await
+        {
+            AdminResponse::AgentKeysListed(keys) => keys,
+            r => panic!("Unexpected response {:?}", r),
+        };
+        let old_info = keys.iter().find(|k| k.agent_key == old_key).unwrap();
+        assert_eq!(old_info.superseded_by, Some(new_key.clone()));
+        assert_eq!(old_info.supersedes, None);
+        let new_info = keys.iter().find(|k| k.agent_key == new_key).unwrap();
+        assert_eq!(new_info.supersedes, Some(old_key.clone()));
+        assert_eq!(new_info.superseded_by, None);
+
+        // A key can only be rotated once
+        assert_matches!(
+            admin_api
+                .handle_admin_request(AdminRequest::RotateAgentKey { agent_key: old_key })
+                .await,
+            AdminResponse::Error(_)
+        );
+        // And only if it's in the keystore
+        assert_matches!(
+            admin_api
+                .handle_admin_request(AdminRequest::RotateAgentKey {
+                    agent_key: fixt!(AgentPubKey),
+                })
+                .await,
+            AdminResponse::Error(_)
+        );
+
+        handle.shutdown().await;
+        tokio::time::timeout(std::time::Duration::from_secs(1), shutdown)
+            .await
+            .ok();
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn export_and_import_keystore() -> Result<()> {
+        observability::test_run().ok();
+        let lair_dir = tempdir::TempDir::new("lair").unwrap();
+        std::fs::write(lair_dir.path().join("store.sqlite3"), b"secret keys").unwrap();
+        let envs = test_environments();
+        let handle = Conductor::builder()
+            .config(ConductorConfig {
+                keystore_path: Some(lair_dir.path().to_path_buf()),
+                ..Default::default()
+            })
+            .test(&envs)
+            .await?;
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        let admin_api = RealAdminInterfaceApi::new(handle.clone());
+
+        let archive = match admin_api
+            .handle_admin_request(AdminRequest::ExportKeystore {
+                passphrase: "hunter2".into(),
+            })
+            .await
+        {
+            AdminResponse::KeystoreExported(archive) => archive,
+            r => panic!("Unexpected response {:?}", r),
+        };
+        let restored = tempdir::TempDir::new("lair_restored").unwrap();
+        restore_lair_dir(&archive, "hunter2", Some(restored.path()))?;
+        assert_eq!(
+            std::fs::read(restored.path().join("store.sqlite3"))?,
+            b"secret keys"
+        );
+
+        // The test keystore already holds agent keys, which the backup would replace
+        assert_matches!(
+            admin_api
+                .handle_admin_request(AdminRequest::ImportKeystore {
+                    archive: archive.clone(),
+                    passphrase: "hunter2".into(),
+                })
+                .await,
+            AdminResponse::Error(_)
+        );
+        assert!(!staged_restore_path(Some(lair_dir.path())).exists());
+
+        handle.shutdown().await;
+        tokio::time::timeout(std::time::Duration::from_secs(1), shutdown)
+            .await
+            .ok();
+
+        // A conductor without agent keys stages the backup to replace its keystore,
+        // next to the keystore's directory
+        let new_dir = tempdir::TempDir::new("new_lair").unwrap();
+        let new_lair_dir = new_dir.path().join("keystore");
+        let envs = test_environments();
+        let handle = Conductor::builder()
+            .config(ConductorConfig {
+                keystore_path: Some(new_lair_dir.clone()),
+                ..Default::default()
+            })
+            .with_keystore(spawn_test_keystore().await?)
+            .test(&envs)
+            .await?;
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        let admin_api = RealAdminInterfaceApi::new(handle.clone());
+
+        assert_matches!(
+            admin_api
+                .handle_admin_request(AdminRequest::ImportKeystore {
+                    archive: archive.clone(),
+                    passphrase: "wrong".into(),
+                })
+                .await,
+            AdminResponse::Error(_)
+        );
+        assert_matches!(
+            admin_api
+                .handle_admin_request(AdminRequest::ImportKeystore {
+                    archive,
+                    passphrase: "hunter2".into(),
+                })
+                .await,
+            AdminResponse::KeystoreImported
+        );
+        assert_eq!(
+            std::fs::read(staged_restore_path(Some(&new_lair_dir)).join("store.sqlite3"))?,
+            b"secret keys"
+        );
+
+        handle.shutdown().await;
+        tokio::time::timeout(std::time::Duration::from_secs(1), shutdown)
+            .await
+            .ok();
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn export_and_import_source_chain() -> Result<()> {
+        observability::test_run().ok();
+        let uid = Uuid::new_v4();
+        let dna = fake_dna_zomes(
+            &uid.to_string(),
+            vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+        );
+        let (dna_path, _tempdir) = write_fake_dna_file(dna.clone()).await.unwrap();
+        let cell_id = CellId::new(dna.dna_hash().clone(), fake_agent_pubkey_1());
+
+        async fn install_and_activate(
+            admin_api: &RealAdminInterfaceApi,
+            dna_path: std::path::PathBuf,
+        ) {
+            let dna_hash = match admin_api
+                .handle_admin_request(AdminRequest::RegisterDna(Box::new(RegisterDnaPayload {
+                    uid: None,
+                    properties: None,
+                    source: DnaSource::Path(dna_path),
+                })))
+                .await
+            {
+                AdminResponse::DnaRegistered(hash) => hash,
+                r => panic!("Unexpected response {:?}", r),
+            };
+            let payload = InstallAppPayload {
+                dnas: vec![InstallAppDnaPayload::hash_only(dna_hash, "".to_string())],
+                installed_app_id: "test".to_string(),
+                agent_key: fake_agent_pubkey_1(),
+            };
+            assert_matches!(
+                admin_api
+                    .handle_admin_request(AdminRequest::InstallApp(Box::new(payload)))
+                    .await,
+                AdminResponse::AppInstalled(_)
+            );
+            assert_matches!(
+                admin_api
+                    .handle_admin_request(AdminRequest::ActivateApp {
+                        installed_app_id: "test".to_string(),
+                    })
+                    .await,
+                AdminResponse::AppActivated
+            );
+        }
+
+        // Export the chain of a cell which has been through genesis
+        let envs = test_environments();
+        let handle = Conductor::builder().test(&envs).await?;
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        let admin_api = RealAdminInterfaceApi::new(handle.clone());
+        install_and_activate(&admin_api, dna_path.clone()).await;
+
+        let archive = match admin_api
+            .handle_admin_request(AdminRequest::ExportSourceChain {
+                cell_id: cell_id.clone(),
+            })
+            .await
+        {
+            AdminResponse::SourceChainExported(archive) => archive,
+            r => panic!("Unexpected response {:?}", r),
+        };
+        assert_eq!(archive.archive.agent_pubkey, *cell_id.agent_pubkey());
+        assert_eq!(archive.archive.elements.len(), 3);
+
+        // The cell is installed here, so its chain can't be imported
+        assert_matches!(
+            admin_api
+                .handle_admin_request(AdminRequest::ImportSourceChain {
+                    archive: archive.clone(),
+                })
+                .await,
+            AdminResponse::Error(_)
+        );
+        handle.shutdown().await;
+        tokio::time::timeout(std::time::Duration::from_secs(1), shutdown)
+            .await
+            .ok();
+
+        // Import it into a fresh conductor with the same agent key
+        let envs = test_environments();
+        let handle = Conductor::builder().test(&envs).await?;
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        let admin_api = RealAdminInterfaceApi::new(handle.clone());
+        assert_matches!(
+            admin_api
+                .handle_admin_request(AdminRequest::ImportSourceChain {
+                    archive: archive.clone(),
+                })
+                .await,
+            AdminResponse::SourceChainImported(id) if id == cell_id
+        );
+        install_and_activate(&admin_api, dna_path).await;
+
+        // Genesis was skipped, so the chain is exactly the imported one
+        let reexported = match admin_api
+            .handle_admin_request(AdminRequest::ExportSourceChain {
+                cell_id: cell_id.clone(),
+            })
+            .await
+        {
+            AdminResponse::SourceChainExported(archive) => archive,
+            r => panic!("Unexpected response {:?}", r),
+        };
+        assert_eq!(reexported.archive, archive.archive);
+
+        handle.shutdown().await;
+        tokio::time::timeout(std::time::Duration::from_secs(1), shutdown)
+            .await
+            .ok();
+        Ok(())
+    }
 }
@@ -1,4 +1,5 @@
 use super::InterfaceApi;
+use crate::conductor::api::error::ConductorApiError;
 use crate::conductor::api::error::ConductorApiResult;
 use crate::conductor::api::error::ExternalApiWireError;
 use crate::conductor::api::error::SerializationError;
@@ -8,8 +9,12 @@ use crate::conductor::state::AppInterfaceId;
 use crate::conductor::ConductorHandle;
 
 use holochain_serialized_bytes::prelude::*;
-
 use holochain_types::prelude::*;
+use holochain_types::signal::Signal;
+use holochain_types::signal::SystemSignal;
+use parking_lot::RwLock;
+use std::collections::HashSet;
+use std::sync::Arc;
 
 pub use holochain_conductor_api::*;
 
@@ -22,6 +27,16 @@ pub trait AppInterfaceApi: 'static + Send + Sync + Clone {
         request: AppRequest,
     ) -> ConductorApiResult<AppResponse>;
 
+    /// A copy of this api for a new connection to the interface.
+    /// The connection must authenticate before it can make any other request.
+    fn for_connection(&self) -> Self;
+
+    /// Whether a signal may be sent across the connection using this api
+    async fn is_signal_permitted(&self, signal: &Signal) -> bool;
+
+    /// Report that a signal was sent across the connection using this api
+    fn signal_sent(&self, signal: &Signal);
+
     // -- provided -- //
 
     /// Deal with error cases produced by `handle_app_request_inner`
@@ -41,15 +56,128 @@ pub trait AppInterfaceApi: 'static + Send + Sync + Clone {
 pub struct RealAppInterfaceApi {
     conductor_handle: ConductorHandle,
     interface_id: AppInterfaceId,
+    access: Arc<RwLock<AppAccess>>,
+}
+
+/// Which Apps a [RealAppInterfaceApi] can access
+#[derive(Clone, Debug)]
+enum AppAccess {
+    /// Any App. Used in process, where there is no connection to authenticate.
+    Unrestricted,
+    /// No App, until the connection authenticates
+    Unauthenticated,
+    /// The App a connection authenticated with a token for.
+    /// The cells of the App are cached, and looked up again whenever the
+    /// conductor state has changed since, so that access follows the App
+    /// through being activated, deactivated, uninstalled and reinstalled.
+    App {
+        installed_app_id: InstalledAppId,
+        expires_at: Timestamp,
+        cell_ids: HashSet<CellId>,
+        /// The [crate::conductor::handle::ConductorHandleT::state_version] the cells
+        /// were looked up at
+        state_version: u64,
+    },
+}
+
+impl AppAccess {
+    /// Check that this access is still valid, returning the App it is
+    /// restricted to, if any
+    fn check(&self) -> ConductorApiResult<Option<&InstalledAppId>> {
+        match self {
+            AppAccess::Unrestricted => Ok(None),
+            AppAccess::Unauthenticated => Err(ConductorApiError::AppAuthenticationFailed(
+                "The connection must authenticate with an AppRequest::Authenticate before making other requests".into(),
+            )),
+            AppAccess::App { expires_at, .. } if *expires_at <= timestamp::now() => Err(
+                ConductorApiError::AppAuthenticationFailed("The token has expired".into()),
+            ),
+            AppAccess::App {
+                installed_app_id, ..
+            } => Ok(Some(installed_app_id)),
+        }
+    }
 }
 
 impl RealAppInterfaceApi {
-    /// Create a new instance from a shared Conductor reference
+    /// Create a new instance from a shared Conductor reference.
+    /// The instance can access every App: use [AppInterfaceApi::for_connection]
+    /// to get one which requires authentication.
     pub fn new(conductor_handle: ConductorHandle, interface_id: AppInterfaceId) -> Self {
         Self {
             conductor_handle,
             interface_id,
+            access: Arc::new(RwLock::new(AppAccess::Unrestricted)),
+        }
+    }
+
+    /// Restrict this api to the App the token was issued for
+    async fn authenticate(&self, token: AppAuthenticationToken) -> ConductorApiResult<AppResponse> {
+        let (installed_app_id, expires_at) = self
+            .conductor_handle
+            .authenticate_app_token(&token)
+            .await
+            .ok_or_else(|| {
+                ConductorApiError::AppAuthenticationFailed(
+                    "The token is not valid or has expired".into(),
+                )
+            })?;
+        let state_version = self.conductor_handle.state_version();
+        let cell_ids = self.app_cell_ids(&installed_app_id).await?;
+        *self.access.write() = AppAccess::App {
+            installed_app_id: installed_app_id.clone(),
+            expires_at,
+            cell_ids,
+            state_version,
+        };
+        Ok(AppResponse::Authenticated { installed_app_id })
+    }
+
+    /// Whether the App this api is restricted to has the cell, looking its
+    /// cells up again if the conductor state has changed since they were
+    async fn app_has_cell(&self, cell_id: &CellId) -> ConductorApiResult<bool> {
+        let current_version = self.conductor_handle.state_version();
+        let installed_app_id = match &*self.access.read() {
+            AppAccess::App {
+                cell_ids,
+                state_version,
+                ..
+            } if *state_version == current_version => return Ok(cell_ids.contains(cell_id)),
+            AppAccess::App {
+                installed_app_id, ..
+            } => installed_app_id.clone(),
+            _ => return Ok(false),
+        };
+        let new_cell_ids = self.app_cell_ids(&installed_app_id).await?;
+        let has_cell = new_cell_ids.contains(cell_id);
+        if let AppAccess::App {
+            cell_ids,
+            state_version,
+            ..
+        } = &mut *self.access.write()
+        {
+            *cell_ids = new_cell_ids;
+            *state_version = current_version;
         }
+        Ok(has_cell)
+    }
+
+    /// The cells of an App as it is installed now, or none if it isn't
+    async fn app_cell_ids(
+        &self,
+        installed_app_id: &InstalledAppId,
+    ) -> ConductorApiResult<HashSet<CellId>> {
+        Ok(self
+            .conductor_handle
+            .get_app_info(installed_app_id)
+            .await?
+            .map(|app| {
+                app.cell_data
+                    .into_iter()
+                    .map(|cell| cell.into_id())
+                    .collect()
+            })
+            .unwrap_or_default())
     }
 }
 
@@ -60,7 +188,33 @@ impl AppInterfaceApi for RealAppInterfaceApi {
         &self,
         request: AppRequest,
     ) -> ConductorApiResult<AppResponse> {
+        if let AppRequest::Authenticate { token } = request {
+            return self.authenticate(token).await;
+        }
+        let restriction = self.access.read().check()?.cloned();
+        if let Some(authenticated_app_id) = restriction {
+            match &request {
+                AppRequest::AppInfo { installed_app_id }
+                    if *installed_app_id != authenticated_app_id =>
+                {
+                    return Err(ConductorApiError::AppAuthenticationFailed(format!(
+                        "The connection is not authorized to access the app {}",
+                        installed_app_id
+                    )));
+                }
+                AppRequest::ZomeCall(call) | AppRequest::ZomeCallInvocation(call) => {
+                    if !self.app_has_cell(&call.cell_id).await? {
+                        return Err(ConductorApiError::AppAuthenticationFailed(format!(
+                            "The connection is not authorized to call the cell {:?}",
+                            call.cell_id
+                        )));
+                    }
+                }
+                _ => (),
+            }
+        }
         match request {
+            AppRequest::Authenticate { .. } => unreachable!("Handled above"),
             AppRequest::AppInfo { installed_app_id } => Ok(AppResponse::AppInfo(
                 self.conductor_handle
                     .get_app_info(&installed_app_id)
@@ -101,6 +255,32 @@ impl AppInterfaceApi for RealAppInterfaceApi {
             AppRequest::Crypto(_) => Ok(AppResponse::Unimplemented(request)),
         }
     }
+
+    fn for_connection(&self) -> Self {
+        Self {
+            conductor_handle: self.conductor_handle.clone(),
+            interface_id: self.interface_id.clone(),
+            access: Arc::new(RwLock::new(AppAccess::Unauthenticated)),
+        }
+    }
+
+    async fn is_signal_permitted(&self, signal: &Signal) -> bool {
+        let cell_id = match signal {
+            Signal::App(cell_id, _) => cell_id,
+            Signal::System(SystemSignal::PostCommitFailed { cell_id, .. }) => cell_id,
+            Signal::System(SystemSignal::ChainForkDetected { cell_id, .. }) => cell_id,
+            Signal::System(SystemSignal::Test(_)) => return self.access.read().check().is_ok(),
+        };
+        let restricted = match self.access.read().check() {
+            Ok(restriction) => restriction.is_some(),
+            Err(_) => return false,
+        };
+        !restricted || self.app_has_cell(cell_id).await.unwrap_or(false)
+    }
+
+    fn signal_sent(&self, signal: &Signal) {
+        self.conductor_handle.signal_receipts().received(signal)
+    }
 }
 
 #[async_trait::async_trait]
@@ -59,6 +59,10 @@ pub enum ConductorApiError {
     #[error(transparent)]
     WorkflowError(#[from] Box<WorkflowError>),
 
+    /// RibosomeError
+    #[error(transparent)]
+    RibosomeError(#[from] RibosomeError),
+
     /// DnaError
     #[error("DnaError: {0}")]
     DnaError(#[from] holochain_types::dna::DnaError),
@@ -94,6 +98,11 @@ pub enum ConductorApiError {
 
     #[error(transparent)]
     JsonDumpError(#[from] serde_json::Error),
+
+    /// An app interface connection tried to do something its
+    /// authentication token does not allow
+    #[error("App interface authentication failed: {0}")]
+    AppAuthenticationFailed(String),
 }
 
 /// All the serialization errors that can occur
@@ -117,6 +126,9 @@ impl From<ConductorApiError> for ExternalApiWireError {
     fn from(err: ConductorApiError) -> Self {
         match err {
             ConductorApiError::DnaReadError(e) => ExternalApiWireError::DnaReadError(e),
+            ConductorApiError::AppAuthenticationFailed(e) => {
+                ExternalApiWireError::AppAuthenticationFailed(e)
+            }
             e => ExternalApiWireError::internal(e),
         }
     }
@@ -130,7 +142,12 @@ impl From<SerializationError> for ExternalApiWireError {
 
 impl From<RibosomeError> for ExternalApiWireError {
     fn from(e: RibosomeError) -> Self {
-        ExternalApiWireError::RibosomeError(e.to_string())
+        match e {
+            RibosomeError::ZomeCallLimitExceeded(..) => {
+                ExternalApiWireError::ZomeCallLimitExceeded(e.to_string())
+            }
+            e => ExternalApiWireError::RibosomeError(e.to_string()),
+        }
     }
 }
 
@@ -3,6 +3,8 @@
 use super::CellConductorApiT;
 use super::ZomeCall;
 use crate::conductor::api::error::ConductorApiResult;
+use crate::conductor::config::WasmConfig;
+use crate::conductor::dpki::DpkiImpl;
 use crate::conductor::interface::SignalBroadcaster;
 use crate::core::workflow::ZomeCallResult;
 use async_trait::async_trait;
@@ -10,6 +12,7 @@ use holo_hash::DnaHash;
 use holochain_keystore::KeystoreSender;
 use holochain_types::prelude::*;
 use mockall::mock;
+use std::sync::Arc;
 
 // Unfortunate workaround to get mockall to work with async_trait, due to the complexity of each.
 // The mock! expansion here creates mocks on a non-async version of the API, and then the actual trait is implemented
@@ -26,7 +29,8 @@ mock! {
             call: ZomeCall,
         ) -> ConductorApiResult<ZomeCallResult>;
 
-        fn sync_dpki_request(&self, method: String, args: String) -> ConductorApiResult<String>;
+        fn mock_dpki(&self) -> Option<DpkiImpl>;
+        fn mock_wasm_config(&self) -> Arc<WasmConfig>;
 
         fn mock_keystore(&self) -> &KeystoreSender;
         fn mock_signal_broadcaster(&self) -> SignalBroadcaster;
@@ -56,8 +60,12 @@ impl CellConductorApiT for MockCellConductorApi {
         self.sync_call_zome(cell_id, call)
     }
 
-    async fn dpki_request(&self, method: String, args: String) -> ConductorApiResult<String> {
-        self.sync_dpki_request(method, args)
+    fn dpki(&self) -> Option<DpkiImpl> {
+        self.mock_dpki()
+    }
+
+    async fn wasm_config(&self) -> Arc<WasmConfig> {
+        self.mock_wasm_config()
     }
 
     fn keystore(&self) -> &KeystoreSender {
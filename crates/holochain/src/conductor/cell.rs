@@ -38,10 +38,12 @@ use futures::future::FutureExt;
 use hash_type::AnyDht;
 use holo_hash::*;
 use holochain_cascade::authority;
+use holochain_keystore::private_entry_key::PrivateEntryKey;
 use holochain_lmdb::db::GetDb;
 use holochain_lmdb::env::EnvironmentRead;
 use holochain_lmdb::env::EnvironmentWrite;
 use holochain_lmdb::env::ReadManager;
+use holochain_lmdb::error::DatabaseError;
 use holochain_serialized_bytes::SerializedBytes;
 use holochain_state::prelude::*;
 use holochain_types::prelude::*;
@@ -122,6 +124,13 @@ impl Cell {
     ) -> CellResult<(Self, InitialQueueTriggers)> {
         let conductor_api = CellConductorApi::new(conductor_handle.clone(), id.clone());
 
+        // Derive the key which private entries are encrypted with up front,
+        // so that reading and writing them doesn't have to wait on the keystore
+        let private_entry_key = PrivateEntryKey::for_agent(&env.keystore(), id.agent_pubkey())
+            .await
+            .map_err(DatabaseError::from)?;
+        env.set_private_entry_key(private_entry_key);
+
         // check if genesis has been run
         let has_genesis = {
             // check if genesis ran on source chain buf
@@ -438,6 +447,8 @@ impl Cell {
             ops,
             Some(from_agent),
             request_validation_receipt,
+            self.id.dna_hash(),
+            self.conductor_api.dpki(),
         )
         .await
         .map_err(Box::new)
@@ -568,33 +579,33 @@ impl Cell {
     async fn handle_validation_receipt(&self, receipt: SerializedBytes) -> CellResult<()> {
         let receipt: SignedValidationReceipt = receipt.try_into()?;
 
-        // Add to authored
-        let db = self.env.get_db(&*AUTHORED_DHT_OPS)?;
-        let mut authored_dht_ops: AuthoredDhtOpsStore =
-            KvBufFresh::new(self.env.clone().into(), db);
-        match authored_dht_ops.get(&receipt.receipt.dht_op_hash)? {
-            Some(mut auth) => {
-                auth.receipt_count += 1;
-                authored_dht_ops.put(receipt.receipt.dht_op_hash.clone(), auth)?;
-            }
-            None => {
-                warn!(
-                    "Got receipt {:?} but it's missing from authored db so throwing receipt away",
-                    receipt
-                );
-                return Err(CellError::OpMissingForReceipt(receipt.receipt.dht_op_hash));
-            }
-        }
-
-        // Add to receipts db
+        let op_hash = receipt.receipt.dht_op_hash.clone();
+        let authored_dht_ops: AuthoredDhtOpsStore = KvBufFresh::new(
+            self.env.clone().into(),
+            self.env.get_db(&*AUTHORED_DHT_OPS)?,
+        );
         let mut receipts_db = ValidationReceiptsBuf::new(&self.env)?;
-        receipts_db.add_if_unique(receipt)?;
 
-        // Write to db
+        // Write to db. The receipt count is read and incremented within the
+        // write transaction, so a receipt arriving at the same time can't
+        // overwrite it.
         self.env.with_commit(|w| {
-            authored_dht_ops.flush_to_txn_ref(w)?;
+            let authored = authored_dht_ops.store();
+            let mut auth = match authored.get(w, &op_hash)? {
+                Some(auth) => auth,
+                None => {
+                    warn!(
+                        "Got receipt {:?} but it's missing from authored db so throwing receipt away",
+                        receipt
+                    );
+                    return Err(CellError::OpMissingForReceipt(op_hash.clone()));
+                }
+            };
+            auth.receipt_count += 1;
+            authored.put(w, &op_hash, &auth)?;
+            receipts_db.add_if_unique(receipt)?;
             receipts_db.flush_to_txn_ref(w)?;
-            DatabaseResult::Ok(())
+            CellResult::Ok(())
         })?;
 
         Ok(())
@@ -754,7 +765,8 @@ impl Cell {
         let dna_def = dna_file.dna_def().clone();
 
         // Get the ribosome
-        let ribosome = RealRibosome::new(dna_file);
+        let ribosome =
+            RealRibosome::new(dna_file).with_wasm_config(conductor_api.wasm_config().await);
 
         // Run the workflow
         let args = InitializeZomesWorkflowArgs { dna_def, ribosome };
@@ -813,7 +825,9 @@ impl Cell {
     // TODO: reevaluate once Workflows are fully implemented (after B-01567)
     pub(crate) async fn get_ribosome(&self) -> CellResult<RealRibosome> {
         match self.conductor_api.get_dna(self.dna_hash()).await {
-            Some(dna) => Ok(RealRibosome::new(dna)),
+            Some(dna) => {
+                Ok(RealRibosome::new(dna).with_wasm_config(self.conductor_api.wasm_config().await))
+            }
             None => Err(DnaError::DnaMissing(self.dna_hash().to_owned()).into()),
         }
     }
@@ -12,17 +12,20 @@ use super::config::AdminInterfaceConfig;
 use super::config::InterfaceDriver;
 use super::dna_store::DnaDefBuf;
 use super::dna_store::RealDnaStore;
+use super::dpki::ChainHeads;
 use super::entry_def_store::get_entry_defs;
 use super::entry_def_store::EntryDefBuf;
 use super::error::ConductorError;
 use super::error::CreateAppError;
 use super::handle::ConductorHandleImpl;
 use super::interface::error::InterfaceResult;
+use super::interface::http::spawn_http_app_interface_task;
 use super::interface::websocket::spawn_admin_interface_task;
 use super::interface::websocket::spawn_app_interface_task;
 use super::interface::websocket::spawn_websocket_listener;
 use super::interface::websocket::SIGNAL_BUFFER_SIZE;
 use super::interface::SignalBroadcaster;
+use super::interface::SignalReceipts;
 use super::manager::keep_alive_task;
 use super::manager::spawn_task_manager;
 use super::manager::ManagedTaskAdd;
@@ -34,6 +37,7 @@ use super::p2p_store::inject_agent_infos;
 use super::paths::EnvironmentRootPath;
 use super::state::AppInterfaceId;
 use super::state::ConductorState;
+use super::state::SupersededAgentKey;
 use super::CellError;
 use super::{api::CellConductorApi, state::AppInterfaceConfig};
 use super::{api::CellConductorApiT, interface::AppInterfaceRuntime};
@@ -41,9 +45,13 @@ use super::{api::RealAdminInterfaceApi, manager::TaskManagerClient};
 use super::{api::RealAppInterfaceApi, p2p_store};
 use crate::conductor::cell::Cell;
 use crate::conductor::config::ConductorConfig;
+use crate::conductor::config::WasmConfig;
 use crate::conductor::error::ConductorResult;
 use crate::conductor::handle::ConductorHandle;
 use crate::core::queue_consumer::InitialQueueTriggers;
+use crate::core::ribosome::instance_pool;
+use crate::core::workflow::health_check_workflow;
+use crate::core::workflow::health_check_workflow::HealthCheckWorkspace;
 use crate::core::workflow::integrate_dht_ops_workflow;
 use crate::{
     conductor::api::error::ConductorApiResult, core::ribosome::real_ribosome::RealRibosome,
@@ -54,9 +62,13 @@ use futures::future;
 use futures::future::TryFutureExt;
 use futures::stream::StreamExt;
 use holo_hash::DnaHash;
+use holochain_conductor_api::AgentKeyInfo;
+use holochain_conductor_api::AppAuthenticationToken;
 use holochain_conductor_api::JsonDump;
 use holochain_keystore::lair_keystore::spawn_lair_keystore;
+use holochain_keystore::private_entry_key::PrivateEntryKey;
 use holochain_keystore::test_keystore::spawn_test_keystore;
+use holochain_keystore::KeystoreError;
 use holochain_keystore::KeystoreSender;
 use holochain_keystore::KeystoreSenderExt;
 use holochain_lmdb::buffer::BufferedStore;
@@ -69,11 +81,15 @@ use holochain_lmdb::env::ReadManager;
 use holochain_lmdb::exports::SingleStore;
 use holochain_lmdb::fresh_reader;
 use holochain_lmdb::prelude::*;
+use holochain_state::chain_forks::ChainForks;
+use holochain_state::source_chain::SignedSourceChainArchive;
 use holochain_state::source_chain::SourceChainBuf;
 use holochain_state::wasm::WasmBuf;
 use holochain_types::prelude::*;
 use kitsune_p2p::agent_store::AgentInfoSigned;
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::*;
@@ -135,6 +151,21 @@ where
     /// Collection app interface data, keyed by id
     app_interfaces: HashMap<AppInterfaceId, AppInterfaceRuntime>,
 
+    /// The tokens which authenticate connections to app interfaces,
+    /// with the App each gives access to and when it expires.
+    /// These are not persisted, so are all revoked by a restart.
+    app_authentication_tokens: HashMap<AppAuthenticationToken, (InstalledAppId, Timestamp)>,
+
+    /// Incremented every time the conductor state changes, so that what's
+    /// read from the state can be cached until it does.
+    /// Shared with the [ConductorHandle], which can read it without a lock.
+    state_version: Arc<AtomicU64>,
+
+    /// The signals waiting to be received by a client of an app interface.
+    /// Shared with the [ConductorHandle], so that connections can report the
+    /// signals they send without a lock.
+    signal_receipts: SignalReceipts,
+
     /// The channels and handles needed to interact with the task_manager task.
     /// If this is None, then the task manager has not yet been initialized.
     task_manager: Option<TaskManagerClient>,
@@ -145,6 +176,13 @@ where
     /// Access to private keys for signing and encryption.
     keystore: KeystoreSender,
 
+    /// The directory of the lair keystore, for backing it up.
+    /// This is None if the conductor doesn't use lair, e.g. in tests.
+    lair_dir: Option<std::path::PathBuf>,
+
+    /// How the wasm of this conductor's zomes is compiled and pooled
+    wasm_config: Arc<WasmConfig>,
+
     /// The root environment directory where all environments are created
     root_env_dir: EnvironmentRootPath,
 
@@ -206,6 +244,13 @@ where
         &mut self.dna_store
     }
 
+    /// The on-disk cache of remote bundle resources, keyed by hash
+    pub(super) fn bundle_cache(&self) -> mr_bundle::ResourceCache {
+        mr_bundle::ResourceCache::new(
+            std::path::PathBuf::from(self.root_env_dir.clone()).join("bundle_cache"),
+        )
+    }
+
     /// Broadcasts the shutdown signal to all managed tasks.
     /// To actually wait for these tasks to complete, be sure to
     /// `take_shutdown_handle` to await for completion.
@@ -256,19 +301,21 @@ where
             let admin_api = admin_api.clone();
             let stop_tx = stop_tx.clone();
             async move {
-                match driver {
-                    InterfaceDriver::Websocket { port } => {
-                        let (listener_handle, listener) = spawn_websocket_listener(port).await?;
-                        let port = listener_handle.local_addr().port().unwrap_or(port);
-                        let handle: ManagedTaskHandle = spawn_admin_interface_task(
-                            listener_handle,
-                            listener,
-                            admin_api.clone(),
-                            stop_tx.subscribe(),
-                        )?;
-                        InterfaceResult::Ok((port, handle))
+                let (listener_handle, listener) = spawn_websocket_listener(&driver).await?;
+                let port = match &driver {
+                    InterfaceDriver::Websocket { port, .. } => {
+                        Some(listener_handle.local_addr().port().unwrap_or(*port))
                     }
-                }
+                    InterfaceDriver::UnixSocket { .. } | InterfaceDriver::Http { .. } => None,
+                };
+                let name = format!("admin interface, {}", listener_handle.local_addr());
+                let handle: ManagedTaskHandle = spawn_admin_interface_task(
+                    listener_handle,
+                    listener,
+                    admin_api.clone(),
+                    stop_tx.subscribe(),
+                )?;
+                InterfaceResult::Ok((port, name, handle))
             }
         };
 
@@ -294,13 +341,10 @@ where
             .await?;
 
             // Now that tasks are spawned, register them with the TaskManager
-            for (port, handle) in handles {
-                ports.push(port);
-                self.manage_task(ManagedTaskAdd::ignore(
-                    handle,
-                    &format!("admin interface, port {}", port),
-                ))
-                .await?
+            for (port, name, handle) in handles {
+                ports.extend(port);
+                self.manage_task(ManagedTaskAdd::ignore(handle, &name))
+                    .await?
             }
             for p in ports {
                 self.add_admin_port(p);
@@ -311,15 +355,33 @@ where
 
     pub(super) async fn add_app_interface_via_handle(
         &mut self,
-        port: either::Either<u16, AppInterfaceId>,
+        driver: either::Either<InterfaceDriver, AppInterfaceId>,
         handle: ConductorHandle,
     ) -> ConductorResult<u16> {
-        let interface_id = match port {
-            either::Either::Left(port) => AppInterfaceId::new(port),
-            either::Either::Right(id) => id,
+        let (interface_id, driver) = match driver {
+            either::Either::Left(driver) => {
+                let id = match &driver {
+                    InterfaceDriver::UnixSocket { path, .. } => {
+                        AppInterfaceId::from_unix_socket_path(path)
+                    }
+                    _ => AppInterfaceId::new(driver.port()),
+                };
+                (id, driver)
+            }
+            either::Either::Right(id) => {
+                // Restart with the options the interface was attached with,
+                // but on the port which was originally asked for.
+                let driver = self
+                    .get_state()
+                    .await?
+                    .interface_by_id(&id)
+                    .map(|config| config.driver)
+                    .unwrap_or_else(|| InterfaceDriver::websocket(id.port()))
+                    .with_port(id.port());
+                (id, driver)
+            }
         };
-        let port = interface_id.port();
-        tracing::debug!("Attaching interface {}", port);
+        tracing::debug!("Attaching interface {:?}", driver);
         let app_api = RealAppInterfaceApi::new(handle, interface_id.clone());
         // This receiver is thrown away because we can produce infinite new
         // receivers from the Sender
@@ -330,23 +392,30 @@ where
             .expect("Task manager not initialized")
             .task_stop_broadcaster()
             .subscribe();
-        let (port, task) = spawn_app_interface_task(port, app_api, signal_tx.clone(), stop_rx)
-            .await
-            .map_err(Box::new)?;
+        let (port, task) = match &driver {
+            InterfaceDriver::Http { .. } => {
+                spawn_http_app_interface_task(&driver, app_api, signal_tx.clone(), stop_rx).await
+            }
+            _ => spawn_app_interface_task(&driver, app_api, signal_tx.clone(), stop_rx).await,
+        }
+        .map_err(Box::new)?;
         // TODO: RELIABILITY: Handle this task by restarting it if it fails and log the error
         self.manage_task(ManagedTaskAdd::ignore(
             task,
             &format!("app interface, port {}", port),
         ))
         .await?;
-        let interface = AppInterfaceRuntime::Websocket { signal_tx };
+        let interface = match &driver {
+            InterfaceDriver::Http { .. } => AppInterfaceRuntime::Http { signal_tx },
+            _ => AppInterfaceRuntime::Websocket { signal_tx },
+        };
 
         if self.app_interfaces.contains_key(&interface_id) {
             return Err(ConductorError::AppInterfaceIdCollision(interface_id));
         }
 
         self.app_interfaces.insert(interface_id.clone(), interface);
-        let config = AppInterfaceConfig::websocket(port);
+        let config = AppInterfaceConfig::new(driver.with_port(port));
         self.update_state(|mut state| {
             state.app_interfaces.insert(interface_id, config);
             Ok(state)
@@ -356,13 +425,53 @@ where
         Ok(port)
     }
 
-    pub(super) async fn list_app_interfaces(&self) -> ConductorResult<Vec<u16>> {
+    pub(super) async fn issue_app_authentication_token(
+        &mut self,
+        installed_app_id: InstalledAppId,
+        expiry_seconds: u64,
+    ) -> ConductorResult<(AppAuthenticationToken, Timestamp)> {
+        if self
+            .get_state()
+            .await?
+            .get_app_info(&installed_app_id)
+            .is_none()
+        {
+            return Err(ConductorError::AppNotInstalled(installed_app_id));
+        }
+        let now = timestamp::now();
+        // Forget about any tokens which have already expired
+        self.app_authentication_tokens
+            .retain(|_, (_, expires_at)| *expires_at > now);
+        let expires_at = now
+            .checked_add(&std::time::Duration::from_secs(expiry_seconds))
+            .ok_or(ConductorError::AppAuthenticationTokenExpiryTooLong(
+                expiry_seconds,
+            ))?;
+        let token = AppAuthenticationToken::from(nanoid::nanoid!(32));
+        self.app_authentication_tokens
+            .insert(token.clone(), (installed_app_id, expires_at));
+        Ok((token, expires_at))
+    }
+
+    /// Get the App a token gives access to, and when that access expires,
+    /// if the token is valid
+    pub(super) fn authenticate_app_token(
+        &self,
+        token: &AppAuthenticationToken,
+    ) -> Option<(InstalledAppId, Timestamp)> {
+        self.app_authentication_tokens
+            .get(token)
+            .filter(|(_, expires_at)| *expires_at > timestamp::now())
+            .cloned()
+    }
+
+    pub(super) async fn list_app_interfaces(&self) -> ConductorResult<Vec<InterfaceDriver>> {
         Ok(self
             .get_state()
             .await?
             .app_interfaces
             .values()
-            .map(|config| config.driver.port())
+            .map(|config| config.driver.clone())
             .collect())
     }
 
@@ -421,12 +530,13 @@ where
                 .cloned()
                 .collect(),
         )
+        .with_receipts(self.signal_receipts.clone())
     }
 
     /// Instantiate a Ribosome for use with a DNA
     pub(crate) fn get_ribosome(&self, dna_hash: &DnaHash) -> ConductorResult<RealRibosome> {
         match self.dna_store().get(dna_hash) {
-            Some(dna) => Ok(RealRibosome::new(dna)),
+            Some(dna) => Ok(RealRibosome::new(dna).with_wasm_config(self.wasm_config.clone())),
             None => Err(DnaError::DnaMissing(dna_hash.to_owned()).into()),
         }
     }
@@ -638,17 +748,37 @@ where
     pub(super) async fn activate_app_in_db(
         &mut self,
         installed_app_id: InstalledAppId,
-    ) -> ConductorResult<()> {
-        self.update_state(move |mut state| {
-            let app = state
-                .inactive_apps
-                .remove(&installed_app_id)
-                .ok_or_else(|| ConductorError::AppNotInstalled(installed_app_id.clone()))?;
-            state.active_apps.insert(app.into_active());
-            Ok(state)
-        })
-        .await?;
-        Ok(())
+    ) -> ConductorResult<Vec<CellId>> {
+        let state = self
+            .update_state({
+                let installed_app_id = installed_app_id.clone();
+                move |mut state| {
+                    let app = state
+                        .inactive_apps
+                        .remove(&installed_app_id)
+                        .ok_or_else(|| ConductorError::AppNotInstalled(installed_app_id.clone()))?;
+                    // Cells can't run as a key which has been rotated
+                    for cell_id in app.all_cells() {
+                        if let Some(by) = state.superseded_by(cell_id.agent_pubkey()) {
+                            return Err(ConductorError::AgentKeySuperseded(
+                                cell_id.agent_pubkey().clone(),
+                                by.clone(),
+                            ));
+                        }
+                    }
+                    state.active_apps.insert(app.into_active());
+                    Ok(state)
+                }
+            })
+            .await?;
+        Ok(state
+            .active_apps
+            .get(&installed_app_id)
+            .expect("This app was just put here")
+            .clone()
+            .all_cells()
+            .cloned()
+            .collect())
     }
 
     /// Deactivate an app in the database
@@ -812,6 +942,7 @@ where
                     tracing::error!("Error cleaning up Cell: {:?}\nCellId: {}", err, cell_id);
                 }
             }
+            instance_pool::evict(&cell_id);
         }
     }
 
@@ -846,7 +977,7 @@ where
         let dna_def_db = environ.get_db(&*holochain_lmdb::db::DNA_DEF)?;
         let entry_def_db = environ.get_db(&*holochain_lmdb::db::ENTRY_DEF)?;
 
-        let zome_defs = get_entry_defs(dna.clone())?;
+        let zome_defs = get_entry_defs(dna.clone(), self.wasm_config.clone())?;
 
         let mut entry_def_buf = EntryDefBuf::new(environ.clone().into(), entry_def_db)?;
 
@@ -909,11 +1040,14 @@ where
         let peer_dump = p2p_store::dump_state(self.p2p_env.clone().into(), Some(cell_id.clone()))?;
         let source_chain_dump = source_chain.dump_state().await?;
         let integration_dump = integrate_dht_ops_workflow::dump_state(arc.clone().into())?;
+        let chain_forks = ChainForks::new(arc.clone().into())?;
+        let chain_forks = fresh_reader!(arc, |r| chain_forks.all(&r))?;
 
         let out = JsonDump {
             peer_dump,
             source_chain_dump,
             integration_dump,
+            chain_forks,
         };
         // Add summary
         let summary = out.to_string();
@@ -921,6 +1055,181 @@ where
         Ok(serde_json::to_string_pretty(&out)?)
     }
 
+    pub(super) async fn export_source_chain(
+        &self,
+        cell_id: &CellId,
+    ) -> ConductorApiResult<SignedSourceChainArchive> {
+        let cell = self.cell_by_id(cell_id)?;
+        let source_chain = SourceChainBuf::new(cell.env().clone().into())?;
+        Ok(source_chain.export_archive().await?)
+    }
+
+    /// Import a source chain archive into the environment of a Cell which
+    /// is not installed yet, so that genesis is skipped once it is
+    pub(super) async fn import_source_chain(
+        &self,
+        archive: SignedSourceChainArchive,
+    ) -> ConductorApiResult<CellId> {
+        let cell_id = CellId::new(
+            archive.archive.dna_hash.clone(),
+            archive.archive.agent_pubkey.clone(),
+        );
+        let state = self.get_state().await?;
+        if state
+            .active_apps
+            .values()
+            .chain(state.inactive_apps.values())
+            .flat_map(|app| app.all_cells())
+            .any(|id| *id == cell_id)
+        {
+            return Err(ConductorError::CellAlreadyInstalled(cell_id).into());
+        }
+        if !self
+            .keystore
+            .list_sign_keypairs()
+            .await?
+            .contains(cell_id.agent_pubkey())
+        {
+            return Err(ConductorError::AgentKeyMissing(cell_id.agent_pubkey().clone()).into());
+        }
+
+        let env = EnvironmentWrite::new_cell(
+            std::path::PathBuf::from(self.root_env_dir.clone()).as_ref(),
+            cell_id.clone(),
+            self.keystore.clone(),
+        )?;
+        // Give the environment the key private entries are encrypted with,
+        // as a Cell would
+        env.set_private_entry_key(
+            PrivateEntryKey::for_agent(&self.keystore, cell_id.agent_pubkey()).await?,
+        );
+        let mut source_chain = SourceChainBuf::new(env.clone().into())?;
+        source_chain.import_archive(archive).await?;
+        env.with_commit(|writer| source_chain.flush_to_txn(writer))?;
+        Ok(cell_id)
+    }
+
+    pub(super) fn check_cell_health(&self, cell_id: &CellId) -> ConductorApiResult<CellHealth> {
+        let cell = self.cell_by_id(cell_id)?;
+        let redundancy_target = self
+            .dna_store
+            .get(cell_id.dna_hash())
+            .map(|dna| dna.dna_def().redundancy_target())
+            .unwrap_or(DEFAULT_REDUNDANCY_TARGET);
+        // The stored receipt counts aren't corrected, so this check has no
+        // side effects
+        let workspace = HealthCheckWorkspace::new(cell.env().clone().into())?;
+        health_check_workflow::health_check_workflow_inner(&workspace, redundancy_target)
+            .map_err(Box::new)
+            .map_err(Into::into)
+    }
+
+    pub(super) async fn list_agent_keys(&self) -> ConductorResult<Vec<AgentKeyInfo>> {
+        let state = self.get_state().await?;
+        let keys = self.keystore.list_sign_keypairs().await?;
+        Ok(keys
+            .into_iter()
+            .map(|agent_key| AgentKeyInfo {
+                cells: self
+                    .cells
+                    .keys()
+                    .filter(|cell_id| cell_id.agent_pubkey() == &agent_key)
+                    .cloned()
+                    .collect(),
+                supersedes: state.supersedes(&agent_key).cloned(),
+                superseded_by: state.superseded_by(&agent_key).cloned(),
+                agent_key,
+            })
+            .collect())
+    }
+
+    /// Fail if the agent key has already been replaced by a new key
+    pub(super) async fn check_agent_key_not_superseded(
+        &self,
+        agent_key: &AgentPubKey,
+    ) -> ConductorResult<()> {
+        match self.get_state().await?.superseded_by(agent_key) {
+            Some(by) => Err(ConductorError::AgentKeySuperseded(
+                agent_key.clone(),
+                by.clone(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// The position of the head of the source chain of each cell of this
+    /// agent, by DNA. Cells which haven't been through genesis are left out.
+    pub(super) fn chain_heads(&self, agent_key: &AgentPubKey) -> ConductorResult<ChainHeads> {
+        let mut chain_heads = ChainHeads::new();
+        for (cell_id, item) in &self.cells {
+            if cell_id.agent_pubkey() != agent_key {
+                continue;
+            }
+            let source_chain =
+                SourceChainBuf::new(item.cell.env().clone().into()).map_err(CellError::from)?;
+            if let Some(head) = source_chain.len().checked_sub(1) {
+                chain_heads.insert(cell_id.dna_hash().clone(), head as u32);
+            }
+        }
+        Ok(chain_heads)
+    }
+
+    /// Record that an agent key has been replaced by a new key, and deactivate
+    /// the apps whose cells run as the old key, since nothing they author from
+    /// now on would be valid.
+    /// Returns the cells of the deactivated apps.
+    pub(super) async fn add_agent_key_rotation_to_db(
+        &self,
+        old: AgentPubKey,
+        new: AgentPubKey,
+        at: Timestamp,
+    ) -> ConductorResult<Vec<CellId>> {
+        let (_state, cells_to_remove) = self
+            .update_state_prime(move |mut state| {
+                if let Some(by) = state.superseded_by(&old) {
+                    return Err(ConductorError::AgentKeySuperseded(old, by.clone()));
+                }
+                let app_ids: Vec<_> = state
+                    .active_apps
+                    .iter()
+                    .filter(|(_, app)| app.all_cells().any(|c| *c.agent_pubkey() == old))
+                    .map(|(id, _)| id.clone())
+                    .collect();
+                let mut cells = Vec::new();
+                for id in app_ids {
+                    let app = state
+                        .active_apps
+                        .remove(&id)
+                        .expect("This app was just found");
+                    cells.extend(app.all_cells().cloned());
+                    state.inactive_apps.insert(
+                        app.into_inactive(DeactivationReason::AgentKeySuperseded {
+                            by: new.clone(),
+                        }),
+                    );
+                }
+                state
+                    .superseded_agent_keys
+                    .insert(old, SupersededAgentKey { by: new, at });
+                Ok((state, cells))
+            })
+            .await?;
+        Ok(cells_to_remove)
+    }
+
+    /// The directory of the lair keystore, failing if this conductor
+    /// doesn't use one
+    pub(super) fn lair_dir(&self) -> ConductorResult<std::path::PathBuf> {
+        Ok(self.lair_dir.clone().ok_or_else(|| {
+            KeystoreError::Other("This conductor's keystore isn't kept in a directory".into())
+        })?)
+    }
+
+    /// How the wasm of this conductor's zomes is compiled and pooled
+    pub(super) fn wasm_config(&self) -> Arc<WasmConfig> {
+        self.wasm_config.clone()
+    }
+
     pub(super) fn p2p_env(&self) -> EnvironmentWrite {
         self.p2p_env.clone()
     }
@@ -983,10 +1292,15 @@ where
             cells: HashMap::new(),
             shutting_down: false,
             app_interfaces: HashMap::new(),
+            app_authentication_tokens: HashMap::new(),
+            state_version: Default::default(),
+            signal_receipts: Default::default(),
             task_manager: None,
             admin_websocket_ports: Vec::new(),
             dna_store,
             keystore,
+            lair_dir: None,
+            wasm_config: Default::default(),
             root_env_dir,
             holochain_p2p,
         })
@@ -1039,6 +1353,7 @@ where
             self.state_db.put(txn, &UnitDbKey, &new_state)?;
             Result::<_, ConductorError>::Ok((new_state, output))
         })?;
+        self.state_version.fetch_add(1, Ordering::SeqCst);
         Ok(output)
     }
 
@@ -1064,7 +1379,14 @@ pub type ConductorStateDb = KvStore<UnitDbKey, ConductorState>;
 mod builder {
     use super::*;
     use crate::conductor::dna_store::RealDnaStore;
+    use crate::conductor::dpki::DpkiImpl;
+    #[cfg(feature = "test_utils")]
+    use crate::conductor::dpki::LocalDpki;
     use crate::conductor::ConductorHandle;
+    use holochain_conductor_api::conductor::DpkiConfig;
+    use holochain_conductor_api::conductor::PassphraseServiceConfig;
+    use holochain_keystore::backup::lair_root_path;
+    use holochain_keystore::passphrase::*;
     use holochain_lmdb::env::EnvironmentKind;
     #[cfg(any(test, feature = "test_utils"))]
     use holochain_lmdb::test_utils::TestEnvironments;
@@ -1078,6 +1400,8 @@ mod builder {
         pub dna_store: DS,
         /// Optional keystore override
         pub keystore: Option<KeystoreSender>,
+        /// Optional DPKI service override
+        pub dpki: Option<DpkiImpl>,
         #[cfg(any(test, feature = "test_utils"))]
         /// Optional state override (for testing)
         pub state: Option<ConductorState>,
@@ -1127,6 +1451,7 @@ mod builder {
 
             tracing::info!(?self.config);
 
+            let mut lair_dir = None;
             let keystore = if let Some(keystore) = self.keystore {
                 keystore
             } else if self.config.use_dangerous_test_keystore {
@@ -1142,7 +1467,16 @@ mod builder {
                     .unwrap();
                 keystore
             } else {
-                spawn_lair_keystore(self.config.keystore_path.as_deref()).await?
+                let passphrase_service = self
+                    .config
+                    .passphrase_service
+                    .as_ref()
+                    .map(passphrase_service_from_config);
+                let keystore =
+                    spawn_lair_keystore(self.config.keystore_path.as_deref(), passphrase_service)
+                        .await?;
+                lair_dir = Some(lair_root_path(self.config.keystore_path.as_deref()));
+                keystore
             };
             let env_path = self.config.environment_path.clone();
 
@@ -1162,7 +1496,10 @@ mod builder {
             let state = self.state;
 
             let Self {
-                dna_store, config, ..
+                dna_store,
+                config,
+                dpki,
+                ..
             } = self;
 
             let network_config = match &config.network {
@@ -1180,7 +1517,7 @@ mod builder {
             let (holochain_p2p, p2p_evt) =
                 holochain_p2p::spawn_holochain_p2p(network_config, tls_config).await?;
 
-            let conductor = Conductor::new(
+            let mut conductor = Conductor::new(
                 environment,
                 wasm_environment,
                 p2p_environment,
@@ -1190,27 +1527,48 @@ mod builder {
                 holochain_p2p,
             )
             .await?;
+            conductor.lair_dir = lair_dir;
 
             #[cfg(any(test, feature = "test_utils"))]
             let conductor = Self::update_fake_state(state, conductor).await?;
 
-            Self::finish(conductor, config, p2p_evt).await
+            Self::finish(conductor, config, dpki, p2p_evt).await
         }
 
         async fn finish(
-            conductor: Conductor<DS>,
+            mut conductor: Conductor<DS>,
             conductor_config: ConductorConfig,
+            dpki: Option<DpkiImpl>,
             p2p_evt: holochain_p2p::event::HolochainP2pEventReceiver,
         ) -> ConductorResult<ConductorHandle> {
+            conductor.wasm_config = Arc::new(conductor_config.wasm.clone().unwrap_or_default());
+
             // Get data before handle
             let keystore = conductor.keystore.clone();
             let holochain_p2p = conductor.holochain_p2p.clone();
+            let state_version = conductor.state_version.clone();
+            let signal_receipts = conductor.signal_receipts.clone();
+
+            // A DPKI service passed to the builder takes precedence over the config
+            let dpki = dpki.or_else(|| {
+                conductor_config
+                    .dpki
+                    .as_ref()
+                    .and_then(|config| match *config {
+                        DpkiConfig::None => None,
+                        #[cfg(feature = "test_utils")]
+                        DpkiConfig::Local => Some(Arc::new(LocalDpki::new()) as DpkiImpl),
+                    })
+            });
 
             // Create handle
             let handle: ConductorHandle = Arc::new(ConductorHandleImpl {
                 conductor: RwLock::new(conductor),
                 keystore,
                 holochain_p2p,
+                dpki,
+                state_version,
+                signal_receipts,
             });
 
             let configs = conductor_config.admin_interfaces.unwrap_or_default();
@@ -1242,6 +1600,12 @@ mod builder {
             self
         }
 
+        /// Plug in a DPKI service, instead of the one selected by the config
+        pub fn with_dpki(mut self, dpki: DpkiImpl) -> Self {
+            self.dpki = Some(dpki);
+            self
+        }
+
         #[cfg(any(test, feature = "test_utils"))]
         /// Sets some fake conductor state for tests
         pub fn fake_state(mut self, state: ConductorState) -> Self {
@@ -1271,11 +1635,14 @@ mod builder {
         /// Build a Conductor with a test environment
         #[cfg(any(test, feature = "test_utils"))]
         pub async fn test(self, envs: &TestEnvironments) -> ConductorResult<ConductorHandle> {
-            let keystore = envs.conductor().keystore();
+            let keystore = self
+                .keystore
+                .clone()
+                .unwrap_or_else(|| envs.conductor().keystore());
             let (holochain_p2p, p2p_evt) =
                 holochain_p2p::spawn_holochain_p2p(self.config.network.clone().unwrap_or_default(), holochain_p2p::kitsune_p2p::dependencies::kitsune_p2p_proxy::TlsConfig::new_ephemeral().await.unwrap())
                     .await?;
-            let conductor = Conductor::new(
+            let mut conductor = Conductor::new(
                 envs.conductor(),
                 envs.wasm(),
                 envs.p2p(),
@@ -1285,10 +1652,26 @@ mod builder {
                 holochain_p2p,
             )
             .await?;
+            // The test keystore isn't served from a directory, so a configured
+            // keystore path stands in for one, for testing backups
+            conductor.lair_dir = self.config.keystore_path.clone();
 
             let conductor = Self::update_fake_state(self.state, conductor).await?;
 
-            Self::finish(conductor, self.config, p2p_evt).await
+            Self::finish(conductor, self.config, self.dpki, p2p_evt).await
+        }
+    }
+
+    /// The passphrase service selected by the config
+    fn passphrase_service_from_config(config: &PassphraseServiceConfig) -> PassphraseServiceImpl {
+        match config {
+            PassphraseServiceConfig::Cmd => Arc::new(PassphraseServiceCmd),
+            PassphraseServiceConfig::UnixSocket { path } => {
+                Arc::new(PassphraseServiceUnixSocket::new(path.clone()))
+            }
+            PassphraseServiceConfig::FromConfig { passphrase } => {
+                Arc::new(PassphraseServiceFromConfig::new(passphrase.clone()))
+            }
         }
     }
 }
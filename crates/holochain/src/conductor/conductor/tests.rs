@@ -453,6 +453,30 @@ async fn test_reactivate_app() {
     assert_eq_retry_10s!(conductor.list_active_apps().await.unwrap().len(), 1);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rotating_agent_key_deactivates_its_apps() {
+    observability::test_run().ok();
+    let zome = simple_zome();
+    let (conductor, app) = common_genesis_test_app(zome).await.unwrap();
+
+    let new_key = conductor.rotate_agent_key(app.agent()).await.unwrap();
+
+    // - The app's cells run as the old key, so it was deactivated
+    let state = conductor.get_state_from_handle().await.unwrap();
+    assert!(state.active_apps.is_empty());
+    assert_eq!(
+        state.inactive_apps.get("app").unwrap().reason(),
+        &DeactivationReason::AgentKeySuperseded { by: new_key }
+    );
+    assert!(conductor.list_cell_ids().await.unwrap().is_empty());
+
+    // - And it can't be activated again
+    assert_matches!(
+        conductor.activate_app("app".to_string()).await,
+        Err(ConductorError::AgentKeySuperseded(_, _))
+    );
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_cells_self_deactivate_on_validation_panic() {
     observability::test_run().ok();
@@ -0,0 +1,304 @@
+//! The DPKI service, the key registry the conductor consults to decide
+//! whether an agent key was valid at the time it was used.
+//!
+//! The timestamp of a header is chosen by its author, so whoever holds a
+//! revoked key could backdate headers to before the revocation. A revocation
+//! therefore cuts off each of the key's source chains at the header which was
+//! its head at the time, and only headers up to that position are valid,
+//! whatever time they claim.
+//!
+//! The service is pluggable: one can be passed to the
+//! [ConductorBuilder](super::ConductorBuilder), or selected with the `dpki`
+//! section of the conductor config. A conductor without a DPKI service takes
+//! every key to be valid.
+
+use holo_hash::AgentPubKey;
+use holo_hash::DnaHash;
+use holochain_types::prelude::*;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// The DPKI service used by a conductor
+pub type DpkiImpl = Arc<dyn DpkiService>;
+
+/// The sequence number of the head of each of an agent's source chains,
+/// by the DNA of the chain
+pub type ChainHeads = HashMap<DnaHash, u32>;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum DpkiError {
+    #[error("The key {0} was already revoked at {1}")]
+    KeyAlreadyRevoked(AgentPubKey, Timestamp),
+
+    #[error("The DPKI service failed: {0}")]
+    ServiceError(String),
+}
+
+#[allow(missing_docs)]
+pub type DpkiResult<T> = Result<T, DpkiError>;
+
+/// The state of an agent key at some point in time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyState {
+    /// The key was valid
+    Valid,
+    /// The key was registered, but only from a later time,
+    /// e.g. as the replacement of a key which was rotated later
+    NotYetValid,
+    /// The key had been revoked, either outright or by rotating it
+    Revoked,
+}
+
+/// A key registry which can say whether an agent key was valid at a given
+/// time.
+#[async_trait::async_trait]
+pub trait DpkiService: Send + Sync {
+    /// The state of the key at the given time
+    async fn key_state(&self, key: &AgentPubKey, at: Timestamp) -> DpkiResult<KeyState>;
+
+    /// The state of the author's key when it authored this header, in its
+    /// source chain of the given DNA.
+    /// A revoked key is only valid for the headers up to the head of that
+    /// chain when it was revoked, as the header's timestamp can't be trusted.
+    async fn header_key_state(&self, dna_hash: &DnaHash, header: &Header) -> DpkiResult<KeyState>;
+
+    /// Called when the conductor rotates one of its agent keys, replacing the
+    /// old key with the new one at the given time. The old key can't author
+    /// any header beyond the heads its chains had at the time.
+    /// Services which learn of rotations some other way can ignore this.
+    async fn key_rotated(
+        &self,
+        _old: &AgentPubKey,
+        _new: &AgentPubKey,
+        _at: Timestamp,
+        _chain_heads: &ChainHeads,
+    ) -> DpkiResult<()> {
+        Ok(())
+    }
+}
+
+/// When a key registered with the [LocalDpki] is valid
+#[derive(Clone, Debug)]
+struct KeyLifetime {
+    valid_from: Timestamp,
+    revoked: Option<Revocation>,
+}
+
+/// When a key was revoked, and where its chains were cut off
+#[derive(Clone, Debug)]
+struct Revocation {
+    at: Timestamp,
+    chain_heads: ChainHeads,
+}
+
+/// A DPKI service keeping its registry in memory, for testing.
+/// Keys it has no record of are valid, so only the keys whose revocation or
+/// rotation is being tested need to be registered.
+/// Clones share the same registry.
+#[derive(Clone, Default)]
+pub struct LocalDpki(Arc<RwLock<HashMap<AgentPubKey, KeyLifetime>>>);
+
+impl LocalDpki {
+    /// Create a new instance with an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a key as valid from the given time
+    pub fn register(&self, key: AgentPubKey, valid_from: Timestamp) {
+        self.0.write().insert(
+            key,
+            KeyLifetime {
+                valid_from,
+                revoked: None,
+            },
+        );
+    }
+
+    /// Revoke a key from the given time on, cutting its chains off at the
+    /// given heads. Chains which aren't in `chain_heads` are cut off before
+    /// their first header.
+    /// Keys the registry has no record of are taken to have always been valid
+    /// until then.
+    pub fn revoke(
+        &self,
+        key: &AgentPubKey,
+        at: Timestamp,
+        chain_heads: ChainHeads,
+    ) -> DpkiResult<()> {
+        let mut registry = self.0.write();
+        let lifetime = registry.entry(key.clone()).or_insert(KeyLifetime {
+            valid_from: Timestamp(i64::MIN, 0),
+            revoked: None,
+        });
+        if let Some(revocation) = &lifetime.revoked {
+            return Err(DpkiError::KeyAlreadyRevoked(key.clone(), revocation.at));
+        }
+        lifetime.revoked = Some(Revocation { at, chain_heads });
+        Ok(())
+    }
+
+    /// Replace a key with a new one at the given time, revoking the old key
+    /// and registering the new key as valid from then on
+    pub fn rotate(
+        &self,
+        old: &AgentPubKey,
+        new: AgentPubKey,
+        at: Timestamp,
+        chain_heads: ChainHeads,
+    ) -> DpkiResult<()> {
+        self.revoke(old, at, chain_heads)?;
+        self.register(new, at);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl DpkiService for LocalDpki {
+    async fn key_state(&self, key: &AgentPubKey, at: Timestamp) -> DpkiResult<KeyState> {
+        let registry = self.0.read();
+        let lifetime = match registry.get(key) {
+            Some(lifetime) => lifetime,
+            None => return Ok(KeyState::Valid),
+        };
+        Ok(match &lifetime.revoked {
+            Some(revocation) if at >= revocation.at => KeyState::Revoked,
+            _ if at < lifetime.valid_from => KeyState::NotYetValid,
+            _ => KeyState::Valid,
+        })
+    }
+
+    async fn header_key_state(&self, dna_hash: &DnaHash, header: &Header) -> DpkiResult<KeyState> {
+        let registry = self.0.read();
+        let revocation = match registry
+            .get(header.author())
+            .and_then(|lifetime| lifetime.revoked.as_ref())
+        {
+            Some(revocation) => revocation,
+            None => return Ok(KeyState::Valid),
+        };
+        Ok(match revocation.chain_heads.get(dna_hash) {
+            Some(head) if header.header_seq() <= *head => KeyState::Valid,
+            _ => KeyState::Revoked,
+        })
+    }
+
+    async fn key_rotated(
+        &self,
+        old: &AgentPubKey,
+        new: &AgentPubKey,
+        at: Timestamp,
+        chain_heads: &ChainHeads,
+    ) -> DpkiResult<()> {
+        self.rotate(old, new.clone(), at, chain_heads.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixt::CreateFixturator;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::AgentPubKeyFixturator;
+    use holo_hash::fixt::DnaHashFixturator;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn local_dpki_revocation_and_rotation() {
+        let dpki = LocalDpki::new();
+        let mut keys = AgentPubKeyFixturator::new(Unpredictable);
+        let old = keys.next().unwrap();
+        let new = keys.next().unwrap();
+
+        // Unknown keys are valid
+        assert_eq!(
+            dpki.key_state(&old, Timestamp(5, 0)).await.unwrap(),
+            KeyState::Valid
+        );
+
+        dpki.rotate(&old, new.clone(), Timestamp(10, 0), ChainHeads::new())
+            .unwrap();
+
+        // The old key is valid until the rotation, and the new key after it
+        assert_eq!(
+            dpki.key_state(&old, Timestamp(9, 0)).await.unwrap(),
+            KeyState::Valid
+        );
+        assert_eq!(
+            dpki.key_state(&old, Timestamp(10, 0)).await.unwrap(),
+            KeyState::Revoked
+        );
+        assert_eq!(
+            dpki.key_state(&new, Timestamp(9, 0)).await.unwrap(),
+            KeyState::NotYetValid
+        );
+        assert_eq!(
+            dpki.key_state(&new, Timestamp(10, 0)).await.unwrap(),
+            KeyState::Valid
+        );
+
+        // A key can't be revoked twice
+        matches::assert_matches!(
+            dpki.revoke(&old, Timestamp(11, 0), ChainHeads::new()),
+            Err(DpkiError::KeyAlreadyRevoked(_, _))
+        );
+
+        // Clones share the registry
+        dpki.clone()
+            .revoke(&new, Timestamp(20, 0), ChainHeads::new())
+            .unwrap();
+        assert_eq!(
+            dpki.key_state(&new, Timestamp(20, 0)).await.unwrap(),
+            KeyState::Revoked
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn revoked_keys_are_cut_off_by_chain_position() {
+        let dpki = LocalDpki::new();
+        let key = fixt!(AgentPubKey);
+        let dna_hash = fixt!(DnaHash);
+        let other_dna_hash = fixt!(DnaHash);
+        let header = |seq: u32, timestamp: Timestamp| {
+            let mut create = fixt!(Create);
+            create.author = key.clone();
+            create.header_seq = seq;
+            create.timestamp = timestamp;
+            Header::Create(create)
+        };
+
+        assert_eq!(
+            dpki.header_key_state(&dna_hash, &header(5, Timestamp(50, 0)))
+                .await
+                .unwrap(),
+            KeyState::Valid
+        );
+
+        let chain_heads = vec![(dna_hash.clone(), 4)].into_iter().collect();
+        dpki.revoke(&key, Timestamp(10, 0), chain_heads).unwrap();
+
+        // Headers up to the head are valid, whatever time they claim
+        assert_eq!(
+            dpki.header_key_state(&dna_hash, &header(4, Timestamp(50, 0)))
+                .await
+                .unwrap(),
+            KeyState::Valid
+        );
+        // Backdating a header beyond the head doesn't make it valid
+        assert_eq!(
+            dpki.header_key_state(&dna_hash, &header(5, Timestamp(1, 0)))
+                .await
+                .unwrap(),
+            KeyState::Revoked
+        );
+        // The key had no chain in the other DNA when it was revoked
+        assert_eq!(
+            dpki.header_key_state(&other_dna_hash, &header(0, Timestamp(1, 0)))
+                .await
+                .unwrap(),
+            KeyState::Revoked
+        );
+    }
+}
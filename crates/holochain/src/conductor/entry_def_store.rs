@@ -7,6 +7,7 @@ use crate::core::ribosome::real_ribosome::RealRibosome;
 use crate::core::ribosome::RibosomeT;
 
 use super::api::CellConductorApiT;
+use super::config::WasmConfig;
 use error::EntryDefStoreError;
 use error::EntryDefStoreResult;
 use fallible_iterator::FallibleIterator;
@@ -20,6 +21,7 @@ use holochain_serialized_bytes::SerializedBytes;
 use holochain_types::prelude::*;
 use std::collections::HashMap;
 use std::convert::TryInto;
+use std::sync::Arc;
 
 pub mod error;
 
@@ -124,7 +126,7 @@ pub(crate) async fn get_entry_def(
     // If it's not found run the ribosome and get the entry defs
     match &entry_def {
         Some(_) => Ok(entry_def),
-        None => Ok(get_entry_defs(dna_file)?
+        None => Ok(get_entry_defs(dna_file, conductor_api.wasm_config().await)?
             .get(entry_def_index.index())
             .map(|(_, v)| v.clone())),
     }
@@ -144,10 +146,11 @@ pub(crate) async fn get_entry_def_from_ids(
     }
 }
 
-#[tracing::instrument(skip(dna))]
+#[tracing::instrument(skip(dna, wasm_config))]
 /// Get all the [EntryDef] for this dna
 pub(crate) fn get_entry_defs(
     dna: DnaFile, // TODO: make generic
+    wasm_config: Arc<WasmConfig>,
 ) -> EntryDefStoreResult<Vec<(EntryDefBufferKey, EntryDef)>> {
     let invocation = EntryDefsInvocation;
 
@@ -160,7 +163,7 @@ pub(crate) fn get_entry_defs(
         .map(|(zome_name, zome)| (zome_name, zome))
         .collect::<HashMap<_, _>>();
 
-    let ribosome = RealRibosome::new(dna);
+    let ribosome = RealRibosome::new(dna).with_wasm_config(wasm_config);
     match ribosome.run_entry_defs(EntryDefsHostAccess, invocation)? {
         EntryDefsResult::Defs(map) => {
             // Turn the defs map into a vec of keys and entry defs
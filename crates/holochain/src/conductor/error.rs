@@ -36,6 +36,9 @@ pub enum ConductorError {
     #[error("Cell was referenced, but is missing from the conductor. CellId: {0:?}")]
     CellMissing(CellId),
 
+    #[error("Cell is already part of an installed app. CellId: {0:?}")]
+    CellAlreadyInstalled(CellId),
+
     #[error(transparent)]
     ConductorConfigError(#[from] ConductorConfigError),
 
@@ -100,6 +103,18 @@ pub enum ConductorError {
     #[error(transparent)]
     KeystoreError(#[from] holochain_keystore::KeystoreError),
 
+    #[error(transparent)]
+    DpkiError(#[from] super::dpki::DpkiError),
+
+    #[error("An app authentication token can't expire {0} seconds from now")]
+    AppAuthenticationTokenExpiryTooLong(u64),
+
+    #[error("The agent key {0} is not in this conductor's keystore")]
+    AgentKeyMissing(AgentPubKey),
+
+    #[error("The agent key {0} has already been rotated, and was superseded by {1}")]
+    AgentKeySuperseded(AgentPubKey, AgentPubKey),
+
     #[error(transparent)]
     KitsuneP2pError(#[from] kitsune_p2p::KitsuneP2pError),
 
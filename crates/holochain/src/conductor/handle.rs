@@ -42,28 +42,44 @@
 use super::api::error::ConductorApiResult;
 use super::api::ZomeCall;
 use super::config::AdminInterfaceConfig;
+use super::config::InterfaceDriver;
+use super::config::WasmConfig;
+use super::dpki::DpkiImpl;
+use super::error::ConductorError;
 use super::error::ConductorResult;
 use super::error::CreateAppError;
 use super::interface::SignalBroadcaster;
+use super::interface::SignalReceipts;
 use super::manager::TaskManagerRunHandle;
 use super::p2p_store::get_agent_info_signed;
 use super::p2p_store::put_agent_info_signed;
 use super::p2p_store::query_agent_info_signed;
 use super::Cell;
 use super::Conductor;
+use crate::core::ribosome::RibosomeT;
 use crate::core::workflow::CallZomeWorkspaceLock;
 use crate::core::workflow::ZomeCallResult;
 use crate::core::{queue_consumer::InitialQueueTriggers, ribosome::real_ribosome::RealRibosome};
 use derive_more::From;
 use futures::future::FutureExt;
 use futures::StreamExt;
+use holochain_conductor_api::AgentKeyInfo;
+use holochain_conductor_api::AppAuthenticationToken;
 use holochain_conductor_api::InstalledAppInfo;
+use holochain_keystore::backup::KeystoreBackup;
+use holochain_keystore::KeystoreError;
+use holochain_keystore::KeystoreSenderExt;
 use holochain_lmdb::env::EnvironmentRead;
 use holochain_p2p::event::HolochainP2pEvent::*;
 use holochain_p2p::HolochainP2pCellT;
+use holochain_state::cap_grant_index::CapGrantIndex;
+use holochain_state::chain_lock::ChainLock;
+use holochain_state::source_chain::SignedSourceChainArchive;
 use holochain_types::prelude::*;
 use kitsune_p2p::agent_store::AgentInfoSigned;
 use kitsune_p2p_types::config::JOIN_NETWORK_TIMEOUT;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::{collections::HashSet, sync::Arc};
 use tokio::sync::RwLock;
 use tracing::*;
@@ -104,11 +120,26 @@ pub trait ConductorHandleT: Send + Sync {
         configs: Vec<AdminInterfaceConfig>,
     ) -> ConductorResult<()>;
 
-    /// Add an app interface
-    async fn add_app_interface(self: Arc<Self>, port: u16) -> ConductorResult<u16>;
+    /// Add an app interface, returning the port it is listening on
+    async fn add_app_interface(self: Arc<Self>, driver: InterfaceDriver) -> ConductorResult<u16>;
 
     /// List the app interfaces currently install.
-    async fn list_app_interfaces(&self) -> ConductorResult<Vec<u16>>;
+    async fn list_app_interfaces(&self) -> ConductorResult<Vec<InterfaceDriver>>;
+
+    /// Issue a token which authenticates app interface connections as having
+    /// access to this App, returning the token and when it expires
+    async fn issue_app_authentication_token(
+        &self,
+        installed_app_id: InstalledAppId,
+        expiry_seconds: u64,
+    ) -> ConductorResult<(AppAuthenticationToken, Timestamp)>;
+
+    /// Get the App an app interface token gives access to, and when it
+    /// expires, or `None` if the token was never issued or has expired
+    async fn authenticate_app_token(
+        &self,
+        token: &AppAuthenticationToken,
+    ) -> Option<(InstalledAppId, Timestamp)>;
 
     /// Install a [Dna] in this Conductor
     async fn register_dna(&self, dna: DnaFile) -> ConductorResult<()>;
@@ -125,6 +156,12 @@ pub trait ConductorHandleT: Send + Sync {
     /// Get an instance of a [RealRibosome] for the DnaHash
     async fn get_ribosome(&self, dna_hash: &DnaHash) -> ConductorResult<RealRibosome>;
 
+    /// List the zome functions and callbacks exported by each zome of a Dna
+    async fn list_zome_functions(
+        &self,
+        dna_hash: &DnaHash,
+    ) -> ConductorApiResult<Vec<(ZomeName, ZomeExports)>>;
+
     /// Get a [EntryDef] from the [EntryDefBuffer]
     async fn get_entry_def(&self, key: &EntryDefBufferKey) -> Option<EntryDef>;
 
@@ -165,6 +202,21 @@ pub trait ConductorHandleT: Send + Sync {
     /// Request access to this conductor's keystore
     fn keystore(&self) -> &KeystoreSender;
 
+    /// The DPKI service this conductor consults about agent keys, if any
+    fn dpki(&self) -> Option<DpkiImpl>;
+
+    /// A number which changes every time the conductor state does, such as
+    /// when an App is installed, activated, deactivated or uninstalled.
+    /// Can be read without waiting for the conductor.
+    fn state_version(&self) -> u64;
+
+    /// Where connections to app interfaces report the signals they send to
+    /// their clients, so that a signal can be known to have been received
+    fn signal_receipts(&self) -> &SignalReceipts;
+
+    /// How the wasm of this conductor's zomes is compiled and pooled
+    async fn wasm_config(&self) -> Arc<WasmConfig>;
+
     /// Request access to this conductor's networking handle
     fn holochain_p2p(&self) -> &holochain_p2p::HolochainP2pRef;
 
@@ -223,6 +275,9 @@ pub trait ConductorHandleT: Send + Sync {
     /// Dump the cells state
     async fn dump_cell_state(&self, cell_id: &CellId) -> ConductorApiResult<String>;
 
+    /// Check how well the ops authored by a cell are held by the network
+    async fn check_cell_health(&self, cell_id: &CellId) -> ConductorApiResult<CellHealth>;
+
     /// Access the broadcast Sender which will send a Signal across every
     /// attached app interface
     async fn signal_broadcaster(&self) -> SignalBroadcaster;
@@ -242,12 +297,60 @@ pub trait ConductorHandleT: Send + Sync {
         cell_id: Option<CellId>,
     ) -> ConductorApiResult<Vec<AgentInfoSigned>>;
 
+    /// List the agent keys in the keystore, with their cells and rotations
+    async fn list_agent_keys(&self) -> ConductorResult<Vec<AgentKeyInfo>>;
+
+    /// Replace an agent key with a newly generated one, returning the new key.
+    /// The apps with cells running as the old key are deactivated,
+    /// and can't be activated again.
+    async fn rotate_agent_key(&self, agent_key: &AgentPubKey) -> ConductorResult<AgentPubKey>;
+
+    /// Back up the keystore, encrypted with the passphrase
+    async fn export_keystore(&self, passphrase: String) -> ConductorResult<KeystoreBackup>;
+
+    /// Stage a keystore backup to replace the keystore the next time the
+    /// conductor starts
+    async fn import_keystore(
+        &self,
+        archive: KeystoreBackup,
+        passphrase: String,
+    ) -> ConductorResult<()>;
+
+    /// Export the whole source chain of a Cell, private entries included
+    async fn export_source_chain(
+        &self,
+        cell_id: &CellId,
+    ) -> ConductorApiResult<SignedSourceChainArchive>;
+
+    /// Import an exported source chain for a Cell which is not installed yet
+    async fn import_source_chain(
+        &self,
+        archive: SignedSourceChainArchive,
+    ) -> ConductorApiResult<CellId>;
+
     /// Print the current setup in a machine readable way.
     async fn print_setup(&self);
 
     /// Retrieve the LMDB environment for this cell.
     async fn get_cell_env_readonly(&self, cell_id: &CellId) -> ConductorApiResult<EnvironmentRead>;
 
+    /// Persist the countersigning session a cell's chain has been locked for,
+    /// without waiting for the zome call's workspace to be flushed.
+    async fn persist_chain_lock(
+        &self,
+        cell_id: &CellId,
+        request: PreflightRequest,
+    ) -> ConductorApiResult<()>;
+
+    /// Count a use of the use-limited cap grant committed by this header on
+    /// the cell's chain, returning false if the grant has no uses left
+    async fn use_cap_grant(
+        &self,
+        cell_id: &CellId,
+        grant_header: HeaderHash,
+        max_uses: u32,
+    ) -> ConductorApiResult<bool>;
+
     /// Retrieve the LMDB environment for this cell. FOR TESTING ONLY.
     #[cfg(any(test, feature = "test_utils"))]
     async fn get_cell_env(&self, cell_id: &CellId) -> ConductorApiResult<EnvironmentWrite>;
@@ -282,6 +385,9 @@ pub struct ConductorHandleImpl<DS: DnaStore + 'static> {
     pub(crate) conductor: RwLock<Conductor<DS>>,
     pub(crate) keystore: KeystoreSender,
     pub(crate) holochain_p2p: holochain_p2p::HolochainP2pRef,
+    pub(crate) dpki: Option<DpkiImpl>,
+    pub(crate) state_version: Arc<AtomicU64>,
+    pub(crate) signal_receipts: SignalReceipts,
 }
 
 #[async_trait::async_trait]
@@ -315,16 +421,35 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         Ok(())
     }
 
-    async fn add_app_interface(self: Arc<Self>, port: u16) -> ConductorResult<u16> {
+    async fn add_app_interface(self: Arc<Self>, driver: InterfaceDriver) -> ConductorResult<u16> {
         let mut lock = self.conductor.write().await;
-        lock.add_app_interface_via_handle(either::Left(port), self.clone())
+        lock.add_app_interface_via_handle(either::Left(driver), self.clone())
             .await
     }
 
-    async fn list_app_interfaces(&self) -> ConductorResult<Vec<u16>> {
+    async fn list_app_interfaces(&self) -> ConductorResult<Vec<InterfaceDriver>> {
         self.conductor.read().await.list_app_interfaces().await
     }
 
+    async fn issue_app_authentication_token(
+        &self,
+        installed_app_id: InstalledAppId,
+        expiry_seconds: u64,
+    ) -> ConductorResult<(AppAuthenticationToken, Timestamp)> {
+        self.conductor
+            .write()
+            .await
+            .issue_app_authentication_token(installed_app_id, expiry_seconds)
+            .await
+    }
+
+    async fn authenticate_app_token(
+        &self,
+        token: &AppAuthenticationToken,
+    ) -> Option<(InstalledAppId, Timestamp)> {
+        self.conductor.read().await.authenticate_app_token(token)
+    }
+
     async fn register_dna(&self, dna: DnaFile) -> ConductorResult<()> {
         self.register_genotype(dna.clone()).await?;
         self.conductor.write().await.register_phenotype(dna).await
@@ -365,6 +490,26 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         self.conductor.read().await.get_ribosome(dna_hash)
     }
 
+    async fn list_zome_functions(
+        &self,
+        dna_hash: &DnaHash,
+    ) -> ConductorApiResult<Vec<(ZomeName, ZomeExports)>> {
+        let ribosome = self.get_ribosome(dna_hash).await?;
+        let zome_names: Vec<ZomeName> = ribosome
+            .dna_def()
+            .zomes
+            .iter()
+            .map(|(zome_name, _)| zome_name.clone())
+            .collect();
+        zome_names
+            .into_iter()
+            .map(|zome_name| {
+                let exports = ribosome.zome_exports(&zome_name)?;
+                Ok((zome_name, exports))
+            })
+            .collect()
+    }
+
     async fn get_entry_def(&self, key: &EntryDefBufferKey) -> Option<EntryDef> {
         self.conductor.read().await.dna_store().get_entry_def(key)
     }
@@ -457,6 +602,22 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         &self.keystore
     }
 
+    fn dpki(&self) -> Option<DpkiImpl> {
+        self.dpki.clone()
+    }
+
+    fn state_version(&self) -> u64 {
+        self.state_version.load(Ordering::SeqCst)
+    }
+
+    fn signal_receipts(&self) -> &SignalReceipts {
+        &self.signal_receipts
+    }
+
+    async fn wasm_config(&self) -> Arc<WasmConfig> {
+        self.conductor.read().await.wasm_config()
+    }
+
     fn holochain_p2p(&self) -> &holochain_p2p::HolochainP2pRef {
         &self.holochain_p2p
     }
@@ -537,13 +698,14 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         } = payload;
 
         let bundle: AppBundle = {
-            let original_bundle = source.resolve().await?;
+            let cache = self.conductor.read().await.bundle_cache();
+            let original_bundle = source.resolve().await?.into_inner().with_cache(cache);
             if let Some(uid) = uid {
                 let mut manifest = original_bundle.manifest().to_owned();
                 manifest.set_uid(uid);
-                AppBundle::from(original_bundle.into_inner().update_manifest(manifest)?)
+                AppBundle::from(original_bundle.update_manifest(manifest)?)
             } else {
-                original_bundle
+                AppBundle::from(original_bundle)
             }
         };
 
@@ -605,12 +767,30 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
     }
 
     async fn activate_app(&self, installed_app_id: InstalledAppId) -> ConductorResult<()> {
-        self.conductor
+        let cell_ids = self
+            .conductor
             .write()
             .await
             .activate_app_in_db(installed_app_id)
-            .await
+            .await?;
         // MD: Should we be doing `Conductor::add_cells()` here? (see below comment)
+
+        // Compile and instantiate the app's zomes in the background,
+        // so that its first zome calls don't have to wait for that
+        for cell_id in cell_ids {
+            if let Ok(ribosome) = self.get_ribosome(cell_id.dna_hash()).await {
+                tokio::task::spawn_blocking(move || {
+                    if let Err(e) = ribosome.prewarm(&cell_id) {
+                        warn!(
+                            ?cell_id,
+                            ?e,
+                            "Could not prewarm the wasm instances of a cell"
+                        );
+                    }
+                });
+            }
+        }
+        Ok(())
     }
 
     async fn deactivate_app(
@@ -660,6 +840,10 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         self.conductor.read().await.dump_cell_state(cell_id).await
     }
 
+    async fn check_cell_health(&self, cell_id: &CellId) -> ConductorApiResult<CellHealth> {
+        self.conductor.read().await.check_cell_health(cell_id)
+    }
+
     async fn signal_broadcaster(&self) -> SignalBroadcaster {
         self.conductor.read().await.signal_broadcaster()
     }
@@ -688,6 +872,82 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         self.conductor.read().await.get_agent_infos(cell_id)
     }
 
+    async fn list_agent_keys(&self) -> ConductorResult<Vec<AgentKeyInfo>> {
+        self.conductor.read().await.list_agent_keys().await
+    }
+
+    async fn rotate_agent_key(&self, agent_key: &AgentPubKey) -> ConductorResult<AgentPubKey> {
+        if !self
+            .keystore
+            .list_sign_keypairs()
+            .await?
+            .contains(agent_key)
+        {
+            return Err(ConductorError::AgentKeyMissing(agent_key.clone()));
+        }
+        let mut conductor = self.conductor.write().await;
+        conductor.check_agent_key_not_superseded(agent_key).await?;
+        let new_key = self
+            .keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await?;
+        let at = timestamp::now();
+        // Tell DPKI first, so that if it refuses the rotation
+        // the conductor still runs as the old key
+        if let Some(dpki) = &self.dpki {
+            let chain_heads = conductor.chain_heads(agent_key)?;
+            dpki.key_rotated(agent_key, &new_key, at, &chain_heads)
+                .await?;
+        }
+        let cell_ids_to_remove = conductor
+            .add_agent_key_rotation_to_db(agent_key.clone(), new_key.clone(), at)
+            .await?;
+        conductor.remove_cells(cell_ids_to_remove).await;
+        Ok(new_key)
+    }
+
+    async fn export_keystore(&self, passphrase: String) -> ConductorResult<KeystoreBackup> {
+        let lair_dir = self.conductor.read().await.lair_dir()?;
+        Ok(self
+            .keystore
+            .export_keystore(Some(lair_dir), passphrase)
+            .await?)
+    }
+
+    async fn import_keystore(
+        &self,
+        archive: KeystoreBackup,
+        passphrase: String,
+    ) -> ConductorResult<()> {
+        let lair_dir = self.conductor.read().await.lair_dir()?;
+        Ok(self
+            .keystore
+            .import_keystore(Some(lair_dir), archive, passphrase)
+            .await?)
+    }
+
+    async fn export_source_chain(
+        &self,
+        cell_id: &CellId,
+    ) -> ConductorApiResult<SignedSourceChainArchive> {
+        self.conductor
+            .read()
+            .await
+            .export_source_chain(cell_id)
+            .await
+    }
+
+    async fn import_source_chain(
+        &self,
+        archive: SignedSourceChainArchive,
+    ) -> ConductorApiResult<CellId> {
+        self.conductor
+            .read()
+            .await
+            .import_source_chain(archive)
+            .await
+    }
+
     async fn print_setup(&self) {
         self.conductor.read().await.print_setup()
     }
@@ -697,6 +957,25 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         Ok(cell.env().clone().into())
     }
 
+    async fn persist_chain_lock(
+        &self,
+        cell_id: &CellId,
+        request: PreflightRequest,
+    ) -> ConductorApiResult<()> {
+        let cell = self.cell_by_id(cell_id).await?;
+        Ok(ChainLock::persist(cell.env(), &request)?)
+    }
+
+    async fn use_cap_grant(
+        &self,
+        cell_id: &CellId,
+        grant_header: HeaderHash,
+        max_uses: u32,
+    ) -> ConductorApiResult<bool> {
+        let cell = self.cell_by_id(cell_id).await?;
+        Ok(CapGrantIndex::use_grant(cell.env(), &grant_header, max_uses)?)
+    }
+
     #[cfg(any(test, feature = "test_utils"))]
     async fn get_cell_env(&self, cell_id: &CellId) -> ConductorApiResult<EnvironmentWrite> {
         let cell = self.cell_by_id(cell_id).await?;
@@ -2,18 +2,25 @@
 //! and dispatch them to the appropriate handlers within Holochain.
 //! They also allow emitting responses and one-way Signals.
 //!
-//! Currently the only InterfaceDriver is a Websocket-based one, whose
-//! implementation can be found in the `websocket` module here.
+//! Interfaces are served either over a Websocket or over a unix domain
+//! socket with the same framing. Both are implemented in the `websocket`
+//! module here. App interfaces can also be served as plain HTTP with JSON
+//! bodies, implemented in the `http` module.
 
 use crate::conductor::api::*;
 use error::InterfaceError;
 use error::InterfaceResult;
 use holochain_types::signal::Signal;
+use parking_lot::Mutex;
 use std::convert::TryInto;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
+use tokio::sync::oneshot;
 
 #[allow(missing_docs)]
 pub mod error;
+pub mod http;
 pub mod websocket;
 
 /// Represents runtime data about an existing App interface.
@@ -26,6 +33,12 @@ pub enum AppInterfaceRuntime {
         signal_tx: broadcast::Sender<Signal>,
     },
 
+    /// An HTTP app interface
+    Http {
+        /// The channel for this interface to send Signals across
+        signal_tx: broadcast::Sender<Signal>,
+    },
+
     #[cfg(any(test, feature = "test_utils"))]
     /// An interface used only for testing
     Test {
@@ -39,6 +52,7 @@ impl AppInterfaceRuntime {
     pub fn signal_tx(&self) -> &broadcast::Sender<Signal> {
         match self {
             Self::Websocket { signal_tx, .. } => signal_tx,
+            Self::Http { signal_tx, .. } => signal_tx,
             #[cfg(any(test, feature = "test_utils"))]
             Self::Test { signal_tx, .. } => signal_tx,
         }
@@ -50,6 +64,38 @@ impl AppInterfaceRuntime {
 #[derive(Clone, Debug)]
 pub struct SignalBroadcaster {
     senders: Vec<broadcast::Sender<Signal>>,
+    receipts: SignalReceipts,
+}
+
+/// The signals waiting to be received by a client.
+///
+/// A connection to an app interface only sends its client the signals of the
+/// App it authenticated for, so a signal being broadcast to an interface
+/// doesn't mean any client received it. Connections report here every signal
+/// they send to their client.
+#[derive(Clone, Debug, Default)]
+pub struct SignalReceipts(Arc<Mutex<Vec<(Signal, oneshot::Sender<()>)>>>);
+
+impl SignalReceipts {
+    /// Report that a signal was sent to a client
+    pub fn received(&self, signal: &Signal) {
+        let mut waiting = self.0.lock();
+        let (received, still_waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut *waiting)
+            .into_iter()
+            .filter(|(_, tx)| !tx.is_closed())
+            .partition(|(expected, _)| expected == signal);
+        *waiting = still_waiting;
+        for (_, tx) in received {
+            tx.send(()).ok();
+        }
+    }
+
+    /// Wait for a client to receive the signal
+    fn wait_for(&self, signal: Signal) -> oneshot::Receiver<()> {
+        let (tx, rx) = oneshot::channel();
+        self.0.lock().push((signal, tx));
+        rx
+    }
 }
 
 impl SignalBroadcaster {
@@ -63,17 +109,43 @@ impl SignalBroadcaster {
         Ok(())
     }
 
+    /// Send the signal to the connected clients of every interface, and wait
+    /// up to the timeout for a client permitted to see it to receive it,
+    /// returning whether one did.
+    /// Unlike [SignalBroadcaster::send], this tells apart a signal nobody
+    /// received from one that was delivered.
+    pub async fn deliver(&mut self, sig: Signal, timeout: Duration) -> bool {
+        let receipt = self.receipts.wait_for(sig.clone());
+        let subscribed = self
+            .senders
+            .iter_mut()
+            .filter(|tx| tx.send(sig.clone()).is_ok())
+            .count();
+        if subscribed == 0 {
+            return false;
+        }
+        matches!(tokio::time::timeout(timeout, receipt).await, Ok(Ok(())))
+    }
+
     /// internal constructor
     pub fn new(senders: Vec<broadcast::Sender<Signal>>) -> Self {
-        Self { senders }
+        Self {
+            senders,
+            receipts: SignalReceipts::default(),
+        }
+    }
+
+    /// Hear from the connections of the interfaces which signals their
+    /// clients received, for [SignalBroadcaster::deliver]
+    pub fn with_receipts(mut self, receipts: SignalReceipts) -> Self {
+        self.receipts = receipts;
+        self
     }
 
     #[cfg(test)]
     /// A sender with nothing to send to. A placeholder for tests
     pub fn noop() -> Self {
-        Self {
-            senders: Vec::new(),
-        }
+        Self::new(Vec::new())
     }
 
     #[cfg(any(test, feature = "test_utils"))]
@@ -0,0 +1,601 @@
+//! Module for establishing HTTP-based App Interfaces,
+//! i.e. those configured with `InterfaceDriver::Http`.
+//!
+//! Zome calls are made with JSON bodies, which are transcoded to and from
+//! the msgpack [`ExternIO`] that zome functions take and return.
+//! Signals are streamed to clients as server-sent events.
+//!
+//! Requests are handled by an [`AppInterfaceApi`] just like those made over a
+//! websocket, so every request must authenticate with an app authentication
+//! token, passed as an `Authorization: Bearer <token>` header. As the tokens
+//! are sent in the clear, the interface is only ever served on a loopback
+//! address.
+
+use super::error::InterfaceError;
+use super::error::InterfaceResult;
+use crate::conductor::api::error::ExternalApiWireError;
+use crate::conductor::api::AppAuthenticationToken;
+use crate::conductor::api::AppInterfaceApi;
+use crate::conductor::api::AppRequest;
+use crate::conductor::api::AppResponse;
+use crate::conductor::api::ZomeCall;
+use crate::conductor::conductor::StopReceiver;
+use crate::conductor::interface::InterfaceDriver;
+use crate::conductor::manager::ManagedTaskHandle;
+use crate::conductor::manager::ManagedTaskResult;
+use holochain_conductor_api::config::DEFAULT_HTTP_MAX_BODY_SIZE;
+use holochain_conductor_api::json::extern_io_to_json;
+use holochain_conductor_api::json::json_to_extern_io;
+use holochain_types::prelude::*;
+use holochain_types::signal::Signal;
+use hyper::body::HttpBody;
+use hyper::header::HeaderValue;
+use hyper::header::AUTHORIZATION;
+use hyper::header::CACHE_CONTROL;
+use hyper::header::CONTENT_LENGTH;
+use hyper::header::CONTENT_TYPE;
+use hyper::service::make_service_fn;
+use hyper::service::service_fn;
+use hyper::Body;
+use hyper::Method;
+use hyper::Request;
+use hyper::Response;
+use hyper::StatusCode;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use tokio::sync::broadcast;
+use tracing::*;
+
+/// Create an App Interface which serves zome calls and signals over HTTP
+pub async fn spawn_http_app_interface_task<A: AppInterfaceApi>(
+    driver: &InterfaceDriver,
+    api: A,
+    signal_broadcaster: broadcast::Sender<Signal>,
+    mut stop_rx: StopReceiver,
+) -> InterfaceResult<(u16, ManagedTaskHandle)> {
+    trace!("Initializing HTTP App interface");
+    let addr = bind_address(driver)?;
+    let max_body_size = match driver {
+        InterfaceDriver::Http { max_body_size, .. } => {
+            max_body_size.unwrap_or(DEFAULT_HTTP_MAX_BODY_SIZE)
+        }
+        _ => DEFAULT_HTTP_MAX_BODY_SIZE,
+    };
+    let builder = hyper::Server::try_bind(&addr)
+        .map_err(|e| InterfaceError::Other(format!("Could not bind to {}: {}", addr, e)))?;
+    let make_service = make_service_fn(move |_| {
+        let api = api.clone();
+        let signal_broadcaster = signal_broadcaster.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle_http_request(
+                    request,
+                    api.clone(),
+                    signal_broadcaster.clone(),
+                    max_body_size,
+                )
+            }))
+        }
+    });
+    let server = builder.serve(make_service);
+    let port = server.local_addr().port();
+    trace!("LISTENING AT: {}", server.local_addr());
+    let task = tokio::task::spawn(async move {
+        // Dropping the server closes every connection, including signal
+        // streams which would otherwise never finish.
+        tokio::select! {
+            r = server => {
+                if let Err(err) = r {
+                    warn!("HTTP interface failed: {}", err);
+                }
+            }
+            _ = stop_rx.recv() => (),
+        }
+        ManagedTaskResult::Ok(())
+    });
+    Ok((port, task))
+}
+
+/// The address to serve the interface on, which must be a loopback address
+fn bind_address(driver: &InterfaceDriver) -> InterfaceResult<SocketAddr> {
+    let addr = SocketAddr::new(driver.bind_address(), driver.port());
+    if !addr.ip().is_loopback() {
+        return Err(InterfaceError::Other(format!(
+            "HTTP interfaces send app authentication tokens in the clear, so can't be served on {}, which is not a loopback address",
+            addr.ip()
+        )));
+    }
+    Ok(addr)
+}
+
+/// A request which can't be handled, and the status to respond with
+#[derive(Debug)]
+struct HttpError(StatusCode, serde_json::Value);
+
+impl HttpError {
+    fn new(status: StatusCode, message: impl ToString) -> Self {
+        Self(
+            status,
+            serde_json::json!({ "message": message.to_string() }),
+        )
+    }
+}
+
+impl From<ExternalApiWireError> for HttpError {
+    fn from(e: ExternalApiWireError) -> Self {
+        let status = match &e {
+            ExternalApiWireError::AppAuthenticationFailed(_) => StatusCode::UNAUTHORIZED,
+            ExternalApiWireError::ZomeCallUnauthorized(_) => StatusCode::FORBIDDEN,
+            ExternalApiWireError::Deserialization(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let body = serde_json::to_value(&e)
+            .unwrap_or_else(|_| serde_json::json!({ "message": format!("{:?}", e) }));
+        Self(status, body)
+    }
+}
+
+/// A route of the HTTP interface
+#[derive(Debug, PartialEq)]
+enum Route {
+    /// `POST /apps/{app}/cells/{cell_nick}/zomes/{zome}/{fn}`
+    ZomeCall {
+        installed_app_id: InstalledAppId,
+        cell_nick: CellNick,
+        zome_name: ZomeName,
+        fn_name: FunctionName,
+    },
+    /// `GET /apps/{app}/signals`
+    Signals { installed_app_id: InstalledAppId },
+}
+
+impl Route {
+    fn parse(method: &Method, path: &str) -> Result<Self, HttpError> {
+        let segments = path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                percent_encoding::percent_decode_str(s)
+                    .decode_utf8()
+                    .map(|s| s.into_owned())
+                    .map_err(|_| HttpError::new(StatusCode::BAD_REQUEST, "Path is not utf8"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let segments: Vec<&str> = segments.iter().map(|s| s.as_str()).collect();
+        let (route, expected_method) = match segments[..] {
+            ["apps", app, "cells", cell, "zomes", zome, func] => (
+                Route::ZomeCall {
+                    installed_app_id: app.to_string(),
+                    cell_nick: cell.to_string(),
+                    zome_name: zome.into(),
+                    fn_name: func.into(),
+                },
+                Method::POST,
+            ),
+            ["apps", app, "signals"] => (
+                Route::Signals {
+                    installed_app_id: app.to_string(),
+                },
+                Method::GET,
+            ),
+            _ => return Err(HttpError::new(StatusCode::NOT_FOUND, "No such route")),
+        };
+        if *method != expected_method {
+            return Err(HttpError::new(
+                StatusCode::METHOD_NOT_ALLOWED,
+                format!("Use {}", expected_method),
+            ));
+        }
+        Ok(route)
+    }
+
+    fn installed_app_id(&self) -> &InstalledAppId {
+        match self {
+            Route::ZomeCall {
+                installed_app_id, ..
+            }
+            | Route::Signals { installed_app_id } => installed_app_id,
+        }
+    }
+}
+
+async fn handle_http_request<A: AppInterfaceApi>(
+    request: Request<Body>,
+    api: A,
+    signal_broadcaster: broadcast::Sender<Signal>,
+    max_body_size: usize,
+) -> Result<Response<Body>, Infallible> {
+    match handle_http_request_inner(request, api, signal_broadcaster, max_body_size).await {
+        Ok(response) => Ok(response),
+        Err(HttpError(status, body)) => {
+            let mut response = json_response(&body);
+            *response.status_mut() = status;
+            Ok(response)
+        }
+    }
+}
+
+async fn handle_http_request_inner<A: AppInterfaceApi>(
+    request: Request<Body>,
+    api: A,
+    signal_broadcaster: broadcast::Sender<Signal>,
+    max_body_size: usize,
+) -> Result<Response<Body>, HttpError> {
+    let route = Route::parse(request.method(), request.uri().path())?;
+
+    // Every request is handled as a new connection
+    let api = api.for_connection();
+    let token = bearer_token(&request)?;
+    match api
+        .handle_app_request(AppRequest::Authenticate { token })
+        .await
+    {
+        AppResponse::Authenticated { installed_app_id }
+            if installed_app_id == *route.installed_app_id() => {}
+        AppResponse::Authenticated { .. } => {
+            return Err(HttpError::new(
+                StatusCode::FORBIDDEN,
+                format!(
+                    "The token was not issued for the app {}",
+                    route.installed_app_id()
+                ),
+            ))
+        }
+        AppResponse::Error(e) => return Err(e.into()),
+        r => return Err(unexpected_response(r)),
+    }
+
+    match route {
+        Route::ZomeCall {
+            installed_app_id,
+            cell_nick,
+            zome_name,
+            fn_name,
+        } => {
+            let cell_id = find_cell(&api, installed_app_id, &cell_nick).await?;
+            let body = read_body(request, max_body_size).await?;
+            let payload = if body.is_empty() {
+                serde_json::Value::Null
+            } else {
+                serde_json::from_slice(&body).map_err(|e| {
+                    HttpError::new(StatusCode::BAD_REQUEST, format!("Invalid JSON: {}", e))
+                })?
+            };
+            let payload = json_to_extern_io(&payload)
+                .map_err(|e| HttpError::new(StatusCode::BAD_REQUEST, e))?;
+            let call = ZomeCall {
+                provenance: cell_id.agent_pubkey().clone(),
+                cell_id,
+                zome_name,
+                fn_name,
+                payload,
+                cap: None,
+            };
+            match api
+                .handle_app_request(AppRequest::ZomeCall(Box::new(call)))
+                .await
+            {
+                AppResponse::ZomeCall(output) => {
+                    let output = extern_io_to_json(&output)
+                        .map_err(|e| HttpError::new(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+                    Ok(json_response(&output))
+                }
+                AppResponse::Error(e) => Err(e.into()),
+                r => Err(unexpected_response(r)),
+            }
+        }
+        Route::Signals { .. } => {
+            let rx = signal_broadcaster.subscribe();
+            let events = futures::stream::unfold((rx, api), |(mut rx, api)| async move {
+                loop {
+                    match rx.recv().await {
+                        Ok(signal) => {
+                            if !api.is_signal_permitted(&signal).await {
+                                continue;
+                            }
+                            match signal_event(signal.clone()) {
+                                Ok(event) => {
+                                    api.signal_sent(&signal);
+                                    return Some((Ok(event), (rx, api)));
+                                }
+                                Err(e) => warn!("Could not transcode signal: {:?}", e),
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            warn!("HTTP signal stream skipped {} signals", n)
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            });
+            let mut response = Response::new(Body::wrap_stream::<_, String, Infallible>(events));
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+            response
+                .headers_mut()
+                .insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+            Ok(response)
+        }
+    }
+}
+
+/// Read the body of a request, refusing it if it is larger than the limit
+async fn read_body(request: Request<Body>, max_body_size: usize) -> Result<Vec<u8>, HttpError> {
+    let too_large = || {
+        HttpError::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Request bodies can be at most {} bytes", max_body_size),
+        )
+    };
+    let content_length = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if content_length.map_or(false, |len| len > max_body_size) {
+        return Err(too_large());
+    }
+    // The content length may be missing or wrong, so the body is counted as
+    // it is read too
+    let mut body = request.into_body();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| HttpError::new(StatusCode::BAD_REQUEST, e))?;
+        if bytes.len() + chunk.len() > max_body_size {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+fn bearer_token(request: &Request<Body>) -> Result<AppAuthenticationToken, HttpError> {
+    request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| AppAuthenticationToken::from(token.trim().to_string()))
+        .ok_or_else(|| {
+            HttpError::new(
+                StatusCode::UNAUTHORIZED,
+                "Requests must carry an app authentication token as an `Authorization: Bearer` header",
+            )
+        })
+}
+
+async fn find_cell<A: AppInterfaceApi>(
+    api: &A,
+    installed_app_id: InstalledAppId,
+    cell_nick: &str,
+) -> Result<CellId, HttpError> {
+    match api
+        .handle_app_request(AppRequest::AppInfo { installed_app_id })
+        .await
+    {
+        AppResponse::AppInfo(Some(app)) => app
+            .cell_data
+            .iter()
+            .find(|cell| cell.as_nick() == cell_nick)
+            .map(|cell| cell.as_id().clone())
+            .ok_or_else(|| {
+                HttpError::new(
+                    StatusCode::NOT_FOUND,
+                    format!("App {} has no cell {}", app.installed_app_id, cell_nick),
+                )
+            }),
+        AppResponse::AppInfo(None) => Err(HttpError::new(StatusCode::NOT_FOUND, "No such app")),
+        AppResponse::Error(e) => Err(e.into()),
+        r => Err(unexpected_response(r)),
+    }
+}
+
+/// Format a signal as a server-sent event.
+/// App signals are sent as `app` events and system signals as `system` events.
+fn signal_event(signal: Signal) -> Result<String, SerializedBytesError> {
+    let (event, data) = match signal {
+        Signal::App(cell_id, signal) => (
+            "app",
+            serde_json::json!({
+                "cell_id": extern_io_to_json(&ExternIO::encode(cell_id)?)?,
+                "payload": extern_io_to_json(&signal.into_inner())?,
+            }),
+        ),
+        Signal::System(signal) => ("system", extern_io_to_json(&ExternIO::encode(signal)?)?),
+    };
+    Ok(format!("event: {}\ndata: {}\n\n", event, data))
+}
+
+fn json_response(value: &serde_json::Value) -> Response<Body> {
+    let mut response = Response::new(Body::from(value.to_string()));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+fn unexpected_response(response: AppResponse) -> HttpError {
+    HttpError::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("Unexpected response: {:?}", response),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::conductor::interface::websocket::test_utils::setup_app;
+    use crate::conductor::interface::websocket::SIGNAL_BUFFER_SIZE;
+    use futures::StreamExt;
+    use holochain_types::test_utils::fake_agent_pubkey_1;
+    use holochain_types::test_utils::fake_dna_zomes;
+    use holochain_wasm_test_utils::TestWasm;
+    use mockall::predicate;
+    use uuid::Uuid;
+
+    #[test]
+    fn parses_routes() {
+        assert_eq!(
+            Route::parse(&Method::POST, "/apps/test%20app/cells/handle/zomes/foo/bar").unwrap(),
+            Route::ZomeCall {
+                installed_app_id: "test app".into(),
+                cell_nick: "handle".into(),
+                zome_name: "foo".into(),
+                fn_name: "bar".into(),
+            }
+        );
+        assert_eq!(
+            Route::parse(&Method::GET, "/apps/my-app/signals").unwrap(),
+            Route::Signals {
+                installed_app_id: "my-app".into()
+            }
+        );
+        assert_eq!(
+            Route::parse(&Method::GET, "/apps/my-app/cells/c/zomes/z/f")
+                .unwrap_err()
+                .0,
+            StatusCode::METHOD_NOT_ALLOWED
+        );
+        assert_eq!(
+            Route::parse(&Method::GET, "/apps/my-app").unwrap_err().0,
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn only_binds_to_loopback_addresses() {
+        let driver = |addr: &str| InterfaceDriver::Http {
+            port: 8080,
+            bind_address: Some(addr.parse().unwrap()),
+            max_body_size: None,
+        };
+        assert!(bind_address(&InterfaceDriver::http(8080)).is_ok());
+        assert!(bind_address(&driver("::1")).is_ok());
+        assert!(bind_address(&driver("0.0.0.0")).is_err());
+        assert!(bind_address(&driver("192.168.1.2")).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn zome_calls_and_signals_over_http() {
+        observability::test_run().ok();
+        let uuid = Uuid::new_v4();
+        let dna = fake_dna_zomes(
+            &uuid.to_string(),
+            vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+        );
+        let dna_hash = dna.dna_hash().clone();
+        let cell_id = CellId::from((dna_hash.clone(), fake_agent_pubkey_1()));
+        let installed_cell = InstalledCell::new(cell_id.clone(), "handle".into());
+
+        let mut dna_store = MockDnaStore::new();
+        dna_store
+            .expect_get()
+            .with(predicate::eq(dna_hash))
+            .returning(move |_| Some(dna.clone()));
+        dna_store
+            .expect_add_dnas::<Vec<_>>()
+            .times(1)
+            .return_const(());
+        dna_store
+            .expect_add_entry_defs::<Vec<_>>()
+            .times(1)
+            .return_const(());
+
+        let (_tmpdir, app_api, handle) = setup_app(vec![(installed_cell, None)], dna_store).await;
+        let (signal_tx, _) = broadcast::channel(SIGNAL_BUFFER_SIZE);
+        let (stop_tx, stop_rx) = broadcast::channel(1);
+        let driver = InterfaceDriver::Http {
+            port: 0,
+            bind_address: None,
+            max_body_size: Some(64),
+        };
+        let (port, task) =
+            spawn_http_app_interface_task(&driver, app_api, signal_tx.clone(), stop_rx)
+                .await
+                .unwrap();
+        let (token, _) = handle
+            .issue_app_authentication_token("test app".to_string(), 60)
+            .await
+            .unwrap();
+
+        let client = hyper::Client::new();
+        let request = |method: Method, path: &str, token: Option<&AppAuthenticationToken>| {
+            let mut builder = Request::builder()
+                .method(method)
+                .uri(format!("http://127.0.0.1:{}{}", port, path));
+            if let Some(token) = token {
+                builder = builder.header(
+                    AUTHORIZATION,
+                    format!("Bearer {}", String::from(token.clone())),
+                );
+            }
+            builder.body(Body::from("null")).unwrap()
+        };
+        let call_path = "/apps/test%20app/cells/handle/zomes/foo/foo";
+
+        // Requests without a token are rejected
+        let response = client
+            .request(request(Method::POST, call_path, None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // The zome call output is transcoded to JSON
+        let response = client
+            .request(request(Method::POST, call_path, Some(&token)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            serde_json::json!("foo")
+        );
+
+        // Bodies over the limit are refused
+        let mut too_large = request(Method::POST, call_path, Some(&token));
+        *too_large.body_mut() = Body::from(serde_json::json!("x".repeat(100)).to_string());
+        let response = client.request(too_large).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // Unknown cells are not found
+        let response = client
+            .request(request(
+                Method::POST,
+                "/apps/test%20app/cells/nope/zomes/foo/foo",
+                Some(&token),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // Signals for the app's cells are streamed as events
+        let response = client
+            .request(request(
+                Method::GET,
+                "/apps/test%20app/signals",
+                Some(&token),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let mut body = response.into_body();
+        signal_tx
+            .send(Signal::App(
+                cell_id.clone(),
+                AppSignal::new(ExternIO::encode("hello").unwrap()),
+            ))
+            .unwrap();
+        let event = body.next().await.unwrap().unwrap();
+        let event = std::str::from_utf8(&event).unwrap();
+        assert!(event.starts_with("event: app\ndata: "));
+        assert!(event.contains(r#""payload":"hello""#));
+
+        stop_tx.send(()).unwrap();
+        task.await.unwrap().unwrap();
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        handle.shutdown().await;
+        shutdown.await.unwrap().unwrap();
+    }
+}
@@ -1,22 +1,29 @@
 //! Module for establishing Websocket-based Interfaces,
-//! i.e. those configured with `InterfaceDriver::Websocket`
+//! i.e. those configured with `InterfaceDriver::Websocket`,
+//! or with `InterfaceDriver::UnixSocket`, which uses the same framing.
 
 use super::error::InterfaceError;
 use super::error::InterfaceResult;
+use crate::conductor::api::AppInterfaceApi;
 use crate::conductor::conductor::StopReceiver;
 use crate::conductor::interface::*;
 use crate::conductor::manager::ManagedTaskHandle;
 use crate::conductor::manager::ManagedTaskResult;
+use holochain_conductor_api::config::DEFAULT_UNIX_SOCKET_MODE;
 use holochain_serialized_bytes::SerializedBytes;
 use holochain_types::signal::Signal;
 use holochain_websocket::ListenerHandle;
 use holochain_websocket::ListenerItem;
+use holochain_websocket::ListenerStream;
 use holochain_websocket::WebsocketConfig;
 use holochain_websocket::WebsocketListener;
 use holochain_websocket::WebsocketMessage;
 use holochain_websocket::WebsocketReceiver;
 use holochain_websocket::WebsocketSender;
+use holochain_websocket::WebsocketTlsConfig;
 use std::convert::TryFrom;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 
 use std::sync::atomic::AtomicIsize;
 use std::sync::atomic::Ordering;
@@ -25,6 +32,7 @@ use tokio::sync::broadcast;
 use tokio_stream::StreamExt;
 use tracing::*;
 use url2::url2;
+use url2::Url2;
 
 // TODO: This is arbitrary, choose reasonable size.
 /// Number of signals in buffer before applying
@@ -34,21 +42,102 @@ const MAX_CONNECTIONS: isize = 400;
 
 /// Create a WebsocketListener to be used in interfaces
 pub async fn spawn_websocket_listener(
-    port: u16,
+    driver: &InterfaceDriver,
 ) -> InterfaceResult<(
     ListenerHandle,
     impl futures::stream::Stream<Item = ListenerItem>,
 )> {
     trace!("Initializing Admin interface");
-    let listener = WebsocketListener::bind_with_handle(
-        url2!("ws://127.0.0.1:{}", port),
-        Arc::new(WebsocketConfig::default()),
-    )
-    .await?;
+    let listener = bind_listener(driver).await?;
     trace!("LISTENING AT: {}", listener.0.local_addr());
     Ok(listener)
 }
 
+/// Where a listener should bind, as specified by an interface driver
+#[derive(Debug, PartialEq)]
+enum ListenAt {
+    Url(Url2),
+    UnixSocket { path: PathBuf, mode: u32 },
+}
+
+/// Bind a listener to wherever the interface driver specifies
+async fn bind_listener(
+    driver: &InterfaceDriver,
+) -> InterfaceResult<(ListenerHandle, ListenerStream)> {
+    let (listen_at, config) = websocket_listener_config(driver).ok_or_else(|| {
+        InterfaceError::Other(format!("{:?} is not served over a websocket", driver))
+    })?;
+    let config = Arc::new(config);
+    match listen_at {
+        ListenAt::Url(url) => {
+            let (handle, listener) = WebsocketListener::bind_with_handle(url, config).await?;
+            Ok((handle, Box::pin(listener)))
+        }
+        #[cfg(unix)]
+        ListenAt::UnixSocket { path, mode } => {
+            let (handle, listener) =
+                WebsocketListener::bind_unix_with_handle(path, mode, config).await?;
+            Ok((handle, Box::pin(listener)))
+        }
+        #[cfg(not(unix))]
+        ListenAt::UnixSocket { .. } => Err(InterfaceError::Other(
+            "Unix socket interfaces are not supported on this platform".into(),
+        )),
+    }
+}
+
+/// Where to bind and the websocket config to listen with,
+/// as specified by an interface driver, if it is served over a websocket
+fn websocket_listener_config(driver: &InterfaceDriver) -> Option<(ListenAt, WebsocketConfig)> {
+    match driver {
+        InterfaceDriver::Websocket {
+            port,
+            tls,
+            allowed_origins,
+            limits,
+            ..
+        } => {
+            let tls = tls.clone().map(|tls| WebsocketTlsConfig {
+                cert_path: tls.cert_path,
+                key_path: tls.key_path,
+            });
+            let mut config = WebsocketConfig::default()
+                .tls(tls)
+                .allowed_origins(allowed_origins.clone());
+            let limits = limits.clone().unwrap_or_default();
+            if let Some(s) = limits.default_request_timeout_s {
+                config = config.default_request_timeout_s(s);
+            }
+            if let Some(max) = limits.max_send_queue {
+                config = config.max_send_queue(max);
+            }
+            if let Some(max) = limits.max_message_size {
+                config = config.max_message_size(max);
+            }
+            if let Some(max) = limits.max_frame_size {
+                config = config.max_frame_size(max);
+            }
+            if let Some(max) = limits.max_pending_connections {
+                config = config.max_pending_connections(max);
+            }
+            let url = url2!(
+                "{}://{}",
+                config.scheme,
+                SocketAddr::new(driver.bind_address(), *port)
+            );
+            Some((ListenAt::Url(url), config))
+        }
+        InterfaceDriver::UnixSocket { path, mode } => Some((
+            ListenAt::UnixSocket {
+                path: path.clone(),
+                mode: mode.unwrap_or(DEFAULT_UNIX_SOCKET_MODE),
+            },
+            WebsocketConfig::default(),
+        )),
+        InterfaceDriver::Http { .. } => None,
+    }
+}
+
 /// Create an Admin Interface, which only receives AdminRequest messages
 /// from the external client
 pub fn spawn_admin_interface_task<A: InterfaceApi>(
@@ -91,23 +180,22 @@ pub fn spawn_admin_interface_task<A: InterfaceApi>(
 
 /// Create an App Interface, which includes the ability to receive signals
 /// from Cells via a broadcast channel
-pub async fn spawn_app_interface_task<A: InterfaceApi>(
-    port: u16,
+pub async fn spawn_app_interface_task<A: InterfaceApi + AppInterfaceApi>(
+    driver: &InterfaceDriver,
     api: A,
     signal_broadcaster: broadcast::Sender<Signal>,
     mut stop_rx: StopReceiver,
 ) -> InterfaceResult<(u16, ManagedTaskHandle)> {
     trace!("Initializing App interface");
-    let (handle, mut listener) = WebsocketListener::bind_with_handle(
-        url2!("ws://127.0.0.1:{}", port),
-        Arc::new(WebsocketConfig::default()),
-    )
-    .await?;
+    let (handle, mut listener) = bind_listener(driver).await?;
     trace!("LISTENING AT: {}", handle.local_addr());
-    let port = handle
-        .local_addr()
-        .port()
-        .ok_or(InterfaceError::PortError)?;
+    let port = match driver {
+        InterfaceDriver::UnixSocket { .. } => 0,
+        _ => handle
+            .local_addr()
+            .port()
+            .ok_or(InterfaceError::PortError)?,
+    };
     // Task that will kill the listener and all child connections.
     tokio::task::spawn(
         handle.close_on(async move { stop_rx.recv().await.map(|_| true).unwrap_or(true) }),
@@ -119,7 +207,7 @@ pub async fn spawn_app_interface_task<A: InterfaceApi>(
                 Ok((tx_to_iface, rx_from_iface)) => {
                     let rx_from_cell = signal_broadcaster.subscribe();
                     tokio::task::spawn(recv_incoming_msgs_and_outgoing_signals(
-                        api.clone(),
+                        api.for_connection(),
                         rx_from_iface,
                         rx_from_cell,
                         tx_to_iface,
@@ -155,7 +243,7 @@ async fn recv_incoming_admin_msgs<A: InterfaceApi>(
 /// Polls for messages coming in from the external client while simultaneously
 /// polling for signals being broadcast from the Cells associated with this
 /// App interface.
-async fn recv_incoming_msgs_and_outgoing_signals<A: InterfaceApi>(
+async fn recv_incoming_msgs_and_outgoing_signals<A: InterfaceApi + AppInterfaceApi>(
     api: A,
     mut rx_from_iface: WebsocketReceiver,
     mut rx_from_cell: broadcast::Receiver<Signal>,
@@ -169,12 +257,18 @@ async fn recv_incoming_msgs_and_outgoing_signals<A: InterfaceApi>(
             // across the interface
             signal = rx_from_cell.recv() => {
                 if let Ok(signal) = signal {
+                    // Only send the signals of the App this connection
+                    // has authenticated for
+                    if !api.is_signal_permitted(&signal).await {
+                        continue;
+                    }
                     trace!(msg = "Sending signal!", ?signal);
                     let bytes = SerializedBytes::try_from(
-                        signal
+                        signal.clone()
                         // .map_err(InterfaceError::SignalReceive)?,
                     )?;
                     tx_to_iface.signal(bytes).await?;
+                    api.signal_sent(&signal);
                 } else {
                     debug!("Closing interface: signal stream empty");
                     break;
@@ -212,6 +306,7 @@ where
 pub mod test_utils {
     use crate::conductor::api::RealAppInterfaceApi;
     use crate::conductor::conductor::ConductorBuilder;
+    use crate::conductor::error::ConductorError;
     use crate::conductor::ConductorHandle;
     use holochain_lmdb::test_utils::test_environments;
     use holochain_serialized_bytes::prelude::*;
@@ -263,8 +358,10 @@ pub mod test {
     use crate::conductor::api::error::ExternalApiWireError;
     use crate::conductor::api::AdminRequest;
     use crate::conductor::api::AdminResponse;
+    use crate::conductor::api::AppAuthenticationToken;
     use crate::conductor::api::RealAdminInterfaceApi;
     use crate::conductor::conductor::ConductorBuilder;
+    use crate::conductor::error::ConductorError;
     use crate::conductor::p2p_store::AgentKv;
     use crate::conductor::p2p_store::AgentKvKey;
     use crate::conductor::state::ConductorState;
@@ -467,6 +564,254 @@ pub mod test {
         shutdown.await.unwrap().unwrap();
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn app_connections_require_authentication() {
+        observability::test_run().ok();
+        let uuid = Uuid::new_v4();
+        let dna = fake_dna_zomes(
+            &uuid.to_string(),
+            vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+        );
+        let dna_hash = dna.dna_hash().clone();
+        let cell_id = CellId::from((dna_hash.clone(), fake_agent_pubkey_1()));
+        let other_cell_id = CellId::from((dna_hash.clone(), fake_agent_pubkey_2()));
+        let installed_cell = InstalledCell::new(cell_id.clone(), "handle".into());
+
+        let mut dna_store = MockDnaStore::new();
+        dna_store
+            .expect_get()
+            .with(predicate::eq(dna_hash))
+            .returning(move |_| Some(dna.clone()));
+        dna_store
+            .expect_add_dnas::<Vec<_>>()
+            .times(1)
+            .return_const(());
+        dna_store
+            .expect_add_entry_defs::<Vec<_>>()
+            .times(1)
+            .return_const(());
+
+        let (_tmpdir, app_api, handle) = setup_app(vec![(installed_cell, None)], dna_store).await;
+        let app_info = || AppRequest::AppInfo {
+            installed_app_id: "test app".to_string(),
+        };
+        let signal = |cell_id: &CellId| {
+            Signal::App(
+                cell_id.clone(),
+                AppSignal::new(ExternIO::encode(()).unwrap()),
+            )
+        };
+
+        // A new connection can't do anything until it authenticates
+        let connection = app_api.for_connection();
+        assert_matches!(
+            connection.handle_app_request(app_info()).await,
+            AppResponse::Error(ExternalApiWireError::AppAuthenticationFailed(_))
+        );
+        assert!(!connection.is_signal_permitted(&signal(&cell_id)).await);
+
+        // Tokens are only issued for installed apps
+        assert!(handle
+            .issue_app_authentication_token("not installed".to_string(), 60)
+            .await
+            .is_err());
+
+        // and must expire at a time that can be represented
+        assert_matches!(
+            handle
+                .issue_app_authentication_token("test app".to_string(), u64::MAX)
+                .await,
+            Err(ConductorError::AppAuthenticationTokenExpiryTooLong(
+                u64::MAX
+            ))
+        );
+
+        // Unknown and expired tokens are rejected
+        let (expired, _) = handle
+            .issue_app_authentication_token("test app".to_string(), 0)
+            .await
+            .unwrap();
+        for token in vec![AppAuthenticationToken::from("made up".to_string()), expired] {
+            assert_matches!(
+                connection
+                    .handle_app_request(AppRequest::Authenticate { token })
+                    .await,
+                AppResponse::Error(ExternalApiWireError::AppAuthenticationFailed(_))
+            );
+        }
+
+        let (token, _) = handle
+            .issue_app_authentication_token("test app".to_string(), 60)
+            .await
+            .unwrap();
+        assert_matches!(
+            connection
+                .handle_app_request(AppRequest::Authenticate { token })
+                .await,
+            AppResponse::Authenticated { installed_app_id } if installed_app_id == "test app"
+        );
+
+        // Now the connection can access its app, but no others
+        assert_matches!(
+            connection.handle_app_request(app_info()).await,
+            AppResponse::AppInfo(Some(_))
+        );
+        assert_matches!(
+            connection
+                .handle_app_request(AppRequest::AppInfo {
+                    installed_app_id: "other app".to_string(),
+                })
+                .await,
+            AppResponse::Error(ExternalApiWireError::AppAuthenticationFailed(_))
+        );
+        let mut call: ZomeCall =
+            crate::fixt::ZomeCallInvocationFixturator::new(crate::fixt::NamedInvocation(
+                other_cell_id.clone(),
+                TestWasm::Foo.into(),
+                "foo".into(),
+                ExternIO::encode(()).unwrap(),
+            ))
+            .next()
+            .unwrap()
+            .into();
+        call.cell_id = other_cell_id.clone();
+        assert_matches!(
+            connection
+                .handle_app_request(AppRequest::ZomeCall(Box::new(call)))
+                .await,
+            AppResponse::Error(ExternalApiWireError::AppAuthenticationFailed(_))
+        );
+        assert!(connection.is_signal_permitted(&signal(&cell_id)).await);
+        assert!(
+            !connection
+                .is_signal_permitted(&signal(&other_cell_id))
+                .await
+        );
+
+        // Other connections are unaffected
+        assert!(
+            !app_api
+                .for_connection()
+                .is_signal_permitted(&signal(&cell_id))
+                .await
+        );
+
+        // Once the app is uninstalled, which the connection notices as a
+        // change to the conductor state, its cells can't be reached through
+        // the connection any more
+        let state_version = handle.state_version();
+        handle.uninstall_app(&"test app".to_string()).await.unwrap();
+        assert_ne!(handle.state_version(), state_version);
+        assert!(!connection.is_signal_permitted(&signal(&cell_id)).await);
+        let mut call: ZomeCall =
+            crate::fixt::ZomeCallInvocationFixturator::new(crate::fixt::NamedInvocation(
+                cell_id.clone(),
+                TestWasm::Foo.into(),
+                "foo".into(),
+                ExternIO::encode(()).unwrap(),
+            ))
+            .next()
+            .unwrap()
+            .into();
+        call.cell_id = cell_id.clone();
+        assert_matches!(
+            connection
+                .handle_app_request(AppRequest::ZomeCall(Box::new(call)))
+                .await,
+            AppResponse::Error(ExternalApiWireError::AppAuthenticationFailed(_))
+        );
+
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        handle.shutdown().await;
+        shutdown.await.unwrap().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn signals_are_only_delivered_to_clients_of_the_cells_app() {
+        use holochain_types::signal::SystemSignal;
+        use holochain_types::test_utils::fake_header_hash;
+        use holochain_zome_types::query::ChainFork;
+
+        observability::test_run().ok();
+        let uuid = Uuid::new_v4();
+        let dna = fake_dna_zomes(
+            &uuid.to_string(),
+            vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+        );
+        let dna_hash = dna.dna_hash().clone();
+        let cell_id = CellId::from((dna_hash.clone(), fake_agent_pubkey_1()));
+        let other_cell_id = CellId::from((dna_hash.clone(), fake_agent_pubkey_2()));
+        let installed_cell = InstalledCell::new(cell_id.clone(), "handle".into());
+
+        let mut dna_store = MockDnaStore::new();
+        dna_store
+            .expect_get()
+            .with(predicate::eq(dna_hash))
+            .returning(move |_| Some(dna.clone()));
+        dna_store
+            .expect_add_dnas::<Vec<_>>()
+            .times(1)
+            .return_const(());
+        dna_store
+            .expect_add_entry_defs::<Vec<_>>()
+            .times(1)
+            .return_const(());
+
+        let (_tmpdir, _app_api, handle) = setup_app(vec![(installed_cell, None)], dna_store).await;
+        let port = handle
+            .clone()
+            .add_app_interface(InterfaceDriver::websocket(0))
+            .await
+            .unwrap();
+        let fork_signal = |cell_id: &CellId| {
+            Signal::System(SystemSignal::ChainForkDetected {
+                cell_id: cell_id.clone(),
+                author: cell_id.agent_pubkey().clone(),
+                fork: ChainFork {
+                    fork_seq: 3,
+                    first_header: fake_header_hash(3),
+                    second_header: fake_header_hash(103),
+                },
+            })
+        };
+        let timeout = std::time::Duration::from_secs(1);
+
+        // The only client belongs to "test app"
+        let (mut client, _client_rx) = crate::sweettest::websocket_client_by_port(port)
+            .await
+            .unwrap();
+        let (token, _) = handle
+            .issue_app_authentication_token("test app".to_string(), 60)
+            .await
+            .unwrap();
+        let response: AppResponse = client
+            .request(AppRequest::Authenticate { token })
+            .await
+            .unwrap();
+        assert_matches!(response, AppResponse::Authenticated { .. });
+
+        // so a signal of a cell of another app isn't delivered to anyone
+        assert!(
+            !handle
+                .signal_broadcaster()
+                .await
+                .deliver(fork_signal(&other_cell_id), timeout)
+                .await
+        );
+        // while a signal of its own app's cell is
+        assert!(
+            handle
+                .signal_broadcaster()
+                .await
+                .deliver(fork_signal(&cell_id), timeout)
+                .await
+        );
+
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        handle.shutdown().await;
+        shutdown.await.unwrap().unwrap();
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn activate_app() {
         observability::test_run().ok();
@@ -593,7 +938,7 @@ pub mod test {
         let maybe_info = state.get_app_info(&"test app".to_string());
         if let Some(info) = maybe_info {
             assert_eq!(info.installed_app_id, "test app");
-            assert_matches!(info.status, InstalledAppStatus::Inactive {..});
+            assert_matches!(info.status, InstalledAppStatus::Inactive { .. });
         } else {
             assert!(false);
         }
@@ -608,11 +953,20 @@ pub mod test {
         let (_tmpdir, conductor_handle) = setup_admin().await;
         let shutdown = conductor_handle.take_shutdown_handle().await.unwrap();
         let admin_api = RealAdminInterfaceApi::new(conductor_handle.clone());
-        let msg = AdminRequest::AttachAppInterface { port: None };
+        let msg = AdminRequest::AttachAppInterface {
+            port: None,
+            driver: None,
+        };
         let msg = msg.try_into().unwrap();
         let respond = |bytes: SerializedBytes| {
             let response: AdminResponse = bytes.try_into().unwrap();
-            assert_matches!(response, AdminResponse::AppInterfaceAttached { .. });
+            assert_matches!(
+                response,
+                AdminResponse::AppInterfaceAttached {
+                    port: Some(_),
+                    path: None
+                }
+            );
             async { Ok(()) }.boxed().into()
         };
         let respond = Respond::Request(Box::new(respond));
@@ -622,6 +976,127 @@ pub mod test {
         shutdown.await.unwrap().unwrap();
     }
 
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn admin_interface_over_unix_socket() {
+        use std::os::unix::fs::PermissionsExt;
+        observability::test_run().ok();
+        let (tmpdir, conductor_handle) = setup_admin().await;
+        let path = tmpdir.path().join("admin.sock");
+        let driver = InterfaceDriver::unix_socket(path.clone());
+        let (listener_handle, listener) = spawn_websocket_listener(&driver).await.unwrap();
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            DEFAULT_UNIX_SOCKET_MODE
+        );
+        let (stop_tx, stop_rx) = broadcast::channel(1);
+        let _task = spawn_admin_interface_task(
+            listener_handle,
+            listener,
+            RealAdminInterfaceApi::new(conductor_handle.clone()),
+            stop_rx,
+        )
+        .unwrap();
+
+        let (mut client, _) =
+            holochain_websocket::connect_unix(path, Arc::new(WebsocketConfig::default()))
+                .await
+                .unwrap();
+        let response: AdminResponse = client.request(AdminRequest::ListDnas).await.unwrap();
+        assert_matches!(response, AdminResponse::DnasListed(_));
+
+        // An app interface served over a unix socket is reported by its path
+        let app_path = tmpdir.path().join("app.sock");
+        let response: AdminResponse = client
+            .request(AdminRequest::AttachAppInterface {
+                port: None,
+                driver: Some(InterfaceDriver::unix_socket(app_path.clone())),
+            })
+            .await
+            .unwrap();
+        assert_matches!(
+            response,
+            AdminResponse::AppInterfaceAttached {
+                port: None,
+                path: Some(path),
+            } if path == app_path
+        );
+
+        // Every app interface served over a unix socket is kept, though
+        // none of them has a port to tell them apart by
+        let other_app_path = tmpdir.path().join("other_app.sock");
+        let response: AdminResponse = client
+            .request(AdminRequest::AttachAppInterface {
+                port: None,
+                driver: Some(InterfaceDriver::unix_socket(other_app_path.clone())),
+            })
+            .await
+            .unwrap();
+        assert_matches!(response, AdminResponse::AppInterfaceAttached { .. });
+        let state = conductor_handle.get_state_from_handle().await.unwrap();
+        let mut paths: Vec<_> = state
+            .app_interfaces
+            .values()
+            .filter_map(|config| match &config.driver {
+                InterfaceDriver::UnixSocket { path, .. } => Some(path.clone()),
+                _ => None,
+            })
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec![app_path.clone(), other_app_path]);
+
+        // and listed with their drivers, so they can't be mistaken for
+        // websocket interfaces
+        let response: AdminResponse = client
+            .request(AdminRequest::ListAppInterfaces)
+            .await
+            .unwrap();
+        assert_matches!(
+            response,
+            AdminResponse::AppInterfacesListed(drivers) if drivers.len() == 2
+                && drivers.contains(&InterfaceDriver::unix_socket(app_path))
+        );
+
+        stop_tx.send(()).unwrap();
+        conductor_handle.shutdown().await;
+    }
+
+    #[test]
+    fn listener_config_from_driver() {
+        let (listen_at, config) =
+            websocket_listener_config(&InterfaceDriver::websocket(1234)).unwrap();
+        assert_eq!(listen_at, ListenAt::Url(url2!("ws://127.0.0.1:1234")));
+        assert_eq!(config.allowed_origins, None);
+        assert_eq!(config.tls, None);
+
+        let driver = InterfaceDriver::Websocket {
+            port: 1234,
+            bind_address: Some("0.0.0.0".parse().unwrap()),
+            tls: Some(holochain_conductor_api::InterfaceTlsConfig {
+                cert_path: "cert.pem".into(),
+                key_path: "key.pem".into(),
+            }),
+            allowed_origins: Some(vec!["http://localhost:8888".into()]),
+            limits: Some(holochain_conductor_api::WebsocketLimits {
+                max_message_size: Some(1024),
+                ..Default::default()
+            }),
+        };
+        let (listen_at, config) = websocket_listener_config(&driver).unwrap();
+        assert_eq!(listen_at, ListenAt::Url(url2!("wss://0.0.0.0:1234")));
+        assert_eq!(config.scheme, "wss");
+        assert!(config.tls.is_some());
+        assert!(config.is_origin_allowed(Some("http://localhost:8888")));
+        assert!(!config.is_origin_allowed(Some("http://example.com")));
+        assert_eq!(config.max_message_size, 1024);
+        assert_eq!(
+            config.max_frame_size,
+            WebsocketConfig::default().max_frame_size
+        );
+
+        assert!(websocket_listener_config(&InterfaceDriver::http(1234)).is_none());
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn dump_state() {
         observability::test_run().ok();
@@ -677,6 +1152,9 @@ pub mod test {
                 uid: uid.to_string(),
                 properties: SerializedBytes::try_from(()).unwrap(),
                 zomes: zomes.clone().into_iter().map(Into::into).collect(),
+                redundancy_target: None,
+                zome_call_limits: None,
+                integrity: None,
             },
             zomes.into_iter().map(Into::into),
         )
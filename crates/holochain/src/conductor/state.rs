@@ -25,6 +25,18 @@ pub struct ConductorState {
     /// List of interfaces any UI can use to access zome functions.
     #[serde(default)]
     pub app_interfaces: HashMap<AppInterfaceId, AppInterfaceConfig>,
+    /// Agent keys which have been rotated, with the key which replaced each
+    #[serde(default)]
+    pub superseded_agent_keys: HashMap<AgentPubKey, SupersededAgentKey>,
+}
+
+/// The link from a rotated agent key to the key which replaced it
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct SupersededAgentKey {
+    /// The key which replaced the rotated key
+    pub by: AgentPubKey,
+    /// When the key was rotated
+    pub at: Timestamp,
 }
 
 /// A unique identifier used to refer to an App Interface internally.
@@ -53,6 +65,14 @@ impl AppInterfaceId {
         };
         Self { port, id }
     }
+    /// Create an id for an interface served over a unix socket, which has
+    /// no port, from the path of the socket
+    pub fn from_unix_socket_path(path: &std::path::Path) -> Self {
+        Self {
+            port: 0,
+            id: Some(format!("unix:{}", path.display())),
+        }
+    }
     /// Get the port intended for this interface
     pub fn port(&self) -> u16 {
         self.port
@@ -73,6 +93,19 @@ impl ConductorState {
             })
     }
 
+    /// The key which replaced this agent key, if it has been rotated
+    pub fn superseded_by(&self, agent_key: &AgentPubKey) -> Option<&AgentPubKey> {
+        self.superseded_agent_keys.get(agent_key).map(|s| &s.by)
+    }
+
+    /// The key this agent key replaced, if it was created by a rotation
+    pub fn supersedes(&self, agent_key: &AgentPubKey) -> Option<&AgentPubKey> {
+        self.superseded_agent_keys
+            .iter()
+            .find(|(_, s)| &s.by == agent_key)
+            .map(|(old, _)| old)
+    }
+
     /// Returns the interface configuration with the given ID if present
     pub fn interface_by_id(&self, id: &AppInterfaceId) -> Option<AppInterfaceConfig> {
         self.app_interfaces.get(id).cloned()
@@ -101,9 +134,14 @@ pub struct AppInterfaceConfig {
 impl AppInterfaceConfig {
     /// Create config for a websocket interface
     pub fn websocket(port: u16) -> Self {
+        Self::new(InterfaceDriver::websocket(port))
+    }
+
+    /// Create config for an interface with the given driver
+    pub fn new(driver: InterfaceDriver) -> Self {
         Self {
             signal_subscriptions: HashMap::new(),
-            driver: InterfaceDriver::Websocket { port },
+            driver,
         }
     }
 }
@@ -17,11 +17,15 @@
 //! | CallZome       | *n/a*            | ChainSequence    | ProduceDhtOps  |
 //! | ProduceDhtOps  | ChainSequence    | Auth'd + IntQ †  | DhtOpIntegr.   |
 //! |                 **integration, common to both paths**                 |
-//! | DhtOpIntegr.   | IntegrationLimbo | IntegratedDhtOps | SysVal + VR    |
+//! | DhtOpIntegr.   | IntegrationLimbo | IntegratedDhtOps | SysVal + VR ‡  |
 //! | ValReceipt.    | IntegratedDhtOps | IntegratedDhtOps | *n/a           |
 //! | Publish        | AuthoredDhtOps   | *n/a*            | *n/a*          |
+//! |                     **autonomic, also on a timer**                    |
+//! | HealthCheck    | AuthoredDhtOps   | AuthoredDhtOps   | Publish        |
+//! | ChainForkCheck | ChainSequence    | ChainForks       | *n/a*          |
 //!
 //! († Auth'd + IntQ is short for: AuthoredDhtOps + IntegrationLimbo)
+//! (‡ also notifies ChainForkCheck when it records a fork in ChainForks)
 //!
 //! Implicitly, every workflow also writes to its own source queue, i.e. to
 //! remove the item it has just processed.
@@ -54,6 +58,10 @@ use crate::conductor::{manager::ManagedTaskAdd, ConductorHandle};
 use holochain_p2p::*;
 use holochain_state::workspace::WorkspaceError;
 use publish_dht_ops_consumer::*;
+mod health_check_consumer;
+use health_check_consumer::*;
+mod chain_fork_check_consumer;
+use chain_fork_check_consumer::*;
 
 use super::workflow::error::WorkflowError;
 
@@ -87,6 +95,23 @@ pub async fn spawn_queue_consumer_tasks(
         .await
         .expect("Failed to manage workflow handle");
 
+    // Health check
+    let (tx_health_check, handle) = spawn_health_check_consumer(
+        env.clone(),
+        conductor_handle.clone(),
+        cell_id.clone(),
+        stop.subscribe(),
+        tx_publish.clone(),
+    );
+    task_sender
+        .send(ManagedTaskAdd::cell_critical(
+            handle,
+            cell_id.clone(),
+            "health_check_consumer",
+        ))
+        .await
+        .expect("Failed to manage workflow handle");
+
     // Validation Receipt
     let (tx_receipt, handle) = spawn_validation_receipt_consumer(
         env.clone(),
@@ -103,6 +128,22 @@ pub async fn spawn_queue_consumer_tasks(
         .await
         .expect("Failed to manage workflow handle");
 
+    // Chain fork check
+    let (tx_chain_fork_check, handle) = spawn_chain_fork_check_consumer(
+        env.clone(),
+        conductor_handle.clone(),
+        stop.subscribe(),
+        cell_network.clone(),
+    );
+    task_sender
+        .send(ManagedTaskAdd::cell_critical(
+            handle,
+            cell_id.clone(),
+            "chain_fork_check_consumer",
+        ))
+        .await
+        .expect("Failed to manage workflow handle");
+
     let (create_tx_sys, get_tx_sys) = tokio::sync::oneshot::channel();
 
     // Integration
@@ -113,6 +154,7 @@ pub async fn spawn_queue_consumer_tasks(
         stop.subscribe(),
         get_tx_sys,
         tx_receipt.clone(),
+        tx_chain_fork_check.clone(),
     );
     task_sender
         .send(ManagedTaskAdd::cell_critical(
@@ -180,7 +222,7 @@ pub async fn spawn_queue_consumer_tasks(
         .expect("Failed to manage workflow handle");
 
     (
-        QueueTriggers::new(tx_sys.clone(), tx_produce.clone()),
+        QueueTriggers::new(tx_sys.clone(), tx_produce.clone(), tx_health_check),
         InitialQueueTriggers::new(
             tx_sys,
             tx_produce,
@@ -188,6 +230,7 @@ pub async fn spawn_queue_consumer_tasks(
             tx_app,
             tx_integration,
             tx_receipt,
+            tx_chain_fork_check,
         ),
     )
 }
@@ -199,6 +242,9 @@ pub struct QueueTriggers {
     pub sys_validation: TriggerSender,
     /// Notify the ProduceDhtOps workflow to run, i.e. after InvokeCallZome
     pub produce_dht_ops: TriggerSender,
+    /// Notify the HealthCheck workflow to run now rather than at its next
+    /// scheduled time
+    pub health_check: TriggerSender,
 }
 
 /// The triggers to run once at the start of a cell
@@ -211,14 +257,20 @@ pub struct InitialQueueTriggers {
     app_validation: TriggerSender,
     integrate_dht_ops: TriggerSender,
     validation_receipt: TriggerSender,
+    chain_fork_check: TriggerSender,
 }
 
 impl QueueTriggers {
     /// Create a new queue trigger
-    pub fn new(sys_validation: TriggerSender, produce_dht_ops: TriggerSender) -> Self {
+    pub fn new(
+        sys_validation: TriggerSender,
+        produce_dht_ops: TriggerSender,
+        health_check: TriggerSender,
+    ) -> Self {
         Self {
             sys_validation,
             produce_dht_ops,
+            health_check,
         }
     }
 }
@@ -231,6 +283,7 @@ impl InitialQueueTriggers {
         app_validation: TriggerSender,
         integrate_dht_ops: TriggerSender,
         validation_receipt: TriggerSender,
+        chain_fork_check: TriggerSender,
     ) -> Self {
         Self {
             sys_validation,
@@ -239,6 +292,7 @@ impl InitialQueueTriggers {
             app_validation,
             integrate_dht_ops,
             validation_receipt,
+            chain_fork_check,
         }
     }
 
@@ -250,6 +304,7 @@ impl InitialQueueTriggers {
         self.integrate_dht_ops.trigger();
         self.produce_dht_ops.trigger();
         self.validation_receipt.trigger();
+        self.chain_fork_check.trigger();
     }
 }
 /// The means of nudging a queue consumer to tell it to look for more work
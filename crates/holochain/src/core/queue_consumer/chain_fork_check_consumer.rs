@@ -0,0 +1,66 @@
+//! The workflow and queue consumer for checking source chains for forks
+
+use super::*;
+
+use crate::conductor::manager::ManagedTaskResult;
+use crate::core::workflow::chain_fork_check_workflow::chain_fork_check_workflow;
+use crate::core::workflow::chain_fork_check_workflow::ChainForkCheckWorkspace;
+use holochain_lmdb::env::EnvironmentWrite;
+
+use tokio::task::JoinHandle;
+use tracing::*;
+
+/// How often a cell checks its own chain against its authorities, when not
+/// triggered sooner.
+pub const CHAIN_FORK_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+/// Spawn the QueueConsumer for the ChainForkCheck workflow.
+/// Like the health check, this one also runs on a timer.
+#[instrument(skip(env, conductor_handle, stop, cell_network))]
+pub fn spawn_chain_fork_check_consumer(
+    env: EnvironmentWrite,
+    conductor_handle: ConductorHandle,
+    mut stop: sync::broadcast::Receiver<()>,
+    cell_network: HolochainP2pCell,
+) -> (TriggerSender, JoinHandle<ManagedTaskResult>) {
+    let (tx, mut rx) = TriggerSender::new();
+    let handle = tokio::spawn(async move {
+        loop {
+            // Wait for next job, or until it's time for a scheduled check
+            let next_job = tokio::time::timeout(
+                CHAIN_FORK_CHECK_INTERVAL,
+                next_job_or_exit(&mut rx, &mut stop),
+            );
+            if let Ok(Job::Shutdown) = next_job.await {
+                tracing::warn!(
+                    "Cell is shutting down: stopping chain_fork_check_workflow queue consumer."
+                );
+                break;
+            }
+
+            // Run the workflow
+            let workspace = ChainForkCheckWorkspace::new(env.clone().into())
+                .expect("Could not create Workspace");
+            // App interfaces may have been added since the last check
+            let mut signal_tx = conductor_handle.signal_broadcaster().await;
+            if let Err(err) = chain_fork_check_workflow(
+                workspace,
+                env.clone().into(),
+                cell_network.clone(),
+                &mut signal_tx,
+            )
+            .await
+            {
+                handle_workflow_error(
+                    conductor_handle.clone(),
+                    cell_network.cell_id(),
+                    err,
+                    "chain_fork_check failure",
+                )
+                .await?
+            }
+        }
+        Ok(())
+    });
+    (tx, handle)
+}
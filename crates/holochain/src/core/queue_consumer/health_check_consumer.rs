@@ -0,0 +1,69 @@
+//! The workflow and queue consumer for the autonomic health check
+
+use super::*;
+
+use crate::conductor::manager::ManagedTaskResult;
+use crate::core::workflow::health_check_workflow::health_check_workflow;
+use crate::core::workflow::health_check_workflow::HealthCheckWorkspace;
+use holochain_lmdb::env::EnvironmentWrite;
+use holochain_types::dna::DEFAULT_REDUNDANCY_TARGET;
+
+use tokio::task::JoinHandle;
+use tracing::*;
+
+/// How often the health of a cell's authored ops is checked, when not
+/// triggered sooner.
+pub const HEALTH_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Spawn the QueueConsumer for the HealthCheck workflow.
+/// Unlike the other consumers, this one also runs on a timer.
+#[instrument(skip(env, conductor_handle, stop, cell_id, trigger_publish))]
+pub fn spawn_health_check_consumer(
+    env: EnvironmentWrite,
+    conductor_handle: ConductorHandle,
+    cell_id: CellId,
+    mut stop: sync::broadcast::Receiver<()>,
+    mut trigger_publish: TriggerSender,
+) -> (TriggerSender, JoinHandle<ManagedTaskResult>) {
+    let (tx, mut rx) = TriggerSender::new();
+    let handle = tokio::spawn(async move {
+        let redundancy_target = conductor_handle
+            .get_dna(cell_id.dna_hash())
+            .await
+            .map(|dna| dna.dna_def().redundancy_target())
+            .unwrap_or(DEFAULT_REDUNDANCY_TARGET);
+        loop {
+            // Wait for next job, or until it's time for a scheduled check
+            let next_job =
+                tokio::time::timeout(HEALTH_CHECK_INTERVAL, next_job_or_exit(&mut rx, &mut stop));
+            if let Ok(Job::Shutdown) = next_job.await {
+                tracing::warn!(
+                    "Cell is shutting down: stopping health_check_workflow queue consumer."
+                );
+                break;
+            }
+
+            // Run the workflow
+            let workspace =
+                HealthCheckWorkspace::new(env.clone().into()).expect("Could not create Workspace");
+            if let Err(err) = health_check_workflow(
+                workspace,
+                env.clone().into(),
+                redundancy_target,
+                &mut trigger_publish,
+            )
+            .await
+            {
+                handle_workflow_error(
+                    conductor_handle.clone(),
+                    cell_id.clone(),
+                    err,
+                    "health_check failure",
+                )
+                .await?
+            }
+        }
+        Ok(())
+    });
+    (tx, handle)
+}
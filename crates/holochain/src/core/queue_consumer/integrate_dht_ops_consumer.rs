@@ -11,7 +11,14 @@ use tokio::task::JoinHandle;
 use tracing::*;
 
 /// Spawn the QueueConsumer for DhtOpIntegration workflow
-#[instrument(skip(env, conductor_handle, stop, trigger_sys, trigger_receipt))]
+#[instrument(skip(
+    env,
+    conductor_handle,
+    stop,
+    trigger_sys,
+    trigger_receipt,
+    trigger_chain_fork_check
+))]
 pub fn spawn_integrate_dht_ops_consumer(
     env: EnvironmentWrite,
     conductor_handle: ConductorHandle,
@@ -19,6 +26,7 @@ pub fn spawn_integrate_dht_ops_consumer(
     mut stop: sync::broadcast::Receiver<()>,
     trigger_sys: sync::oneshot::Receiver<TriggerSender>,
     mut trigger_receipt: TriggerSender,
+    mut trigger_chain_fork_check: TriggerSender,
 ) -> (TriggerSender, JoinHandle<ManagedTaskResult>) {
     let (tx, mut rx) = TriggerSender::new();
     let mut trigger_self = tx.clone();
@@ -41,6 +49,7 @@ pub fn spawn_integrate_dht_ops_consumer(
                 env.clone().into(),
                 &mut trigger_sys,
                 &mut trigger_receipt,
+                &mut trigger_chain_fork_check,
             )
             .await
             {
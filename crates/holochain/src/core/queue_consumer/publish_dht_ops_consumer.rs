@@ -6,6 +6,7 @@ use crate::conductor::manager::ManagedTaskResult;
 use crate::core::workflow::publish_dht_ops_workflow::publish_dht_ops_workflow;
 use crate::core::workflow::publish_dht_ops_workflow::PublishDhtOpsWorkspace;
 use holochain_lmdb::env::EnvironmentWrite;
+use holochain_types::dna::DEFAULT_REDUNDANCY_TARGET;
 
 use tokio::task::JoinHandle;
 use tracing::*;
@@ -21,6 +22,11 @@ pub fn spawn_publish_dht_ops_consumer(
     let (tx, mut rx) = TriggerSender::new();
     let mut trigger_self = tx.clone();
     let handle = tokio::spawn(async move {
+        let redundancy_target = conductor_handle
+            .get_dna(cell_network.cell_id().dna_hash())
+            .await
+            .map(|dna| dna.dna_def().redundancy_target())
+            .unwrap_or(DEFAULT_REDUNDANCY_TARGET);
         loop {
             // Wait for next job
             if let Job::Shutdown = next_job_or_exit(&mut rx, &mut stop).await {
@@ -32,7 +38,8 @@ pub fn spawn_publish_dht_ops_consumer(
 
             // Run the workflow
             let workspace = PublishDhtOpsWorkspace::new(env.clone().into())
-                .expect("Could not create Workspace");
+                .expect("Could not create Workspace")
+                .with_redundancy_target(redundancy_target);
             match publish_dht_ops_workflow(workspace, env.clone().into(), &mut cell_network).await {
                 Ok(WorkComplete::Incomplete) => trigger_self.trigger(),
                 Err(err) => {
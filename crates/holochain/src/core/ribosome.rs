@@ -7,9 +7,12 @@
 
 // This allow is here because #[automock] automaticaly creates a struct without
 // documentation, and there seems to be no way to add docs to it after the fact
+pub mod curry;
 pub mod error;
 pub mod guest_callback;
 pub mod host_fn;
+pub mod instance_pool;
+pub mod metering;
 pub mod real_ribosome;
 
 use crate::conductor::api::CellConductorApi;
@@ -86,7 +89,7 @@ pub enum HostAccess {
     EntryDefs(EntryDefsHostAccess),
     MigrateAgent(MigrateAgentHostAccess),
     ValidationPackage(ValidationPackageHostAccess),
-    PostCommit(PostCommitHostAccess), // TODO: add emit_signal access here?
+    PostCommit(PostCommitHostAccess),
 }
 
 impl From<&HostAccess> for HostFnAccess {
@@ -152,7 +155,8 @@ impl HostAccess {
     /// Get the signal broadcaster, panics if none was provided
     pub fn signal_tx(&mut self) -> &mut SignalBroadcaster {
         match self {
-            Self::ZomeCall(ZomeCallHostAccess { signal_tx, .. }) => signal_tx,
+            Self::ZomeCall(ZomeCallHostAccess { signal_tx, .. })
+            | Self::PostCommit(PostCommitHostAccess { signal_tx, .. }) => signal_tx,
             _ => panic!(
                 "Gave access to a host function that uses the signal broadcaster without providing one"
             ),
@@ -162,11 +166,28 @@ impl HostAccess {
     /// Get the associated CellId, panics if not applicable
     pub fn cell_id(&self) -> &CellId {
         match self {
-            Self::ZomeCall(ZomeCallHostAccess { cell_id, .. }) => cell_id,
+            Self::ZomeCall(ZomeCallHostAccess { cell_id, .. })
+            | Self::PostCommit(PostCommitHostAccess { cell_id, .. }) => cell_id,
             _ => panic!("Gave access to a host function that references a CellId"),
         }
     }
 
+    /// The cell a call with this access is made for, if the access has one.
+    /// Unlike [`HostAccess::cell_id`], this never panics.
+    pub fn call_cell_id(&self) -> Option<CellId> {
+        match self {
+            Self::ZomeCall(ZomeCallHostAccess { cell_id, .. })
+            | Self::PostCommit(PostCommitHostAccess { cell_id, .. }) => Some(cell_id.clone()),
+            Self::Init(InitHostAccess { network, .. })
+            | Self::ValidationPackage(ValidationPackageHostAccess { network, .. })
+            | Self::Validate(ValidateHostAccess { network, .. })
+            | Self::ValidateCreateLink(ValidateLinkHostAccess { network, .. }) => {
+                Some(holochain_p2p::HolochainP2pCellT::cell_id(network))
+            }
+            Self::GenesisSelfCheck(_) | Self::EntryDefs(_) | Self::MigrateAgent(_) => None,
+        }
+    }
+
     /// Get the call zome handle, panics if none was provided
     pub fn call_zome_handle(&self) -> &CellConductorReadHandle {
         match self {
@@ -257,21 +278,70 @@ impl ZomeCallInvocation {
     /// - we need to find a live (committed and not deleted) cap grant that matches the secret
     /// - if the live cap grant is for the current author the call is ALWAYS authorized ELSE
     /// - the live cap grant needs to include the invocation's provenance AND zome/function name
+    /// - the live cap grant must not have expired or been used up
+    /// - if the live cap grant curries a payload into the function the invocation's payload must
+    ///   agree with it, and is completed from it
+    /// - if more than one live cap grant matches, the most specific one the invocation agrees
+    ///   with and which has uses left authorizes it
+    ///
+    /// an authorized call counts as a use of the use-limited grant authorizing it
     #[allow(clippy::extra_unused_lifetimes)]
-    pub fn is_authorized<'a>(&self, host_access: &ZomeCallHostAccess) -> RibosomeResult<bool> {
+    pub fn is_authorized<'a>(&mut self, host_access: &ZomeCallHostAccess) -> RibosomeResult<bool> {
         let check_function = (self.zome.zome_name().clone(), self.fn_name.clone());
         let check_agent = self.provenance.clone();
         let check_secret = self.cap;
+        let payload = &mut self.payload;
 
         tokio_helper::block_forever_on(async move {
-            let maybe_grant: Option<CapGrant> = host_access
+            let grants = host_access
                 .workspace
                 .read()
                 .await
                 .source_chain
-                .valid_cap_grant(&check_function, &check_agent, check_secret.as_ref())?;
+                .valid_cap_grants_with_headers(
+                    &check_function,
+                    &check_agent,
+                    check_secret.as_ref(),
+                )?;
+
+            // the most specific grant is tried first, falling back to the
+            // others if the call contradicts its curried payload or it has
+            // been used up
+            for (grant, grant_header) in grants {
+                let grant = match grant {
+                    CapGrant::ChainAuthor(_) => return Ok(true),
+                    CapGrant::RemoteAgent(grant) => grant,
+                };
 
-            Ok(maybe_grant.is_some())
+                let curried_payload = match grant.curry_payloads.get(&check_function) {
+                    Some(curried) => match curry::curry_payload(curried, payload) {
+                        Some(curried_payload) => Some(curried_payload),
+                        // the call contradicts this grant
+                        None => continue,
+                    },
+                    None => None,
+                };
+
+                // the grant is only used once the call is known to be
+                // authorized by it, and the use is checked against the limit
+                // as it is counted
+                if let (Some(max_uses), Some(grant_header)) = (grant.max_uses, grant_header) {
+                    if !host_access
+                        .call_zome_handle
+                        .use_cap_grant(grant_header, max_uses)
+                        .await
+                        .map_err(Box::new)?
+                    {
+                        continue;
+                    }
+                }
+
+                if let Some(curried_payload) = curried_payload {
+                    *payload = curried_payload;
+                }
+                return Ok(true);
+            }
+            Ok(false)
         })
     }
 }
@@ -440,18 +510,17 @@ pub trait RibosomeT: Sized + std::fmt::Debug {
         to_call: &FunctionName,
     ) -> Result<Option<ExternIO>, RibosomeError>;
 
-    /// @todo list out all the available callbacks and maybe cache them somewhere
-    fn list_callbacks(&self) {
-        unimplemented!()
-        // pseudocode
-        // self.instance().exports().filter(|e| e.is_callback())
+    /// The functions exported by a zome, split into zome functions and callbacks
+    fn zome_exports(&self, zome_name: &ZomeName) -> RibosomeResult<ZomeExports>;
+
+    /// List all the callbacks implemented by a zome
+    fn list_callbacks(&self, zome_name: &ZomeName) -> RibosomeResult<Vec<FunctionName>> {
+        Ok(self.zome_exports(zome_name)?.callbacks)
     }
 
-    /// @todo list out all the available zome functions and maybe cache them somewhere
-    fn list_zome_fns(&self) {
-        unimplemented!()
-        // pseudocode
-        // self.instance().exports().filter(|e| !e.is_callback())
+    /// List all the zome functions which can be called in a zome
+    fn list_zome_fns(&self, zome_name: &ZomeName) -> RibosomeResult<Vec<FunctionName>> {
+        Ok(self.zome_exports(zome_name)?.zome_fns)
     }
 
     fn run_genesis_self_check(
@@ -600,6 +669,127 @@ pub mod wasm_test {
         }};
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn is_authorized_falls_back_to_less_specific_grants() {
+        use crate::core::ribosome::ZomeCallInvocation;
+        use crate::core::workflow::CallZomeWorkspace;
+        use crate::fixt::*;
+        use ::fixt::prelude::*;
+        use holochain_types::prelude::*;
+        use std::collections::BTreeMap;
+        use std::collections::BTreeSet;
+
+        #[derive(Debug, serde::Serialize, serde::Deserialize, SerializedBytes, PartialEq)]
+        struct Post {
+            channel: String,
+            content: String,
+        }
+
+        #[derive(Debug, serde::Serialize, serde::Deserialize, SerializedBytes)]
+        struct Channel {
+            channel: String,
+        }
+
+        let channel = |channel: &str| {
+            SerializedBytes::try_from(Channel {
+                channel: channel.into(),
+            })
+            .unwrap()
+        };
+
+        let test_env = holochain_lmdb::test_utils::test_cell_env();
+        let env = test_env.env();
+        let mut workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
+        crate::core::workflow::fake_genesis(&mut workspace.source_chain)
+            .await
+            .unwrap();
+
+        let mut invocation = ZomeCallInvocationFixturator::new(Empty).next().unwrap();
+        invocation.provenance = holochain_types::test_utils::fake_agent_pubkey_2();
+        let secret = invocation.cap.unwrap();
+        let function: GrantedFunction = (
+            invocation.zome.zome_name().clone(),
+            invocation.fn_name.clone(),
+        );
+        let functions: GrantedFunctions =
+            vec![function.clone()].into_iter().collect::<BTreeSet<_>>();
+        let curried = |c: &str| {
+            CurryPayloads(
+                vec![(function.clone(), channel(c))]
+                    .into_iter()
+                    .collect::<BTreeMap<_, _>>(),
+            )
+        };
+
+        // the secret pins the call to one channel, anyone may post to another
+        for grant in vec![
+            ZomeCallCapGrant::new("x".into(), CapAccess::from(secret), functions.clone())
+                .with_curry_payloads(curried("x")),
+            ZomeCallCapGrant::new("y".into(), CapAccess::Unrestricted, functions)
+                .with_curry_payloads(curried("y")),
+        ] {
+            let (entry, entry_hash) =
+                EntryHashed::from_content_sync(Entry::CapGrant(grant)).into_inner();
+            workspace
+                .source_chain
+                .put(
+                    builder::Create {
+                        entry_type: EntryType::CapGrant,
+                        entry_hash,
+                    },
+                    Some(entry),
+                )
+                .await
+                .unwrap();
+        }
+
+        let mut host_access = fixt!(ZomeCallHostAccess);
+        host_access.workspace = crate::core::workflow::CallZomeWorkspaceLock::new(workspace);
+
+        let call = |payload: ExternIO| {
+            let mut invocation: ZomeCallInvocation = invocation.clone();
+            invocation.payload = payload;
+            let authorized = invocation.is_authorized(&host_access).unwrap();
+            (authorized, invocation.payload)
+        };
+        let post = |channel: &str| Post {
+            channel: channel.into(),
+            content: "hi".into(),
+        };
+
+        // the most specific grant completes the payload
+        let (authorized, payload) = call(
+            ExternIO::encode(Channel {
+                channel: "x".into(),
+            })
+            .unwrap(),
+        );
+        assert!(authorized);
+        assert_eq!(payload.decode::<Channel>().unwrap().channel, "x");
+
+        let (authorized, payload) = call(ExternIO::encode(post("x")).unwrap());
+        assert!(authorized);
+        assert_eq!(payload.decode::<Post>().unwrap(), post("x"));
+
+        // a call contradicting it falls back to the unrestricted grant
+        let (authorized, payload) = call(ExternIO::encode(post("y")).unwrap());
+        assert!(authorized);
+        assert_eq!(payload.decode::<Post>().unwrap(), post("y"));
+
+        // and is refused if it contradicts that too
+        assert!(!call(ExternIO::encode(post("z")).unwrap()).0);
+
+        // a payload giving a field twice is refused by every grant
+        let duplicated = rmpv::Value::Map(vec![
+            (rmpv::Value::from("channel"), rmpv::Value::from("x")),
+            (rmpv::Value::from("content"), rmpv::Value::from("hi")),
+            (rmpv::Value::from("channel"), rmpv::Value::from("z")),
+        ]);
+        let mut bytes = Vec::new();
+        rmpv::encode::write_value(&mut bytes, &duplicated).unwrap();
+        assert!(!call(ExternIO::from(bytes)).0);
+    }
+
     #[test]
     fn fn_components_iterate() {
         let fn_components = FnComponents::from(vec!["foo".into(), "bar".into(), "baz".into()]);
@@ -0,0 +1,145 @@
+//! Currying the payloads pinned by a capability grant into the input of a
+//! zome call made with it.
+//!
+//! Both are messagepack, as structs are serialized as maps of their fields.
+//! The curried payload is merged into the input field by field: fields the
+//! input leaves out, or leaves nil, are filled in, and fields it gives must
+//! match. A curried payload which is not a map pins the whole input.
+//!
+//! A map with the same key more than once is refused, as which of the values
+//! the zome function sees is up to its deserializer, and it could otherwise
+//! get a value other than the one checked against the curried payload.
+
+use holochain_types::prelude::*;
+use rmpv::Value;
+
+/// Merge a payload curried into a function into the input of a call to it.
+/// Returns None if the input contradicts the curried payload, in which case
+/// the call must not be made.
+pub fn curry_payload(curried: &SerializedBytes, input: &ExternIO) -> Option<ExternIO> {
+    let merged = merge(read(curried.bytes())?, read(input.as_bytes())?)?;
+    let mut bytes = Vec::new();
+    rmpv::encode::write_value(&mut bytes, &merged).ok()?;
+    Some(ExternIO::from(bytes))
+}
+
+/// Read a single messagepack value, rejecting anything trailing it
+fn read(mut bytes: &[u8]) -> Option<Value> {
+    let value = rmpv::decode::read_value(&mut bytes).ok()?;
+    if bytes.is_empty() {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+fn merge(curried: Value, input: Value) -> Option<Value> {
+    match (curried, input) {
+        (curried, Value::Nil) => Some(curried),
+        (Value::Map(curried), Value::Map(mut input)) => {
+            if has_duplicate_keys(&curried) || has_duplicate_keys(&input) {
+                return None;
+            }
+            for (key, curried_value) in curried {
+                match input.iter_mut().find(|(input_key, _)| *input_key == key) {
+                    Some((_, input_value)) => {
+                        let given = std::mem::replace(input_value, Value::Nil);
+                        *input_value = merge(curried_value, given)?;
+                    }
+                    None => input.push((key, curried_value)),
+                }
+            }
+            Some(Value::Map(input))
+        }
+        (curried, input) if curried == input => Some(input),
+        _ => None,
+    }
+}
+
+fn has_duplicate_keys(map: &[(Value, Value)]) -> bool {
+    map.iter()
+        .enumerate()
+        .any(|(i, (key, _))| map[..i].iter().any(|(earlier, _)| earlier == key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Post {
+        channel: String,
+        content: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, SerializedBytes)]
+    struct Channel {
+        channel: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Content {
+        content: String,
+    }
+
+    fn channel(channel: &str) -> SerializedBytes {
+        SerializedBytes::try_from(Channel {
+            channel: channel.into(),
+        })
+        .unwrap()
+    }
+
+    fn post(channel: &str, content: &str) -> Post {
+        Post {
+            channel: channel.into(),
+            content: content.into(),
+        }
+    }
+
+    #[test]
+    fn curried_fields_are_filled_in() {
+        let input = ExternIO::encode(Content {
+            content: "hi".into(),
+        })
+        .unwrap();
+        let curried = curry_payload(&channel("x"), &input).unwrap();
+        assert_eq!(curried.decode::<Post>().unwrap(), post("x", "hi"));
+    }
+
+    #[test]
+    fn curried_fields_must_match() {
+        let matching = ExternIO::encode(post("x", "hi")).unwrap();
+        assert_eq!(
+            curry_payload(&channel("x"), &matching)
+                .unwrap()
+                .decode::<Post>()
+                .unwrap(),
+            post("x", "hi")
+        );
+
+        let contradicting = ExternIO::encode(post("y", "hi")).unwrap();
+        assert_eq!(curry_payload(&channel("x"), &contradicting), None);
+    }
+
+    #[test]
+    fn non_map_payloads_pin_the_whole_input() {
+        let curried = SerializedBytes::try_from(()).unwrap();
+        assert!(curry_payload(&curried, &ExternIO::encode(()).unwrap()).is_some());
+        assert_eq!(
+            curry_payload(&curried, &ExternIO::encode(post("x", "hi")).unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn duplicate_keys_are_refused() {
+        let input = Value::Map(vec![
+            (Value::from("channel"), Value::from("x")),
+            (Value::from("content"), Value::from("hi")),
+            (Value::from("channel"), Value::from("y")),
+        ]);
+        let mut bytes = Vec::new();
+        rmpv::encode::write_value(&mut bytes, &input).unwrap();
+        assert_eq!(curry_payload(&channel("x"), &ExternIO::from(bytes)), None);
+    }
+}
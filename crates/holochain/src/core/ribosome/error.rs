@@ -3,6 +3,7 @@
 
 use crate::conductor::api::error::ConductorApiError;
 use crate::conductor::interface::error::InterfaceError;
+use crate::core::ribosome::metering::LimitExceeded;
 use crate::core::workflow::produce_dht_ops_workflow::dht_op_light::error::DhtOpConvertError;
 use holo_hash::AnyDhtHash;
 use holochain_cascade::error::CascadeError;
@@ -36,6 +37,14 @@ pub enum RibosomeError {
     #[error("Attempted to call a zome function that doesn't exist: Zome: {0} Fn {1}")]
     ZomeFnNotExists(ZomeName, FunctionName),
 
+    /// A zome called a host function it has not been granted in the DNA manifest
+    #[error("Zome '{0}' is not permitted to call the host function '{1}', which requires the '{2}' permission in its host_fn_access")]
+    HostFnPermissions(ZomeName, String, String),
+
+    /// A call into a zome was aborted for exceeding one of the DNA's zome call limits
+    #[error("The call to '{1}' in zome '{0}' was aborted because it {2}")]
+    ZomeCallLimitExceeded(ZomeName, FunctionName, LimitExceeded),
+
     /// a problem with entry defs
     #[error("An error with entry defs in zome '{0}': {1}")]
     EntryDefs(ZomeName, String),
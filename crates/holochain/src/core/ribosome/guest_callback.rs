@@ -15,6 +15,50 @@ use fallible_iterator::FallibleIterator;
 use holochain_types::dna::zome::Zome;
 use holochain_zome_types::ExternIO;
 
+/// The callbacks the host calls by exactly this name
+const CALLBACKS: &[&str] = &[
+    "entry_defs",
+    "genesis_self_check",
+    "init",
+    "migrate_agent",
+    "migrate_agent_open",
+    "migrate_agent_close",
+    "post_commit",
+    "validate",
+    "validate_create_link",
+    "validate_delete_link",
+    "validation_package",
+];
+
+/// Whether a function exported by a zome is a callback, i.e. has one of the
+/// names the host calls callbacks by, such as `validate_create_entry_post`.
+/// A zome function which only starts with the name of a callback, such as
+/// `init_profile`, is not a callback.
+pub fn is_callback(fn_name: &str) -> bool {
+    if CALLBACKS.contains(&fn_name) {
+        return true;
+    }
+    // validation_package_<zome_id>
+    if let Some(zome_id) = fn_name.strip_prefix("validation_package_") {
+        return !zome_id.is_empty() && zome_id.bytes().all(|b| b.is_ascii_digit());
+    }
+    // validate_<op>, validate_<op>_agent, validate_<op>_entry
+    // and validate_<op>_entry_<entry_def_id>
+    if let Some(rest) = fn_name.strip_prefix("validate_") {
+        let rest = ["create", "update", "delete"]
+            .iter()
+            .find_map(|op| rest.strip_prefix(op));
+        return match rest {
+            Some("") | Some("_agent") | Some("_entry") => true,
+            Some(rest) => rest
+                .strip_prefix("_entry_")
+                .map_or(false, |entry_def_id| !entry_def_id.is_empty()),
+            None => false,
+        };
+    }
+    false
+}
+
 pub struct CallIterator<R: RibosomeT, I: Invocation> {
     host_access: HostAccess,
     ribosome: R,
@@ -70,6 +114,7 @@ impl<R: RibosomeT, I: Invocation + 'static> FallibleIterator for CallIterator<R,
 #[cfg(test)]
 #[cfg(feature = "slow_tests")]
 mod tests {
+    use super::is_callback;
     use super::CallIterator;
     use crate::core::ribosome::FnComponents;
     use crate::core::ribosome::MockInvocation;
@@ -154,4 +199,41 @@ mod tests {
         let output: Vec<(_, ExternIO)> = call_iterator.collect().unwrap();
         assert_eq!(output.len(), zomes.len() * fn_components.0.len());
     }
+
+    #[test]
+    fn callbacks_are_recognised() {
+        for name in &[
+            "init",
+            "entry_defs",
+            "validate",
+            "validate_create",
+            "validate_update_agent",
+            "validate_create_entry",
+            "validate_create_entry_post",
+            "validate_create_link",
+            "validate_delete_link",
+            "validation_package",
+            "validation_package_0",
+            "migrate_agent_open",
+            "post_commit",
+        ] {
+            assert!(is_callback(name), "{} is a callback", name);
+        }
+        for name in &[
+            "create_post",
+            "initialize",
+            "init_profile",
+            "entry_defs_for_ui",
+            "validated_posts",
+            "validate_posts",
+            "validate_created_posts",
+            "validate_create_entry_",
+            "validation_package_post",
+            "migrate_agent_elsewhere",
+            "post_commit_hook",
+            "recv_remote_signal",
+        ] {
+            assert!(!is_callback(name), "{} is a zome fn", name);
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use crate::conductor::interface::SignalBroadcaster;
 use crate::core::ribosome::FnComponents;
 use crate::core::ribosome::HostAccess;
 use crate::core::ribosome::Invocation;
@@ -21,11 +22,15 @@ impl PostCommitInvocation {
     }
 }
 
+/// The `post_commit` callback runs after the zome call's workspace has been
+/// flushed, and its own workspace is never flushed, so it can't write to it.
 #[derive(Clone, Constructor)]
 pub struct PostCommitHostAccess {
     pub workspace: CallZomeWorkspaceLock,
     pub keystore: KeystoreSender,
     pub network: HolochainP2pCell,
+    pub signal_tx: SignalBroadcaster,
+    pub cell_id: CellId,
 }
 
 impl From<PostCommitHostAccess> for HostAccess {
@@ -36,7 +41,9 @@ impl From<PostCommitHostAccess> for HostAccess {
 
 impl From<&PostCommitHostAccess> for HostFnAccess {
     fn from(_: &PostCommitHostAccess) -> Self {
-        Self::all()
+        let mut access = Self::all();
+        access.write_workspace = Permission::Deny;
+        access
     }
 }
 
@@ -97,6 +104,7 @@ mod test {
     use crate::fixt::PostCommitInvocationFixturator;
     use ::fixt::prelude::*;
     use holochain_types::dna::zome::HostFnAccess;
+    use holochain_types::dna::zome::Permission;
     use holochain_zome_types::post_commit::PostCommitCallbackResult;
     use holochain_zome_types::ExternIO;
 
@@ -148,10 +156,9 @@ mod test {
         let post_commit_host_access = PostCommitHostAccessFixturator::new(::fixt::Unpredictable)
             .next()
             .unwrap();
-        assert_eq!(
-            HostFnAccess::from(&post_commit_host_access),
-            HostFnAccess::all()
-        );
+        let mut access = HostFnAccess::all();
+        access.write_workspace = Permission::Deny;
+        assert_eq!(HostFnAccess::from(&post_commit_host_access), access);
     }
 
     #[test]
@@ -38,9 +38,10 @@ impl From<ValidateHostAccess> for HostAccess {
     }
 }
 
-impl From<&ValidateHostAccess> for HostFnAccess {
-    fn from(_: &ValidateHostAccess) -> Self {
-        let mut access = Self::none();
+impl ValidateHostAccess {
+    /// The host functions which validation callbacks may use
+    pub fn host_fn_access() -> HostFnAccess {
+        let mut access = HostFnAccess::none();
         access.read_workspace = Permission::Allow;
         access.keystore = Permission::Allow;
         access.dna_bindings = Permission::Allow;
@@ -48,6 +49,12 @@ impl From<&ValidateHostAccess> for HostFnAccess {
     }
 }
 
+impl From<&ValidateHostAccess> for HostFnAccess {
+    fn from(_: &ValidateHostAccess) -> Self {
+        ValidateHostAccess::host_fn_access()
+    }
+}
+
 impl Invocation for ValidateInvocation {
     fn zomes(&self) -> ZomesToInvoke {
         // Entries are specific to zomes, so they only validate in the zome the entry is defined in.
@@ -67,7 +74,7 @@ impl Invocation for ValidateInvocation {
         }
         match self.element.entry().as_option() {
             Some(Entry::Agent(_)) => fns.push("agent".into()),
-            Some(Entry::App(_)) => {
+            Some(Entry::App(_)) | Some(Entry::CounterSign(_, _)) => {
                 fns.push("entry".into());
                 if let Some(EntryDefId::App(entry_def_id)) = self.entry_def_id.clone() {
                     fns.push(entry_def_id);
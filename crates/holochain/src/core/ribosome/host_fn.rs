@@ -47,6 +47,9 @@ host_fn_api_impls! {
     // holochain_zome_types::zome_io
     // TODO: is there a way to unhygienically import this code in both places?
 
+    // Accept a countersigning session, locking the source chain until it ends.
+    fn accept_countersigning_preflight_request (zt::countersigning::PreflightRequest) -> zt::countersigning::PreflightRequestAcceptance;
+
     // Info about the calling agent.
     fn agent_info (()) -> zt::info::AgentInfo;
 
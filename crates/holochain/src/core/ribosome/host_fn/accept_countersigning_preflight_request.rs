@@ -0,0 +1,69 @@
+use crate::core::ribosome::CallContext;
+use crate::core::ribosome::RibosomeT;
+use crate::core::workflow::call_zome_workflow::CallZomeWorkspace;
+use holochain_keystore::AgentPubKeyExt;
+use holochain_state::source_chain::SourceChainError;
+use holochain_types::prelude::*;
+use holochain_wasmer_host::prelude::WasmError;
+use std::sync::Arc;
+
+/// Lock the source chain for a countersigning session, and sign the chain's state
+/// for the session so that the response can be sent back to whoever started it.
+pub fn accept_countersigning_preflight_request(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: PreflightRequest,
+) -> Result<PreflightRequestAcceptance, WasmError> {
+    if let Err(counter_signing_error) = input.check() {
+        return Ok(PreflightRequestAcceptance::Invalid(
+            counter_signing_error.to_string(),
+        ));
+    }
+    let now = timestamp::now();
+    let latest_start = (now + SESSION_START_FUTURE_MAX)
+        .map_err(|timestamp_error| WasmError::Host(timestamp_error.to_string()))?;
+    if input.session_times.start > latest_start {
+        return Ok(PreflightRequestAcceptance::UnacceptableFutureStart);
+    }
+    if input.session_times.end < now {
+        return Ok(PreflightRequestAcceptance::Invalid(
+            "The countersigning session has already ended".into(),
+        ));
+    }
+    tokio_helper::block_forever_on(async move {
+        let mut guard = call_context.host_access.workspace().write().await;
+        let workspace: &mut CallZomeWorkspace = &mut guard;
+        let source_chain = &mut workspace.source_chain;
+        let author = source_chain
+            .agent_pubkey()
+            .map_err(|source_chain_error| WasmError::Host(source_chain_error.to_string()))?;
+        if input.agent_index(&author).is_none() {
+            return Ok(PreflightRequestAcceptance::UnacceptableAgentNotFound);
+        }
+        let agent_state = match source_chain.lock_for_countersigning(input.clone()) {
+            Ok(agent_state) => agent_state,
+            Err(locked @ SourceChainError::ChainLocked(_)) => {
+                return Ok(PreflightRequestAcceptance::Invalid(locked.to_string()))
+            }
+            Err(source_chain_error) => return Err(WasmError::Host(source_chain_error.to_string())),
+        };
+        // The chain's state is signed for the session below, so the lock
+        // has to outlive this call even if the call fails after this.
+        call_context
+            .host_access
+            .call_zome_handle()
+            .persist_chain_lock(input.clone())
+            .await
+            .map_err(|conductor_api_error| WasmError::Host(conductor_api_error.to_string()))?;
+        let data = PreflightResponse::encode_for_signature(&input, &agent_state)?;
+        let signature = author
+            .sign_raw(call_context.host_access.keystore(), &data)
+            .await
+            .map_err(|keystore_error| WasmError::Host(keystore_error.to_string()))?;
+        Ok(PreflightRequestAcceptance::Accepted(PreflightResponse {
+            request: input,
+            agent_state,
+            signature,
+        }))
+    })
+}
@@ -210,6 +210,9 @@ pub mod wasm_test {
                 uid: "ba1d046d-ce29-4778-914b-47e6010d2faf".to_string(),
                 properties: SerializedBytes::try_from(()).unwrap(),
                 zomes: zomes.clone().into_iter().map(Into::into).collect(),
+                redundancy_target: None,
+                zome_call_limits: None,
+                integrity: None,
             },
             zomes.into_iter().map(Into::into),
         )
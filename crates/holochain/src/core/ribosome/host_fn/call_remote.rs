@@ -58,6 +58,9 @@ pub mod wasm_test {
             uid: "ba1d046d-ce29-4778-914b-47e6010d2faf".to_string(),
             properties: SerializedBytes::try_from(()).unwrap(),
             zomes: vec![TestWasm::WhoAmI.into()].into(),
+            redundancy_target: None,
+            zome_call_limits: None,
+            integrity: None,
         };
         let dna_file = DnaFile::new(dna_def, vec![TestWasm::WhoAmI.into()])
             .await
@@ -288,6 +288,9 @@ pub mod wasm_test {
                 uid: "ba1d046d-ce29-4778-914b-47e6010d2faf".to_string(),
                 properties: SerializedBytes::try_from(()).unwrap(),
                 zomes: vec![TestWasm::MultipleCalls.into()].into(),
+                redundancy_target: None,
+                zome_call_limits: None,
+                integrity: None,
             },
             vec![TestWasm::MultipleCalls.into()],
         )
@@ -93,6 +93,9 @@ mod tests {
                     // empty access converts to unrestricted
                     access: ().into(),
                     functions,
+                    expires_at: None,
+                    max_uses: None,
+                    curry_payloads: CurryPayloads::default(),
                 };
                 api.create(EntryWithDefId::new(
                     EntryDefId::CapGrant,
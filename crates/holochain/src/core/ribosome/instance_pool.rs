@@ -0,0 +1,315 @@
+//! Pools of wasm instances for each zome, so that calls into a zome don't
+//! pay the cost of instantiating its wasm every time.
+//!
+//! The imports of an instance are bound when it is created, so the host
+//! functions of a pooled instance can't capture the context of any one call.
+//! Instead they read it from the instance's [`InstanceContextSlot`], which is
+//! filled when the instance is checked out for a call, and emptied again when
+//! the call returns.
+//!
+//! Instances are only ever shared between calls for the same cell, and
+//! before an instance is returned to its pool, its memory and exported
+//! globals are restored to the state they were in when the instance was
+//! created, so nothing leaks from one call into the next. Instances which
+//! can't be restored, because their memory or tables have grown, and
+//! instances which trapped, which may have left the guest's internal globals
+//! in any state, are discarded instead.
+//!
+//! The pools of a cell are emptied with [`evict`] when the cell is removed.
+
+use super::metering::ZomeCallMeter;
+use super::real_ribosome::RealRibosome;
+use super::CallContext;
+use holochain_types::prelude::*;
+use holochain_wasmer_host::prelude::*;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+use wasmer_runtime::Export;
+use wasmer_runtime::Global;
+use wasmer_runtime::Table;
+use wasmer_runtime::Value;
+
+/// The idle instances of every zome
+static INSTANCE_POOLS: Lazy<Mutex<HashMap<InstanceKey, Vec<PooledInstance>>>> =
+    Lazy::new(Default::default);
+
+/// Everything the imports of an instance need to know about the current call
+pub struct InstanceContext {
+    /// The ribosome making the call
+    pub ribosome: Arc<RealRibosome>,
+    /// The zome and host access of the call
+    pub call_context: Arc<CallContext>,
+    /// The meter enforcing the DNA's zome call limits on the call
+    pub meter: Arc<ZomeCallMeter>,
+}
+
+/// Where the imports of an instance find the context of the current call.
+/// Empty while the instance is idle.
+pub type InstanceContextSlot = Arc<RwLock<Option<InstanceContext>>>;
+
+/// Instances with the same key are interchangeable: they are instances of
+/// the same zome of the same DNA, with the same host functions imported,
+/// used by the same cell.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InstanceKey {
+    /// The DNA of the zome
+    pub dna_hash: DnaHash,
+    /// The cell the calls are made for. None for the callbacks which are
+    /// run for a DNA before there is any cell, like `entry_defs`.
+    pub cell_id: Option<CellId>,
+    /// The zome
+    pub zome_name: ZomeName,
+    /// The host functions available to the calls the instance is used for
+    pub host_fn_access: HostFnAccess,
+}
+
+/// An instance which can be returned to a pool after use
+pub struct PooledInstance {
+    instance: Instance,
+    slot: InstanceContextSlot,
+    /// The contents of the instance's memory when it was created, or None if
+    /// its module has no memory
+    initial_memory: Option<Vec<u8>>,
+    /// The exported mutable globals, with their values when it was created
+    initial_globals: Vec<(Global, Value)>,
+    /// The exported tables, with their sizes when it was created
+    initial_tables: Vec<(Table, u32)>,
+}
+
+impl PooledInstance {
+    /// Wrap a freshly created instance, whose imports read from `slot`
+    pub fn new(instance: Instance, slot: InstanceContextSlot) -> Self {
+        let initial_memory = if has_memory(&instance) {
+            Some(
+                instance
+                    .context()
+                    .memory(0)
+                    .view::<u8>()
+                    .iter()
+                    .map(|byte| byte.get())
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        let mut initial_globals = Vec::new();
+        let mut initial_tables = Vec::new();
+        for (_, export) in instance.exports() {
+            match export {
+                Export::Global(global) if global.descriptor().mutable => {
+                    let value = global.get();
+                    initial_globals.push((global, value));
+                }
+                Export::Table(table) => {
+                    let size = table.size();
+                    initial_tables.push((table, size));
+                }
+                _ => (),
+            }
+        }
+        Self {
+            instance,
+            slot,
+            initial_memory,
+            initial_globals,
+            initial_tables,
+        }
+    }
+
+    /// The instance, to call into
+    pub fn instance_mut(&mut self) -> &mut Instance {
+        &mut self.instance
+    }
+
+    /// Give the imports the context of the call about to be made
+    pub fn set_context(&self, context: InstanceContext) {
+        *self.slot.write() = Some(context);
+    }
+
+    /// Forget the context of the call which has returned
+    pub fn clear_context(&self) {
+        self.slot.write().take();
+    }
+
+    /// Restore the memory and exported globals to their initial state.
+    /// Returns false if that's not possible.
+    fn reset(&mut self) -> bool {
+        if self
+            .initial_tables
+            .iter()
+            .any(|(table, size)| table.size() != *size)
+        {
+            return false;
+        }
+        if let Some(initial_memory) = &self.initial_memory {
+            let view = self.instance.context().memory(0).view::<u8>();
+            if view.len() != initial_memory.len() {
+                return false;
+            }
+            // SAFETY: a Cell<u8> has the same in-memory representation as a u8,
+            // the view covers the whole memory, and nothing else can be using
+            // the memory of an instance which isn't checked out.
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    initial_memory.as_ptr(),
+                    view.as_ptr() as *const u8 as *mut u8,
+                    initial_memory.len(),
+                );
+            }
+        }
+        for (global, value) in self.initial_globals.iter() {
+            global.set(value.clone());
+        }
+        true
+    }
+}
+
+/// Whether the instance has a memory, either its own or imported.
+/// A wasm module can have at most one, so it's always memory 0.
+fn has_memory(instance: &Instance) -> bool {
+    let module = instance.module();
+    let info = module.info();
+    info.memories.len() + info.imported_memories.len() > 0
+}
+
+/// Take an idle instance from a pool, if there is one
+pub fn checkout(key: &InstanceKey) -> Option<PooledInstance> {
+    INSTANCE_POOLS.lock().get_mut(key).and_then(Vec::pop)
+}
+
+/// Return an instance to its pool after a successful call.
+/// The instance is dropped if it can't be reset, or if the pool already
+/// holds `pool_size` idle instances.
+pub fn checkin(key: InstanceKey, mut instance: PooledInstance, pool_size: usize) {
+    instance.clear_context();
+    if pool_size == 0 || idle_count(&key) >= pool_size || !instance.reset() {
+        return;
+    }
+    let mut pools = INSTANCE_POOLS.lock();
+    let pool = pools.entry(key).or_default();
+    if pool.len() < pool_size {
+        pool.push(instance);
+    }
+}
+
+/// The number of idle instances in a pool
+pub fn idle_count(key: &InstanceKey) -> usize {
+    INSTANCE_POOLS.lock().get(key).map_or(0, Vec::len)
+}
+
+/// Drop the idle instances of a cell which has been removed
+pub fn evict(cell_id: &CellId) {
+    INSTANCE_POOLS
+        .lock()
+        .retain(|key, _| key.cell_id.as_ref() != Some(cell_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::DnaHashFixturator;
+    use holochain_types::test_utils::fake_cell_id;
+
+    #[test]
+    fn instances_are_reset_before_reuse() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 0) "\01")
+                (global (export "global") (mut i32) (i32.const 1)))"#,
+        )
+        .unwrap();
+        let new_instance = || {
+            let instance = holochain_wasmer_host::instantiate::instantiate(
+                b"instances_are_reset_before_reuse",
+                &wasm,
+                &imports! {},
+                None,
+            )
+            .unwrap();
+            PooledInstance::new(instance, Default::default())
+        };
+        let key = InstanceKey {
+            dna_hash: fixt!(DnaHash),
+            cell_id: Some(fake_cell_id(1)),
+            zome_name: "zome".into(),
+            host_fn_access: HostFnAccess::all(),
+        };
+        let first_byte = |instance: &mut PooledInstance| {
+            instance.instance_mut().context().memory(0).view::<u8>()[0].get()
+        };
+        let global = |instance: &mut PooledInstance| {
+            instance
+                .instance_mut()
+                .exports()
+                .find_map(|(name, export)| match export {
+                    Export::Global(global) if name == "global" => Some(global),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        // A call leaves its mark on the memory and globals
+        let mut instance = new_instance();
+        assert_eq!(first_byte(&mut instance), 1);
+        assert_eq!(global(&mut instance).get(), Value::I32(1));
+        instance.instance_mut().context().memory(0).view::<u8>()[0].set(42);
+        global(&mut instance).set(Value::I32(42));
+        checkin(key.clone(), instance, 1);
+        assert_eq!(idle_count(&key), 1);
+
+        // Which is gone when the instance is reused
+        let mut instance = checkout(&key).unwrap();
+        assert_eq!(idle_count(&key), 0);
+        assert_eq!(first_byte(&mut instance), 1);
+        assert_eq!(global(&mut instance).get(), Value::I32(1));
+
+        // Other cells never get it
+        let other_cell = InstanceKey {
+            cell_id: Some(fake_cell_id(2)),
+            ..key.clone()
+        };
+        checkin(key.clone(), instance, 1);
+        assert!(checkout(&other_cell).is_none());
+
+        // The pool is bounded
+        checkin(key.clone(), new_instance(), 1);
+        assert_eq!(idle_count(&key), 1);
+
+        // And emptied when the cell is removed
+        checkin(other_cell.clone(), new_instance(), 1);
+        evict(&fake_cell_id(1));
+        assert_eq!(idle_count(&key), 0);
+        assert_eq!(idle_count(&other_cell), 1);
+    }
+
+    #[test]
+    fn instances_without_memory_can_be_pooled() {
+        let wasm = wat::parse_str(r#"(module (global (export "global") (mut i32) (i32.const 1)))"#)
+            .unwrap();
+        let instance = holochain_wasmer_host::instantiate::instantiate(
+            b"instances_without_memory_can_be_pooled",
+            &wasm,
+            &imports! {},
+            None,
+        )
+        .unwrap();
+        let key = InstanceKey {
+            dna_hash: fixt!(DnaHash),
+            cell_id: Some(fake_cell_id(3)),
+            zome_name: "zome".into(),
+            host_fn_access: HostFnAccess::all(),
+        };
+        checkin(
+            key.clone(),
+            PooledInstance::new(instance, Default::default()),
+            1,
+        );
+        assert_eq!(idle_count(&key), 1);
+        evict(&fake_cell_id(3));
+    }
+}
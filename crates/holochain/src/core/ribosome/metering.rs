@@ -0,0 +1,435 @@
+//! Enforcement of a DNA's [`ZomeCallLimits`] on its wasm zomes.
+//!
+//! Wasmer can't meter the guest by itself, so when a DNA sets limits, the
+//! wasm of its zomes is instrumented before being compiled:
+//! - every block of code starts with a call to an imported `gas` function,
+//!   which charges the fuel the block is about to burn, and which also checks
+//!   the wall-clock deadline, so that even a tight infinite loop is aborted.
+//! - every `memory.grow` is preceded by a call to an imported guard, which
+//!   checks the size the memory would grow to against the limit. The memory
+//!   maximum is also capped, so the limit holds even without the guard.
+//!
+//! Both imports are backed by a [`ZomeCallMeter`], which is created for each
+//! call and records which limit was exceeded, so that the trap raised in the
+//! guest can be reported as a [`RibosomeError::ZomeCallLimitExceeded`].
+//!
+//! [`RibosomeError::ZomeCallLimitExceeded`]: crate::core::ribosome::error::RibosomeError::ZomeCallLimitExceeded
+
+use holochain_types::prelude::*;
+use holochain_wasmer_host::prelude::*;
+use once_cell::sync::OnceCell;
+use parity_wasm::builder;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Internal;
+use parity_wasm::elements::MemoryType;
+use parity_wasm::elements::Module as WasmModule;
+use parity_wasm::elements::Section;
+use parity_wasm::elements::ValueType;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+/// The module from which the metering functions are imported
+pub const METERING_IMPORT_MODULE: &str = "env";
+
+/// The import which charges fuel, as injected by `pwasm_utils`
+pub const GAS_IMPORT: &str = "gas";
+
+/// The import which guards every `memory.grow`
+pub const MEMORY_GROW_IMPORT: &str = "__hc_memory_grow";
+
+/// The limit which a zome call exceeded
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum LimitExceeded {
+    /// The call ran for longer than the timeout
+    #[error("exceeded the timeout of {0:?}")]
+    Timeout(Duration),
+    /// The call burned more fuel than allowed
+    #[error("exhausted its fuel limit of {0}")]
+    Fuel(u64),
+    /// The call tried to grow its memory beyond the limit
+    #[error("tried to grow its memory beyond the limit of {0} pages")]
+    MemoryPages(u32),
+}
+
+/// Tracks the resources used by a single call into a wasm zome
+#[derive(Debug)]
+pub struct ZomeCallMeter {
+    limits: ZomeCallLimits,
+    deadline: OnceCell<Option<Instant>>,
+    fuel_used: AtomicU64,
+    exceeded: OnceCell<LimitExceeded>,
+}
+
+impl ZomeCallMeter {
+    /// Create a meter for a call subject to these limits
+    pub fn new(limits: ZomeCallLimits) -> Self {
+        Self {
+            limits,
+            deadline: OnceCell::new(),
+            fuel_used: AtomicU64::new(0),
+            exceeded: OnceCell::new(),
+        }
+    }
+
+    /// Start the clock. Time spent before this, e.g. compiling the module,
+    /// does not count towards the timeout.
+    pub fn start(&self) {
+        let timeout = self.limits.timeout();
+        self.deadline
+            .get_or_init(|| timeout.map(|timeout| Instant::now() + timeout));
+    }
+
+    /// The fuel burned so far
+    pub fn fuel_used(&self) -> u64 {
+        self.fuel_used.load(Ordering::Relaxed)
+    }
+
+    /// The limit which was exceeded, if any
+    pub fn exceeded(&self) -> Option<&LimitExceeded> {
+        self.exceeded.get()
+    }
+
+    /// Burn some fuel, and check the deadline.
+    /// An error traps the guest.
+    pub fn charge(&self, fuel: u64) -> Result<(), WasmError> {
+        let used = self.fuel_used.fetch_add(fuel, Ordering::Relaxed) + fuel;
+        if let Some(max_fuel) = self.limits.max_fuel {
+            if used > max_fuel {
+                return self.exceed(LimitExceeded::Fuel(max_fuel));
+            }
+        }
+        if let Some(Some(deadline)) = self.deadline.get() {
+            if Instant::now() > *deadline {
+                return self.exceed(LimitExceeded::Timeout(
+                    self.limits.timeout().unwrap_or_default(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that growing the memory from `current` pages by `delta` pages
+    /// stays within the limit. An error traps the guest.
+    pub fn check_grow(&self, current: u32, delta: u32) -> Result<(), WasmError> {
+        match self.limits.max_memory_pages {
+            Some(max_pages) if current.saturating_add(delta) > max_pages => {
+                self.exceed(LimitExceeded::MemoryPages(max_pages))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn exceed(&self, limit: LimitExceeded) -> Result<(), WasmError> {
+        let message = limit.to_string();
+        // Only the first limit exceeded is the cause of the trap
+        let _ = self.exceeded.set(limit);
+        Err(WasmError::Host(message))
+    }
+}
+
+/// Instrument wasm so that it can be held to these limits.
+/// Wasm which doesn't need instrumenting is returned unchanged.
+pub fn instrument(wasm: &[u8], limits: &ZomeCallLimits) -> Result<Vec<u8>, WasmError> {
+    if limits.is_unlimited() {
+        return Ok(wasm.to_vec());
+    }
+    let compile_err = |e: &dyn std::fmt::Display| WasmError::Compile(e.to_string());
+    let mut module: WasmModule =
+        parity_wasm::deserialize_buffer(wasm).map_err(|e| compile_err(&e))?;
+
+    if let Some(max_pages) = limits.max_memory_pages {
+        cap_memory(&mut module, max_pages)?;
+        module = guard_memory_grow(module);
+    }
+    if limits.is_metered() {
+        module = pwasm_utils::inject_gas_counter(
+            module,
+            &pwasm_utils::rules::Set::default(),
+            METERING_IMPORT_MODULE,
+        )
+        .map_err(|_| compile_err(&"Could not inject the gas counter"))?;
+    }
+
+    // Function indices have been shifted, so the names are now wrong
+    module.sections_mut().retain(|section| match section {
+        Section::Name(_) => false,
+        Section::Custom(custom) => custom.name() != "name",
+        _ => true,
+    });
+    parity_wasm::serialize(module).map_err(|e| compile_err(&e))
+}
+
+/// Cap the maximum size of every memory at `max_pages`
+fn cap_memory(module: &mut WasmModule, max_pages: u32) -> Result<(), WasmError> {
+    let cap = |memory: &mut MemoryType| {
+        let limits = memory.limits();
+        if limits.initial() > max_pages {
+            return Err(WasmError::Compile(format!(
+                "The zome's memory starts at {} pages, over the limit of {} pages",
+                limits.initial(),
+                max_pages
+            )));
+        }
+        let maximum = limits.maximum().map_or(max_pages, |m| m.min(max_pages));
+        *memory = MemoryType::new(limits.initial(), Some(maximum));
+        Ok(())
+    };
+    if let Some(section) = module.memory_section_mut() {
+        for memory in section.entries_mut() {
+            cap(memory)?;
+        }
+    }
+    if let Some(section) = module.import_section_mut() {
+        for import in section.entries_mut() {
+            if let parity_wasm::elements::External::Memory(memory) = import.external_mut() {
+                cap(memory)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Import the memory grow guard, and call it before every `memory.grow`.
+/// The guard returns the delta it is passed, leaving the stack as it was.
+fn guard_memory_grow(module: WasmModule) -> WasmModule {
+    let mut module_builder = builder::from_module(module);
+    let signature = module_builder.push_signature(
+        builder::signature()
+            .with_param(ValueType::I32)
+            .with_result(ValueType::I32)
+            .build_sig(),
+    );
+    module_builder.push_import(
+        builder::import()
+            .module(METERING_IMPORT_MODULE)
+            .field(MEMORY_GROW_IMPORT)
+            .external()
+            .func(signature)
+            .build(),
+    );
+    let mut module = module_builder.build();
+
+    // The new import is the last imported function, so every function
+    // defined in the module moves up by one
+    let guard = (module.import_count(parity_wasm::elements::ImportCountType::Function) - 1) as u32;
+    let shift = |index: &mut u32| {
+        if *index >= guard {
+            *index += 1;
+        }
+    };
+    for section in module.sections_mut() {
+        match section {
+            Section::Code(code) => {
+                for body in code.bodies_mut() {
+                    let instructions = body.code_mut().elements_mut();
+                    let mut guarded = Vec::with_capacity(instructions.len());
+                    for mut instruction in instructions.drain(..) {
+                        match &mut instruction {
+                            Instruction::Call(index) => shift(index),
+                            Instruction::GrowMemory(_) => guarded.push(Instruction::Call(guard)),
+                            _ => (),
+                        }
+                        guarded.push(instruction);
+                    }
+                    *instructions = guarded;
+                }
+            }
+            Section::Export(exports) => {
+                for export in exports.entries_mut() {
+                    if let Internal::Function(index) = export.internal_mut() {
+                        shift(index);
+                    }
+                }
+            }
+            Section::Element(elements) => {
+                for segment in elements.entries_mut() {
+                    segment.members_mut().iter_mut().for_each(shift);
+                }
+            }
+            Section::Start(index) => shift(index),
+            _ => (),
+        }
+    }
+    module
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A module which loops forever, and one which grows its memory
+    const WAT: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (func (export "spin") (loop (br 0)))
+            (func (export "grow") (result i32) (memory.grow (i32.const 4)))
+        )
+    "#;
+
+    fn imports_of(wasm: &[u8]) -> Vec<String> {
+        let module: WasmModule = parity_wasm::deserialize_buffer(wasm).unwrap();
+        module
+            .import_section()
+            .map(|section| {
+                section
+                    .entries()
+                    .iter()
+                    .map(|import| import.field().to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn unlimited_wasm_is_untouched() {
+        let wasm = wat::parse_str(WAT).unwrap();
+        assert_eq!(instrument(&wasm, &ZomeCallLimits::default()).unwrap(), wasm);
+    }
+
+    #[test]
+    fn instruments_wasm_for_limits() {
+        let wasm = wat::parse_str(WAT).unwrap();
+        let limits = ZomeCallLimits {
+            max_fuel: Some(1000),
+            max_memory_pages: Some(2),
+            ..Default::default()
+        };
+        let instrumented = instrument(&wasm, &limits).unwrap();
+        let imports = imports_of(&instrumented);
+        assert!(imports.contains(&GAS_IMPORT.to_string()));
+        assert!(imports.contains(&MEMORY_GROW_IMPORT.to_string()));
+
+        let module: WasmModule = parity_wasm::deserialize_buffer(&instrumented).unwrap();
+        let memory = &module.memory_section().unwrap().entries()[0];
+        assert_eq!(memory.limits().maximum(), Some(2));
+
+        // Memory which starts over the limit can never be used
+        let limits = ZomeCallLimits {
+            max_memory_pages: Some(0),
+            ..Default::default()
+        };
+        assert!(instrument(&wasm, &limits).is_err());
+    }
+
+    #[test]
+    fn meter_enforces_limits() {
+        let meter = ZomeCallMeter::new(ZomeCallLimits {
+            max_fuel: Some(10),
+            max_memory_pages: Some(4),
+            ..Default::default()
+        });
+        meter.start();
+        assert!(meter.charge(10).is_ok());
+        assert!(meter.check_grow(1, 3).is_ok());
+        assert!(meter.exceeded().is_none());
+        assert!(meter.charge(1).is_err());
+        assert_eq!(meter.exceeded(), Some(&LimitExceeded::Fuel(10)));
+        assert_eq!(meter.fuel_used(), 11);
+
+        let meter = ZomeCallMeter::new(ZomeCallLimits {
+            max_memory_pages: Some(4),
+            ..Default::default()
+        });
+        assert!(meter.check_grow(2, 3).is_err());
+        assert_eq!(meter.exceeded(), Some(&LimitExceeded::MemoryPages(4)));
+
+        let meter = ZomeCallMeter::new(ZomeCallLimits {
+            timeout_ms: Some(0),
+            ..Default::default()
+        });
+        // The clock doesn't run until the call starts
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(meter.charge(1).is_ok());
+        meter.start();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(meter.charge(1).is_err());
+        assert_eq!(
+            meter.exceeded(),
+            Some(&LimitExceeded::Timeout(Duration::from_millis(0)))
+        );
+    }
+
+    /// A zome with the exports the host needs to call into it, whose `spin`
+    /// function never returns
+    const SPIN_ZOME_WAT: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (func (export "__allocate") (param i32) (result i32) (i32.const 8))
+            (func (export "__deallocate") (param i32) (param i32))
+            (func (export "spin") (param i32) (result i32) (loop (br 0)) (i32.const 0))
+        )
+    "#;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn zome_calls_over_the_limit_are_aborted_and_reported() {
+        use crate::conductor::api::AppInterfaceApi;
+        use crate::conductor::api::AppRequest;
+        use crate::conductor::api::AppResponse;
+        use crate::conductor::api::ExternalApiWireError;
+        use crate::conductor::api::ZomeCall;
+        use crate::conductor::interface::websocket::test_utils::setup_app;
+        use holochain_types::test_utils::fake_agent_pubkey_1;
+        use holochain_types::test_utils::fake_dna_zomes;
+        use matches::assert_matches;
+        use mockall::predicate;
+
+        observability::test_run().ok();
+        let wasm = DnaWasm::from(wat::parse_str(SPIN_ZOME_WAT).unwrap());
+        let dna = fake_dna_zomes(
+            "zome_calls_over_the_limit_are_aborted_and_reported",
+            vec![("spin".into(), wasm)],
+        );
+        let dna = DnaFile::new(
+            DnaDef {
+                zome_call_limits: Some(ZomeCallLimits {
+                    max_fuel: Some(1_000_000),
+                    ..Default::default()
+                }),
+                ..dna.dna_def().clone()
+            },
+            dna.code().values().cloned(),
+        )
+        .await
+        .unwrap();
+        let dna_hash = dna.dna_hash().clone();
+        let cell_id = CellId::from((dna_hash.clone(), fake_agent_pubkey_1()));
+        let installed_cell = InstalledCell::new(cell_id.clone(), "handle".into());
+
+        let mut dna_store = MockDnaStore::new();
+        dna_store
+            .expect_get()
+            .with(predicate::eq(dna_hash))
+            .returning(move |_| Some(dna.clone()));
+        dna_store
+            .expect_add_dnas::<Vec<_>>()
+            .times(1)
+            .return_const(());
+        dna_store
+            .expect_add_entry_defs::<Vec<_>>()
+            .times(1)
+            .return_const(());
+        let (_tmpdir, app_api, handle) = setup_app(vec![(installed_cell, None)], dna_store).await;
+
+        // The call runs out of fuel instead of spinning forever
+        let call = ZomeCall {
+            cell_id: cell_id.clone(),
+            zome_name: "spin".into(),
+            fn_name: "spin".into(),
+            payload: ExternIO::encode(()).unwrap(),
+            cap: None,
+            provenance: cell_id.agent_pubkey().clone(),
+        };
+        assert_matches!(
+            app_api
+                .handle_app_request(AppRequest::ZomeCall(Box::new(call)))
+                .await,
+            AppResponse::Error(ExternalApiWireError::ZomeCallLimitExceeded(_))
+        );
+
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        handle.shutdown().await;
+        shutdown.await.unwrap().unwrap();
+    }
+}
@@ -8,6 +8,7 @@ use super::host_fn::get_agent_activity::get_agent_activity;
 use super::host_fn::HostFnApi;
 use super::HostAccess;
 use super::ZomeCallHostAccess;
+use crate::conductor::config::WasmConfig;
 use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::error::RibosomeResult;
 use crate::core::ribosome::guest_callback::entry_defs::EntryDefsInvocation;
@@ -17,6 +18,7 @@ use crate::core::ribosome::guest_callback::genesis_self_check::GenesisSelfCheckI
 use crate::core::ribosome::guest_callback::genesis_self_check::GenesisSelfCheckResult;
 use crate::core::ribosome::guest_callback::init::InitInvocation;
 use crate::core::ribosome::guest_callback::init::InitResult;
+use crate::core::ribosome::guest_callback::is_callback;
 use crate::core::ribosome::guest_callback::migrate_agent::MigrateAgentInvocation;
 use crate::core::ribosome::guest_callback::migrate_agent::MigrateAgentResult;
 use crate::core::ribosome::guest_callback::post_commit::PostCommitInvocation;
@@ -29,6 +31,7 @@ use crate::core::ribosome::guest_callback::validate_link::ValidateLinkResult;
 use crate::core::ribosome::guest_callback::validation_package::ValidationPackageInvocation;
 use crate::core::ribosome::guest_callback::validation_package::ValidationPackageResult;
 use crate::core::ribosome::guest_callback::CallIterator;
+use crate::core::ribosome::host_fn::accept_countersigning_preflight_request::accept_countersigning_preflight_request;
 use crate::core::ribosome::host_fn::agent_info::agent_info;
 use crate::core::ribosome::host_fn::app_info::app_info;
 use crate::core::ribosome::host_fn::call::call;
@@ -67,6 +70,13 @@ use crate::core::ribosome::host_fn::x_25519_x_salsa20_poly1305_encrypt::x_25519_
 use crate::core::ribosome::host_fn::x_salsa20_poly1305_decrypt::x_salsa20_poly1305_decrypt;
 use crate::core::ribosome::host_fn::x_salsa20_poly1305_encrypt::x_salsa20_poly1305_encrypt;
 use crate::core::ribosome::host_fn::zome_info::zome_info;
+use crate::core::ribosome::instance_pool;
+use crate::core::ribosome::instance_pool::InstanceContext;
+use crate::core::ribosome::instance_pool::InstanceContextSlot;
+use crate::core::ribosome::instance_pool::InstanceKey;
+use crate::core::ribosome::instance_pool::PooledInstance;
+use crate::core::ribosome::metering;
+use crate::core::ribosome::metering::ZomeCallMeter;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::Invocation;
 use crate::core::ribosome::RibosomeT;
@@ -75,11 +85,43 @@ use fallible_iterator::FallibleIterator;
 use holochain_types::prelude::*;
 
 use holochain_wasmer_host::prelude::*;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::collections::HashMap;
 use std::sync::Arc;
 
-/// Path to the wasm cache path
+/// Path to the wasm cache path, used when the conductor has not configured one.
+/// The test suites use this to share compiled wasm between test runs.
 const WASM_CACHE_PATH_ENV: &str = "HC_WASM_CACHE_PATH";
 
+/// The error returned by a host function of an instance which is not
+/// checked out for a call. This should never happen.
+const NO_CALL_CONTEXT: &str = "A host function was called without a call context";
+
+/// The exports of each wasm module, which only need to be inspected once
+static ZOME_EXPORTS_CACHE: Lazy<RwLock<HashMap<WasmHash, ZomeExports>>> =
+    Lazy::new(Default::default);
+
+/// Wasm instrumented to enforce zome call limits, by wasm cache key
+static INSTRUMENTED_WASM_CACHE: Lazy<RwLock<HashMap<Vec<u8>, Arc<Box<[u8]>>>>> =
+    Lazy::new(Default::default);
+
+/// Split the names a zome exports into zome functions and callbacks
+fn classify_exports<S: AsRef<str>>(names: impl Iterator<Item = S>) -> ZomeExports {
+    let mut exports = ZomeExports::default();
+    for name in names {
+        let name = name.as_ref();
+        if is_callback(name) {
+            exports.callbacks.push(name.into());
+        } else {
+            exports.zome_fns.push(name.into());
+        }
+    }
+    exports.zome_fns.sort();
+    exports.callbacks.sort();
+    exports
+}
+
 /// The only RealRibosome is a Wasm ribosome.
 /// note that this is cloned on every invocation so keep clones cheap!
 #[derive(Clone, Debug)]
@@ -89,78 +131,200 @@ pub struct RealRibosome {
     //      - is already in the wasm cache, and only include the DnaDef portion
     //      - here in the ribosome.
     pub dna_file: DnaFile,
+    /// How the conductor this ribosome runs in compiles and pools wasm
+    wasm_config: Arc<WasmConfig>,
 }
 
 impl RealRibosome {
-    /// Create a new instance
+    /// Create a new instance, with the default wasm config
     pub fn new(dna_file: DnaFile) -> Self {
-        Self { dna_file }
+        Self {
+            dna_file,
+            wasm_config: Default::default(),
+        }
+    }
+
+    /// Compile and pool wasm as configured by the conductor
+    pub fn with_wasm_config(mut self, wasm_config: Arc<WasmConfig>) -> Self {
+        self.wasm_config = wasm_config;
+        self
     }
 
     pub fn dna_file(&self) -> &DnaFile {
         &self.dna_file
     }
 
+    /// The directory in which compiled wasm modules are cached on disk, if any
+    fn wasm_cache_path(&self) -> Option<std::ffi::OsString> {
+        self.wasm_config
+            .cache_path
+            .clone()
+            .map(Into::into)
+            .or_else(|| std::env::var_os(WASM_CACHE_PATH_ENV))
+    }
+
     pub fn module(&self, zome_name: &ZomeName) -> RibosomeResult<Module> {
-        let wasm: Arc<Box<[u8]>> = self.dna_file.get_wasm_for_zome(zome_name)?.code();
         Ok(holochain_wasmer_host::instantiate::module(
             &self.wasm_cache_key(zome_name)?,
-            &wasm,
-            std::env::var_os(WASM_CACHE_PATH_ENV),
+            &self.wasm(zome_name)?,
+            self.wasm_cache_path(),
         )?)
     }
 
-    pub fn wasm_cache_key(&self, zome_name: &ZomeName) -> Result<&[u8], DnaError> {
+    /// The wasm for a zome, instrumented to enforce the DNA's zome call limits
+    /// if it sets any
+    fn wasm(&self, zome_name: &ZomeName) -> RibosomeResult<Arc<Box<[u8]>>> {
+        let wasm: Arc<Box<[u8]>> = self.dna_file.get_wasm_for_zome(zome_name)?.code();
+        let limits = self.dna_file.dna_def().zome_call_limits();
+        if limits.is_unlimited() {
+            return Ok(wasm);
+        }
+        let key = self.wasm_cache_key(zome_name)?;
+        if let Some(instrumented) = INSTRUMENTED_WASM_CACHE.read().get(&key) {
+            return Ok(instrumented.clone());
+        }
+        let instrumented: Arc<Box<[u8]>> =
+            Arc::new(metering::instrument(&wasm, &limits)?.into_boxed_slice());
+        INSTRUMENTED_WASM_CACHE
+            .write()
+            .insert(key, instrumented.clone());
+        Ok(instrumented)
+    }
+
+    pub fn wasm_cache_key(&self, zome_name: &ZomeName) -> Result<Vec<u8>, DnaError> {
         // TODO: make this actually the hash of the wasm once we can do that
         // watch out for cache misses in the tests that make things slooow if you change this!
         // format!("{}{}", &self.dna.dna_hash(), zome_name).into_bytes()
-        Ok(self
+        let mut key = self
             .dna_file
             .dna()
             .get_wasm_zome(zome_name)?
             .wasm_hash
-            .get_raw_39())
+            .get_raw_39()
+            .to_vec();
+        // The same wasm is compiled differently depending on how it is limited
+        let limits = self.dna_file.dna_def().zome_call_limits();
+        if limits.is_metered() {
+            key.push(1);
+        }
+        if let Some(max_pages) = limits.max_memory_pages {
+            key.extend_from_slice(&max_pages.to_le_bytes());
+        }
+        Ok(key)
+    }
+
+    /// Get an instance of a zome's wasm for a call, from the zome's pool if
+    /// it has an idle instance. Return it with [`instance_pool::checkin`] once
+    /// the call has succeeded.
+    pub fn instance(
+        &self,
+        call_context: CallContext,
+        meter: Arc<ZomeCallMeter>,
+    ) -> RibosomeResult<(InstanceKey, PooledInstance)> {
+        let host_access = call_context.host_access();
+        let host_fn_access: HostFnAccess = (&host_access).into();
+        let key = self.instance_key(
+            host_access.call_cell_id(),
+            call_context.zome.zome_name(),
+            host_fn_access,
+        );
+        let instance = match instance_pool::checkout(&key) {
+            Some(instance) => instance,
+            None => self.new_instance(&call_context.zome, host_fn_access)?,
+        };
+        instance.set_context(InstanceContext {
+            ribosome: Arc::new(self.clone()),
+            call_context: Arc::new(call_context),
+            meter,
+        });
+        Ok((key, instance))
+    }
+
+    fn instance_key(
+        &self,
+        cell_id: Option<CellId>,
+        zome_name: &ZomeName,
+        host_fn_access: HostFnAccess,
+    ) -> InstanceKey {
+        InstanceKey {
+            dna_hash: self.dna_file.dna_hash().clone(),
+            cell_id,
+            zome_name: zome_name.clone(),
+            host_fn_access,
+        }
     }
 
-    pub fn instance(&self, call_context: CallContext) -> RibosomeResult<Instance> {
-        let zome_name = call_context.zome.zome_name().clone();
-        let wasm: Arc<Box<[u8]>> = self.dna_file.get_wasm_for_zome(&zome_name)?.code();
-        let imports: ImportObject = Self::imports(self, call_context);
-        Ok(holochain_wasmer_host::instantiate::instantiate(
-            self.wasm_cache_key(&zome_name)?,
-            &wasm,
+    fn new_instance(
+        &self,
+        zome: &Zome,
+        host_fn_access: HostFnAccess,
+    ) -> RibosomeResult<PooledInstance> {
+        let slot = InstanceContextSlot::default();
+        let imports: ImportObject = Self::imports(self, zome, host_fn_access, slot.clone());
+        let instance = holochain_wasmer_host::instantiate::instantiate(
+            &self.wasm_cache_key(zome.zome_name())?,
+            &self.wasm(zome.zome_name())?,
             &imports,
-            std::env::var_os(WASM_CACHE_PATH_ENV),
-        )?)
+            self.wasm_cache_path(),
+        )?;
+        Ok(PooledInstance::new(instance, slot))
     }
 
-    fn imports(&self, call_context: CallContext) -> ImportObject {
-        let host_fn_access = (&call_context.host_access()).into();
+    /// Compile the wasm of every zome, and make sure the cell's pools for zome
+    /// calls and validation, the hottest paths, hold an idle instance of each
+    /// zome, so that the first calls into the cell don't pay for that.
+    pub fn prewarm(&self, cell_id: &CellId) -> RibosomeResult<()> {
+        let pool_size = self.wasm_config.instance_pool_size;
+        let accesses = [HostFnAccess::all(), ValidateHostAccess::host_fn_access()];
+        for (zome_name, zome_def) in self.dna_file.dna_def().zomes.iter() {
+            if let ZomeDef::Wasm(_) = zome_def {
+                let zome = Zome::new(zome_name.clone(), zome_def.clone());
+                for host_fn_access in accesses.iter() {
+                    let key = self.instance_key(Some(cell_id.clone()), zome_name, *host_fn_access);
+                    if instance_pool::idle_count(&key) == 0 {
+                        let instance = self.new_instance(&zome, *host_fn_access)?;
+                        instance_pool::checkin(key, instance, pool_size);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 
-        // it is important that RealRibosome and ZomeCallInvocation are cheap to clone here
-        let self_arc = std::sync::Arc::new((*self).clone());
-        let call_context_arc = std::sync::Arc::new(call_context);
+    /// The imports of an instance are shared by every call it is used for,
+    /// so they find the context of the current call in the `slot`.
+    fn imports(
+        &self,
+        zome: &Zome,
+        host_fn_access: HostFnAccess,
+        slot: InstanceContextSlot,
+    ) -> ImportObject {
+        // The host functions the zome itself has declared in the DNA manifest
+        let zome_host_fn_access = zome.zome_def().host_fn_access();
 
         macro_rules! invoke_host_function {
             ( $host_function:ident ) => {{
-                let closure_self_arc = std::sync::Arc::clone(&self_arc);
-                let closure_call_context_arc = std::sync::Arc::clone(&call_context_arc);
+                let closure_slot = slot.clone();
                 move |ctx: &mut Ctx, guest_allocation_ptr: GuestPtr| -> Result<Len, WasmError> {
-                    let result = match $crate::holochain_wasmer_host::guest::from_guest_ptr(
-                        ctx,
-                        guest_allocation_ptr,
+                    // it is important that RealRibosome and CallContext are cheap to clone here
+                    let context = closure_slot.read().as_ref().map(|context| {
+                        (
+                            std::sync::Arc::clone(&context.ribosome),
+                            std::sync::Arc::clone(&context.call_context),
+                        )
+                    });
+                    let result = match (
+                        $crate::holochain_wasmer_host::guest::from_guest_ptr(
+                            ctx,
+                            guest_allocation_ptr,
+                        ),
+                        context,
                     ) {
-                        Ok(input) => {
-                            match $host_function(
-                                std::sync::Arc::clone(&closure_self_arc),
-                                std::sync::Arc::clone(&closure_call_context_arc),
-                                input,
-                            ) {
-                                Ok(output) => Ok::<_, WasmError>(output),
-                                Err(wasm_error) => Err::<_, WasmError>(wasm_error),
-                            }
+                        (Ok(input), Some((ribosome, call_context))) => {
+                            $host_function(ribosome, call_context, input)
                         }
-                        Err(wasm_error) => Err::<_, WasmError>(wasm_error),
+                        (Ok(_), None) => Err(WasmError::Host(NO_CALL_CONTEXT.to_string())),
+                        (Err(wasm_error), _) => Err::<_, WasmError>(wasm_error),
                     };
                     $crate::holochain_wasmer_host::import::set_context_data(ctx, result)
                 }
@@ -175,212 +339,124 @@ impl RealRibosome {
             func!(holochain_wasmer_host::import::__import_data),
         );
 
+        // metering injected into the wasm to enforce the zome call limits
+        let gas_slot = slot.clone();
+        ns.insert(
+            metering::GAS_IMPORT,
+            func!(move |_ctx: &mut Ctx, fuel: u32| -> Result<(), WasmError> {
+                match gas_slot.read().as_ref() {
+                    Some(context) => context.meter.charge(fuel as u64),
+                    None => Ok(()),
+                }
+            }),
+        );
+        let grow_slot = slot.clone();
+        ns.insert(
+            metering::MEMORY_GROW_IMPORT,
+            func!(move |ctx: &mut Ctx, delta: u32| -> Result<u32, WasmError> {
+                if let Some(context) = grow_slot.read().as_ref() {
+                    context.meter.check_grow(ctx.memory(0).size().0, delta)?;
+                }
+                Ok(delta)
+            }),
+        );
+
         // imported host functions for core
         ns.insert("__trace", func!(invoke_host_function!(trace)));
         ns.insert("__hash_entry", func!(invoke_host_function!(hash_entry)));
         ns.insert("__version", func!(invoke_host_function!(version)));
         ns.insert("__unreachable", func!(invoke_host_function!(unreachable)));
 
-        if let HostFnAccess {
-            keystore: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert(
-                "__verify_signature",
-                func!(invoke_host_function!(verify_signature)),
-            );
-            ns.insert("__sign", func!(invoke_host_function!(sign)));
-            ns.insert(
-                "__sign_ephemeral",
-                func!(invoke_host_function!(sign_ephemeral)),
-            );
-            ns.insert(
-                "__create_x25519_keypair",
-                func!(invoke_host_function!(create_x25519_keypair)),
-            );
-            ns.insert(
-                "__x_salsa20_poly1305_encrypt",
-                func!(invoke_host_function!(x_salsa20_poly1305_encrypt)),
-            );
-            ns.insert(
-                "__x_salsa20_poly1305_decrypt",
-                func!(invoke_host_function!(x_salsa20_poly1305_decrypt)),
-            );
-            ns.insert(
-                "__x_25519_x_salsa20_poly1305_encrypt",
-                func!(invoke_host_function!(x_25519_x_salsa20_poly1305_encrypt)),
-            );
-            ns.insert(
-                "__x_25519_x_salsa20_poly1305_decrypt",
-                func!(invoke_host_function!(x_25519_x_salsa20_poly1305_decrypt)),
-            );
-        } else {
-            ns.insert(
-                "__verify_signature",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert("__sign", func!(invoke_host_function!(unreachable)));
-            ns.insert(
-                "__sign_ephemeral",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__create_x25519_keypair",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__x_salsa20_poly1305_encrypt",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__x_salsa20_poly1305_decrypt",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__x_25519_x_salsa20_poly1305_encrypt",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__x_25519_x_salsa20_poly1305_decrypt",
-                func!(invoke_host_function!(unreachable)),
-            );
-        }
-
-        if let HostFnAccess {
-            dna_bindings: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert("__zome_info", func!(invoke_host_function!(zome_info)));
-            ns.insert("__app_info", func!(invoke_host_function!(app_info)));
-            ns.insert("__dna_info", func!(invoke_host_function!(dna_info)));
-            ns.insert("__call_info", func!(invoke_host_function!(call_info)));
-        } else {
-            ns.insert("__zome_info", func!(invoke_host_function!(unreachable)));
-            ns.insert("__app_info", func!(invoke_host_function!(unreachable)));
-            ns.insert("__dna_info", func!(invoke_host_function!(unreachable)));
-            ns.insert("__call_info", func!(invoke_host_function!(unreachable)));
-        }
-
-        if let HostFnAccess {
-            non_determinism: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert("__random_bytes", func!(invoke_host_function!(random_bytes)));
-            ns.insert("__sys_time", func!(invoke_host_function!(sys_time)));
-            ns.insert("__sleep", func!(invoke_host_function!(sleep)));
-        } else {
-            ns.insert("__random_bytes", func!(invoke_host_function!(unreachable)));
-            ns.insert("__sys_time", func!(invoke_host_function!(unreachable)));
-            ns.insert("__sleep", func!(invoke_host_function!(unreachable)));
-        }
-
-        if let HostFnAccess {
-            agent_info: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert("__agent_info", func!(invoke_host_function!(agent_info)));
-            ns.insert(
-                "__capability_claims",
-                func!(invoke_host_function!(capability_claims)),
-            );
-            ns.insert(
-                "__capability_grants",
-                func!(invoke_host_function!(capability_grants)),
-            );
-            ns.insert(
-                "__capability_info",
-                func!(invoke_host_function!(capability_info)),
-            );
-        } else {
-            ns.insert("__agent_info", func!(invoke_host_function!(unreachable)));
-            ns.insert(
-                "__capability_claims",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__capability_grants",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__capability_info",
-                func!(invoke_host_function!(unreachable)),
-            );
-        }
-
-        if let HostFnAccess {
-            read_workspace: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert("__get", func!(invoke_host_function!(get)));
-            ns.insert("__get_details", func!(invoke_host_function!(get_details)));
-            ns.insert("__get_links", func!(invoke_host_function!(get_links)));
-            ns.insert(
-                "__get_link_details",
-                func!(invoke_host_function!(get_link_details)),
-            );
-            ns.insert(
-                "__get_agent_activity",
-                func!(invoke_host_function!(get_agent_activity)),
-            );
-            ns.insert("__query", func!(invoke_host_function!(query)));
-        } else {
-            ns.insert("__get", func!(invoke_host_function!(unreachable)));
-            ns.insert("__get_details", func!(invoke_host_function!(unreachable)));
-            ns.insert("__get_links", func!(invoke_host_function!(unreachable)));
-            ns.insert(
-                "__get_link_details",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert(
-                "__get_agent_activity",
-                func!(invoke_host_function!(unreachable)),
-            );
-            ns.insert("__query", func!(invoke_host_function!(unreachable)));
+        // A host function the zome has not been granted in the DNA manifest
+        // returns an error to the guest, rather than being unreachable, as a
+        // zome is free to try calling anything it was compiled against.
+        macro_rules! deny_host_function {
+            ( $host_function:ident, $permission:ident ) => {{
+                let zome_name = zome.zome_name().clone();
+                move |ctx: &mut Ctx, _guest_allocation_ptr: GuestPtr| -> Result<Len, WasmError> {
+                    let error = RibosomeError::HostFnPermissions(
+                        zome_name.clone(),
+                        stringify!($host_function).to_string(),
+                        stringify!($permission).to_string(),
+                    );
+                    $crate::holochain_wasmer_host::import::set_context_data(
+                        ctx,
+                        Err::<(), _>(WasmError::Host(error.to_string())),
+                    )
+                }
+            }};
         }
 
-        if let HostFnAccess {
-            write_network: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert("__call_remote", func!(invoke_host_function!(call_remote)));
-            ns.insert(
-                "__remote_signal",
-                func!(invoke_host_function!(remote_signal)),
-            );
-        } else {
-            ns.insert("__call_remote", func!(invoke_host_function!(unreachable)));
-            ns.insert("__remote_signal", func!(invoke_host_function!(unreachable)));
+        // Insert a group of host functions which all require the same
+        // permission, which must be allowed by both the call context and
+        // the zome's declared access.
+        macro_rules! insert_host_functions {
+            ( $permission:ident => $( $host_function:ident ),+ $(,)? ) => {{
+                match (host_fn_access.$permission, zome_host_fn_access.$permission) {
+                    (Permission::Allow, Permission::Allow) => {
+                        $( ns.insert(
+                            concat!("__", stringify!($host_function)),
+                            func!(invoke_host_function!($host_function)),
+                        ); )+
+                    }
+                    (Permission::Allow, Permission::Deny) => {
+                        $( ns.insert(
+                            concat!("__", stringify!($host_function)),
+                            func!(deny_host_function!($host_function, $permission)),
+                        ); )+
+                    }
+                    (Permission::Deny, _) => {
+                        $( ns.insert(
+                            concat!("__", stringify!($host_function)),
+                            func!(invoke_host_function!(unreachable)),
+                        ); )+
+                    }
+                }
+            }};
         }
 
-        if let HostFnAccess {
-            write_workspace: Permission::Allow,
-            ..
-        } = host_fn_access
-        {
-            ns.insert("__call", func!(invoke_host_function!(call)));
-            ns.insert("__create", func!(invoke_host_function!(create)));
-            ns.insert("__emit_signal", func!(invoke_host_function!(emit_signal)));
-            ns.insert("__create_link", func!(invoke_host_function!(create_link)));
-            ns.insert("__delete_link", func!(invoke_host_function!(delete_link)));
-            ns.insert("__update", func!(invoke_host_function!(update)));
-            ns.insert("__delete", func!(invoke_host_function!(delete)));
-            ns.insert("__schedule", func!(invoke_host_function!(schedule)));
-        } else {
-            ns.insert("__call", func!(invoke_host_function!(unreachable)));
-            ns.insert("__create", func!(invoke_host_function!(unreachable)));
-            ns.insert("__emit_signal", func!(invoke_host_function!(unreachable)));
-            ns.insert("__create_link", func!(invoke_host_function!(unreachable)));
-            ns.insert("__delete_link", func!(invoke_host_function!(unreachable)));
-            ns.insert("__update", func!(invoke_host_function!(unreachable)));
-            ns.insert("__delete", func!(invoke_host_function!(unreachable)));
-            ns.insert("__schedule", func!(invoke_host_function!(unreachable)));
-        }
+        insert_host_functions!(
+            keystore =>
+            verify_signature,
+            sign,
+            sign_ephemeral,
+            create_x25519_keypair,
+            x_salsa20_poly1305_encrypt,
+            x_salsa20_poly1305_decrypt,
+            x_25519_x_salsa20_poly1305_encrypt,
+            x_25519_x_salsa20_poly1305_decrypt,
+        );
+        insert_host_functions!(dna_bindings => zome_info, app_info, dna_info, call_info);
+        insert_host_functions!(non_determinism => random_bytes, sys_time, sleep);
+        insert_host_functions!(
+            agent_info =>
+            agent_info,
+            capability_claims,
+            capability_grants,
+            capability_info,
+        );
+        insert_host_functions!(
+            read_workspace =>
+            get,
+            get_details,
+            get_links,
+            get_link_details,
+            get_agent_activity,
+            query,
+        );
+        insert_host_functions!(write_network => call_remote, remote_signal);
+        insert_host_functions!(
+            write_workspace =>
+            call,
+            create,
+            emit_signal,
+            create_link,
+            delete_link,
+            update,
+            delete,
+            schedule,
+            accept_countersigning_preflight_request,
+        );
         imports.register("env", ns);
 
         imports
@@ -437,18 +513,48 @@ impl RibosomeT for RealRibosome {
                     // there is a callback to_call and it is implemented in the wasm
                     // it is important to fully instantiate this (e.g. don't try to use the module above)
                     // because it builds guards against memory leaks and handles imports correctly
-                    let mut instance = self.instance(call_context)?;
+                    let limits = self.dna_file.dna_def().zome_call_limits();
+                    let limits = match call_context.host_access {
+                        // validation must reach the same outcome on every node,
+                        // however fast it is, so it is only limited by fuel
+                        HostAccess::Validate(_) | HostAccess::ValidateCreateLink(_) => {
+                            limits.without_timeout()
+                        }
+                        _ => limits,
+                    };
+                    let meter = Arc::new(ZomeCallMeter::new(limits));
+                    let (key, mut instance) = self.instance(call_context, meter.clone())?;
+                    let input = invocation.to_owned().host_input()?;
 
+                    meter.start();
                     let result: Result<ExternIO, WasmError> = holochain_wasmer_host::guest::call(
-                        &mut instance,
+                        instance.instance_mut(),
                         to_call.as_ref(),
                         // be aware of this clone!
                         // the whole invocation is cloned!
                         // @todo - is this a problem for large payloads like entries?
-                        invocation.to_owned().host_input()?,
+                        input,
                     );
 
-                    Ok(Some(result?))
+                    match (result, meter.exceeded()) {
+                        (Ok(output), _) => {
+                            // Only an instance which returned normally is
+                            // in a fit state to be reused
+                            instance_pool::checkin(
+                                key,
+                                instance,
+                                self.wasm_config.instance_pool_size,
+                            );
+                            Ok(Some(output))
+                        }
+                        // The guest was trapped for exceeding a limit
+                        (Err(_), Some(limit)) => Err(RibosomeError::ZomeCallLimitExceeded(
+                            zome.zome_name().clone(),
+                            to_call.clone(),
+                            limit.clone(),
+                        )),
+                        (Err(e), None) => Err(e.into()),
+                    }
                 } else {
                     // the func doesn't exist
                     // the callback is not implemented
@@ -464,6 +570,33 @@ impl RibosomeT for RealRibosome {
         }
     }
 
+    fn zome_exports(&self, zome_name: &ZomeName) -> RibosomeResult<ZomeExports> {
+        match self.dna_file.dna().get_zome(zome_name)?.zome_def() {
+            ZomeDef::Wasm(WasmZome { wasm_hash, .. }) => {
+                if let Some(exports) = ZOME_EXPORTS_CACHE.read().get(wasm_hash) {
+                    return Ok(exports.clone());
+                }
+                let module = self.module(zome_name)?;
+                // Skip the memory and the exports used by the hdk to pass
+                // data between host and guest.
+                let exports = classify_exports(
+                    module
+                        .info()
+                        .exports
+                        .keys()
+                        .filter(|name| !name.starts_with("__") && name.as_str() != "memory"),
+                );
+                ZOME_EXPORTS_CACHE
+                    .write()
+                    .insert(wasm_hash.clone(), exports.clone());
+                Ok(exports)
+            }
+            ZomeDef::Inline(zome) => Ok(classify_exports(
+                zome.function_names().into_iter().map(|name| name.0),
+            )),
+        }
+    }
+
     fn call_iterator<I: crate::core::ribosome::Invocation>(
         &self,
         access: HostAccess,
@@ -477,7 +610,7 @@ impl RibosomeT for RealRibosome {
     fn call_zome_function(
         &self,
         host_access: ZomeCallHostAccess,
-        invocation: ZomeCallInvocation,
+        mut invocation: ZomeCallInvocation,
     ) -> RibosomeResult<ZomeCallResponse> {
         Ok(if invocation.is_authorized(&host_access)? {
             // make a copy of these for the error handling below
@@ -572,6 +705,11 @@ pub mod wasm_test {
     use crate::fixt::ZomeCallHostAccessFixturator;
     use ::fixt::prelude::*;
     use hdk::prelude::*;
+    use holochain_types::prelude::DnaDefHashed;
+    use holochain_types::prelude::DnaFile;
+    use holochain_types::prelude::HostFnAccess;
+    use holochain_types::prelude::Permission;
+    use holochain_types::prelude::WasmZome;
     use holochain_wasm_test_utils::TestWasm;
 
     #[tokio::test(flavor = "multi_thread")]
@@ -600,4 +738,61 @@ pub mod wasm_test {
 
         assert_eq!("foobar", bar_result.as_str());
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    /// A zome can't call host functions it has not declared in the manifest
+    async fn ribosome_zome_host_fn_access_test() {
+        let test_env = holochain_lmdb::test_utils::test_cell_env();
+        let env = test_env.env();
+        let mut workspace =
+            crate::core::workflow::CallZomeWorkspace::new(env.clone().into()).unwrap();
+        crate::core::workflow::fake_genesis(&mut workspace.source_chain)
+            .await
+            .unwrap();
+        let workspace_lock = crate::core::workflow::CallZomeWorkspaceLock::new(workspace);
+
+        let mut host_access = fixt!(ZomeCallHostAccess, Predictable);
+        host_access.workspace = workspace_lock;
+
+        let ribosome = crate::fixt::RealRibosomeFixturator::new(crate::fixt::curve::Zomes(vec![
+            TestWasm::Sign.into(),
+        ]))
+        .next()
+        .unwrap();
+
+        // Restrict the zome to everything but the keystore
+        let mut dna_def = ribosome.dna_file().dna_def().clone();
+        let wasm_hash = dna_def.zomes[0]
+            .1
+            .wasm_hash(&TestWasm::Sign.into())
+            .unwrap();
+        let mut access = HostFnAccess::all();
+        access.keystore = Permission::Deny;
+        dna_def.zomes[0].1 = WasmZome::new(wasm_hash).with_host_fn_access(access).into();
+        let ribosome = super::RealRibosome::new(DnaFile::from_parts(
+            DnaDefHashed::from_content_sync(dna_def),
+            ribosome.dna_file().code().clone().into(),
+        ));
+
+        let invocation =
+            crate::fixt::ZomeCallInvocationFixturator::new(crate::fixt::NamedInvocation(
+                test_utils::fake_cell_id(1),
+                TestWasm::Sign.into(),
+                "sign".into(),
+                ExternIO::encode(Sign::new_raw(
+                    test_utils::fake_agent_pubkey_1(),
+                    vec![1, 2, 3],
+                ))
+                .unwrap(),
+            ))
+            .next()
+            .unwrap();
+
+        let err = ribosome
+            .call_zome_function(host_access, invocation)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("is not permitted to call the host function 'sign'"));
+    }
 }
@@ -5,14 +5,19 @@ use super::queue_consumer::TriggerSender;
 use super::workflow::incoming_dht_ops_workflow::incoming_dht_ops_workflow;
 use super::workflow::sys_validation_workflow::SysValidationWorkspace;
 use crate::conductor::api::CellConductorApiT;
+use crate::conductor::dpki::DpkiImpl;
+use crate::conductor::dpki::KeyState;
 use crate::conductor::entry_def_store::get_entry_def;
 use fallible_iterator::FallibleIterator;
+use holochain_cascade::Cascade;
 use holochain_keystore::AgentPubKeyExt;
 use holochain_lmdb::env::EnvironmentWrite;
 use holochain_lmdb::error::DatabaseResult;
 use holochain_lmdb::fresh_reader;
 use holochain_p2p::HolochainP2pCell;
+use holochain_p2p::HolochainP2pCellT;
 use holochain_state::metadata::ChainItemKey;
+use holochain_state::metadata::LinkMetaKey;
 use holochain_state::metadata::MetadataBufT;
 use holochain_types::prelude::*;
 use std::convert::TryInto;
@@ -29,15 +34,6 @@ mod error;
 #[cfg(test)]
 mod tests;
 
-/// 16mb limit on Entries due to websocket limits.
-/// Consider splitting large entries up.
-pub const MAX_ENTRY_SIZE: usize = 16_000_000;
-
-/// 400b limit on LinkTags.
-/// Tags are used as keys to the database to allow
-/// fast lookup so they need to be small.
-pub const MAX_TAG_SIZE: usize = 400;
-
 /// Verify the signature for this header
 pub async fn verify_header_signature(
     sig: &Signature,
@@ -50,11 +46,35 @@ pub async fn verify_header_signature(
     }
 }
 
-/// Verify the author key was valid at the time
-/// of signing with dpki
-/// TODO: This is just a stub until we have dpki.
-pub async fn author_key_is_valid(_author: &AgentPubKey) -> SysValidationResult<bool> {
-    Ok(true)
+/// Verify the author key is valid at the given time with dpki.
+/// Only use this where the time isn't chosen by the author, e.g. the current
+/// time at genesis; headers are checked with [header_author_key_is_valid].
+/// Without a dpki service every key is valid.
+pub async fn author_key_is_valid(
+    author: &AgentPubKey,
+    at: Timestamp,
+    dpki: Option<&DpkiImpl>,
+) -> SysValidationResult<bool> {
+    match dpki {
+        Some(dpki) => Ok(dpki.key_state(author, at).await? == KeyState::Valid),
+        None => Ok(true),
+    }
+}
+
+/// Verify the author key was valid when it authored this header
+/// in its source chain of the given DNA, with dpki.
+/// The header's timestamp is chosen by its author, so a revoked key is
+/// judged by the header's position in the chain instead.
+/// Without a dpki service every key is valid.
+pub async fn header_author_key_is_valid(
+    dna_hash: &DnaHash,
+    header: &Header,
+    dpki: Option<&DpkiImpl>,
+) -> SysValidationResult<bool> {
+    match dpki {
+        Some(dpki) => Ok(dpki.header_key_state(dna_hash, header).await? == KeyState::Valid),
+        None => Ok(true),
+    }
 }
 
 /// Check that previous header makes sense
@@ -146,10 +166,61 @@ pub async fn check_chain_rollback(
     Ok(())
 }
 
-/// Placeholder for future spam check.
-/// Check header timestamps don't exceed MAX_PUBLISH_FREQUENCY
-pub async fn check_spam(_header: &Header) -> SysValidationResult<()> {
-    Ok(())
+/// Check the author hasn't authored more headers than the DNA's publish rate
+/// limit allows within the limit's window, ending at this header's timestamp.
+/// Only the headers before this one on its own chain are counted, so every
+/// node that validates this header comes to the same outcome. Timestamps
+/// strictly increase along a chain, so the walk back stops at the first
+/// header outside the window.
+pub async fn check_spam<Network: HolochainP2pCellT + Clone + Send + 'static>(
+    header: &Header,
+    publish_rate_limit: Option<&PublishRateLimit>,
+    cascade: &mut Cascade<'_, Network>,
+) -> SysValidationResult<()> {
+    let limit = match publish_rate_limit {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+    // There aren't enough headers on this chain to exceed the limit
+    if header.header_seq() < limit.max_headers {
+        return Ok(());
+    }
+    let window_start = (header.timestamp() - limit.window()).unwrap_or(Timestamp(i64::MIN, 0));
+    let mut earliest = header.timestamp();
+    let mut prev_header_hash = header.prev_header().cloned();
+    for _ in 0..limit.max_headers {
+        let prev_hash = match prev_header_hash {
+            Some(prev_hash) => prev_hash,
+            None => return Ok(()),
+        };
+        let prev_header = cascade
+            .retrieve_header(prev_hash.clone(), Default::default())
+            .await?
+            .ok_or_else(|| ValidationOutcome::DepMissingFromDht(prev_hash.into()))?;
+        let prev_header = prev_header.header();
+        if prev_header.timestamp() < window_start {
+            return Ok(());
+        }
+        earliest = prev_header.timestamp();
+        prev_header_hash = prev_header.prev_header().cloned();
+    }
+
+    // This header and the max headers before it are all in the window.
+    // There may be more, but the walk back stops as soon as the limit is
+    // exceeded, so the outcome reports the headers which were counted and
+    // the time they span.
+    let span_ms = header
+        .timestamp()
+        .checked_difference_signed(&earliest)
+        .map_or(limit.window_ms, |span| span.num_milliseconds() as u64);
+    Err(ValidationOutcome::PublishRateExceeded(
+        header.author().clone(),
+        limit.max_headers as usize + 1,
+        span_ms,
+        limit.max_headers,
+        limit.window_ms,
+    )
+    .into())
 }
 
 /// Check previous header timestamp is before this header
@@ -178,6 +249,7 @@ pub fn check_entry_type(entry_type: &EntryType, entry: &Entry) -> SysValidationR
     match (entry_type, entry) {
         (EntryType::AgentPubKey, Entry::Agent(_)) => Ok(()),
         (EntryType::App(_), Entry::App(_)) => Ok(()),
+        (EntryType::App(_), Entry::CounterSign(_, _)) => Ok(()),
         (EntryType::CapClaim, Entry::CapClaim(_)) => Ok(()),
         (EntryType::CapGrant, Entry::CapGrant(_)) => Ok(()),
         _ => Err(ValidationOutcome::EntryType.into()),
@@ -245,15 +317,15 @@ pub fn check_new_entry_header(header: &Header) -> SysValidationResult<()> {
     }
 }
 
-/// Check the entry size is under the MAX_ENTRY_SIZE
-pub fn check_entry_size(entry: &Entry) -> SysValidationResult<()> {
-    match entry {
-        Entry::App(bytes) => {
+/// Check the entry size is under the DNA's max entry size
+pub fn check_entry_size(entry: &Entry, max_entry_size: usize) -> SysValidationResult<()> {
+    match entry.as_app_entry() {
+        Some(bytes) => {
             let size = std::mem::size_of_val(&bytes.bytes()[..]);
-            if size < MAX_ENTRY_SIZE {
+            if size < max_entry_size {
                 Ok(())
             } else {
-                Err(ValidationOutcome::EntryTooLarge(size, MAX_ENTRY_SIZE).into())
+                Err(ValidationOutcome::EntryTooLarge(size, max_entry_size).into())
             }
         }
         // Other entry types are small
@@ -261,13 +333,128 @@ pub fn check_entry_size(entry: &Entry) -> SysValidationResult<()> {
     }
 }
 
-/// Check the link tag size is under the MAX_TAG_SIZE
-pub fn check_tag_size(tag: &LinkTag) -> SysValidationResult<()> {
+/// Check a countersigned entry was signed by every agent in its session,
+/// and that the header committing it is at the chain position its author
+/// signed for, within the session window
+pub async fn check_countersigning_session_data(
+    header: &Header,
+    session_data: &CounterSigningSessionData,
+    app_entry: &AppEntryBytes,
+) -> SysValidationResult<()> {
+    session_data
+        .check()
+        .map_err(|e| ValidationOutcome::from(CounterSignedEntryError::from(e)))?;
+    let request = &session_data.preflight_request;
+    if request.app_entry_hash != EntryHash::with_data_sync(&Entry::App(app_entry.clone())) {
+        return Err(
+            ValidationOutcome::from(CounterSignedEntryError::AppEntryHash(
+                request.app_entry_hash.clone(),
+            ))
+            .into(),
+        );
+    }
+    for (agent, agent_state, signature) in session_data.signers() {
+        let data = PreflightResponse::encode_for_signature(request, agent_state)
+            .map_err(SourceChainError::from)?;
+        if !agent.verify_signature_raw(signature, &data).await? {
+            return Err(
+                ValidationOutcome::from(CounterSignedEntryError::Signature(agent.clone())).into(),
+            );
+        }
+    }
+    let agent_state = session_data
+        .agent_state_for(header.author())
+        .ok_or_else(|| CounterSignedEntryError::AuthorNotSigner(header.author().clone()))
+        .map_err(ValidationOutcome::from)?;
+    if header.prev_header() != Some(&agent_state.chain_top)
+        || agent_state.header_seq.checked_add(1) != Some(header.header_seq())
+    {
+        return Err(
+            ValidationOutcome::from(CounterSignedEntryError::ChainPosition(agent_state.clone()))
+                .into(),
+        );
+    }
+    if !request.session_times.contains(&header.timestamp()) {
+        return Err(
+            ValidationOutcome::from(CounterSignedEntryError::SessionTimes(
+                header.timestamp(),
+                request.session_times,
+            ))
+            .into(),
+        );
+    }
+    Ok(())
+}
+
+/// Check the link tag size is under the DNA's max tag size
+pub fn check_tag_size(tag: &LinkTag, max_tag_size: usize) -> SysValidationResult<()> {
     let size = std::mem::size_of_val(&tag.0[..]);
-    if size < MAX_TAG_SIZE {
+    if size < max_tag_size {
         Ok(())
     } else {
-        Err(ValidationOutcome::TagTooLarge(size, MAX_TAG_SIZE).into())
+        Err(ValidationOutcome::TagTooLarge(size, max_tag_size).into())
+    }
+}
+
+/// Check this link wouldn't take its base over the DNA's max links per base.
+/// Every link on the base that can be found counts towards the limit,
+/// whether or not it has been deleted.
+///
+/// This is the author's check when committing the link.
+/// Authorities check against their own link index with
+/// [check_held_links_per_base].
+pub async fn check_links_per_base<Network: HolochainP2pCellT + Clone + Send + 'static>(
+    link_add: &CreateLink,
+    max_links_per_base: Option<u32>,
+    cascade: &mut Cascade<'_, Network>,
+) -> SysValidationResult<()> {
+    let max = match max_links_per_base {
+        Some(max) => max,
+        None => return Ok(()),
+    };
+    let link_add_hash = HeaderHash::with_data_sync(&Header::CreateLink(link_add.clone()));
+    let key = LinkMetaKey::Base(&link_add.base_address);
+    let held = cascade
+        .get_link_details(&key, Default::default())
+        .await?
+        .into_iter()
+        .filter(|(link, _)| *link.header_address() != link_add_hash)
+        .count();
+
+    check_link_count(link_add, held, max)
+}
+
+/// Check this link wouldn't take the base over the DNA's max links per base
+/// in the base authority's link index.
+/// Only links that have been integrated count towards the limit,
+/// whether or not they have been deleted.
+pub fn check_held_links_per_base(
+    link_add: &CreateLink,
+    max_links_per_base: Option<u32>,
+    meta_vault: &impl MetadataBufT,
+) -> SysValidationResult<()> {
+    let max = match max_links_per_base {
+        Some(max) => max,
+        None => return Ok(()),
+    };
+    let link_add_hash = HeaderHash::with_data_sync(&Header::CreateLink(link_add.clone()));
+    let key = LinkMetaKey::Base(&link_add.base_address);
+    let held = fresh_reader!(meta_vault.env(), |r| {
+        meta_vault
+            .get_links_all(&r, &key)?
+            .filter(|link| Ok(link.link_add_hash != link_add_hash))
+            .count()
+    })?;
+    check_link_count(link_add, held, max)
+}
+
+fn check_link_count(link_add: &CreateLink, held: usize, max: u32) -> SysValidationResult<()> {
+    // This link is on the base too
+    let links = held + 1;
+    if links > max as usize {
+        Err(ValidationOutcome::TooManyLinks(link_add.base_address.clone(), links, max).into())
+    } else {
+        Ok(())
     }
 }
 
@@ -445,6 +632,8 @@ where
 pub struct IncomingDhtOpSender {
     env: EnvironmentWrite,
     sys_validation_trigger: TriggerSender,
+    dna_hash: DnaHash,
+    dpki: Option<DpkiImpl>,
 }
 
 impl IncomingDhtOpSender {
@@ -456,9 +645,17 @@ impl IncomingDhtOpSender {
     ) -> SysValidationResult<()> {
         if let Some(op) = make_op(element) {
             let ops = vec![op];
-            incoming_dht_ops_workflow(&self.env, self.sys_validation_trigger, ops, None, false)
-                .await
-                .map_err(Box::new)?;
+            incoming_dht_ops_workflow(
+                &self.env,
+                self.sys_validation_trigger,
+                ops,
+                None,
+                false,
+                &self.dna_hash,
+                self.dpki,
+            )
+            .await
+            .map_err(Box::new)?;
         }
         Ok(())
     }
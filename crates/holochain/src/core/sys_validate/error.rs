@@ -2,6 +2,7 @@ use std::convert::TryFrom;
 
 use super::SourceChainError;
 use crate::conductor::api::error::ConductorApiError;
+use crate::conductor::dpki::DpkiError;
 use crate::conductor::entry_def_store::error::EntryDefStoreError;
 use crate::core::validation::OutcomeOrError;
 use crate::core::workflow::error::WorkflowError;
@@ -30,6 +31,8 @@ pub enum SysValidationError {
     #[error(transparent)]
     DatabaseError(#[from] DatabaseError),
     #[error(transparent)]
+    DpkiError(#[from] DpkiError),
+    #[error(transparent)]
     EntryDefStoreError(#[from] EntryDefStoreError),
     #[error(transparent)]
     KeystoreError(#[from] KeystoreError),
@@ -88,19 +91,21 @@ impl<E> TryFrom<OutcomeOrError<ValidationOutcome, E>> for ValidationOutcome {
 pub enum ValidationOutcome {
     #[error("The element with signature {0:?} and header {1:?} was found to be counterfeit")]
     Counterfeit(Signature, Header),
+    #[error(transparent)]
+    CounterSignedEntry(#[from] CounterSignedEntryError),
     #[error("The dependency {0:?} was not found on the DHT")]
     DepMissingFromDht(AnyDhtHash),
     #[error("The app entry type {0:?} entry def id was out of range")]
     EntryDefId(AppEntryType),
     #[error("The entry has a different hash to the header's entry hash")]
     EntryHash,
-    #[error("The entry size {0} was bigger then the MAX_ENTRY_SIZE {1}")]
+    #[error("The entry size {0} was bigger then the DNA's max entry size {1}")]
     EntryTooLarge(usize, usize),
     #[error("The entry has a different type to the header's entry type")]
     EntryType,
     #[error("The app entry type {0:?} visibility didn't match the zome")]
     EntryVisibility(AppEntryType),
-    #[error("The link tag size {0} was bigger then the MAX_TAG_SIZE {1}")]
+    #[error("The link tag size {0} was bigger then the DNA's max tag size {1}")]
     TagTooLarge(usize, usize),
     #[error("The header {0:?} was expected to be a link add header")]
     NotCreateLink(HeaderHash),
@@ -112,6 +117,12 @@ pub enum ValidationOutcome {
     PrevHeaderError(#[from] PrevHeaderError),
     #[error("StoreEntry should not be gossiped for private entries")]
     PrivateEntry,
+    #[error(
+        "The author {0} has authored at least {1} headers within {2}ms, more than the DNA's limit of {3} within {4}ms"
+    )]
+    PublishRateExceeded(AgentPubKey, usize, u64, u32, u64),
+    #[error("The base {0} would have {1} links, more than the DNA's limit of {2}")]
+    TooManyLinks(EntryHash, usize, u32),
     #[error("Update original EntryType: {0:?} doesn't match new EntryType {1:?}")]
     UpdateTypeMismatch(EntryType, EntryType),
     #[error("Signature {0:?} failed to verify for Header {1:?}")]
@@ -148,3 +159,19 @@ pub enum PrevHeaderError {
     #[error("The previous header's timestamp is not before the current header's timestamp")]
     Timestamp,
 }
+
+#[derive(Error, Debug)]
+pub enum CounterSignedEntryError {
+    #[error(transparent)]
+    SessionData(#[from] CounterSigningError),
+    #[error("The preflight request is for the app entry {0}, not the countersigned one")]
+    AppEntryHash(EntryHash),
+    #[error("The preflight response signature of agent {0} failed to verify")]
+    Signature(AgentPubKey),
+    #[error("The author {0} is not one of the signing agents")]
+    AuthorNotSigner(AgentPubKey),
+    #[error("The header is not at the chain position {0:?} its author signed for")]
+    ChainPosition(CounterSigningAgentState),
+    #[error("The header timestamp {0} is outside the countersigning session {1:?}")]
+    SessionTimes(Timestamp, CounterSigningSessionTimes),
+}
@@ -1,6 +1,8 @@
 use super::*;
 use crate::conductor::api::error::ConductorApiError;
 use crate::conductor::api::MockCellConductorApi;
+use crate::conductor::dpki::ChainHeads;
+use crate::conductor::dpki::LocalDpki;
 use crate::meta_mock;
 use ::fixt::prelude::*;
 use error::SysValidationError;
@@ -14,6 +16,7 @@ use holochain_zome_types::Header;
 use matches::assert_matches;
 use observability;
 use std::convert::TryFrom;
+use std::sync::Arc;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn verify_header_signature_test() {
@@ -36,6 +39,65 @@ async fn verify_header_signature_test() {
     );
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn author_key_is_valid_test() {
+    let old_key = fake_agent_pubkey_1();
+    let new_key = fake_agent_pubkey_2();
+    let dna_hash = fixt!(DnaHash);
+
+    // Without dpki every key is valid
+    assert_matches!(
+        author_key_is_valid(&old_key, Timestamp(20, 0), None).await,
+        Ok(true)
+    );
+
+    let local_dpki = LocalDpki::new();
+    local_dpki
+        .rotate(
+            &old_key,
+            new_key.clone(),
+            Timestamp(10, 0),
+            vec![(dna_hash.clone(), 3)]
+                .into_iter()
+                .collect::<ChainHeads>(),
+        )
+        .unwrap();
+    let dpki: DpkiImpl = Arc::new(local_dpki);
+
+    // Keys are only valid while they are held
+    assert_matches!(
+        author_key_is_valid(&old_key, Timestamp(9, 0), Some(&dpki)).await,
+        Ok(true)
+    );
+    assert_matches!(
+        author_key_is_valid(&old_key, Timestamp(10, 0), Some(&dpki)).await,
+        Ok(false)
+    );
+    assert_matches!(
+        author_key_is_valid(&new_key, Timestamp(9, 0), Some(&dpki)).await,
+        Ok(false)
+    );
+    assert_matches!(
+        author_key_is_valid(&new_key, Timestamp(10, 0), Some(&dpki)).await,
+        Ok(true)
+    );
+
+    // Headers are judged by their position in the chain, not their timestamp
+    let mut create = fixt!(Create);
+    create.author = old_key.clone();
+    create.timestamp = Timestamp(5, 0);
+    create.header_seq = 3;
+    assert_matches!(
+        header_author_key_is_valid(&dna_hash, &create.clone().into(), Some(&dpki)).await,
+        Ok(true)
+    );
+    create.header_seq = 4;
+    assert_matches!(
+        header_author_key_is_valid(&dna_hash, &create.into(), Some(&dpki)).await,
+        Ok(false)
+    );
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn check_previous_header() {
     let mut header = fixt!(CreateLink);
@@ -208,15 +270,24 @@ async fn check_entry_hash_test() {
 
 #[tokio::test(flavor = "multi_thread")]
 async fn check_entry_size_test() {
-    // let tiny = Entry::App(SerializedBytes::from(UnsafeBytes::from(vec![0; 1])));
-    // let bytes = (0..16_000_000).map(|_| 0u8).into_iter().collect::<Vec<_>>();
-    // let huge = Entry::App(SerializedBytes::from(UnsafeBytes::from(bytes)));
-    // assert_matches!(check_entry_size(&tiny), Ok(()));
-
-    // assert_matches!(
-    //     check_entry_size(&huge),
-    //     Err(SysValidationError::ValidationOutcome(ValidationOutcome::EntryTooLarge(_, _)))
-    // );
+    let tiny = Entry::App(AppEntryBytes(SerializedBytes::from(UnsafeBytes::from(
+        vec![0; 1],
+    ))));
+    let big = Entry::App(AppEntryBytes(SerializedBytes::from(UnsafeBytes::from(
+        vec![0; 1_000],
+    ))));
+    assert_matches!(check_entry_size(&tiny, 1_000), Ok(()));
+
+    assert_matches!(
+        check_entry_size(&big, 1_000),
+        Err(SysValidationError::ValidationOutcome(ValidationOutcome::EntryTooLarge(1_000, 1_000)))
+    );
+
+    // Other entry types aren't limited
+    assert_matches!(
+        check_entry_size(&Entry::Agent(fixt!(AgentPubKey)), 1),
+        Ok(())
+    );
 }
 
 #[tokio::test(flavor = "multi_thread")]
@@ -259,12 +330,19 @@ async fn check_link_tag_size_test() {
     let tiny = LinkTag(vec![0; 1]);
     let bytes = (0..401).map(|_| 0u8).into_iter().collect::<Vec<_>>();
     let huge = LinkTag(bytes);
-    assert_matches!(check_tag_size(&tiny), Ok(()));
+    let max_tag_size = IntegrityLimits::default().max_tag_size();
+    assert_matches!(check_tag_size(&tiny, max_tag_size), Ok(()));
 
     assert_matches!(
-        check_tag_size(&huge),
+        check_tag_size(&huge, max_tag_size),
         Err(SysValidationError::ValidationOutcome(ValidationOutcome::TagTooLarge(_, _)))
     );
+
+    // The DNA can lower the limit
+    assert_matches!(
+        check_tag_size(&tiny, 1),
+        Err(SysValidationError::ValidationOutcome(ValidationOutcome::TagTooLarge(1, 1)))
+    );
 }
 
 #[tokio::test(flavor = "multi_thread")]
@@ -277,6 +355,9 @@ async fn check_app_entry_type_test() {
             uid: "ba1d046d-ce29-4778-914b-47e6010d2faf".to_string(),
             properties: SerializedBytes::try_from(()).unwrap(),
             zomes: vec![TestWasm::EntryDefs.into()].into(),
+            redundancy_target: None,
+            zome_call_limits: None,
+            integrity: None,
         },
         vec![TestWasm::EntryDefs.into()],
     )
@@ -307,6 +388,9 @@ async fn check_app_entry_type_test() {
     // # Dna but no entry def in buffer
     // ## ZomeId out of range
     conductor_api.checkpoint();
+    conductor_api
+        .expect_mock_wasm_config()
+        .returning(Default::default);
     conductor_api.expect_sync_get_entry_def().return_const(None);
     conductor_api
         .expect_sync_get_dna()
@@ -360,3 +444,231 @@ async fn check_entry_not_private_test() {
         ))
     );
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn check_spam_test() {
+    use holochain_state::workspace::Workspace;
+    use std::time::Duration;
+
+    let test_env = test_cell_env();
+    let env = test_env.env();
+    let author = fake_agent_pubkey_1();
+    let now = timestamp::now();
+    let header_after = |prev: Option<&Header>, timestamp: Timestamp| {
+        let mut header = fixt!(CreateLink);
+        header.author = author.clone();
+        header.timestamp = timestamp.into();
+        if let Some(prev) = prev {
+            header.header_seq = prev.header_seq() + 1;
+            header.prev_header = HeaderHash::with_data_sync(prev);
+        }
+        Header::CreateLink(header)
+    };
+
+    // The author published one header long ago and two recently
+    let mut chain: Vec<Header> = Vec::new();
+    for timestamp in vec![
+        (now - Duration::from_secs(3600)).unwrap(),
+        (now - Duration::from_secs(20)).unwrap(),
+        (now - Duration::from_secs(10)).unwrap(),
+    ] {
+        let header = header_after(chain.last(), timestamp);
+        chain.push(header);
+    }
+    {
+        let mut workspace = SysValidationWorkspace::new(env.clone().into()).unwrap();
+        for header in chain.iter().cloned() {
+            let header = SignedHeaderHashed::with_presigned(
+                HeaderHashed::from_content_sync(header),
+                fixt!(Signature),
+            );
+            workspace.element_pending.put(header, None).unwrap();
+        }
+        env.with_commit(|writer| workspace.flush_to_txn_ref(writer))
+            .unwrap();
+    }
+    let mut workspace = SysValidationWorkspace::new(env.clone().into()).unwrap();
+    let mut cascade = workspace.local_cascade();
+    let header = header_after(chain.last(), now);
+
+    // Without a limit anything goes
+    assert_matches!(check_spam(&header, None, &mut cascade).await, Ok(()));
+
+    // Only the recent headers count towards the limit
+    let limit = PublishRateLimit {
+        max_headers: 3,
+        window_ms: 60_000,
+    };
+    assert_matches!(
+        check_spam(&header, Some(&limit), &mut cascade).await,
+        Ok(())
+    );
+
+    let limit = PublishRateLimit {
+        max_headers: 2,
+        window_ms: 60_000,
+    };
+    assert_matches!(
+        check_spam(&header, Some(&limit), &mut cascade).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::PublishRateExceeded(_, 3, 20_000, 2, 60_000)
+        ))
+    );
+
+    // Headers which aren't held yet can't be counted
+    let header = header_after(Some(&header_after(chain.last(), now)), now);
+    assert_matches!(
+        check_spam(&header, Some(&limit), &mut cascade).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::DepMissingFromDht(_)
+        ))
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn check_links_per_base_test() {
+    use holochain_state::workspace::Workspace;
+
+    let test_env = test_cell_env();
+    let env = test_env.env();
+    let base_address = fixt!(EntryHash);
+    let link_on_base = || {
+        let mut link_add = fixt!(CreateLink);
+        link_add.base_address = base_address.clone();
+        link_add
+    };
+
+    // The base already has two links
+    {
+        let mut workspace = SysValidationWorkspace::new(env.clone().into()).unwrap();
+        for link_add in vec![link_on_base(), link_on_base()] {
+            workspace.meta_cache.add_link(link_add.clone()).unwrap();
+            let header = SignedHeaderHashed::with_presigned(
+                HeaderHashed::from_content_sync(Header::CreateLink(link_add)),
+                fixt!(Signature),
+            );
+            workspace.element_cache.put(header, None).unwrap();
+        }
+        env.with_commit(|writer| workspace.flush_to_txn_ref(writer))
+            .unwrap();
+    }
+    let mut workspace = SysValidationWorkspace::new(env.clone().into()).unwrap();
+    let mut cascade = workspace.local_cascade();
+    let link_add = link_on_base();
+
+    // Without a limit anything goes
+    assert_matches!(
+        check_links_per_base(&link_add, None, &mut cascade).await,
+        Ok(())
+    );
+
+    assert_matches!(
+        check_links_per_base(&link_add, Some(3), &mut cascade).await,
+        Ok(())
+    );
+    assert_matches!(
+        check_links_per_base(&link_add, Some(2), &mut cascade).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::TooManyLinks(_, 3, 2)
+        ))
+    );
+
+    // Links on other bases don't count
+    assert_matches!(
+        check_links_per_base(&fixt!(CreateLink), Some(1), &mut cascade).await,
+        Ok(())
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn check_countersigning_session_data_test() {
+    use std::time::Duration;
+
+    let keystore = holochain_lmdb::test_utils::test_keystore();
+    let alice = fake_agent_pubkey_1();
+    let bob = fake_agent_pubkey_2();
+    let now = timestamp::now();
+    let app_entry = fixt!(AppEntryBytes);
+    let request = PreflightRequest::new(
+        EntryHash::with_data_sync(&Entry::App(app_entry.clone())),
+        vec![alice.clone(), bob.clone()],
+        CounterSigningSessionTimes::new(
+            (now - Duration::from_secs(10)).unwrap(),
+            (now + Duration::from_secs(60)).unwrap(),
+        ),
+        PreflightBytes(vec![]),
+    );
+    let alice_state = CounterSigningAgentState::new(0, fixt!(HeaderHash), 5);
+    let bob_state = CounterSigningAgentState::new(1, fixt!(HeaderHash), 9);
+    let mut responses = Vec::new();
+    for (agent, agent_state) in vec![(&alice, &alice_state), (&bob, &bob_state)] {
+        let data = PreflightResponse::encode_for_signature(&request, agent_state).unwrap();
+        let signature = agent.sign_raw(&keystore, &data).await.unwrap();
+        responses.push((agent_state.clone(), signature));
+    }
+    let session_data = CounterSigningSessionData {
+        preflight_request: request,
+        responses,
+    };
+
+    // Alice commits the entry where she said she would
+    let mut create = fixt!(Create);
+    create.author = alice.clone();
+    create.prev_header = alice_state.chain_top.clone();
+    create.header_seq = alice_state.header_seq + 1;
+    create.timestamp = now.into();
+    let header = Header::Create(create.clone());
+    assert_matches!(
+        check_countersigning_session_data(&header, &session_data, &app_entry).await,
+        Ok(())
+    );
+
+    // Every signature is checked
+    let mut forged = session_data.clone();
+    forged.responses[1].1 = forged.responses[0].1.clone();
+    assert_matches!(
+        check_countersigning_session_data(&header, &forged, &app_entry).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::CounterSignedEntry(CounterSignedEntryError::Signature(_))
+        ))
+    );
+
+    // The entry must be the one in the request
+    assert_matches!(
+        check_countersigning_session_data(&header, &session_data, &fixt!(AppEntryBytes)).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::CounterSignedEntry(CounterSignedEntryError::AppEntryHash(_))
+        ))
+    );
+
+    // The header must be at the signed chain position
+    let mut moved = create.clone();
+    moved.header_seq += 1;
+    assert_matches!(
+        check_countersigning_session_data(&Header::Create(moved), &session_data, &app_entry).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::CounterSignedEntry(CounterSignedEntryError::ChainPosition(_))
+        ))
+    );
+
+    // Within the session
+    let mut late = create.clone();
+    late.timestamp = (now + Duration::from_secs(120)).unwrap().into();
+    assert_matches!(
+        check_countersigning_session_data(&Header::Create(late), &session_data, &app_entry).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::CounterSignedEntry(CounterSignedEntryError::SessionTimes(_, _))
+        ))
+    );
+
+    // And by one of the signing agents
+    let mut stranger = create;
+    stranger.author = fixt!(AgentPubKey);
+    assert_matches!(
+        check_countersigning_session_data(&Header::Create(stranger), &session_data, &app_entry)
+            .await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::CounterSignedEntry(CounterSignedEntryError::AuthorNotSigner(_))
+        ))
+    );
+}
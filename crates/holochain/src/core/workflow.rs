@@ -25,7 +25,9 @@ pub mod error;
 
 pub mod app_validation_workflow;
 pub mod call_zome_workflow;
+pub mod chain_fork_check_workflow;
 pub mod genesis_workflow;
+pub mod health_check_workflow;
 pub mod incoming_dht_ops_workflow;
 pub mod initialize_zomes_workflow;
 pub mod integrate_dht_ops_workflow;
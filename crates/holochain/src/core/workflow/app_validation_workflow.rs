@@ -225,7 +225,7 @@ async fn validate_op(
     };
 
     // Create the ribosome
-    let ribosome = RealRibosome::new(dna_file);
+    let ribosome = RealRibosome::new(dna_file).with_wasm_config(conductor_api.wasm_config().await);
 
     // Get the validation package
     let validation_package = get_validation_package(
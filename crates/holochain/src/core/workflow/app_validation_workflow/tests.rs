@@ -42,6 +42,9 @@ async fn app_validation_workflow_test() {
                 TestWasm::Create.into(),
             ]
             .into(),
+            redundancy_target: None,
+            zome_call_limits: None,
+            integrity: None,
         },
         vec![
             TestWasm::Validate.into(),
@@ -7,6 +7,9 @@ use crate::core::queue_consumer::OneshotWriter;
 use crate::core::queue_consumer::TriggerSender;
 use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::guest_callback::post_commit::PostCommitHostAccess;
+use crate::core::ribosome::guest_callback::post_commit::PostCommitInvocation;
+use crate::core::ribosome::guest_callback::post_commit::PostCommitResult;
 use crate::core::ribosome::RibosomeT;
 use crate::core::ribosome::ZomeCallHostAccess;
 use crate::core::ribosome::ZomeCallInvocation;
@@ -58,7 +61,7 @@ pub struct CallZomeWorkflowArgs<Ribosome: RibosomeT + Send, C: CellConductorApiT
 ))]
 pub async fn call_zome_workflow<
     'env,
-    Ribosome: RibosomeT + Send + 'static,
+    Ribosome: RibosomeT + Clone + Send + 'static,
     C: CellConductorApiT,
 >(
     workspace_lock: CallZomeWorkspaceLock,
@@ -69,6 +72,15 @@ pub async fn call_zome_workflow<
     mut trigger_produce_dht_ops: TriggerSender,
 ) -> WorkflowResult<ZomeCallResult> {
     let should_write = args.is_root_zome_call;
+    let post_commit_args = PostCommitArgs {
+        ribosome: args.ribosome.clone(),
+        zome: args.invocation.zome.clone(),
+        cell_id: args.invocation.cell_id.clone(),
+        keystore: keystore.clone(),
+        network: network.clone(),
+        signal_tx: args.signal_tx.clone(),
+    };
+    let chain_head_start_len = workspace_lock.read().await.source_chain.len();
     let result = call_zome_workflow_inner(workspace_lock.clone(), network, keystore, args).await?;
 
     // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
@@ -77,7 +89,25 @@ pub async fn call_zome_workflow<
     if should_write {
         let mut guard = workspace_lock.write().await;
         let workspace = &mut guard;
+
+        // Collect the headers committed by this call, including those
+        // committed by any zome calls it made to this cell
+        let mut headers = Vec::new();
+        if let Ok(ZomeCallResponse::Ok(_)) = &result {
+            let mut i = chain_head_start_len;
+            while let Some(element) = workspace.source_chain.get_at_index(i as u32)? {
+                headers.push(element.header_address().clone());
+                i += 1;
+            }
+        }
+
         writer.with_writer(|writer| Ok(workspace.flush_to_txn_ref(writer)?))?;
+
+        // Only now that the commit has succeeded is it safe to tell the zome
+        if !headers.is_empty() {
+            let env = workspace.env().clone();
+            tokio::task::spawn(post_commit(post_commit_args, env, headers));
+        }
     }
 
     trigger_produce_dht_ops.trigger();
@@ -85,6 +115,68 @@ pub async fn call_zome_workflow<
     Ok(result)
 }
 
+/// Everything needed to run the `post_commit` callback once the zome call
+/// which committed the headers has finished.
+struct PostCommitArgs<Ribosome: RibosomeT> {
+    ribosome: Ribosome,
+    zome: Zome,
+    cell_id: CellId,
+    keystore: KeystoreSender,
+    network: HolochainP2pCell,
+    signal_tx: SignalBroadcaster,
+}
+
+/// Run the `post_commit` callback of the called zome with the headers that
+/// were committed. This runs in the background, so failures can't be returned
+/// to the caller, and are instead logged and emitted as system signals.
+async fn post_commit<Ribosome: RibosomeT + Send + 'static>(
+    args: PostCommitArgs<Ribosome>,
+    env: EnvironmentRead,
+    headers: Vec<HeaderHash>,
+) {
+    let PostCommitArgs {
+        ribosome,
+        zome,
+        cell_id,
+        keystore,
+        network,
+        mut signal_tx,
+    } = args;
+    let zome_name = zome.zome_name().clone();
+    let result = tokio::task::spawn_blocking({
+        let signal_tx = signal_tx.clone();
+        let cell_id = cell_id.clone();
+        let headers = headers.clone();
+        move || -> WorkflowResult<PostCommitResult> {
+            // The callback reads from a fresh workspace, which includes the
+            // data which has just been committed.
+            let workspace = CallZomeWorkspace::new(env)?;
+            let host_access =
+                PostCommitHostAccess::new(workspace.into(), keystore, network, signal_tx, cell_id);
+            let invocation = PostCommitInvocation::new(zome, headers.into());
+            Ok(ribosome.run_post_commit(host_access, invocation)?)
+        }
+    })
+    .await;
+
+    let error = match result {
+        Ok(Ok(PostCommitResult::Success)) => return,
+        Ok(Ok(PostCommitResult::Fail(_, reason))) => reason,
+        Ok(Err(e)) => e.to_string(),
+        Err(e) => e.to_string(),
+    };
+    tracing::error!(?cell_id, %zome_name, %error, "post_commit callback failed");
+    let signal = SystemSignal::PostCommitFailed {
+        cell_id,
+        zome_name,
+        headers,
+        error,
+    };
+    if let Err(e) = signal_tx.send(signal.into()) {
+        tracing::warn!(?e, "Could not send post_commit failure signal");
+    }
+}
+
 async fn call_zome_workflow_inner<
     'env,
     Ribosome: RibosomeT + Send + 'static,
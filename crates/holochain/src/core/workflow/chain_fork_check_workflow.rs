@@ -0,0 +1,307 @@
+//! # Chain Fork Check Workflow
+//!
+//! Authorities holding an agent's activity notice when two valid headers take
+//! the same place in the agent's chain, and record the fork while integrating.
+//! The author of a forked chain would never find out about it that way, so
+//! every cell also asks the authorities for its own activity and compares it
+//! with its source chain. Any header the authorities hold at a sequence number
+//! where our chain has a different header means the chain has been forked,
+//! e.g. by running the same agent on two devices, or by restoring a backup of
+//! a chain which has since grown.
+//!
+//! Every fork this cell has recorded, whether as the author or as an
+//! authority, is then emitted as a [`SystemSignal::ChainForkDetected`], at
+//! every check until a client permitted to see the cell's signals receives it.
+
+use super::error::WorkflowResult;
+use crate::conductor::interface::SignalBroadcaster;
+use crate::core::queue_consumer::OneshotWriter;
+use fallible_iterator::FallibleIterator;
+use holo_hash::*;
+use holochain_lmdb::buffer::BufferedStore;
+use holochain_lmdb::fresh_reader;
+use holochain_lmdb::prelude::*;
+use holochain_lmdb::transaction::Writer;
+use holochain_p2p::actor::GetActivityOptions;
+use holochain_p2p::HolochainP2pCell;
+use holochain_p2p::HolochainP2pCellT;
+use holochain_state::prelude::*;
+use holochain_types::prelude::*;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::*;
+
+/// How long to wait for a client to receive a chain fork signal before
+/// trying again at the next check
+const SIGNAL_RECEIPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Database buffers required for checking our chain for forks
+pub struct ChainForkCheckWorkspace {
+    /// Our own source chain, to compare with the authorities' view of it
+    source_chain: SourceChainBuf,
+    /// The forks this cell has detected
+    chain_forks: ChainForks,
+}
+
+/// Check our own chain against the agent activity held by its authorities,
+/// and signal every fork this cell has detected which hasn't been signalled.
+#[instrument(skip(workspace, writer, network, signal_tx))]
+pub async fn chain_fork_check_workflow(
+    mut workspace: ChainForkCheckWorkspace,
+    writer: OneshotWriter,
+    mut network: HolochainP2pCell,
+    signal_tx: &mut SignalBroadcaster,
+) -> WorkflowResult<()> {
+    let cell_id = network.cell_id();
+    let author = cell_id.agent_pubkey().clone();
+    let env = workspace.source_chain.env().clone();
+
+    if !workspace.source_chain.is_empty() {
+        let own_chain = workspace
+            .source_chain
+            .iter_back()
+            .map(|shh| Ok((shh.header().header_seq(), shh.header_address().clone())))
+            .collect::<HashMap<_, _>>()?;
+        match network
+            .get_agent_activity(
+                author.clone(),
+                ChainQueryFilter::new(),
+                GetActivityOptions::default(),
+            )
+            .await
+        {
+            Ok(responses) => {
+                if let Some(fork) = find_own_chain_fork(&own_chain, &responses) {
+                    let recorded = fresh_reader!(env, |r| workspace.chain_forks.record(
+                        &r,
+                        author.clone(),
+                        fork.clone()
+                    ))?;
+                    if recorded {
+                        error!(?fork, "Our source chain has been forked");
+                    }
+                }
+            }
+            // The authorities may just not be reachable right now,
+            // in which case we'll find out at the next check
+            Err(e) => warn!(?e, "Could not get our agent activity to check for forks"),
+        }
+    }
+
+    let unsignalled = fresh_reader!(env, |r| workspace.chain_forks.all(&r))?
+        .into_iter()
+        .filter(|detected| !detected.signalled);
+    for detected in unsignalled {
+        let signal = SystemSignal::ChainForkDetected {
+            cell_id: cell_id.clone(),
+            author: detected.author.clone(),
+            fork: detected.fork,
+        };
+        // Nobody hears the signal if no client of the cell's app is connected
+        // to an app interface, so try again at the next check
+        if signal_tx
+            .deliver(signal.into(), SIGNAL_RECEIPT_TIMEOUT)
+            .await
+        {
+            fresh_reader!(env, |r| workspace
+                .chain_forks
+                .mark_signalled(&r, &detected.author))?;
+        } else {
+            debug!("No client received the chain fork signal");
+        }
+    }
+
+    // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
+
+    // commit the workspace
+    writer.with_writer(|writer| Ok(workspace.flush_to_txn(writer)?))?;
+
+    Ok(())
+}
+
+/// Find the earliest fork of our chain in the agent activity held by its
+/// authorities, given the hash of each header on our chain by its sequence
+/// number.
+///
+/// Authorities may hold headers past the head of our chain, which we can't
+/// tell apart from headers which haven't been authored on this device. Those
+/// are only a fork once our chain reaches their sequence number.
+pub fn find_own_chain_fork(
+    own_chain: &HashMap<u32, HeaderHash>,
+    responses: &[AgentActivityResponse],
+) -> Option<ChainFork> {
+    let mut forks = Vec::new();
+    for response in responses {
+        if let ChainStatus::Forked(fork) = &response.status {
+            forks.push(fork.clone());
+        }
+        let activity = match &response.valid_activity {
+            ChainItems::Full(headers) => headers
+                .iter()
+                .map(|shh| (shh.header().header_seq(), shh.header_address().clone()))
+                .collect(),
+            ChainItems::Hashes(hashes) => hashes.clone(),
+            ChainItems::NotRequested => Vec::new(),
+        };
+        for (seq, hash) in activity {
+            match own_chain.get(&seq) {
+                Some(own_hash) if *own_hash != hash => forks.push(ChainFork {
+                    fork_seq: seq,
+                    first_header: own_hash.clone(),
+                    second_header: hash,
+                }),
+                _ => (),
+            }
+        }
+    }
+    forks.into_iter().min_by_key(|fork| fork.fork_seq)
+}
+
+impl Workspace for ChainForkCheckWorkspace {
+    fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> WorkspaceResult<()> {
+        self.chain_forks.flush_to_txn_ref(writer)?;
+        Ok(())
+    }
+}
+
+impl ChainForkCheckWorkspace {
+    /// Constructor
+    pub fn new(env: EnvironmentRead) -> WorkspaceResult<Self> {
+        // Only the headers are compared, so private entries are left out
+        let source_chain = SourceChainBuf::public_only(env.clone())?;
+        let chain_forks = ChainForks::new(env)?;
+        Ok(Self {
+            source_chain,
+            chain_forks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conductor::interface::SignalReceipts;
+    use ::fixt::prelude::*;
+    use holochain_lmdb::test_utils::test_cell_env;
+    use holochain_p2p::HolochainP2pCellFixturator;
+    use holochain_types::test_utils::fake_agent_pubkey_1;
+    use holochain_types::test_utils::fake_header_hash;
+    use tokio::sync::broadcast;
+
+    fn response(status: ChainStatus, hashes: Vec<(u32, HeaderHash)>) -> AgentActivityResponse {
+        AgentActivityResponse {
+            agent: fake_agent_pubkey_1(),
+            valid_activity: ChainItems::Hashes(hashes),
+            rejected_activity: ChainItems::NotRequested,
+            status,
+            highest_observed: None,
+        }
+    }
+
+    #[test]
+    fn finds_the_earliest_fork_of_our_chain() {
+        let own_chain: HashMap<_, _> = (0..5).map(|i| (i, fake_header_hash(i as u8))).collect();
+
+        // The authorities agree with our chain, and one holds a header
+        // past our head
+        let agree = response(
+            ChainStatus::Empty,
+            (0..6).map(|i| (i, fake_header_hash(i as u8))).collect(),
+        );
+        assert_eq!(find_own_chain_fork(&own_chain, &[agree.clone()]), None);
+
+        // One authority holds a different header at 3
+        let differs = response(
+            ChainStatus::Empty,
+            vec![(2, fake_header_hash(2)), (3, fake_header_hash(103))],
+        );
+        // Another has seen a fork at 4
+        let fork_at_4 = ChainFork {
+            fork_seq: 4,
+            first_header: fake_header_hash(4),
+            second_header: fake_header_hash(104),
+        };
+        let forked = response(ChainStatus::Forked(fork_at_4.clone()), Vec::new());
+
+        assert_eq!(
+            find_own_chain_fork(&own_chain, &[agree.clone(), forked.clone()]),
+            Some(fork_at_4)
+        );
+        assert_eq!(
+            find_own_chain_fork(&own_chain, &[forked, differs, agree]),
+            Some(ChainFork {
+                fork_seq: 3,
+                first_header: fake_header_hash(3),
+                second_header: fake_header_hash(103),
+            })
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn forks_are_signalled_once_a_client_receives_them() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let network = fixt!(HolochainP2pCell);
+        let author = network.cell_id().agent_pubkey().clone();
+        let fork = ChainFork {
+            fork_seq: 3,
+            first_header: fake_header_hash(3),
+            second_header: fake_header_hash(103),
+        };
+        {
+            let mut workspace = ChainForkCheckWorkspace::new(env.clone().into()).unwrap();
+            fresh_reader!(env, |r| workspace.chain_forks.record(
+                &r,
+                author.clone(),
+                fork.clone()
+            ))
+            .unwrap();
+            env.guard()
+                .with_commit(|writer| workspace.flush_to_txn(writer))
+                .unwrap();
+        }
+        let (tx, _) = broadcast::channel(10);
+        let receipts = SignalReceipts::default();
+        let signal_tx = SignalBroadcaster::new(vec![tx.clone()]).with_receipts(receipts.clone());
+        let check = || {
+            let workspace = ChainForkCheckWorkspace::new(env.clone().into()).unwrap();
+            let network = network.clone();
+            let writer = env.clone().into();
+            let mut signal_tx = signal_tx.clone();
+            async move {
+                chain_fork_check_workflow(workspace, writer, network, &mut signal_tx)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let expected = Signal::System(SystemSignal::ChainForkDetected {
+            cell_id: network.cell_id(),
+            author,
+            fork,
+        });
+
+        // No client is connected, so the fork is signalled at the next check
+        check().await;
+
+        // Nor if the only connection doesn't send it to its client
+        let mut rx = tx.subscribe();
+        check().await;
+        assert_eq!(rx.try_recv().unwrap(), expected);
+
+        // A client received it
+        let mut client_rx = tx.subscribe();
+        let client = tokio::task::spawn(async move {
+            let signal = client_rx.recv().await.unwrap();
+            receipts.received(&signal);
+            signal
+        });
+        check().await;
+        assert_eq!(client.await.unwrap(), expected);
+        assert_eq!(rx.try_recv().unwrap(), expected);
+
+        // so it isn't signalled again
+        check().await;
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -9,6 +9,7 @@
 
 use super::error::WorkflowError;
 use super::error::WorkflowResult;
+use crate::core::sys_validate::author_key_is_valid;
 use crate::core::{
     queue_consumer::OneshotWriter,
     ribosome::guest_callback::genesis_self_check::{
@@ -90,13 +91,8 @@ where
         return Err(WorkflowError::GenesisFailure(reason));
     }
 
-    // TODO: this is a placeholder for a real DPKI request to show intent
-    if api
-        .dpki_request("is_agent_pubkey_valid".into(), agent_pubkey.to_string())
-        .await
-        .expect("TODO: actually implement this")
-        == "INVALID"
-    {
+    // Don't start a chain for an agent whose key isn't valid
+    if !author_key_is_valid(&agent_pubkey, timestamp::now(), api.dpki().as_ref()).await? {
         return Err(WorkflowError::AgentInvalid(agent_pubkey.clone()));
     }
 
@@ -139,6 +135,9 @@ pub mod tests {
     use super::*;
 
     use crate::conductor::api::MockCellConductorApi;
+    use crate::conductor::dpki::ChainHeads;
+    use crate::conductor::dpki::DpkiImpl;
+    use crate::conductor::dpki::LocalDpki;
     use crate::core::ribosome::MockRibosomeT;
     use crate::core::SourceChainResult;
     use fallible_iterator::FallibleIterator;
@@ -149,6 +148,7 @@ pub mod tests {
     use holochain_zome_types::Header;
     use matches::assert_matches;
     use observability;
+    use std::sync::Arc;
 
     pub async fn fake_genesis(source_chain: &mut SourceChain) -> SourceChainResult<()> {
         let dna = fake_dna_file("cool dna");
@@ -169,8 +169,7 @@ pub mod tests {
         {
             let workspace = GenesisWorkspace::new(arc.clone().into()).await?;
             let mut api = MockCellConductorApi::new();
-            api.expect_sync_dpki_request()
-                .returning(|_, _| Ok("mocked dpki request response".to_string()));
+            api.expect_mock_dpki().returning(|| None);
             let mut ribosome = MockRibosomeT::new();
             ribosome
                 .expect_run_genesis_self_check()
@@ -206,6 +205,41 @@ pub mod tests {
 
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn genesis_fails_for_revoked_agent() -> Result<(), anyhow::Error> {
+        observability::test_run()?;
+        let test_env = test_cell_env();
+        let arc = test_env.env();
+        let dna = fake_dna_file("a");
+        let agent_pubkey = fake_agent_pubkey_1();
+
+        let dpki = LocalDpki::new();
+        dpki.revoke(&agent_pubkey, Timestamp(0, 0), ChainHeads::new())?;
+
+        let workspace = GenesisWorkspace::new(arc.clone().into()).await?;
+        let mut api = MockCellConductorApi::new();
+        api.expect_mock_dpki()
+            .returning(move || Some(Arc::new(dpki.clone()) as DpkiImpl));
+        let mut ribosome = MockRibosomeT::new();
+        ribosome
+            .expect_run_genesis_self_check()
+            .returning(|_, _| Ok(GenesisSelfCheckResult::Valid));
+        let args = GenesisWorkflowArgs {
+            dna_file: dna.clone(),
+            agent_pubkey: agent_pubkey.clone(),
+            membrane_proof: None,
+            ribosome,
+        };
+        assert_matches!(
+            genesis_workflow(workspace, arc.clone().into(), api, args).await,
+            Err(WorkflowError::AgentInvalid(a)) if a == agent_pubkey
+        );
+
+        let source_chain = SourceChain::new(arc.clone().into())?;
+        assert!(source_chain.into_inner().is_empty());
+        Ok(())
+    }
 }
 
 /* TODO: make doc-able
@@ -238,7 +272,7 @@ Data X (data & structure) from Store Y:
 
 Functions / Workflows:
 
-- check that agent key is valid [MOCKED dpki] (via real dpki [LEAPFROG])
+- check that agent key is valid with dpki, if the conductor has a dpki service
 
 - retrieve DNA from file path [in the future from HCHC]
 
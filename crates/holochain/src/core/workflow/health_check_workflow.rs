@@ -0,0 +1,256 @@
+//! # Health Check Workflow
+//!
+//! The autonomic [`AutonomicProcess::HealthCheck`] of a cell. For every op the
+//! cell has authored, count the valid validation receipts we hold for it, i.e.
+//! the number of distinct authorities which have told us they are holding it.
+//! That count is recorded against the authored op, and any op held by fewer
+//! authorities than the DNA's redundancy target is reported.
+//!
+//! The [`AutonomicProcess::SlowHeal`] which follows an unhealthy check is just
+//! the publish workflow, which republishes every op whose receipt count is
+//! below the redundancy target.
+
+use super::error::WorkflowResult;
+use super::produce_dht_ops_workflow::dht_op_light::error::DhtOpConvertError;
+use super::produce_dht_ops_workflow::dht_op_light::light_to_op;
+use crate::core::queue_consumer::OneshotWriter;
+use crate::core::queue_consumer::TriggerSender;
+use fallible_iterator::FallibleIterator;
+use holo_hash::*;
+use holochain_lmdb::buffer::BufferedStore;
+use holochain_lmdb::buffer::KvBufFresh;
+use holochain_lmdb::db::AUTHORED_DHT_OPS;
+use holochain_lmdb::fresh_reader;
+use holochain_lmdb::prelude::*;
+use holochain_lmdb::transaction::Writer;
+use holochain_state::prelude::*;
+use holochain_types::prelude::*;
+use tracing::*;
+
+/// Database buffers required for checking the health of authored [DhtOp]s
+pub struct HealthCheckWorkspace {
+    /// Database of authored DhtOps, with their receipt counts
+    authored_dht_ops: AuthoredDhtOpsStore,
+    /// Element store for telling which ops are never published
+    elements: ElementBuf<AuthoredPrefix>,
+    /// The validation receipts we have received for our authored ops
+    validation_receipts: ValidationReceiptsBuf,
+}
+
+/// Check the health of this cell's authored ops, and trigger a SlowHeal
+/// (i.e. a publish) if any of them are held by too few authorities.
+#[instrument(skip(workspace, writer, trigger_publish))]
+pub async fn health_check_workflow(
+    mut workspace: HealthCheckWorkspace,
+    writer: OneshotWriter,
+    redundancy_target: u32,
+    trigger_publish: &mut TriggerSender,
+) -> WorkflowResult<CellHealth> {
+    let health = health_check_workflow_inner(&workspace, redundancy_target)?;
+
+    // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
+
+    // correct the stored receipt counts
+    writer.with_writer(|writer| correct_receipt_counts(&mut workspace, writer))?;
+
+    if !health.is_healthy() {
+        debug!(
+            ops_below_target = health.ops_below_target.len(),
+            "slow heal"
+        );
+        trigger_publish.trigger();
+    }
+
+    Ok(health)
+}
+
+/// Count the receipts for each authored op.
+/// Nothing is written to the database, so this can also be used to inspect
+/// the health of a cell without side effects.
+pub fn health_check_workflow_inner(
+    workspace: &HealthCheckWorkspace,
+    redundancy_target: u32,
+) -> WorkflowResult<CellHealth> {
+    let checked_at = timestamp::now();
+    let env = workspace.elements.headers().env().clone();
+
+    let counts: Vec<(DhtOpHash, AuthoredDhtOpsValue, u32)> = {
+        let authored_dht_ops = &workspace.authored_dht_ops;
+        let validation_receipts = &workspace.validation_receipts;
+        fresh_reader!(env, |r| authored_dht_ops
+            .iter(&r)?
+            .map(|(k, v)| {
+                let op_hash = DhtOpHash::from_raw_39_panicky(k.to_vec());
+                let count = validation_receipts.count_valid(&r, &op_hash)? as u32;
+                Ok((op_hash, v, count))
+            })
+            .collect::<Vec<_>>())?
+    };
+
+    let mut authored_ops = 0;
+    let mut ops_below_target = Vec::new();
+    for (op_hash, value, count) in counts {
+        // Private entries are never published, so nobody will ever hold them.
+        match light_to_op(value.op.clone(), &workspace.elements) {
+            Err(DhtOpConvertError::StoreEntryOnPrivate) => continue,
+            r => r?,
+        };
+        authored_ops += 1;
+        if count < redundancy_target {
+            ops_below_target.push((op_hash, count));
+        }
+    }
+
+    Ok(CellHealth {
+        redundancy_target,
+        authored_ops,
+        ops_below_target,
+        checked_at,
+    })
+}
+
+/// The count kept by the authored store is incremented for every receipt that
+/// arrives, including duplicates, so correct it to the number of valid
+/// receipts held. The counts are read and written within the same write
+/// transaction, so a receipt which arrives during the check isn't lost.
+fn correct_receipt_counts(
+    workspace: &mut HealthCheckWorkspace,
+    writer: &mut Writer,
+) -> WorkspaceResult<()> {
+    let authored = workspace.authored_dht_ops.store();
+    let validation_receipts = &workspace.validation_receipts;
+    let reader: &Writer = writer;
+    let corrected: Vec<(DhtOpHash, AuthoredDhtOpsValue)> = authored
+        .iter(reader)?
+        .filter_map(|(k, mut value)| {
+            let op_hash = DhtOpHash::from_raw_39_panicky(k.to_vec());
+            let count = validation_receipts.count_valid(reader, &op_hash)? as u32;
+            Ok(if value.receipt_count != count {
+                value.receipt_count = count;
+                Some((op_hash, value))
+            } else {
+                None
+            })
+        })
+        .collect()?;
+    for (op_hash, value) in corrected {
+        authored.put(writer, &op_hash, &value)?;
+    }
+    Ok(())
+}
+
+impl Workspace for HealthCheckWorkspace {
+    fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> WorkspaceResult<()> {
+        self.authored_dht_ops.flush_to_txn_ref(writer)?;
+        Ok(())
+    }
+}
+
+impl HealthCheckWorkspace {
+    /// Constructor
+    pub fn new(env: EnvironmentRead) -> WorkspaceResult<Self> {
+        let db = env.get_db(&*AUTHORED_DHT_OPS)?;
+        let authored_dht_ops = KvBufFresh::new(env.clone(), db);
+        let validation_receipts = ValidationReceiptsBuf::new(&env)?;
+        let elements = ElementBuf::authored(env, false)?;
+        Ok(Self {
+            authored_dht_ops,
+            elements,
+            validation_receipts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixt::CreateLinkFixturator;
+    use ::fixt::prelude::*;
+    use holochain_keystore::KeystoreSenderExt;
+    use holochain_lmdb::test_utils::test_cell_env;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn counts_receipts_against_target() {
+        observability::test_run().ok();
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let keystore = holochain_lmdb::test_utils::test_keystore();
+
+        // Author an op
+        let sig = fixt!(Signature);
+        let link_add = fixt!(CreateLink);
+        let op_hashed =
+            DhtOpHashed::from_content_sync(DhtOp::RegisterAddLink(sig.clone(), link_add.clone()));
+        let op_hash = op_hashed.as_hash().clone();
+        let header_hash = HeaderHashed::from_content_sync(link_add.clone().into());
+        let op_light = DhtOpLight::RegisterAddLink(
+            header_hash.as_hash().clone(),
+            link_add.base_address.into(),
+        );
+        {
+            let mut workspace = HealthCheckWorkspace::new(env.clone().into()).unwrap();
+            let mut value = AuthoredDhtOpsValue::from_light(op_light);
+            // Duplicate receipts inflate the stored count
+            value.receipt_count = 7;
+            workspace
+                .authored_dht_ops
+                .put(op_hash.clone(), value)
+                .unwrap();
+            workspace
+                .elements
+                .put(SignedHeaderHashed::with_presigned(header_hash, sig), None)
+                .unwrap();
+
+            // Two authorities are holding it
+            for _ in 0..2 {
+                let validator = keystore
+                    .clone()
+                    .generate_sign_keypair_from_pure_entropy()
+                    .await
+                    .unwrap();
+                let receipt = ValidationReceipt {
+                    dht_op_hash: op_hash.clone(),
+                    validation_status: ValidationStatus::Valid,
+                    validator,
+                    when_integrated: timestamp::now(),
+                };
+                workspace
+                    .validation_receipts
+                    .add_if_unique(receipt.sign(&keystore).await.unwrap())
+                    .unwrap();
+            }
+            env.guard()
+                .with_commit::<DatabaseError, _, _>(|writer| {
+                    workspace.authored_dht_ops.flush_to_txn(writer)?;
+                    workspace.elements.flush_to_txn(writer)?;
+                    workspace.validation_receipts.flush_to_txn(writer)?;
+                    Ok(())
+                })
+                .unwrap();
+        }
+
+        let mut workspace = HealthCheckWorkspace::new(env.clone().into()).unwrap();
+        let health = health_check_workflow_inner(&workspace, 3).unwrap();
+        assert_eq!(health.authored_ops, 1);
+        assert_eq!(health.ops_below_target, vec![(op_hash.clone(), 2)]);
+        assert!(!health.is_healthy());
+
+        let health = health_check_workflow_inner(&workspace, 2).unwrap();
+        assert!(health.is_healthy());
+
+        // The stored count is corrected
+        env.guard()
+            .with_commit(|writer| correct_receipt_counts(&mut workspace, writer))
+            .unwrap();
+        let workspace = HealthCheckWorkspace::new(env.clone().into()).unwrap();
+        assert_eq!(
+            workspace
+                .authored_dht_ops
+                .get(&op_hash)
+                .unwrap()
+                .unwrap()
+                .receipt_count,
+            2
+        );
+    }
+}
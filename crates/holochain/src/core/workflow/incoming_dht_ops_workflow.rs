@@ -4,9 +4,11 @@ use super::error::WorkflowResult;
 use super::integrate_dht_ops_workflow::integrate_single_data;
 use super::produce_dht_ops_workflow::dht_op_light::error::DhtOpConvertResult;
 use super::sys_validation_workflow::counterfeit_check;
+use crate::conductor::dpki::DpkiImpl;
 use crate::core::queue_consumer::TriggerSender;
 use holo_hash::AgentPubKey;
 use holo_hash::DhtOpHash;
+use holo_hash::DnaHash;
 use holochain_cascade::integrate_single_metadata;
 use holochain_lmdb::buffer::BufferedStore;
 use holochain_lmdb::buffer::KvBufFresh;
@@ -27,13 +29,15 @@ use tracing::instrument;
 #[cfg(test)]
 mod test;
 
-#[instrument(skip(state_env, sys_validation_trigger, ops))]
+#[instrument(skip(state_env, sys_validation_trigger, ops, dna_hash, dpki))]
 pub async fn incoming_dht_ops_workflow(
     state_env: &EnvironmentWrite,
     mut sys_validation_trigger: TriggerSender,
     ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
     from_agent: Option<AgentPubKey>,
     request_validation_receipt: bool,
+    dna_hash: &DnaHash,
+    dpki: Option<DpkiImpl>,
 ) -> WorkflowResult<()> {
     // set up our workspace
     let mut workspace = IncomingDhtOpsWorkspace::new(state_env.clone().into())?;
@@ -42,7 +46,7 @@ pub async fn incoming_dht_ops_workflow(
     for (hash, op) in ops {
         if !workspace.op_exists(&hash)? {
             tracing::debug!(?hash, ?op);
-            if should_keep(&op).await? {
+            if should_keep(&op, dna_hash, dpki.as_ref()).await? {
                 workspace.add_to_pending(
                     hash,
                     op,
@@ -81,12 +85,16 @@ fn needs_receipt(op: &DhtOp, from_agent: &Option<AgentPubKey>) -> bool {
         .unwrap_or(false)
 }
 
-#[instrument(skip(op))]
+#[instrument(skip(op, dna_hash, dpki))]
 /// If this op fails the counterfeit check it should be dropped
-async fn should_keep(op: &DhtOp) -> WorkflowResult<bool> {
+async fn should_keep(
+    op: &DhtOp,
+    dna_hash: &DnaHash,
+    dpki: Option<&DpkiImpl>,
+) -> WorkflowResult<bool> {
     let header = op.header();
     let signature = op.signature();
-    Ok(counterfeit_check(signature, &header).await?)
+    Ok(counterfeit_check(signature, &header, dna_hash, dpki).await?)
 }
 
 #[allow(missing_docs)]
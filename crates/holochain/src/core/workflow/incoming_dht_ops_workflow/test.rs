@@ -1,5 +1,6 @@
 use super::*;
 use ::fixt::prelude::*;
+use holo_hash::fixt::DnaHashFixturator;
 use holochain_keystore::AgentPubKeyExt;
 
 #[tokio::test(flavor = "multi_thread")]
@@ -20,9 +21,17 @@ async fn incoming_ops_to_limbo() {
     let hash = DhtOpHash::with_data_sync(&op);
     let ops = vec![(hash.clone(), op.clone())];
 
-    incoming_dht_ops_workflow(&env, sys_validation_trigger.clone(), ops, None, false)
-        .await
-        .unwrap();
+    incoming_dht_ops_workflow(
+        &env,
+        sys_validation_trigger.clone(),
+        ops,
+        None,
+        false,
+        &fixt!(DnaHash),
+        None,
+    )
+    .await
+    .unwrap();
     rx.listen().await.unwrap();
 
     let workspace = IncomingDhtOpsWorkspace::new(env.clone().into()).unwrap();
@@ -9,6 +9,7 @@ use crate::core::validation::DhtOpOrder;
 use crate::core::validation::OrderedOp;
 use error::WorkflowResult;
 use fallible_iterator::FallibleIterator;
+use holo_hash::AgentPubKey;
 use holo_hash::DhtOpHash;
 use holo_hash::EntryHash;
 use holo_hash::HeaderHash;
@@ -43,12 +44,19 @@ mod disintegrate;
 #[cfg(feature = "test_utils")]
 mod tests;
 
-#[instrument(skip(workspace, writer, trigger_sys, trigger_receipt))]
+#[instrument(skip(
+    workspace,
+    writer,
+    trigger_sys,
+    trigger_receipt,
+    trigger_chain_fork_check
+))]
 pub async fn integrate_dht_ops_workflow(
     mut workspace: IntegrateDhtOpsWorkspace,
     writer: OneshotWriter,
     trigger_sys: &mut TriggerSender,
     trigger_receipt: &mut TriggerSender,
+    trigger_chain_fork_check: &mut TriggerSender,
 ) -> WorkflowResult<WorkComplete> {
     // one of many possible ways to access the env
     let env = workspace.elements.headers().env().clone();
@@ -76,6 +84,7 @@ pub async fn integrate_dht_ops_workflow(
     }
 
     let mut total_integrated: usize = 0;
+    let mut forks_detected = false;
 
     // Try to process the queue over and over again, until we either exhaust
     // the queue, or we can no longer integrate anything in the queue.
@@ -92,10 +101,19 @@ pub async fn integrate_dht_ops_workflow(
                 value,
                 order,
             } = so;
+            let activity_author = match &op {
+                DhtOp::RegisterAgentActivity(_, h) => Some(h.author().clone()),
+                _ => None,
+            };
             // Check validation status and put in correct dbs
             let outcome = integrate_single_dht_op(value.clone(), op, &mut workspace).await?;
             match outcome {
                 Outcome::Integrated(integrated) => {
+                    if let (Some(author), ValidationStatus::Valid) =
+                        (activity_author, integrated.validation_status)
+                    {
+                        forks_detected |= record_chain_fork(author, &mut workspace)?;
+                    }
                     // TODO We could create a prefix for the integrated ops db
                     // and separate rejected ops from valid ops.
                     // Currently you need to check the IntegratedDhtOpsValue for
@@ -145,6 +163,9 @@ pub async fn integrate_dht_ops_workflow(
         trigger_sys.trigger();
         trigger_receipt.trigger();
     }
+    if forks_detected {
+        trigger_chain_fork_check.trigger();
+    }
 
     Ok(result)
 }
@@ -243,6 +264,29 @@ fn update_activity_status(
     Ok(())
 }
 
+/// Record the fork of the author's chain if the valid agent activity
+/// just integrated has forked it.
+/// Returns true if it is the earliest fork of the chain seen so far.
+fn record_chain_fork(
+    author: AgentPubKey,
+    workspace: &mut IntegrateDhtOpsWorkspace,
+) -> WorkflowResult<bool> {
+    let fork = match workspace.meta.get_activity_status(&author)? {
+        Some(ChainStatus::Forked(fork)) => fork,
+        _ => return Ok(false),
+    };
+    let env = workspace.elements.headers().env().clone();
+    let recorded = fresh_reader!(env, |r| workspace.chain_forks.record(
+        &r,
+        author.clone(),
+        fork.clone()
+    ))?;
+    if recorded {
+        warn!(?author, ?fork, "Detected a fork of a source chain");
+    }
+    Ok(recorded)
+}
+
 /// Rejected headers still need to be stored in the metadata vault so
 /// they can be served for a get details call.
 fn update_validation_status(
@@ -475,6 +519,8 @@ pub struct IntegrateDhtOpsWorkspace {
     pub meta_rejected: MetadataBuf<RejectedPrefix>,
     /// Ops to disintegrate
     pub to_disintegrate_pending: Vec<DhtOpLight>,
+    /// Forks of the source chains this cell holds activity for
+    pub chain_forks: ChainForks,
     /// READ ONLY
     /// Need the validation limbo to make sure we don't
     /// remove data that is in this limbo
@@ -496,6 +542,7 @@ impl Workspace for IntegrateDhtOpsWorkspace {
         self.meta_pending.flush_to_txn_ref(writer)?;
         self.element_rejected.flush_to_txn_ref(writer)?;
         self.meta_rejected.flush_to_txn_ref(writer)?;
+        self.chain_forks.flush_to_txn_ref(writer)?;
         Ok(())
     }
 }
@@ -510,6 +557,7 @@ impl IntegrateDhtOpsWorkspace {
         let integration_limbo = KvBufFresh::new(env.clone(), db);
 
         let validation_limbo = ValidationLimboStore::new(env.clone())?;
+        let chain_forks = ChainForks::new(env.clone())?;
 
         let elements = ElementBuf::vault(env.clone(), true)?;
         let meta = MetadataBuf::vault(env.clone())?;
@@ -530,6 +578,7 @@ impl IntegrateDhtOpsWorkspace {
             element_rejected,
             meta_rejected,
             validation_limbo,
+            chain_forks,
             to_disintegrate_pending: Vec::new(),
         })
     }
@@ -19,6 +19,7 @@ use holochain_lmdb::env::EnvironmentWrite;
 use holochain_lmdb::env::ReadManager;
 use holochain_lmdb::env::WriteManager;
 use holochain_lmdb::error::DatabaseError;
+use holochain_lmdb::fresh_reader_test;
 use holochain_lmdb::test_utils::test_cell_env;
 use holochain_state::metadata::ChainItemKey;
 use holochain_state::metadata::LinkMetaKey;
@@ -534,7 +535,8 @@ async fn call_workflow<'env>(env: EnvironmentWrite) {
     let workspace = IntegrateDhtOpsWorkspace::new(env.clone().into()).unwrap();
     let (mut qt, _rx) = TriggerSender::new();
     let (mut qt2, _rx) = TriggerSender::new();
-    integrate_dht_ops_workflow(workspace, env.clone().into(), &mut qt, &mut qt2)
+    let (mut qt3, _rx) = TriggerSender::new();
+    integrate_dht_ops_workflow(workspace, env.clone().into(), &mut qt, &mut qt2, &mut qt3)
         .await
         .unwrap();
 }
@@ -1247,6 +1249,52 @@ async fn test_wasm_api_without_integration_delete() {
     );
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integrate_forked_agent_activity() {
+    observability::test_run().ok();
+    let test_env = test_cell_env();
+    let env = test_env.env();
+
+    // Two chains by the same author, each starting with its own Dna header
+    let first = fixt!(Dna);
+    let mut second = fixt!(Dna);
+    second.author = first.author.clone();
+    let headers: Vec<Header> = vec![first.clone().into(), second.into()];
+    let pre_state = headers
+        .iter()
+        .flat_map(|h| {
+            let op = DhtOp::RegisterAgentActivity(fixt!(Signature), h.clone());
+            add_op_to_judged(vec![Db::IntQueue(op.clone())], &op)
+        })
+        .collect();
+    Db::set(pre_state, env.clone()).await;
+
+    let workspace = IntegrateDhtOpsWorkspace::new(env.clone().into()).unwrap();
+    let (mut qt, _rx) = TriggerSender::new();
+    let (mut qt2, _rx) = TriggerSender::new();
+    let (mut qt3, mut rx3) = TriggerSender::new();
+    integrate_dht_ops_workflow(workspace, env.clone().into(), &mut qt, &mut qt2, &mut qt3)
+        .await
+        .unwrap();
+
+    // The fork is recorded, and the fork check is triggered to signal it
+    let chain_forks = ChainForks::new(env.clone().into()).unwrap();
+    let detected = fresh_reader_test!(env, |r| chain_forks.get(&r, &first.author))
+        .unwrap()
+        .unwrap();
+    assert_eq!(detected.fork.fork_seq, 0);
+    let mut forked_headers = vec![detected.fork.first_header, detected.fork.second_header];
+    forked_headers.sort();
+    let mut expected: Vec<_> = headers.iter().map(HeaderHash::with_data_sync).collect();
+    expected.sort();
+    assert_eq!(forked_headers, expected);
+    assert!(!detected.signalled);
+    tokio::time::timeout(std::time::Duration::from_secs(1), rx3.listen())
+        .await
+        .expect("The chain fork check was not triggered")
+        .unwrap();
+}
+
 #[tokio::test(flavor = "multi_thread")]
 #[ignore = "write this test"]
 async fn test_integrate_single_register_replaced_by_for_header() {
@@ -1325,6 +1373,9 @@ mod slow_tests {
                 uid: "ba1d046d-ce29-4778-914b-47e6010d2faf".to_string(),
                 properties: SerializedBytes::try_from(()).unwrap(),
                 zomes: vec![TestWasm::Create.into()].into(),
+                redundancy_target: None,
+                zome_call_limits: None,
+                integrity: None,
             },
             vec![TestWasm::Create.into()],
         )
@@ -31,11 +31,11 @@ use std::collections::HashMap;
 use std::time;
 use tracing::*;
 
-/// Default redundancy factor for validation receipts
+/// Default redundancy factor for validation receipts, used when the DNA does
+/// not specify a redundancy target
 // TODO: Pull this from the wasm entry def and only use this if it's missing
-// TODO: Put a default in the DnaBundle
 // TODO: build zome_types/entry_def map to get the (AppEntryType map to entry def)
-pub const DEFAULT_RECEIPT_BUNDLE_SIZE: u32 = 5;
+pub const DEFAULT_RECEIPT_BUNDLE_SIZE: u32 = DEFAULT_REDUNDANCY_TARGET;
 
 /// Don't publish a DhtOp more than once during this interval.
 /// This allows us to trigger the publish workflow as often as we like, without
@@ -48,6 +48,8 @@ pub struct PublishDhtOpsWorkspace {
     authored_dht_ops: AuthoredDhtOpsStore,
     /// Element store for looking up data to construct ops
     elements: ElementBuf<AuthoredPrefix>,
+    /// Ops with fewer validation receipts than this are (re)published
+    redundancy_target: u32,
 }
 
 #[instrument(skip(workspace, writer, network))]
@@ -86,12 +88,13 @@ pub async fn publish_dht_ops_workflow_inner(
 
     // one of many ways to access the env
     let env = workspace.elements.headers().env().clone();
+    let redundancy_target = workspace.redundancy_target;
 
     let values = fresh_reader!(env, |r| workspace
         .authored()
         .iter(&r)?
         .filter_map(|(k, mut r)| {
-            Ok(if r.receipt_count < DEFAULT_RECEIPT_BUNDLE_SIZE {
+            Ok(if r.receipt_count < redundancy_target {
                 let needs_publish = r
                     .last_publish_time
                     .and_then(|last| now.checked_difference_signed(&last))
@@ -149,9 +152,17 @@ impl PublishDhtOpsWorkspace {
         Ok(Self {
             authored_dht_ops,
             elements,
+            redundancy_target: DEFAULT_RECEIPT_BUNDLE_SIZE,
         })
     }
 
+    /// Publish ops until they have this many validation receipts, rather than
+    /// the default
+    pub fn with_redundancy_target(mut self, redundancy_target: u32) -> Self {
+        self.redundancy_target = redundancy_target;
+        self
+    }
+
     fn authored(&mut self) -> &mut AuthoredDhtOpsStore {
         &mut self.authored_dht_ops
     }
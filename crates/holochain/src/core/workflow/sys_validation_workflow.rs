@@ -3,6 +3,7 @@
 
 use super::*;
 use crate::conductor::api::CellConductorApiT;
+use crate::conductor::dpki::DpkiImpl;
 use crate::core::queue_consumer::OneshotWriter;
 use crate::core::queue_consumer::TriggerSender;
 use crate::core::queue_consumer::WorkComplete;
@@ -133,8 +134,12 @@ async fn sys_validation_workflow_inner(
         // Create an incoming ops sender for any dependencies we find
         // that we are meant to be holding but aren't.
         // If we are not holding them they will be added to our incoming ops.
-        let incoming_dht_ops_sender =
-            IncomingDhtOpSender::new(workspace.env.clone().into(), sys_validation_trigger.clone());
+        let incoming_dht_ops_sender = IncomingDhtOpSender::new(
+            workspace.env.clone().into(),
+            sys_validation_trigger.clone(),
+            conductor_api.cell_id().dna_hash().clone(),
+            conductor_api.dpki(),
+        );
 
         let outcome = validate_op(
             &op,
@@ -235,6 +240,7 @@ fn handle_failed(error: ValidationOutcome) -> Outcome {
         ValidationOutcome::Counterfeit(_, _) => {
             unreachable!("Counterfeit ops are dropped before sys validation")
         }
+        ValidationOutcome::CounterSignedEntry(_) => Rejected,
         ValidationOutcome::DepMissingFromDht(_) => MissingDhtDep,
         ValidationOutcome::EntryDefId(_) => Rejected,
         ValidationOutcome::EntryHash => Rejected,
@@ -250,6 +256,8 @@ fn handle_failed(error: ValidationOutcome) -> Outcome {
         }
         ValidationOutcome::PrevHeaderError(_) => Rejected,
         ValidationOutcome::PrivateEntry => Rejected,
+        ValidationOutcome::PublishRateExceeded(_, _, _, _, _) => Rejected,
+        ValidationOutcome::TooManyLinks(_, _, _) => Rejected,
         ValidationOutcome::UpdateTypeMismatch(_, _) => Rejected,
         ValidationOutcome::VerifySignature(_, _) => Rejected,
         ValidationOutcome::ZomeId(_) => Rejected,
@@ -295,8 +303,14 @@ async fn validate_op_inner(
             Ok(())
         }
         DhtOp::RegisterAgentActivity(_, header) => {
-            register_agent_activity(header, workspace, network.clone(), incoming_dht_ops_sender)
-                .await?;
+            register_agent_activity(
+                header,
+                workspace,
+                network.clone(),
+                conductor_api,
+                incoming_dht_ops_sender,
+            )
+            .await?;
             store_element(header, workspace, network).await?;
             Ok(())
         }
@@ -342,7 +356,23 @@ async fn validate_op_inner(
             Ok(())
         }
         DhtOp::RegisterAddLink(_, header) => {
-            register_add_link(header, workspace, network, incoming_dht_ops_sender).await?;
+            register_add_link(
+                header,
+                workspace,
+                network,
+                conductor_api,
+                incoming_dht_ops_sender,
+            )
+            .await?;
+            // As the base's authority check against the links we hold on it
+            let max_links_per_base = conductor_api
+                .get_this_dna()
+                .await
+                .map_err(Box::new)?
+                .dna_def()
+                .integrity_limits()
+                .max_links_per_base;
+            check_held_links_per_base(header, max_links_per_base, &workspace.meta_vault)?;
             Ok(())
         }
         DhtOp::RegisterRemoveLink(_, header) => {
@@ -398,7 +428,8 @@ async fn sys_validate_element_inner(
     let header = element.header();
     let entry = element.entry().as_option();
     let incoming_dht_ops_sender = None;
-    if !counterfeit_check(signature, header).await? {
+    let dna_hash = conductor_api.cell_id().dna_hash();
+    if !counterfeit_check(signature, header, dna_hash, conductor_api.dpki().as_ref()).await? {
         return Err(ValidationOutcome::Counterfeit(signature.clone(), header.clone()).into());
     }
     store_element(header, workspace, network.clone()).await?;
@@ -425,7 +456,24 @@ async fn sys_validate_element_inner(
                 .await?;
         }
         Header::CreateLink(header) => {
-            register_add_link(header, workspace, network, incoming_dht_ops_sender).await?;
+            register_add_link(
+                header,
+                workspace,
+                network.clone(),
+                conductor_api,
+                incoming_dht_ops_sender,
+            )
+            .await?;
+            // The author checks against every link on the base it can find
+            let max_links_per_base = conductor_api
+                .get_this_dna()
+                .await
+                .map_err(Box::new)?
+                .dna_def()
+                .integrity_limits()
+                .max_links_per_base;
+            let mut cascade = workspace.full_cascade(network);
+            check_links_per_base(header, max_links_per_base, &mut cascade).await?;
         }
         Header::DeleteLink(header) => {
             register_delete_link(header, workspace, network, incoming_dht_ops_sender).await?;
@@ -440,19 +488,28 @@ async fn sys_validate_element_inner(
 pub async fn counterfeit_check(
     signature: &Signature,
     header: &Header,
+    dna_hash: &DnaHash,
+    dpki: Option<&DpkiImpl>,
 ) -> SysValidationResult<bool> {
     Ok(verify_header_signature(&signature, &header).await?
-        && author_key_is_valid(header.author()).await?)
+        && header_author_key_is_valid(dna_hash, header, dpki).await?)
 }
 
 async fn register_agent_activity(
     header: &Header,
     workspace: &mut SysValidationWorkspace,
     network: HolochainP2pCell,
+    conductor_api: &impl CellConductorApiT,
     incoming_dht_ops_sender: Option<IncomingDhtOpSender>,
 ) -> SysValidationResult<()> {
     // Get data ready to validate
     let prev_header_hash = header.prev_header();
+    let limits = conductor_api
+        .get_this_dna()
+        .await
+        .map_err(Box::new)?
+        .dna_def()
+        .integrity_limits();
 
     // Checks
     check_prev_header(&header)?;
@@ -461,13 +518,15 @@ async fn register_agent_activity(
         check_and_hold_register_agent_activity(
             prev_header_hash,
             workspace,
-            network,
+            network.clone(),
             incoming_dht_ops_sender,
             |_| Ok(()),
         )
         .await?;
     }
     check_chain_rollback(&header, &workspace).await?;
+    let mut cascade = workspace.full_cascade(network);
+    check_spam(&header, limits.publish_rate_limit.as_ref(), &mut cascade).await?;
     Ok(())
 }
 
@@ -503,6 +562,12 @@ async fn store_entry(
     // Get data ready to validate
     let entry_type = header.entry_type();
     let entry_hash = header.entry_hash();
+    let limits = conductor_api
+        .get_this_dna()
+        .await
+        .map_err(Box::new)?
+        .dna_def()
+        .integrity_limits();
 
     // Checks
     check_entry_type(entry_type, entry)?;
@@ -511,7 +576,14 @@ async fn store_entry(
         check_not_private(&entry_def)?;
     }
     check_entry_hash(entry_hash, entry).await?;
-    check_entry_size(entry)?;
+    check_entry_size(entry, limits.max_entry_size())?;
+    if let Entry::CounterSign(session_data, app_entry) = entry {
+        let header = match header {
+            NewEntryHeaderRef::Create(create) => Header::Create(create.clone()),
+            NewEntryHeaderRef::Update(update) => Header::Update(update.clone()),
+        };
+        check_countersigning_session_data(&header, session_data, app_entry).await?;
+    }
 
     // Additional checks if this is an Update
     if let NewEntryHeaderRef::Update(entry_update) = header {
@@ -626,11 +698,18 @@ async fn register_add_link(
     link_add: &CreateLink,
     workspace: &mut SysValidationWorkspace,
     network: HolochainP2pCell,
+    conductor_api: &impl CellConductorApiT,
     incoming_dht_ops_sender: Option<IncomingDhtOpSender>,
 ) -> SysValidationResult<()> {
     // Get data ready to validate
     let base_entry_address = &link_add.base_address;
     let target_entry_address = &link_add.target_address;
+    let limits = conductor_api
+        .get_this_dna()
+        .await
+        .map_err(Box::new)?
+        .dna_def()
+        .integrity_limits();
 
     // Checks
     check_and_hold_any_store_entry(
@@ -648,7 +727,7 @@ async fn register_add_link(
         .await?
         .ok_or_else(|| ValidationOutcome::DepMissingFromDht(target_entry_address.clone().into()))?;
 
-    check_tag_size(&link_add.tag)?;
+    check_tag_size(&link_add.tag, limits.max_tag_size())?;
     Ok(())
 }
 
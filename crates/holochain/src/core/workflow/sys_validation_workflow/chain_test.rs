@@ -3,6 +3,7 @@ use crate::conductor::ConductorHandle;
 use crate::test_utils::setup_app;
 use crate::test_utils::wait_for_integration;
 use ::fixt::prelude::*;
+use holo_hash::fixt::DnaHashFixturator;
 use holochain_keystore::AgentPubKeyExt;
 use holochain_serialized_bytes::SerializedBytes;
 use holochain_state::source_chain::SourceChain;
@@ -27,6 +28,9 @@ async fn sys_validation_agent_activity_test() {
             uid: "ba1d046d-ce29-4778-914b-47e6010d2faf".to_string(),
             properties: SerializedBytes::try_from(()).unwrap(),
             zomes: vec![TestWasm::Create.into()].into(),
+            redundancy_target: None,
+            zome_call_limits: None,
+            integrity: None,
         },
         vec![TestWasm::Create.into()],
     )
@@ -121,6 +125,8 @@ async fn run_test(alice_cell_id: CellId, handle: ConductorHandle) {
         ops,
         None,
         false,
+        &fixt!(DnaHash),
+        None,
     )
     .await
     .unwrap();
@@ -178,6 +184,8 @@ async fn run_test(alice_cell_id: CellId, handle: ConductorHandle) {
         ops,
         None,
         false,
+        &fixt!(DnaHash),
+        None,
     )
     .await
     .unwrap();
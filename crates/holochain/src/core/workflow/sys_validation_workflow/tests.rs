@@ -36,6 +36,9 @@ async fn sys_validation_workflow_test() {
             uid: "ba1d046d-ce29-4778-914b-47e6010d2faf".to_string(),
             properties: SerializedBytes::try_from(()).unwrap(),
             zomes: vec![TestWasm::Create.into()].into(),
+            redundancy_target: None,
+            zome_call_limits: None,
+            integrity: None,
         },
         vec![TestWasm::Create.into()],
     )
@@ -423,6 +426,107 @@ async fn dodgy_bob(bob_cell_id: &CellId, handle: &ConductorHandle, dna_file: &Dn
     triggers.produce_dht_ops.trigger();
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn authorities_reject_links_over_the_limit() {
+    observability::test_run().ok();
+
+    let dna_file = DnaFile::new(
+        DnaDef {
+            name: "authorities_reject_links_over_the_limit".to_string(),
+            uid: "ba1d046d-ce29-4778-914b-47e6010d2faf".to_string(),
+            properties: SerializedBytes::try_from(()).unwrap(),
+            zomes: vec![TestWasm::Create.into()].into(),
+            redundancy_target: None,
+            zome_call_limits: None,
+            integrity: Some(IntegrityLimits {
+                max_links_per_base: Some(1),
+                ..Default::default()
+            }),
+        },
+        vec![TestWasm::Create.into()],
+    )
+    .await
+    .unwrap();
+
+    let alice_agent_id = fake_agent_pubkey_1();
+    let alice_cell_id = CellId::new(dna_file.dna_hash().to_owned(), alice_agent_id.clone());
+    let alice_installed_cell = InstalledCell::new(alice_cell_id.clone(), "alice_handle".into());
+
+    let bob_agent_id = fake_agent_pubkey_2();
+    let bob_cell_id = CellId::new(dna_file.dna_hash().to_owned(), bob_agent_id.clone());
+    let bob_installed_cell = InstalledCell::new(bob_cell_id.clone(), "bob_handle".into());
+
+    let (_tmpdir, _app_api, handle) = setup_app(
+        vec![(
+            "test_app",
+            vec![(alice_installed_cell, None), (bob_installed_cell, None)],
+        )],
+        vec![dna_file.clone()],
+    )
+    .await;
+
+    let alice_env = handle.get_cell_env(&alice_cell_id).await.unwrap();
+    let base = Post("One link only".into());
+    let target = Post("The one link".into());
+    let base_entry_hash = EntryHash::with_data_sync(&Entry::try_from(base.clone()).unwrap());
+    let target_entry_hash = EntryHash::with_data_sync(&Entry::try_from(target.clone()).unwrap());
+
+    // Bob commits directly so the author's checks are skipped
+    let call_data = HostFnCaller::create(&bob_cell_id, &handle, &dna_file).await;
+    call_data
+        .commit_entry(base.try_into().unwrap(), POST_ID)
+        .await;
+    call_data
+        .commit_entry(target.try_into().unwrap(), POST_ID)
+        .await;
+    call_data
+        .create_link(
+            base_entry_hash.clone(),
+            target_entry_hash.clone(),
+            fixt!(LinkTag),
+        )
+        .await;
+    let mut triggers = handle.get_cell_triggers(&bob_cell_id).await.unwrap();
+    triggers.produce_dht_ops.trigger();
+
+    // Genesis plus the two entries and the first link
+    let expected_count = 14 + 9;
+    wait_for_integration(&alice_env, expected_count, 100, Duration::from_millis(100)).await;
+
+    // The second link takes the base over the limit
+    let over_the_limit = call_data
+        .create_link(base_entry_hash, target_entry_hash, fixt!(LinkTag))
+        .await;
+    triggers.produce_dht_ops.trigger();
+
+    let expected_count = expected_count + 3;
+    wait_for_integration(&alice_env, expected_count, 100, Duration::from_millis(100)).await;
+
+    let workspace = IncomingDhtOpsWorkspace::new(alice_env.clone().into()).unwrap();
+    assert_eq!(
+        fresh_reader_test!(alice_env, |r| workspace
+            .integrated_dht_ops
+            .iter(&r)
+            .unwrap()
+            .inspect(|(_, i)| {
+                match &i.op {
+                    DhtOpLight::RegisterAddLink(hh, _) if hh == &over_the_limit => {
+                        assert_eq!(i.validation_status, ValidationStatus::Rejected)
+                    }
+                    _ => assert_eq!(i.validation_status, ValidationStatus::Valid),
+                }
+                Ok(())
+            })
+            .count()
+            .unwrap()),
+        expected_count
+    );
+
+    let shutdown = handle.take_shutdown_handle().await.unwrap();
+    handle.shutdown().await;
+    shutdown.await.unwrap().unwrap();
+}
+
 //////////////////////
 //// Test Ideas
 //////////////////////
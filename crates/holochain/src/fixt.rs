@@ -150,10 +150,7 @@ fixturator!(
         for (hash, _) in wasms {
             zomes.push((
                 zome_name_fixturator.next().unwrap(),
-                WasmZome {
-                    wasm_hash: hash.to_owned(),
-                }
-                .into(),
+                WasmZome::new(hash.to_owned()).into(),
             ));
         }
         let mut dna_def = DnaDefFixturator::new(Unpredictable).next().unwrap();
@@ -173,10 +170,7 @@ fixturator!(
         for (hash, _) in wasms {
             zomes.push((
                 zome_name_fixturator.next().unwrap(),
-                WasmZome {
-                    wasm_hash: hash.to_owned(),
-                }
-                .into(),
+                WasmZome::new(hash.to_owned()).into(),
             ));
         }
         let mut dna_def = DnaDefFixturator::new_indexed(Predictable, get_fixt_index!())
@@ -340,7 +334,7 @@ fixturator!(
 
 fixturator!(
     PostCommitHostAccess;
-    constructor fn new(CallZomeWorkspaceLock, KeystoreSender, HolochainP2pCell);
+    constructor fn new(CallZomeWorkspaceLock, KeystoreSender, HolochainP2pCell, SignalBroadcaster, CellId);
 );
 
 fixturator!(
@@ -515,6 +515,9 @@ async fn setup(
             uid,
             properties: SerializedBytes::try_from(()).unwrap(),
             zomes: zomes.clone().into_iter().map(Into::into).collect(),
+            redundancy_target: None,
+            zome_call_limits: None,
+            integrity: None,
         },
         zomes.into_iter().map(Into::into),
     )
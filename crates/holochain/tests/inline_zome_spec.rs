@@ -14,7 +14,10 @@ use holochain::{
     core::ribosome::guest_callback::validate::ValidateResult, test_utils::wait_for_integration_1m,
 };
 use holochain::{core::SourceChainError, test_utils::display_agent_infos};
-use holochain_types::{dna::zome::inline_zome::InlineZome, signal::Signal};
+use holochain_types::{
+    dna::zome::inline_zome::InlineZome,
+    signal::{Signal, SystemSignal},
+};
 use holochain_zome_types::element::ElementEntry;
 use tokio_stream::StreamExt;
 
@@ -309,6 +312,41 @@ async fn signal_subscription() {
     assert_eq!(signals.len(), N);
 }
 
+/// The post_commit callback runs after the zome call has committed,
+/// and a failure is reported as a system signal
+#[tokio::test(flavor = "multi_thread")]
+#[cfg(feature = "test_utils")]
+async fn post_commit_failure_is_signalled() {
+    observability::test_run().ok();
+
+    let zome = simple_crud_zome().callback("post_commit", |_api, headers: HeaderHashes| {
+        Ok(PostCommitCallbackResult::Fail(headers, "no thanks".into()))
+    });
+    let (dna_file, _) = SweetDnaFile::unique_from_inline_zome("zome1", zome)
+        .await
+        .unwrap();
+    let mut conductor = SweetConductor::from_config(Default::default()).await;
+    let app = conductor.setup_app("app", &[dna_file]).await.unwrap();
+    let cell = &app.cells()[0];
+    let mut signals = conductor.signals();
+
+    let hash: HeaderHash = conductor.call(&cell.zome("zome1"), "create_unit", ()).await;
+
+    let signal = tokio::time::timeout(std::time::Duration::from_secs(10), signals.next())
+        .await
+        .expect("post_commit failure was not signalled")
+        .unwrap();
+    assert_eq!(
+        signal,
+        Signal::System(SystemSignal::PostCommitFailed {
+            cell_id: cell.cell_id().clone(),
+            zome_name: "zome1".into(),
+            headers: vec![hash],
+            error: "no thanks".into(),
+        })
+    );
+}
+
 /// Simple zome which contains a validation rule which can fail
 fn simple_validation_zome() -> InlineZome {
     let entry_def = EntryDef::default_with_id("string");
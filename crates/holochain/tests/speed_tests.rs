@@ -135,6 +135,9 @@ async fn speed_test(n: Option<usize>) -> TestEnvironments {
             uid: "ba1d046d-ce29-4778-914b-47e6010d2faf".to_string(),
             properties: SerializedBytes::try_from(()).unwrap(),
             zomes: vec![TestWasm::Anchor.into()].into(),
+            redundancy_target: None,
+            zome_call_limits: None,
+            integrity: None,
         },
         vec![TestWasm::Anchor.into()],
     )
@@ -194,14 +197,32 @@ async fn speed_test(n: Option<usize>) -> TestEnvironments {
 
     // Setup websocket handle and app interface
     let (mut client, _) = websocket_client(&handle).await.unwrap();
-    let request = AdminRequest::AttachAppInterface { port: None };
+    let request = AdminRequest::AttachAppInterface {
+        port: None,
+        driver: None,
+    };
     let response = client.request(request);
     let response = response.await.unwrap();
     let app_port = match response {
-        AdminResponse::AppInterfaceAttached { port } => port,
+        AdminResponse::AppInterfaceAttached {
+            port: Some(port), ..
+        } => port,
         _ => panic!("Attach app interface failed: {:?}", response),
     };
     let (mut app_interface, _) = websocket_client_by_port(app_port).await.unwrap();
+    let request = AdminRequest::IssueAppAuthenticationToken {
+        installed_app_id: "test app".to_string(),
+        expiry_seconds: None,
+    };
+    let token = match client.request(request).await.unwrap() {
+        AdminResponse::AppAuthenticationTokenIssued { token, .. } => token,
+        response => panic!("Issuing app token failed: {:?}", response),
+    };
+    let response: AppResponse = app_interface
+        .request(AppRequest::Authenticate { token })
+        .await
+        .unwrap();
+    assert_matches!(response, AppResponse::Authenticated { .. });
 
     // /////////////
     // END CONDUCTOR
@@ -317,7 +338,7 @@ pub async fn setup_app(
     let conductor_handle = ConductorBuilder::with_mock_dna_store(dna_store)
         .config(ConductorConfig {
             admin_interfaces: Some(vec![AdminInterfaceConfig {
-                driver: InterfaceDriver::Websocket { port: 0 },
+                driver: InterfaceDriver::websocket(0),
             }]),
             ..Default::default()
         })
@@ -73,7 +73,7 @@ pub async fn check_started(holochain: &mut Child) {
 fn create_config(port: u16, environment_path: PathBuf) -> ConductorConfig {
     ConductorConfig {
         admin_interfaces: Some(vec![AdminInterfaceConfig {
-            driver: InterfaceDriver::Websocket { port },
+            driver: InterfaceDriver::websocket(port),
         }]),
         environment_path: environment_path.into(),
         network: None,
@@ -86,6 +86,7 @@ fn create_config(port: u16, environment_path: PathBuf) -> ConductorConfig {
         }),
         keystore_path: None,
         use_dangerous_test_keystore: true,
+        wasm: None,
     }
 }
 
@@ -212,9 +213,15 @@ pub async fn start_holochain(config_path: PathBuf) -> Child {
     holochain
 }
 
-pub async fn call_foo_fn(app_port: u16, original_dna_hash: DnaHash, holochain: &mut Child) {
+pub async fn call_foo_fn(
+    app_port: u16,
+    original_dna_hash: DnaHash,
+    holochain: &mut Child,
+    admin_tx: &mut WebsocketSender,
+) {
     // Connect to App Interface
     let (mut app_tx, _) = websocket_client_by_port(app_port).await.unwrap();
+    authenticate_app_connection(holochain, admin_tx, &mut app_tx, "test").await;
     let cell_id = CellId::from((original_dna_hash, fake_agent_pubkey_1()));
     call_zome_fn(
         holochain,
@@ -253,16 +260,38 @@ pub async fn call_zome_fn<S>(
     assert_matches!(call_response, AppResponse::ZomeCallInvocation(_));
 }
 
+pub async fn authenticate_app_connection(
+    holochain: &mut Child,
+    admin_tx: &mut WebsocketSender,
+    app_tx: &mut WebsocketSender,
+    installed_app_id: &str,
+) {
+    let request = AdminRequest::IssueAppAuthenticationToken {
+        installed_app_id: installed_app_id.to_string(),
+        expiry_seconds: None,
+    };
+    let response = admin_tx.request(request);
+    let token = match check_timeout(holochain, response, 1000).await {
+        AdminResponse::AppAuthenticationTokenIssued { token, .. } => token,
+        response => panic!("Issuing app token failed: {:?}", response),
+    };
+    let response = app_tx.request(AppRequest::Authenticate { token });
+    let response = check_timeout(holochain, response, 1000).await;
+    assert_matches!(response, AppResponse::Authenticated { .. });
+}
+
 pub async fn attach_app_interface(
     client: &mut WebsocketSender,
     holochain: &mut Child,
     port: Option<u16>,
 ) -> u16 {
-    let request = AdminRequest::AttachAppInterface { port };
+    let request = AdminRequest::AttachAppInterface { port, driver: None };
     let response = client.request(request);
     let response = check_timeout(holochain, response, 1000).await;
     match response {
-        AdminResponse::AppInterfaceAttached { port } => port,
+        AdminResponse::AppInterfaceAttached {
+            port: Some(port), ..
+        } => port,
         _ => panic!("Attach app interface failed: {:?}", response),
     }
 }
@@ -393,7 +422,13 @@ async fn call_zome() {
     assert_eq!(app_port, app_port_rcvd);
 
     // Call Zome
-    call_foo_fn(app_port, original_dna_hash.clone(), &mut holochain).await;
+    call_foo_fn(
+        app_port,
+        original_dna_hash.clone(),
+        &mut holochain,
+        &mut client,
+    )
+    .await;
 
     // Ensure that the other client does not receive any messages, i.e. that
     // responses are not broadcast to all connected clients, only the one
@@ -414,8 +449,10 @@ async fn call_zome() {
 
     tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
 
-    // Call Zome again on the existing app interface port
-    call_foo_fn(app_port, original_dna_hash, &mut holochain).await;
+    // Call Zome again on the existing app interface port.
+    // Tokens don't survive a restart, so a new one is needed.
+    let (mut client, _) = websocket_client_by_port(admin_port).await.unwrap();
+    call_foo_fn(app_port, original_dna_hash, &mut holochain, &mut client).await;
 
     // Shutdown holochain
     holochain.kill().await.expect("Failed to kill holochain");
@@ -534,7 +571,9 @@ async fn emit_signals() {
     // Emit signals (the real test!)
 
     let (mut app_tx_1, app_rx_1) = websocket_client_by_port(app_port).await.unwrap();
-    let (_, app_rx_2) = websocket_client_by_port(app_port).await.unwrap();
+    let (mut app_tx_2, app_rx_2) = websocket_client_by_port(app_port).await.unwrap();
+    authenticate_app_connection(&mut holochain, &mut admin_tx, &mut app_tx_1, "test").await;
+    authenticate_app_connection(&mut holochain, &mut admin_tx, &mut app_tx_2, "test").await;
 
     call_zome_fn(
         &mut holochain,
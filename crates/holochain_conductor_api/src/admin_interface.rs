@@ -1,8 +1,11 @@
 use holo_hash::*;
+use holochain_keystore::backup::KeystoreBackup;
+use holochain_state::source_chain::SignedSourceChainArchive;
 use holochain_types::prelude::*;
 use holochain_zome_types::cell::CellId;
 use kitsune_p2p::agent_store::AgentInfoSigned;
 
+use crate::AppAuthenticationToken;
 use crate::InstalledAppInfo;
 
 /// Represents the available conductor functions to call over an Admin interface
@@ -102,6 +105,19 @@ pub enum AdminRequest {
     /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
     ListDnas,
 
+    /// List the zome functions and callbacks exported by each zome of an
+    /// installed `Dna`, so that clients can discover what can be called.
+    ///
+    /// Will be responded to with an [`AdminResponse::ZomeFunctionsListed`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::ZomeFunctionsListed`]: enum.AdminResponse.html#variant.ZomeFunctionsListed
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    ListZomeFunctions {
+        /// The hash of the `Dna` to inspect
+        dna_hash: DnaHash,
+    },
+
     /// Generate a new AgentPubKey.
     /// Takes no arguments.
     ///
@@ -174,9 +190,39 @@ pub enum AdminRequest {
         /// Optional port, use None to let the
         /// OS choose a free port
         port: Option<u16>,
+        /// Optional driver config, to set the bind address, tls,
+        /// allowed origins or limits of the interface, or to serve it
+        /// over a unix socket or HTTP instead.
+        /// If set, the port of the driver is used instead of `port`.
+        #[serde(default)]
+        driver: Option<crate::config::InterfaceDriver>,
     },
     /// List all the app interfaces currently attached with [`AttachAppInterface`].
     ListAppInterfaces,
+    /// Issue a token which authenticates connections to app interfaces
+    /// as having access to the App specified by argument `installed_app_id`.
+    ///
+    /// Every connection to an app interface must send an
+    /// [`AppRequest::Authenticate`] with such a token before it can make
+    /// any other request, and may then only call zome functions in the cells
+    /// of that App, get info about that App, and receive its signals.
+    /// The token can be used for any number of connections until it expires.
+    ///
+    /// Will be responded to with an [`AdminResponse::AppAuthenticationTokenIssued`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AppRequest::Authenticate`]: enum.AppRequest.html#variant.Authenticate
+    /// [`AdminResponse::AppAuthenticationTokenIssued`]: enum.AdminResponse.html#variant.AppAuthenticationTokenIssued
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    /// [`DEFAULT_APP_AUTHENTICATION_TOKEN_EXPIRY_SECONDS`]: constant.DEFAULT_APP_AUTHENTICATION_TOKEN_EXPIRY_SECONDS.html
+    IssueAppAuthenticationToken {
+        /// The App which the token gives access to
+        installed_app_id: InstalledAppId,
+        /// How many seconds until the token expires.
+        /// Defaults to [`DEFAULT_APP_AUTHENTICATION_TOKEN_EXPIRY_SECONDS`].
+        #[serde(default)]
+        expiry_seconds: Option<u64>,
+    },
     /// Dump the full state of the `Cell` specified by argument `cell_id`,
     /// including its chain, as a string containing JSON.
     ///
@@ -189,6 +235,22 @@ pub enum AdminRequest {
         /// The `CellId` for which to dump state
         cell_id: Box<CellId>,
     },
+    /// Check how many authorities are holding each op authored by the `Cell`
+    /// specified by argument `cell_id`, judging by the validation receipts it
+    /// has received, and compare that with the DNA's redundancy target.
+    ///
+    /// The cell runs this same check periodically and republishes any ops
+    /// below the target. This request only reports on the current health.
+    ///
+    /// Will be responded to with an [`AdminResponse::CellHealthChecked`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    /// [`AdminResponse::CellHealthChecked`]: enum.AdminResponse.html#variant.CellHealthChecked
+    CheckCellHealth {
+        /// The `CellId` to check
+        cell_id: Box<CellId>,
+    },
     /// Add a list [AgentInfoSigned] to this conductor's peer store.
     /// This is another way of finding peers on a dht.
     ///
@@ -214,6 +276,104 @@ pub enum AdminRequest {
         /// Optionally choose a specific agent info
         cell_id: Option<CellId>,
     },
+    /// List the agent keys in this conductor's keystore, with the `Cell`s
+    /// each is the agent of and the keys each has replaced or been replaced by.
+    ///
+    /// Will be responded to with an [`AdminResponse::AgentKeysListed`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    /// [`AdminResponse::AgentKeysListed`]: enum.AdminResponse.html#variant.AgentKeysListed
+    ListAgentKeys,
+    /// Replace the agent key specified by argument `agent_key` with a newly
+    /// generated one, marking the old key as superseded by the new one.
+    ///
+    /// The rotation is reported to the conductor's DPKI service, if it has
+    /// one. Apps with `Cell`s running as the old key are deactivated, and
+    /// can't be activated again, so they should be reinstalled with the new key.
+    ///
+    /// Will be responded to with an [`AdminResponse::AgentKeyRotated`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    /// [`AdminResponse::AgentKeyRotated`]: enum.AdminResponse.html#variant.AgentKeyRotated
+    RotateAgentKey {
+        /// The key to rotate, which must be in this conductor's keystore
+        agent_key: AgentPubKey,
+    },
+    /// Export the whole source chain of the `Cell` specified by argument
+    /// `cell_id`, private entries included, signed by its agent.
+    ///
+    /// The archive contains private entries in the clear, so it should be
+    /// stored as carefully as a keystore backup.
+    ///
+    /// Will be responded to with an [`AdminResponse::SourceChainExported`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    /// [`AdminResponse::SourceChainExported`]: enum.AdminResponse.html#variant.SourceChainExported
+    ExportSourceChain {
+        /// The `Cell` whose source chain to export
+        cell_id: CellId,
+    },
+    /// Import a source chain exported by [`AdminRequest::ExportSourceChain`]
+    /// into this conductor, e.g. after losing the disk it was on.
+    ///
+    /// The agent key of the archive must be in this conductor's keystore,
+    /// which can be restored from a backup with [`AdminRequest::ImportKeystore`]
+    /// or `holochain --restore-keystore`,
+    /// and the `Cell` must not be part of an installed App yet.
+    /// Once imported, install the App with the same agent key: genesis will
+    /// be skipped, and the `Cell` will produce and publish the ops of the
+    /// imported chain when it starts.
+    ///
+    /// Will be responded to with an [`AdminResponse::SourceChainImported`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminRequest::ExportSourceChain`]: enum.AdminRequest.html#variant.ExportSourceChain
+    /// [`AdminRequest::ImportKeystore`]: enum.AdminRequest.html#variant.ImportKeystore
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    /// [`AdminResponse::SourceChainImported`]: enum.AdminResponse.html#variant.SourceChainImported
+    ImportSourceChain {
+        /// The signed archive to import
+        archive: SignedSourceChainArchive,
+    },
+    /// Back up this conductor's keystore, encrypted with the argument
+    /// `passphrase`, so that its agent keys can be restored onto new hardware
+    /// with [`AdminRequest::ImportKeystore`] or `holochain --restore-keystore`.
+    ///
+    /// The keystore keeps running while it is backed up.
+    ///
+    /// Will be responded to with an [`AdminResponse::KeystoreExported`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminRequest::ImportKeystore`]: enum.AdminRequest.html#variant.ImportKeystore
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    /// [`AdminResponse::KeystoreExported`]: enum.AdminResponse.html#variant.KeystoreExported
+    ExportKeystore {
+        /// The passphrase to encrypt the backup with
+        passphrase: String,
+    },
+    /// Import a keystore backup made by [`AdminRequest::ExportKeystore`],
+    /// decrypting it with the argument `passphrase`.
+    ///
+    /// This conductor's keystore must not hold any agent keys yet, e.g. on a
+    /// newly set up conductor. A running keystore can't be replaced, so the
+    /// backup replaces it the next time the conductor starts, once lair has
+    /// been stopped. The keystore it replaces is kept next to it.
+    ///
+    /// Will be responded to with an [`AdminResponse::KeystoreImported`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminRequest::ExportKeystore`]: enum.AdminRequest.html#variant.ExportKeystore
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    /// [`AdminResponse::KeystoreImported`]: enum.AdminResponse.html#variant.KeystoreImported
+    ImportKeystore {
+        /// The encrypted backup to import
+        archive: KeystoreBackup,
+        /// The passphrase the backup was encrypted with
+        passphrase: String,
+    },
 }
 
 /// Represents the possible responses to an [`AdminRequest`]
@@ -296,6 +456,13 @@ pub enum AdminResponse {
     /// [`AdminRequest::ListDnas`]: enum.AdminRequest.html#variant.ListDnas
     DnasListed(Vec<DnaHash>),
 
+    /// The succesful response to an [`AdminRequest::ListZomeFunctions`].
+    ///
+    /// The exports of each zome of the `Dna`, in zome order.
+    ///
+    /// [`AdminRequest::ListZomeFunctions`]: enum.AdminRequest.html#variant.ListZomeFunctions
+    ZomeFunctionsListed(Vec<(ZomeName, ZomeExports)>),
+
     /// The succesful response to an [`AdminRequest::ListCellIds`].
     ///
     /// Contains a list of all the `Cell` ids in the conductor
@@ -314,16 +481,34 @@ pub enum AdminResponse {
     ///
     /// `AppInterfaceApi` successfully attached.
     /// Contains the port number that was selected (if not specified) by Holochain
-    /// for running this App interface
+    /// for running this App interface, or the path of its unix socket
     ///
     /// [`AdminRequest::AttachAppInterface`]: enum.AdminRequest.html#variant.AttachAppInterface
     AppInterfaceAttached {
-        /// Networking port of the new `AppInterfaceApi`
-        port: u16,
+        /// Networking port of the new `AppInterfaceApi`,
+        /// if it is served over a websocket or HTTP
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        port: Option<u16>,
+        /// Path of the socket file of the new `AppInterfaceApi`,
+        /// if it is served over a unix socket
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<std::path::PathBuf>,
     },
 
-    /// The list of attached app interfaces.
-    AppInterfacesListed(Vec<u16>),
+    /// The drivers of the attached app interfaces, with the ports they were
+    /// bound to, so that websocket, HTTP and unix socket interfaces can be
+    /// told apart.
+    AppInterfacesListed(Vec<crate::config::InterfaceDriver>),
+
+    /// The succesful response to an [`AdminRequest::IssueAppAuthenticationToken`].
+    ///
+    /// [`AdminRequest::IssueAppAuthenticationToken`]: enum.AdminRequest.html#variant.IssueAppAuthenticationToken
+    AppAuthenticationTokenIssued {
+        /// The token to send with an `AppRequest::Authenticate`
+        token: AppAuthenticationToken,
+        /// When the token stops being accepted
+        expires_at: Timestamp,
+    },
 
     /// The succesful response to an [`AdminRequest::ActivateApp`].
     ///
@@ -347,6 +532,14 @@ pub enum AdminResponse {
     /// [`AdminRequest::DumpState`]: enum.AdminRequest.html#variant.DumpState
     StateDumped(String),
 
+    /// The succesful response to an [`AdminRequest::CheckCellHealth`].
+    ///
+    /// Reports which authored ops are held by fewer authorities than the
+    /// DNA's redundancy target.
+    ///
+    /// [`AdminRequest::CheckCellHealth`]: enum.AdminRequest.html#variant.CheckCellHealth
+    CellHealthChecked(CellHealth),
+
     /// The succesful response to an [`AdminRequest::AddAgentInfo`].
     ///
     /// This means the agent info was successfully added to the peer store.
@@ -360,6 +553,60 @@ pub enum AdminResponse {
     ///
     /// [`AdminRequest::RequestAgentInfo`]: enum.AdminRequest.html#variant.RequestAgentInfo
     AgentInfoRequested(Vec<AgentInfoSigned>),
+
+    /// The succesful response to an [`AdminRequest::ListAgentKeys`].
+    ///
+    /// [`AdminRequest::ListAgentKeys`]: enum.AdminRequest.html#variant.ListAgentKeys
+    AgentKeysListed(Vec<AgentKeyInfo>),
+
+    /// The succesful response to an [`AdminRequest::RotateAgentKey`].
+    ///
+    /// Contains the new `AgentPubKey` which replaces the rotated key
+    ///
+    /// [`AdminRequest::RotateAgentKey`]: enum.AdminRequest.html#variant.RotateAgentKey
+    AgentKeyRotated(AgentPubKey),
+
+    /// The succesful response to an [`AdminRequest::ExportSourceChain`].
+    ///
+    /// Contains the signed archive of the source chain
+    ///
+    /// [`AdminRequest::ExportSourceChain`]: enum.AdminRequest.html#variant.ExportSourceChain
+    SourceChainExported(SignedSourceChainArchive),
+
+    /// The succesful response to an [`AdminRequest::ImportSourceChain`].
+    ///
+    /// Contains the `CellId` the source chain was imported for
+    ///
+    /// [`AdminRequest::ImportSourceChain`]: enum.AdminRequest.html#variant.ImportSourceChain
+    SourceChainImported(CellId),
+
+    /// The succesful response to an [`AdminRequest::ExportKeystore`].
+    ///
+    /// Contains the encrypted backup of the keystore
+    ///
+    /// [`AdminRequest::ExportKeystore`]: enum.AdminRequest.html#variant.ExportKeystore
+    KeystoreExported(KeystoreBackup),
+
+    /// The succesful response to an [`AdminRequest::ImportKeystore`].
+    ///
+    /// The backup replaces the keystore the next time the conductor starts
+    ///
+    /// [`AdminRequest::ImportKeystore`]: enum.AdminRequest.html#variant.ImportKeystore
+    KeystoreImported,
+}
+
+/// Info about an agent key in the conductor's keystore,
+/// returned as part of [`AdminResponse::AgentKeysListed`]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, SerializedBytes)]
+pub struct AgentKeyInfo {
+    /// The agent key
+    pub agent_key: AgentPubKey,
+    /// The `Cell`s this key is the agent of
+    pub cells: Vec<CellId>,
+    /// The key this key replaced, if it was created by a rotation
+    pub supersedes: Option<AgentPubKey>,
+    /// The key which replaced this key, if it has been rotated
+    pub superseded_by: Option<AgentPubKey>,
 }
 
 /// Error type that goes over the websocket wire.
@@ -383,6 +630,12 @@ pub enum ExternalApiWireError {
     ActivateApp(String),
     /// The zome call is unauthorized
     ZomeCallUnauthorized(String),
+    /// The zome call was aborted for exceeding one of the DNA's limits
+    /// on the time, fuel or memory a zome call may use
+    ZomeCallLimitExceeded(String),
+    /// The connection to an app interface has not been authenticated,
+    /// or is not authorized to access this App
+    AppAuthenticationFailed(String),
 }
 
 impl ExternalApiWireError {
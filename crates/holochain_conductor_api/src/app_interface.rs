@@ -6,6 +6,20 @@ use holochain_types::prelude::*;
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, SerializedBytes)]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
 pub enum AppRequest {
+    /// Authenticate this connection with a token issued by
+    /// an `AdminRequest::IssueAppAuthenticationToken`.
+    /// This must be the first request made on a connection to an app interface,
+    /// and all other requests are refused until it succeeds.
+    ///
+    /// Will be responded to with an [`AppResponse::Authenticated`]
+    /// or an [`AppResponse::Error`]
+    ///
+    /// [`AppResponse::Authenticated`]: enum.AppResponse.html#variant.Authenticated
+    /// [`AppResponse::Error`]: enum.AppResponse.html#variant.Error
+    Authenticate {
+        /// The token which was issued for the App this connection will access
+        token: AppAuthenticationToken,
+    },
     /// Get info about the App identified by the given `installed_app_id` argument,
     /// including info about each Cell installed by this App.
     /// Requires `installed_app_id` because an App interface can be the interface to multiple
@@ -83,6 +97,42 @@ pub enum AppResponse {
 
     /// DEPRECATED. See `ZomeCall`.
     ZomeCallInvocation(Box<ExternIO>),
+
+    /// The successful response to an [`AppRequest::Authenticate`].
+    ///
+    /// The connection can now access the App the token was issued for.
+    ///
+    /// [`AppRequest::Authenticate`]: enum.AppRequest.html#variant.Authenticate
+    Authenticated {
+        /// The App this connection can access
+        installed_app_id: InstalledAppId,
+    },
+}
+
+/// How long an [`AppAuthenticationToken`] is accepted for, if no expiry is
+/// given when it is issued: one day.
+pub const DEFAULT_APP_AUTHENTICATION_TOKEN_EXPIRY_SECONDS: u64 = 60 * 60 * 24;
+
+/// A secret token which authenticates connections to an app interface
+/// as having access to a single App.
+#[derive(
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    derive_more::From,
+    derive_more::Into,
+)]
+#[serde(transparent)]
+pub struct AppAuthenticationToken(String);
+
+impl std::fmt::Debug for AppAuthenticationToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Don't leak tokens into logs
+        f.write_str("AppAuthenticationToken(..)")
+    }
 }
 
 /// The data provided across an App interface in order to make a zome call
@@ -11,6 +11,7 @@ mod dpki_config;
 mod error;
 mod passphrase_service_config;
 pub mod paths;
+mod wasm_config;
 //mod logger_config;
 //mod signal_config;
 pub use paths::EnvironmentRootPath;
@@ -20,6 +21,7 @@ pub use dpki_config::DpkiConfig;
 //pub use logger_config::LoggerConfig;
 pub use error::*;
 pub use passphrase_service_config::PassphraseServiceConfig;
+pub use wasm_config::*;
 //pub use signal_config::SignalConfig;
 use std::path::Path;
 use std::path::PathBuf;
@@ -56,8 +58,8 @@ pub struct ConductorConfig {
     /// over this websocket.
     pub decryption_service_uri: Option<String>,
 
-    /// Optional DPKI configuration if conductor is using a DPKI app to initalize and manage
-    /// keys for new instances
+    /// Optional DPKI service, which sys validation consults to check that the
+    /// authors of headers held their keys when they signed them
     pub dpki: Option<DpkiConfig>,
 
     /// Optional path for keystore directory.  If not specified will use the default provided
@@ -75,6 +77,9 @@ pub struct ConductorConfig {
 
     /// Config options for the network module. Optional.
     pub network: Option<holochain_p2p::kitsune_p2p::KitsuneP2pConfig>,
+
+    /// Config options for compiling and instantiating wasm. Optional.
+    pub wasm: Option<WasmConfig>,
     //
     //
     // /// Which signals to emit
@@ -150,6 +155,7 @@ pub mod tests {
                 keystore_path: None,
                 admin_interfaces: None,
                 use_dangerous_test_keystore: false,
+                wasm: None,
             }
         );
     }
@@ -169,8 +175,7 @@ pub mod tests {
       type: cmd
 
     dpki:
-      instance_id: some_id
-      init_params: some_params
+      type: local
 
     admin_interfaces:
       - driver:
@@ -199,6 +204,9 @@ pub mod tests {
         proxy_keepalive_ms: 42
         proxy_to_expire_ms: 42
       network_type: quic_bootstrap
+
+    wasm:
+      cache_path: /path/to/wasm_cache
     "#;
         let result: ConductorConfigResult<ConductorConfig> = config_from_yaml(yaml);
         use holochain_p2p::kitsune_p2p::*;
@@ -235,20 +243,33 @@ pub mod tests {
                 signing_service_uri: Some("ws://localhost:9001".into()),
                 encryption_service_uri: Some("ws://localhost:9002".into()),
                 decryption_service_uri: Some("ws://localhost:9003".into()),
-                dpki: Some(DpkiConfig {
-                    instance_id: "some_id".into(),
-                    init_params: "some_params".into()
-                }),
+                dpki: Some(DpkiConfig::Local),
                 passphrase_service: Some(PassphraseServiceConfig::Cmd),
                 keystore_path: None,
                 admin_interfaces: Some(vec![AdminInterfaceConfig {
-                    driver: InterfaceDriver::Websocket { port: 1234 }
+                    driver: InterfaceDriver::websocket(1234)
                 }]),
                 network: Some(network_config),
+                wasm: Some(WasmConfig {
+                    cache_path: Some(PathBuf::from("/path/to/wasm_cache")),
+                    instance_pool_size: DEFAULT_INSTANCE_POOL_SIZE,
+                }),
             }
         );
     }
 
+    #[test]
+    fn test_config_no_dpki() {
+        let yaml = r#"---
+    environment_path: /path/to/env
+
+    dpki:
+      type: none
+    "#;
+        let result: ConductorConfig = config_from_yaml(yaml).unwrap();
+        assert_eq!(result.dpki, Some(DpkiConfig::None));
+    }
+
     #[test]
     fn test_config_keystore() {
         let yaml = r#"---
@@ -276,6 +297,7 @@ pub mod tests {
                 keystore_path: Some(PathBuf::from("/path/to/keystore").into()),
                 admin_interfaces: None,
                 use_dangerous_test_keystore: true,
+                wasm: None,
             }
         );
     }
@@ -1,13 +1,22 @@
-// Legacy config that will probably change
-#![allow(missing_docs)]
-
 use serde::Deserialize;
 use serde::Serialize;
 
-/// Configure which app instance id to treat as the DPKI application handler
-/// as well as what parameters to pass it on its initialization
+/// Selects the DPKI service the conductor consults to decide whether an agent
+/// key was valid when it signed something.
+/// Without one, every key is taken to be valid.
 #[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
-pub struct DpkiConfig {
-    pub instance_id: String,
-    pub init_params: String,
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum DpkiConfig {
+    /// No DPKI service, so that every key is taken to be valid, the same as
+    /// leaving `dpki` out of the config.
+    /// There is no DPKI service to connect to yet, so outside of testing this
+    /// is the only choice. A service can still be plugged in with
+    /// `ConductorBuilder::with_dpki`.
+    None,
+    /// A key registry kept in memory by the conductor, which starts out
+    /// empty, so that every key is valid until it's revoked.
+    /// Keys can only be revoked by code holding the service, so this is only
+    /// useful for testing, and only available with the `test_utils` feature.
+    #[cfg(any(test, feature = "test_utils"))]
+    Local,
 }
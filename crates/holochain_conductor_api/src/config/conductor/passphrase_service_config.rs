@@ -8,7 +8,9 @@ use std::path::PathBuf;
 /// Setting the type to "unixsocket" and providing a path to a file socket enables
 /// arbitrary UIs to connect to the conductor and prompt the user for a passphrase.
 /// The according `PassphraseServiceUnixSocket` will send a request message over the socket
-/// then receives bytes as passphrase until a newline is sent.
+/// (`request_passphrase` followed by a newline)
+/// then receives bytes as passphrase until a newline (or `\r\n`) is sent.
+/// The request fails if no passphrase arrives within five minutes.
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum PassphraseServiceConfig {
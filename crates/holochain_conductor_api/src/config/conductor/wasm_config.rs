@@ -0,0 +1,32 @@
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// The number of idle instances of each zome kept ready for reuse, if not
+/// configured otherwise.
+pub const DEFAULT_INSTANCE_POOL_SIZE: usize = 4;
+
+/// Configures how the conductor compiles and instantiates the wasm of zomes.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default, rename_all = "snake_case")]
+pub struct WasmConfig {
+    /// A directory in which compiled wasm modules are cached, so that each
+    /// module only needs to be compiled once, even across restarts.
+    /// If omitted, compiled modules are only cached in memory.
+    pub cache_path: Option<PathBuf>,
+
+    /// The most idle instances of each zome which are kept ready for reuse.
+    /// Calls which find no idle instance create a new one, so this bounds the
+    /// memory held by idle instances, not the number of concurrent calls.
+    /// Zero disables pooling.
+    pub instance_pool_size: usize,
+}
+
+impl Default for WasmConfig {
+    fn default() -> Self {
+        Self {
+            cache_path: None,
+            instance_pool_size: DEFAULT_INSTANCE_POOL_SIZE,
+        }
+    }
+}
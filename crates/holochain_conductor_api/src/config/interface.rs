@@ -1,11 +1,15 @@
 use serde::Deserialize;
 use serde::Serialize;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
 
 /// Information neeeded to spawn an Admin interface
 #[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
 pub struct AdminInterfaceConfig {
     /// By what means will the interface be exposed?
-    /// Current only option is a local websocket running on a configurable port.
+    /// Either a websocket running on a configurable port,
+    /// or a unix domain socket at a configurable path.
     pub driver: InterfaceDriver,
     // /// How long will this interface be accessible between authentications?
     // /// TODO: implement once we have authentication
@@ -24,14 +28,240 @@ pub enum InterfaceDriver {
     Websocket {
         /// The port on which to establish the WebsocketListener
         port: u16,
+        /// The address on which to establish the WebsocketListener.
+        /// [default = 127.0.0.1]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bind_address: Option<IpAddr>,
+        /// If set, the interface is served over tls, i.e. as `wss`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tls: Option<InterfaceTlsConfig>,
+        /// If set, only connections from browsers on these origins are
+        /// accepted. `"*"` matches any origin. Connections which are not made
+        /// from a browser send no origin, and are always accepted.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        allowed_origins: Option<Vec<String>>,
+        /// Overrides for the default websocket limits
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limits: Option<WebsocketLimits>,
     },
+    /// An interface implemented via a unix domain socket. Messages are framed
+    /// exactly as they are for a websocket interface, but no tcp port is
+    /// opened, and access is controlled by the permissions of the socket file.
+    UnixSocket {
+        /// The path at which to create the socket file.
+        /// A stale socket file left at this path is replaced.
+        path: PathBuf,
+        /// The permissions to give the socket file. Only users which can
+        /// write to the file can connect.
+        /// [default = 0o600, i.e. only the user running the conductor]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mode: Option<u32>,
+    },
+    /// An app interface served as plain HTTP with JSON bodies, for clients
+    /// which can't speak msgpack over a websocket. Zome functions are called
+    /// with `POST /apps/{app}/cells/{cell_nick}/zomes/{zome}/{fn}`, and
+    /// signals are streamed as server-sent events from `GET /apps/{app}/signals`.
+    /// Every request must carry an app authentication token as a
+    /// `Authorization: Bearer` header.
+    ///
+    /// Only app interfaces can use this driver. As HTTP is served without
+    /// tls, the tokens would be sent in the clear over any other network,
+    /// so it can only be bound to a loopback address.
+    Http {
+        /// The port on which to serve HTTP
+        port: u16,
+        /// The loopback address on which to serve HTTP.
+        /// [default = 127.0.0.1]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bind_address: Option<IpAddr>,
+        /// Maximum size in bytes of a request body.
+        /// [default = 16MB, the default websocket frame size]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_body_size: Option<usize>,
+    },
+}
+
+/// The permissions given to a unix socket interface when none are specified
+pub const DEFAULT_UNIX_SOCKET_MODE: u32 = 0o600;
+
+/// The maximum size of an HTTP request body when none is specified
+pub const DEFAULT_HTTP_MAX_BODY_SIZE: usize = 16 << 20;
+
+/// The certificate and private key used to serve an interface over tls.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct InterfaceTlsConfig {
+    /// Path to the PEM encoded certificate chain
+    pub cert_path: PathBuf,
+    /// Path to the PEM encoded PKCS #8 private key
+    pub key_path: PathBuf,
+}
+
+/// Limits for the connections to a websocket interface.
+/// Any limit which is not set uses the websocket default.
+#[derive(Clone, Default, Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct WebsocketLimits {
+    /// Seconds after which a request with no response is dropped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_request_timeout_s: Option<usize>,
+    /// How many outgoing messages may be queued per connection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_send_queue: Option<usize>,
+    /// Maximum size in bytes of a single message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_message_size: Option<usize>,
+    /// Maximum size in bytes of a single websocket frame
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_frame_size: Option<usize>,
+    /// Maximum number of new connections waiting to be accepted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_pending_connections: Option<usize>,
 }
 
 impl InterfaceDriver {
+    /// A websocket driver on the given port, with all other options
+    /// left at their defaults.
+    pub fn websocket(port: u16) -> Self {
+        InterfaceDriver::Websocket {
+            port,
+            bind_address: None,
+            tls: None,
+            allowed_origins: None,
+            limits: None,
+        }
+    }
+
+    /// A unix socket driver at the given path, with the default permissions.
+    pub fn unix_socket(path: PathBuf) -> Self {
+        InterfaceDriver::UnixSocket { path, mode: None }
+    }
+
+    /// An http driver on the given port, bound to the default address.
+    pub fn http(port: u16) -> Self {
+        InterfaceDriver::Http {
+            port,
+            bind_address: None,
+            max_body_size: None,
+        }
+    }
+
     /// Get the port for this driver.
+    /// Drivers which don't listen on a port return 0.
     pub fn port(&self) -> u16 {
         match self {
-            InterfaceDriver::Websocket { port } => *port,
+            InterfaceDriver::Websocket { port, .. } => *port,
+            InterfaceDriver::UnixSocket { .. } => 0,
+            InterfaceDriver::Http { port, .. } => *port,
         }
     }
+
+    /// Get the address this driver binds to.
+    pub fn bind_address(&self) -> IpAddr {
+        match self {
+            InterfaceDriver::Websocket { bind_address, .. }
+            | InterfaceDriver::Http { bind_address, .. } => {
+                bind_address.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            }
+            InterfaceDriver::UnixSocket { .. } => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        }
+    }
+
+    /// Get a copy of this driver with a different port.
+    /// Used when binding to port 0 lets the OS choose the port.
+    /// Drivers which don't listen on a port are unchanged.
+    pub fn with_port(mut self, new_port: u16) -> Self {
+        match &mut self {
+            InterfaceDriver::Websocket { port, .. } | InterfaceDriver::Http { port, .. } => {
+                *port = new_port
+            }
+            InterfaceDriver::UnixSocket { .. } => (),
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn websocket_driver_options_default() {
+        let driver: InterfaceDriver = serde_yaml::from_str("type: websocket\nport: 1234").unwrap();
+        assert_eq!(driver, InterfaceDriver::websocket(1234));
+        assert_eq!(driver.bind_address(), IpAddr::V4(Ipv4Addr::LOCALHOST));
+        // Unset options are not serialized, so existing configs are unchanged
+        assert_eq!(
+            serde_yaml::to_string(&driver).unwrap(),
+            "---\ntype: websocket\nport: 1234\n"
+        );
+    }
+
+    #[test]
+    fn websocket_driver_options() {
+        let yaml = r#"---
+type: websocket
+port: 1234
+bind_address: 0.0.0.0
+tls:
+  cert_path: /path/to/cert.pem
+  key_path: /path/to/key.pem
+allowed_origins:
+  - "http://localhost:8888"
+limits:
+  max_message_size: 1024
+"#;
+        let driver: InterfaceDriver = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            driver,
+            InterfaceDriver::Websocket {
+                port: 1234,
+                bind_address: Some(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+                tls: Some(InterfaceTlsConfig {
+                    cert_path: "/path/to/cert.pem".into(),
+                    key_path: "/path/to/key.pem".into(),
+                }),
+                allowed_origins: Some(vec!["http://localhost:8888".into()]),
+                limits: Some(WebsocketLimits {
+                    max_message_size: Some(1024),
+                    ..Default::default()
+                }),
+            }
+        );
+        let round_trip: InterfaceDriver =
+            serde_yaml::from_str(&serde_yaml::to_string(&driver).unwrap()).unwrap();
+        assert_eq!(round_trip, driver);
+    }
+
+    #[test]
+    fn unix_socket_driver() {
+        let yaml = "type: unix_socket\npath: /tmp/admin.sock\nmode: 0o660";
+        let driver: InterfaceDriver = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            driver,
+            InterfaceDriver::UnixSocket {
+                path: "/tmp/admin.sock".into(),
+                mode: Some(0o660),
+            }
+        );
+        assert_eq!(driver.port(), 0);
+        assert_eq!(driver.clone().with_port(1234), driver);
+    }
+
+    #[test]
+    fn http_driver() {
+        let driver: InterfaceDriver = serde_yaml::from_str("type: http\nport: 8080").unwrap();
+        assert_eq!(driver, InterfaceDriver::http(8080));
+        assert_eq!(driver.clone().with_port(0), InterfaceDriver::http(0));
+
+        let yaml = "type: http\nport: 8080\nbind_address: \"::1\"\nmax_body_size: 1024";
+        let driver: InterfaceDriver = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            driver,
+            InterfaceDriver::Http {
+                port: 8080,
+                bind_address: Some(IpAddr::V6(std::net::Ipv6Addr::LOCALHOST)),
+                max_body_size: Some(1024),
+            }
+        );
+    }
 }
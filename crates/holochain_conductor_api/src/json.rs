@@ -0,0 +1,216 @@
+//! Transcoding between JSON and the msgpack [`ExternIO`] which zome
+//! functions take and return, for clients which don't speak msgpack.
+//!
+//! Holo hashes are shown in their base64 form and
+//! other bytes are shown as arrays of numbers.
+//! Strings in JSON which are the base64 form of a holo hash are
+//! sent to zome functions as the hash, so hashes round trip.
+
+use holochain_types::prelude::*;
+use serde::de::Visitor;
+use serde::Serialize;
+
+/// Transcode a JSON value to msgpack.
+pub fn json_to_extern_io(value: &serde_json::Value) -> Result<ExternIO, SerializedBytesError> {
+    ExternIO::encode(EncodedValue(value))
+}
+
+/// Transcode msgpack to a JSON value.
+pub fn extern_io_to_json(io: &ExternIO) -> Result<serde_json::Value, SerializedBytesError> {
+    let value: DecodedValue = io.decode()?;
+    Ok(value.0)
+}
+
+/// A JSON value which serializes the base64 form of
+/// holo hashes as the msgpack binary type.
+struct EncodedValue<'a>(&'a serde_json::Value);
+
+impl serde::Serialize for EncodedValue<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.0 {
+            serde_json::Value::String(s) => match encode_hash(s) {
+                Some(bytes) => serializer.serialize_bytes(&bytes),
+                None => serializer.serialize_str(s),
+            },
+            serde_json::Value::Array(values) => {
+                serializer.collect_seq(values.iter().map(EncodedValue))
+            }
+            serde_json::Value::Object(values) => {
+                serializer.collect_map(values.iter().map(|(k, v)| (k, EncodedValue(v))))
+            }
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+/// A JSON value that can also be deserialized from
+/// the msgpack binary type.
+#[derive(Debug)]
+struct DecodedValue(serde_json::Value);
+
+impl<'de> serde::Deserialize<'de> for DecodedValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(DecodedValueVisitor)
+    }
+}
+
+struct DecodedValueVisitor;
+
+impl<'de> Visitor<'de> for DecodedValueVisitor {
+    type Value = DecodedValue;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "any msgpack value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(DecodedValue(v.into()))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(DecodedValue(v.into()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(DecodedValue(v.into()))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(DecodedValue(v.into()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(DecodedValue(v.into()))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(DecodedValue(decode_bytes(v)))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(DecodedValue(serde_json::Value::Null))
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(DecodedValue(serde_json::Value::Null))
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        serde::Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut values = Vec::new();
+        while let Some(DecodedValue(v)) = seq.next_element()? {
+            values.push(v);
+        }
+        Ok(DecodedValue(values.into()))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut values = serde_json::Map::new();
+        while let Some((DecodedValue(k), DecodedValue(v))) = map.next_entry()? {
+            let k = match k {
+                serde_json::Value::String(k) => k,
+                k => k.to_string(),
+            };
+            values.insert(k, v);
+        }
+        Ok(DecodedValue(values.into()))
+    }
+}
+
+/// The inverse of [decode_bytes] for hashes
+fn encode_hash(s: &str) -> Option<Vec<u8>> {
+    if !s.starts_with('u') {
+        return None;
+    }
+    let bytes = holo_hash::encode::holo_hash_decode_unchecked(s).ok()?;
+    if AgentPubKey::from_raw_39(bytes.clone()).is_ok()
+        || AnyDhtHash::from_raw_39(bytes.clone()).is_ok()
+        || DnaHash::from_raw_39(bytes.clone()).is_ok()
+    {
+        Some(bytes)
+    } else {
+        None
+    }
+}
+
+fn decode_bytes(bytes: &[u8]) -> serde_json::Value {
+    if bytes.len() == HOLO_HASH_FULL_LEN {
+        let bytes = bytes.to_vec();
+        if let Ok(h) = AgentPubKey::from_raw_39(bytes.clone()) {
+            return h.to_string().into();
+        }
+        if let Ok(h) = AnyDhtHash::from_raw_39(bytes.clone()) {
+            return h.to_string().into();
+        }
+        if let Ok(h) = DnaHash::from_raw_39(bytes) {
+            return h.to_string().into();
+        }
+    }
+    bytes.to_vec().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let value = serde_json::json!({"title": "hi", "count": 3, "tags": ["a", "b"]});
+        let io = json_to_extern_io(&value).unwrap();
+        assert_eq!(extern_io_to_json(&io).unwrap(), value);
+    }
+
+    #[test]
+    fn hashes_decode_as_base64() {
+        let agent = AgentPubKey::from_raw_36(vec![0xdb; 36]);
+        let output = ExternIO::encode(agent.clone()).unwrap();
+        assert_eq!(
+            extern_io_to_json(&output).unwrap(),
+            serde_json::Value::String(agent.to_string())
+        );
+        let output = ExternIO::encode(ExternIO(vec![1, 2, 3])).unwrap();
+        assert_eq!(
+            extern_io_to_json(&output).unwrap(),
+            serde_json::json!([1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn hashes_encode_from_base64() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Input {
+            agent: AgentPubKey,
+            note: String,
+        }
+
+        let agent = AgentPubKey::from_raw_36(vec![0xdb; 36]);
+        let value = serde_json::json!({"agent": agent.to_string(), "note": "uhi"});
+        let io = json_to_extern_io(&value).unwrap();
+        assert_eq!(
+            io.decode::<Input>().unwrap(),
+            Input {
+                agent,
+                note: "uhi".into()
+            }
+        );
+        assert_eq!(extern_io_to_json(&io).unwrap(), value);
+    }
+}
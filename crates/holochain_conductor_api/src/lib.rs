@@ -3,6 +3,7 @@
 mod admin_interface;
 mod app_interface;
 pub mod config;
+pub mod json;
 pub mod signal_subscription;
 pub mod state_dump;
 
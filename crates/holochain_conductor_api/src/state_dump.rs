@@ -1,5 +1,6 @@
 use holo_hash::AgentPubKey;
 use holo_hash::DnaHash;
+use holochain_state::chain_forks::DetectedFork;
 use holochain_state::source_chain::SourceChainJsonDump;
 use serde::Deserialize;
 use serde::Serialize;
@@ -9,6 +10,9 @@ pub struct JsonDump {
     pub peer_dump: P2pStateDump,
     pub source_chain_dump: SourceChainJsonDump,
     pub integration_dump: IntegrationStateDump,
+    /// The forks of source chains this cell has detected,
+    /// including its own
+    pub chain_forks: Vec<DetectedFork>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -76,7 +80,19 @@ impl std::fmt::Display for JsonDump {
             "Elements authored: {}, Ops published: {}",
             s.elements.len(),
             s.published_ops_count
-        )
+        )?;
+        writeln!(f, "Chain forks detected: {}", self.chain_forks.len())?;
+        for detected in &self.chain_forks {
+            writeln!(
+                f,
+                "  {} forked at {} ({} / {})",
+                detected.author,
+                detected.fork.fork_seq,
+                detected.fork.first_header,
+                detected.fork.second_header
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -0,0 +1,393 @@
+//! Encrypted backups of the keystore, for recovering a conductor's agent keys
+//! onto new hardware.
+//!
+//! Lair never hands out private keys over its api, so a backup is a copy of
+//! lair's store on disk. The copy is either taken while lair is stopped, or
+//! through [KeystoreSenderExt::export_keystore], which retries it until lair
+//! added no entries while the files were read, so that the copy is
+//! consistent. The files are encrypted with XSalsa20Poly1305 under a
+//! key derived from a backup passphrase with Argon2id, so a backup is only as
+//! useful as the passphrase is secret. Restoring writes the files into an
+//! empty lair directory, which lair then unlocks with its own passphrase as
+//! usual. A running keystore can't be replaced, so
+//! [KeystoreSenderExt::import_keystore] stages the restore instead, and it is
+//! moved into place the next time the keystore is spawned.
+
+use crate::*;
+use lair_keystore_api::Config;
+use std::path::Path;
+use std::path::PathBuf;
+use xsalsa20poly1305::aead::generic_array::GenericArray;
+use xsalsa20poly1305::aead::Aead;
+use xsalsa20poly1305::aead::NewAead;
+use xsalsa20poly1305::XSalsa20Poly1305;
+
+/// The length of the random salt the backup key is derived with
+const SALT_LEN: usize = 16;
+
+/// The length of the random nonce stored before the ciphertext
+const NONCE_LEN: usize = 24;
+
+/// An encrypted copy of a lair keystore directory
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize, SerializedBytes)]
+pub struct KeystoreBackup {
+    #[serde(with = "serde_bytes")]
+    salt: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    encrypted: Vec<u8>,
+}
+
+impl std::fmt::Debug for KeystoreBackup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeystoreBackup")
+            .field("len", &self.encrypted.len())
+            .finish()
+    }
+}
+
+/// The files of a lair directory, by their path relative to the directory
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct LairFiles(Vec<(PathBuf, serde_bytes::ByteBuf)>);
+
+impl KeystoreBackup {
+    /// Encode the backup, for writing it to a file
+    pub fn to_bytes(&self) -> KeystoreApiResult<Vec<u8>> {
+        Ok(holochain_serialized_bytes::encode(self)?)
+    }
+
+    /// Decode a backup written with [KeystoreBackup::to_bytes]
+    pub fn from_bytes(bytes: &[u8]) -> KeystoreApiResult<Self> {
+        Ok(holochain_serialized_bytes::decode(bytes)?)
+    }
+}
+
+/// The directory lair keeps its store in, given the keystore path from the
+/// conductor config
+pub fn lair_root_path(lair_dir: Option<&Path>) -> PathBuf {
+    lair_config(lair_dir).get_root_path().to_path_buf()
+}
+
+/// The directory a restore staged with [KeystoreSenderExt::import_keystore]
+/// waits in, next to the lair directory
+pub fn staged_restore_path(lair_dir: Option<&Path>) -> PathBuf {
+    sibling_path(&lair_root_path(lair_dir), "restore")
+}
+
+/// Back up the lair directory, encrypted with the passphrase.
+/// Lair must not be running, since it could be writing to its store while the
+/// files are read. The pid file and socket it leaves behind are left out.
+/// To back up the keystore of a running lair use
+/// [KeystoreSenderExt::export_keystore].
+pub fn export_lair_dir(
+    lair_dir: Option<&Path>,
+    passphrase: &str,
+) -> KeystoreApiResult<KeystoreBackup> {
+    let config = lair_config(lair_dir);
+    if lair_is_running(&config) {
+        return Err(KeystoreError::Other(format!(
+            "Lair is running on the keystore at {}. Stop it before backing up the keystore",
+            config.get_root_path().display()
+        )));
+    }
+    encrypt_lair_files(read_lair_dir(lair_dir)?, passphrase)
+}
+
+/// Restore a backup made with [export_lair_dir] into the lair directory.
+/// Lair must not be running, and the directory must not already contain a
+/// keystore, so that restoring never overwrites keys.
+pub fn restore_lair_dir(
+    backup: &KeystoreBackup,
+    passphrase: &str,
+    lair_dir: Option<&Path>,
+) -> KeystoreApiResult<()> {
+    let root = lair_root_path(lair_dir);
+    if root.exists() && std::fs::read_dir(&root).map_err(io_err)?.next().is_some() {
+        return Err(KeystoreError::Other(format!(
+            "Refusing to restore the keystore into {}, which is not empty",
+            root.display()
+        )));
+    }
+    write_files(&root, decrypt_lair_files(backup, passphrase)?)
+}
+
+/// Move a restore staged with [KeystoreSenderExt::import_keystore] into place,
+/// returning whether there was one.
+/// The lair directory it replaces is kept next to it, so that no keys are
+/// lost if any were generated after the restore was staged.
+pub fn apply_staged_restore(lair_dir: Option<&Path>) -> KeystoreApiResult<bool> {
+    let staged = staged_restore_path(lair_dir);
+    if !staged.is_dir() {
+        return Ok(false);
+    }
+    let config = lair_config(lair_dir);
+    let root = config.get_root_path();
+    if lair_is_running(&config) {
+        return Err(KeystoreError::Other(format!(
+            "Lair is running on the keystore at {}. Stop it to restore the backup staged at {}",
+            root.display(),
+            staged.display()
+        )));
+    }
+    if root.exists() {
+        let replaced = sibling_path(root, "replaced");
+        if replaced.exists() {
+            return Err(KeystoreError::Other(format!(
+                "Refusing to restore the backup staged at {}, as the keystore it replaces can't be kept at {}, which already exists",
+                staged.display(),
+                replaced.display()
+            )));
+        }
+        std::fs::rename(root, &replaced).map_err(io_err)?;
+    }
+    std::fs::rename(&staged, root).map_err(io_err)?;
+    Ok(true)
+}
+
+/// Decrypt a backup into the staging directory, replacing any restore
+/// staged before it
+pub(crate) fn stage_restore(
+    backup: &KeystoreBackup,
+    passphrase: &str,
+    lair_dir: Option<&Path>,
+) -> KeystoreApiResult<()> {
+    let files = decrypt_lair_files(backup, passphrase)?;
+    let staged = staged_restore_path(lair_dir);
+    if staged.exists() {
+        std::fs::remove_dir_all(&staged).map_err(io_err)?;
+    }
+    write_files(&staged, files)
+}
+
+/// Read the files of the lair directory, except the pid file and socket
+pub(crate) fn read_lair_dir(lair_dir: Option<&Path>) -> KeystoreApiResult<LairFiles> {
+    let config = lair_config(lair_dir);
+    let root = config.get_root_path();
+    let skip = [config.get_pid_path(), config.get_socket_path()];
+    let mut files = Vec::new();
+    read_files(root, root, &skip, &mut files)?;
+    if files.is_empty() {
+        return Err(KeystoreError::Other(format!(
+            "There is no keystore at {} to back up",
+            root.display()
+        )));
+    }
+    Ok(LairFiles(files))
+}
+
+/// Encrypt the files of a lair directory with the passphrase
+pub(crate) fn encrypt_lair_files(
+    files: LairFiles,
+    passphrase: &str,
+) -> KeystoreApiResult<KeystoreBackup> {
+    let salt: [u8; SALT_LEN] = rand::random();
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    let plaintext = holochain_serialized_bytes::encode(&files)?;
+    let ciphertext = backup_cipher(passphrase, &salt)?
+        .encrypt(GenericArray::from_slice(&nonce), &plaintext[..])
+        .map_err(|_| KeystoreError::Other("Could not encrypt the keystore backup".into()))?;
+    let mut encrypted = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    encrypted.extend_from_slice(&nonce);
+    encrypted.extend(ciphertext);
+    Ok(KeystoreBackup {
+        salt: salt.to_vec(),
+        encrypted,
+    })
+}
+
+fn decrypt_lair_files(backup: &KeystoreBackup, passphrase: &str) -> KeystoreApiResult<LairFiles> {
+    if backup.encrypted.len() < NONCE_LEN {
+        return Err(KeystoreError::DecryptionFailed);
+    }
+    let (nonce, ciphertext) = backup.encrypted.split_at(NONCE_LEN);
+    let plaintext = backup_cipher(passphrase, &backup.salt)?
+        .decrypt(GenericArray::from_slice(nonce), ciphertext)
+        .map_err(|_| KeystoreError::DecryptionFailed)?;
+    let files: LairFiles = holochain_serialized_bytes::decode(&plaintext)?;
+    for (relative, _) in &files.0 {
+        if relative.is_absolute()
+            || relative
+                .components()
+                .any(|c| matches!(c, std::path::Component::ParentDir))
+        {
+            return Err(KeystoreError::Other(format!(
+                "The keystore backup contains an invalid path {}",
+                relative.display()
+            )));
+        }
+    }
+    Ok(files)
+}
+
+fn write_files(root: &Path, LairFiles(files): LairFiles) -> KeystoreApiResult<()> {
+    for (relative, contents) in files {
+        let path = root.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err)?;
+        }
+        std::fs::write(&path, contents.as_ref()).map_err(io_err)?;
+    }
+    Ok(())
+}
+
+/// A path next to the lair directory, e.g. `<root>.restore`
+fn sibling_path(root: &Path, extension: &str) -> PathBuf {
+    let mut path = root.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    path.into()
+}
+
+/// Prompt for the backup passphrase on the terminal, without echoing it
+pub fn prompt_backup_passphrase() -> KeystoreApiResult<String> {
+    rpassword::read_password_from_tty(Some("Keystore backup passphrase: ")).map_err(|e| {
+        KeystoreError::PassphraseUnavailable(format!("Could not read from the tty: {}", e))
+    })
+}
+
+fn lair_config(lair_dir: Option<&Path>) -> std::sync::Arc<Config> {
+    let mut config = Config::builder();
+    if let Some(lair_dir) = lair_dir {
+        config = config.set_root_path(lair_dir);
+    }
+    config.build()
+}
+
+/// Whether a lair process is serving the keystore of this config
+fn lair_is_running(config: &Config) -> bool {
+    #[cfg(unix)]
+    {
+        std::os::unix::net::UnixStream::connect(config.get_socket_path()).is_ok()
+    }
+    #[cfg(not(unix))]
+    {
+        config.get_pid_path().exists()
+    }
+}
+
+fn backup_cipher(passphrase: &str, salt: &[u8]) -> KeystoreApiResult<XSalsa20Poly1305> {
+    let config = argon2::Config {
+        variant: argon2::Variant::Argon2id,
+        hash_length: 32,
+        ..argon2::Config::default()
+    };
+    let key = argon2::hash_raw(passphrase.as_bytes(), salt, &config)
+        .map_err(|e| KeystoreError::Other(format!("Could not derive the backup key: {}", e)))?;
+    Ok(XSalsa20Poly1305::new(GenericArray::from_slice(&key[..])))
+}
+
+fn read_files(
+    root: &Path,
+    dir: &Path,
+    skip: &[&Path],
+    files: &mut Vec<(PathBuf, serde_bytes::ByteBuf)>,
+) -> KeystoreApiResult<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir).map_err(io_err)? {
+        let path = entry.map_err(io_err)?.path();
+        if skip.contains(&path.as_path()) {
+            continue;
+        }
+        if path.is_dir() {
+            read_files(root, &path, skip, files)?;
+        } else if path.is_file() {
+            let contents = std::fs::read(&path).map_err(io_err)?;
+            let relative = path
+                .strip_prefix(root)
+                .expect("The path was found under the root")
+                .to_path_buf();
+            files.push((relative, serde_bytes::ByteBuf::from(contents)));
+        }
+    }
+    Ok(())
+}
+
+fn io_err(e: std::io::Error) -> KeystoreError {
+    KeystoreError::Other(format!("Keystore backup io error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_round_trip() {
+        let from = tempdir::TempDir::new("lair_from").unwrap();
+        std::fs::write(from.path().join("store.sqlite3"), b"secret keys").unwrap();
+        std::fs::create_dir(from.path().join("nested")).unwrap();
+        std::fs::write(from.path().join("nested").join("other"), b"more").unwrap();
+
+        // A running lair's store can't be backed up
+        #[cfg(unix)]
+        {
+            let config = lair_config(Some(from.path()));
+            let _lair = std::os::unix::net::UnixListener::bind(config.get_socket_path()).unwrap();
+            assert!(export_lair_dir(Some(from.path()), "hunter2").is_err());
+        }
+
+        let backup = export_lair_dir(Some(from.path()), "hunter2").unwrap();
+        let backup = KeystoreBackup::from_bytes(&backup.to_bytes().unwrap()).unwrap();
+        assert!(!backup
+            .encrypted
+            .windows(b"secret keys".len())
+            .any(|w| w == b"secret keys"));
+
+        let to = tempdir::TempDir::new("lair_to").unwrap();
+        assert_eq!(
+            restore_lair_dir(&backup, "wrong", Some(to.path())),
+            Err(KeystoreError::DecryptionFailed)
+        );
+        restore_lair_dir(&backup, "hunter2", Some(to.path())).unwrap();
+        assert_eq!(
+            std::fs::read(to.path().join("store.sqlite3")).unwrap(),
+            b"secret keys"
+        );
+        assert_eq!(
+            std::fs::read(to.path().join("nested").join("other")).unwrap(),
+            b"more"
+        );
+
+        // A keystore is never overwritten
+        assert!(restore_lair_dir(&backup, "hunter2", Some(to.path())).is_err());
+    }
+
+    #[test]
+    fn staged_restore_replaces_the_keystore() {
+        let dir = tempdir::TempDir::new("lair").unwrap();
+        let root = dir.path().join("keystore");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(root.join("store.sqlite3"), b"old keys").unwrap();
+        let backup = encrypt_lair_files(
+            LairFiles(vec![(
+                "store.sqlite3".into(),
+                serde_bytes::ByteBuf::from(b"new keys".to_vec()),
+            )]),
+            "hunter2",
+        )
+        .unwrap();
+
+        assert!(!apply_staged_restore(Some(&root)).unwrap());
+        assert_eq!(
+            stage_restore(&backup, "wrong", Some(&root)),
+            Err(KeystoreError::DecryptionFailed)
+        );
+        stage_restore(&backup, "hunter2", Some(&root)).unwrap();
+        // The keystore is only replaced once the restore is applied
+        assert_eq!(
+            std::fs::read(root.join("store.sqlite3")).unwrap(),
+            b"old keys"
+        );
+
+        assert!(apply_staged_restore(Some(&root)).unwrap());
+        assert_eq!(
+            std::fs::read(root.join("store.sqlite3")).unwrap(),
+            b"new keys"
+        );
+        assert_eq!(
+            std::fs::read(dir.path().join("keystore.replaced").join("store.sqlite3")).unwrap(),
+            b"old keys"
+        );
+        assert!(!staged_restore_path(Some(&root)).exists());
+    }
+}
@@ -24,6 +24,19 @@ pub enum KeystoreError {
     #[error("Secure primitive error: {0}")]
     SecurePrimitiveError(#[from] holochain_zome_types::SecurePrimitiveError),
 
+    /// The passphrase service could not provide the passphrase
+    #[error("The keystore passphrase could not be obtained: {0}")]
+    PassphraseUnavailable(String),
+
+    /// Lair could not be unlocked with the passphrase it was given
+    #[error("The keystore could not be unlocked, the passphrase is likely incorrect: {0}")]
+    IncorrectPassphrase(lair_keystore_api::LairError),
+
+    /// Encrypted data could not be decrypted, because it was encrypted with
+    /// another key or has been tampered with
+    #[error("The data could not be decrypted with this key")]
+    DecryptionFailed,
+
     /// Unexpected Internal Error.
     #[error("Other: {0}")]
     Other(String),
@@ -1,6 +1,7 @@
 //! This module contains all the types needed to implement a keystore actor.
 //! We will re-export the main KeystoreSender usable by clients at the lib.
 
+use crate::backup::*;
 use crate::*;
 use ghost_actor::dependencies::futures::future::FutureExt;
 use holo_hash::{HOLO_HASH_CORE_LEN, HOLO_HASH_PREFIX_LEN};
@@ -11,6 +12,11 @@ use holochain_zome_types::x_salsa20_poly1305::{
 use lair_keystore_api::actor::{
     Cert, CertDigest, CertPrivKey, LairClientApiSender, LairEntryType, TlsCertOptions,
 };
+use std::path::PathBuf;
+
+/// How many times a backup of a running keystore is retried if entries are
+/// added to it while its files are read
+const EXPORT_ATTEMPTS: usize = 5;
 
 /// GhostSender type for the KeystoreApi
 pub type KeystoreSender = ghost_actor::GhostSender<lair_keystore_api::actor::LairClientApi>;
@@ -27,6 +33,10 @@ pub trait KeystoreSenderExt {
     /// Generates a new pure entropy keypair in the keystore, returning the public key.
     fn generate_sign_keypair_from_pure_entropy(&self) -> KeystoreApiFuture<holo_hash::AgentPubKey>;
 
+    /// List the public keys of all the signing keypairs in the keystore,
+    /// in the order they were added
+    fn list_sign_keypairs(&self) -> KeystoreApiFuture<Vec<holo_hash::AgentPubKey>>;
+
     /// Generate a signature for a given blob of binary data.
     fn sign(&self, input: Sign) -> KeystoreApiFuture<Signature>;
 
@@ -60,6 +70,28 @@ pub trait KeystoreSenderExt {
     ) -> KeystoreApiFuture<
         Option<holochain_zome_types::x_salsa20_poly1305::data::XSalsa20Poly1305Data>,
     >;
+
+    /// Back up the lair directory this keystore is served from, encrypted
+    /// with the passphrase, while lair keeps running.
+    /// The files are read again if an entry was added to the keystore while
+    /// they were being read.
+    fn export_keystore(
+        &self,
+        lair_dir: Option<PathBuf>,
+        passphrase: String,
+    ) -> KeystoreApiFuture<KeystoreBackup>;
+
+    /// Stage a backup made with [KeystoreSenderExt::export_keystore] to
+    /// replace the lair directory this keystore is served from, the next time
+    /// the keystore is spawned.
+    /// Fails if the passphrase doesn't decrypt the backup, or if the keystore
+    /// already holds signing keypairs, which the backup would replace.
+    fn import_keystore(
+        &self,
+        lair_dir: Option<PathBuf>,
+        backup: KeystoreBackup,
+        passphrase: String,
+    ) -> KeystoreApiFuture<()>;
 }
 
 impl KeystoreSenderExt for KeystoreSender {
@@ -73,6 +105,23 @@ impl KeystoreSenderExt for KeystoreSender {
         .into()
     }
 
+    fn list_sign_keypairs(&self) -> KeystoreApiFuture<Vec<holo_hash::AgentPubKey>> {
+        let this = self.clone();
+        async move {
+            let last_index = this.lair_get_last_entry_index().await?;
+            let mut keys = Vec::new();
+            for i in 1..=*last_index {
+                if let Ok(LairEntryType::SignEd25519) = this.lair_get_entry_type(i.into()).await {
+                    let pk = this.sign_ed25519_get(i.into()).await?;
+                    keys.push(holo_hash::AgentPubKey::from_raw_32(pk.to_vec()));
+                }
+            }
+            Ok(keys)
+        }
+        .boxed()
+        .into()
+    }
+
     fn sign(&self, input: Sign) -> KeystoreApiFuture<Signature> {
         let fut = self.sign_ed25519_sign_by_pub_key(
             input.key.as_ref()[HOLO_HASH_PREFIX_LEN..HOLO_HASH_PREFIX_LEN + HOLO_HASH_CORE_LEN]
@@ -197,6 +246,59 @@ impl KeystoreSenderExt for KeystoreSender {
         .boxed()
         .into()
     }
+
+    fn export_keystore(
+        &self,
+        lair_dir: Option<PathBuf>,
+        passphrase: String,
+    ) -> KeystoreApiFuture<KeystoreBackup> {
+        let this = self.clone();
+        async move {
+            for _ in 0..EXPORT_ATTEMPTS {
+                let before = this.lair_get_last_entry_index().await?;
+                let dir = lair_dir.clone();
+                let files = tokio::task::spawn_blocking(move || read_lair_dir(dir.as_deref()))
+                    .await
+                    .map_err(|e| KeystoreError::Other(e.to_string()))??;
+                let after = this.lair_get_last_entry_index().await?;
+                if *before == *after {
+                    return tokio::task::spawn_blocking(move || {
+                        encrypt_lair_files(files, &passphrase)
+                    })
+                    .await
+                    .map_err(|e| KeystoreError::Other(e.to_string()))?;
+                }
+            }
+            Err(KeystoreError::Other(
+                "The keystore kept changing while it was being backed up".into(),
+            ))
+        }
+        .boxed()
+        .into()
+    }
+
+    fn import_keystore(
+        &self,
+        lair_dir: Option<PathBuf>,
+        backup: KeystoreBackup,
+        passphrase: String,
+    ) -> KeystoreApiFuture<()> {
+        let this = self.clone();
+        async move {
+            if !this.list_sign_keypairs().await?.is_empty() {
+                return Err(KeystoreError::Other(
+                    "Refusing to import a keystore backup over a keystore which already holds agent keys".into(),
+                ));
+            }
+            tokio::task::spawn_blocking(move || {
+                stage_restore(&backup, &passphrase, lair_dir.as_deref())
+            })
+            .await
+            .map_err(|e| KeystoreError::Other(e.to_string()))?
+        }
+        .boxed()
+        .into()
+    }
 }
 
 #[cfg(test)]
@@ -213,4 +315,63 @@ mod tests {
         assert_eq!(cert1, cert2);
         assert_eq!(priv1, priv2);
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_list_sign_keypairs() {
+        let keystore = spawn_test_keystore().await.unwrap();
+        assert!(keystore.list_sign_keypairs().await.unwrap().is_empty());
+        let key1 = keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await
+            .unwrap();
+        let key2 = keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await
+            .unwrap();
+        // Other kinds of entries aren't listed
+        keystore.get_or_create_first_tls_cert().await.unwrap();
+        assert_eq!(
+            keystore.list_sign_keypairs().await.unwrap(),
+            vec![key1, key2]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_export_and_import_keystore() {
+        let keystore = spawn_test_keystore().await.unwrap();
+        let from = tempdir::TempDir::new("lair_from").unwrap();
+        std::fs::write(from.path().join("store.sqlite3"), b"secret keys").unwrap();
+        let backup = keystore
+            .export_keystore(Some(from.path().to_path_buf()), "hunter2".into())
+            .await
+            .unwrap();
+
+        // The restore is staged next to the lair directory
+        let dir = tempdir::TempDir::new("lair_to").unwrap();
+        let to = dir.path().join("keystore");
+        assert_eq!(
+            keystore
+                .import_keystore(Some(to.clone()), backup.clone(), "wrong".into())
+                .await,
+            Err(KeystoreError::DecryptionFailed)
+        );
+        keystore
+            .import_keystore(Some(to.clone()), backup.clone(), "hunter2".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read(staged_restore_path(Some(&to)).join("store.sqlite3")).unwrap(),
+            b"secret keys"
+        );
+
+        // The agent keys of a keystore are never replaced
+        keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await
+            .unwrap();
+        assert!(keystore
+            .import_keystore(Some(to.clone()), backup, "hunter2".into())
+            .await
+            .is_err());
+    }
 }
@@ -1,15 +1,38 @@
 //! Keystore backed by lair_keystore_client.
 
+use crate::backup::apply_staged_restore;
+use crate::passphrase::*;
 use crate::*;
 use ghost_actor::dependencies::futures::future::FutureExt;
 use ghost_actor::dependencies::futures::stream::StreamExt;
 use lair_keystore_api::actor::*;
 use lair_keystore_api::*;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+/// The passphrase lair is unlocked with when no passphrase service is given
+const BLANK_PASSPHRASE: &str = "[blank-passphrase]";
 
 /// Spawn a new keystore backed by lair_keystore_client.
+///
+/// Lair is unlocked with the passphrase provided by the passphrase service,
+/// or a blank passphrase if there is none. The keystore is read from right
+/// away, so that a wrong passphrase fails here with
+/// [KeystoreError::IncorrectPassphrase], rather than the first time a key is
+/// used.
+///
+/// A restore staged with [KeystoreSenderExt::import_keystore] replaces the
+/// lair directory before lair is started, which fails if lair is still running.
 pub async fn spawn_lair_keystore(
     lair_dir: Option<&std::path::Path>,
+    passphrase_service: Option<PassphraseServiceImpl>,
 ) -> KeystoreApiResult<KeystoreSender> {
+    if apply_staged_restore(lair_dir)? {
+        tracing::info!("Restored the keystore from a staged backup");
+    }
+
     let mut config = Config::builder();
     if let Some(lair_dir) = lair_dir {
         config = config.set_root_path(lair_dir);
@@ -17,18 +40,50 @@ pub async fn spawn_lair_keystore(
     let config = config.build();
     let (api, mut evt) = lair_keystore_client::assert_running_lair_and_connect(config).await?;
 
-    // TODO - actual passphrase handling
-    tokio::task::spawn(async move {
-        while let Some(r) = evt.next().await {
-            match r {
-                LairClientEvent::RequestUnlockPassphrase { respond, .. } => {
-                    respond.respond(Ok(async move { Ok("[blank-passphrase]".to_string()) }
+    let passphrase_service = passphrase_service.unwrap_or_else(|| {
+        Arc::new(PassphraseServiceFromConfig::new(BLANK_PASSPHRASE.into())) as PassphraseServiceImpl
+    });
+    let requested = Arc::new(AtomicBool::new(false));
+    let service_error = Arc::new(Mutex::new(None));
+    {
+        let requested = requested.clone();
+        let service_error = service_error.clone();
+        tokio::task::spawn(async move {
+            while let Some(r) = evt.next().await {
+                match r {
+                    LairClientEvent::RequestUnlockPassphrase { respond, .. } => {
+                        requested.store(true, Ordering::SeqCst);
+                        let passphrase = passphrase_service.request_passphrase();
+                        let service_error = service_error.clone();
+                        respond.respond(Ok(async move {
+                            match passphrase.await {
+                                // Hand the passphrase over to lair without
+                                // leaving a copy behind
+                                Ok(mut passphrase) => Ok(std::mem::take(&mut *passphrase)),
+                                Err(e) => {
+                                    let lair_error = LairError::other(e.to_string());
+                                    *service_error.lock().unwrap() = Some(e);
+                                    Err(lair_error)
+                                }
+                            }
+                        }
                         .boxed()
                         .into()));
+                    }
                 }
             }
+        });
+    }
+
+    if let Err(e) = api.lair_get_last_entry_index().await {
+        if let Some(service_error) = service_error.lock().unwrap().take() {
+            return Err(service_error);
         }
-    });
+        if requested.load(Ordering::SeqCst) {
+            return Err(KeystoreError::IncorrectPassphrase(e));
+        }
+        return Err(e.into());
+    }
 
     Ok(api)
 }
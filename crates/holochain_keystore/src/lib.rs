@@ -44,5 +44,8 @@ use keystore_actor::*;
 mod agent_pubkey_ext;
 pub use agent_pubkey_ext::*;
 
+pub mod backup;
 pub mod lair_keystore;
+pub mod passphrase;
+pub mod private_entry_key;
 pub mod test_keystore;
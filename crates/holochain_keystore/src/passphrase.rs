@@ -0,0 +1,211 @@
+//! Services providing the passphrase which unlocks an encrypted keystore.
+//!
+//! Lair asks for the passphrase when it needs to unlock the keystore, and the
+//! conductor answers with whichever [PassphraseService] it was configured
+//! with.
+//!
+//! Passphrases are handed around as [Zeroizing] strings, so that they are
+//! wiped from memory as soon as they are dropped.
+
+use crate::*;
+use ghost_actor::dependencies::futures::future::FutureExt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// The message sent over a unix socket to request the passphrase.
+/// The passphrase is expected in reply, ended by a newline.
+pub const PASSPHRASE_REQUEST: &[u8] = b"request_passphrase\n";
+
+/// How long to wait for the passphrase over a unix socket by default.
+/// The UI on the other end is likely prompting a user for it.
+pub const DEFAULT_PASSPHRASE_SOCKET_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// A shared PassphraseService
+pub type PassphraseServiceImpl = Arc<dyn PassphraseService>;
+
+/// Something which can provide the passphrase of the keystore when asked
+pub trait PassphraseService: 'static + Send + Sync {
+    /// Get the passphrase
+    fn request_passphrase(&self) -> KeystoreApiFuture<Zeroizing<String>>;
+}
+
+/// Prompts for the passphrase on the terminal, without echoing it
+pub struct PassphraseServiceCmd;
+
+impl PassphraseService for PassphraseServiceCmd {
+    fn request_passphrase(&self) -> KeystoreApiFuture<Zeroizing<String>> {
+        async move {
+            let passphrase = tokio::task::spawn_blocking(|| {
+                rpassword::read_password_from_tty(Some("Keystore passphrase: "))
+            })
+            .await
+            .map_err(|e| KeystoreError::Other(e.to_string()))?
+            .map_err(|e| {
+                KeystoreError::PassphraseUnavailable(format!("Could not read from the tty: {}", e))
+            })?;
+            Ok(Zeroizing::new(passphrase))
+        }
+        .boxed()
+        .into()
+    }
+}
+
+/// Requests the passphrase over a unix domain socket, so that a UI wrapping
+/// the conductor can prompt the user for it.
+/// Each request connects to the socket, sends [PASSPHRASE_REQUEST], and reads
+/// the passphrase until a newline, which may be preceded by a carriage return.
+/// The request fails if no passphrase arrives within the timeout.
+pub struct PassphraseServiceUnixSocket {
+    path: PathBuf,
+    timeout: Duration,
+}
+
+impl PassphraseServiceUnixSocket {
+    /// Request the passphrase over the socket at this path, waiting up to
+    /// [DEFAULT_PASSPHRASE_SOCKET_TIMEOUT] for it
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            timeout: DEFAULT_PASSPHRASE_SOCKET_TIMEOUT,
+        }
+    }
+
+    /// Wait up to this long for the passphrase instead
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl PassphraseService for PassphraseServiceUnixSocket {
+    #[cfg(unix)]
+    fn request_passphrase(&self) -> KeystoreApiFuture<Zeroizing<String>> {
+        use tokio::io::AsyncBufReadExt;
+        use tokio::io::AsyncWriteExt;
+
+        let path = self.path.clone();
+        let timeout = self.timeout;
+        async move {
+            let unavailable = |e: std::io::Error| {
+                KeystoreError::PassphraseUnavailable(format!(
+                    "Could not request it over the socket {}: {}",
+                    path.display(),
+                    e
+                ))
+            };
+            let mut reply = Zeroizing::new(Vec::new());
+            let request = async {
+                let mut stream = tokio::net::UnixStream::connect(&path).await?;
+                stream.write_all(PASSPHRASE_REQUEST).await?;
+                tokio::io::BufReader::new(stream)
+                    .read_until(b'\n', &mut reply)
+                    .await
+            };
+            tokio::time::timeout(timeout, request)
+                .await
+                .map_err(|_| unavailable(std::io::ErrorKind::TimedOut.into()))?
+                .map_err(unavailable)?;
+            if reply.pop() != Some(b'\n') {
+                return Err(unavailable(std::io::ErrorKind::UnexpectedEof.into()));
+            }
+            if reply.last() == Some(&b'\r') {
+                reply.pop();
+            }
+            let passphrase = std::str::from_utf8(&reply).map_err(|_| {
+                KeystoreError::PassphraseUnavailable("The passphrase is not valid utf8".into())
+            })?;
+            Ok(Zeroizing::new(passphrase.to_string()))
+        }
+        .boxed()
+        .into()
+    }
+
+    #[cfg(not(unix))]
+    fn request_passphrase(&self) -> KeystoreApiFuture<Zeroizing<String>> {
+        async move {
+            Err(KeystoreError::PassphraseUnavailable(
+                "Unix sockets are not supported on this platform".into(),
+            ))
+        }
+        .boxed()
+        .into()
+    }
+}
+
+/// Provides a passphrase given up front, e.g. in the conductor config
+pub struct PassphraseServiceFromConfig {
+    passphrase: Zeroizing<String>,
+}
+
+impl PassphraseServiceFromConfig {
+    /// Always provide this passphrase
+    pub fn new(passphrase: String) -> Self {
+        Self {
+            passphrase: Zeroizing::new(passphrase),
+        }
+    }
+}
+
+impl PassphraseService for PassphraseServiceFromConfig {
+    fn request_passphrase(&self) -> KeystoreApiFuture<Zeroizing<String>> {
+        let passphrase = self.passphrase.clone();
+        async move { Ok(passphrase) }.boxed().into()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn passphrase_over_unix_socket() {
+        let dir = tempdir::TempDir::new("passphrase_over_unix_socket").unwrap();
+        let path = dir.path().join("passphrase.sock");
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let service = PassphraseServiceUnixSocket::new(path);
+
+        // Reply to the request with the passphrase
+        let ui = tokio::task::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; PASSPHRASE_REQUEST.len()];
+            stream.read_exact(&mut request).await.unwrap();
+            assert_eq!(request, PASSPHRASE_REQUEST);
+            stream.write_all(b"hunter2\n").await.unwrap();
+
+            // Reply with a carriage return before the newline
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.write_all(b"hunter2\r\n").await.unwrap();
+
+            // Hang up without replying
+            let (stream, _) = listener.accept().await.unwrap();
+            drop(stream);
+
+            // Never reply
+            listener.accept().await.unwrap()
+        });
+
+        assert_eq!(
+            service.request_passphrase().await.unwrap().as_str(),
+            "hunter2"
+        );
+        assert_eq!(
+            service.request_passphrase().await.unwrap().as_str(),
+            "hunter2"
+        );
+        assert!(matches!(
+            service.request_passphrase().await,
+            Err(KeystoreError::PassphraseUnavailable(_))
+        ));
+
+        let service = service.with_timeout(Duration::from_millis(100));
+        assert!(matches!(
+            service.request_passphrase().await,
+            Err(KeystoreError::PassphraseUnavailable(_))
+        ));
+        ui.await.unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+//! The key which an agent's private entries are encrypted with at rest.
+//!
+//! The key is derived from the agent's signing keypair, by hashing the agent's
+//! signature of a fixed context. Ed25519 signatures are deterministic, so the
+//! same key is derived every time, but only by a keystore which holds the
+//! agent's private key. The database alone doesn't reveal private entries.
+//!
+//! Entries are encrypted with XSalsa20Poly1305, a.k.a. libsodium
+//! crypto_secretbox, under a random nonce which is stored with the ciphertext.
+//!
+//! The key is derived once, when a cell is created, and kept only by the
+//! cell's environment. It is zeroed when the last copy of it is dropped.
+
+use crate::*;
+use ghost_actor::dependencies::futures::future::FutureExt;
+use holo_hash::AgentPubKey;
+use holochain_zome_types::signature::Sign;
+use std::sync::Arc;
+use xsalsa20poly1305::aead::generic_array::GenericArray;
+use xsalsa20poly1305::aead::Aead;
+use xsalsa20poly1305::aead::NewAead;
+use xsalsa20poly1305::XSalsa20Poly1305;
+use zeroize::Zeroize;
+use zeroize::Zeroizing;
+
+/// The data the agent signs to derive the key.
+/// Changing this changes the key, so existing private entries could no longer
+/// be read.
+pub const PRIVATE_ENTRY_KEY_CONTEXT: &[u8] = b"holochain private entry encryption key v1";
+
+/// The length of the random nonce stored before each ciphertext
+const NONCE_LEN: usize = 24;
+
+/// A symmetric key for encrypting an agent's private entries
+#[derive(Clone)]
+pub struct PrivateEntryKey(Arc<Zeroizing<[u8; 32]>>);
+
+impl std::fmt::Debug for PrivateEntryKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the key itself
+        f.write_str("PrivateEntryKey(..)")
+    }
+}
+
+impl PrivateEntryKey {
+    /// Derive the key for this agent, with the keystore which holds the
+    /// agent's private key
+    pub fn for_agent(
+        keystore: &KeystoreSender,
+        agent: &AgentPubKey,
+    ) -> KeystoreApiFuture<PrivateEntryKey> {
+        let keystore = keystore.clone();
+        let agent = agent.clone();
+        async move {
+            let mut signature = keystore
+                .sign(Sign::new_raw(agent, PRIVATE_ENTRY_KEY_CONTEXT.to_vec()))
+                .await?;
+            let mut hash = holo_hash::encode::blake2b_256(&signature.0[..]);
+            let mut key = Zeroizing::new([0; 32]);
+            key.copy_from_slice(&hash);
+            signature.0.zeroize();
+            hash.zeroize();
+            Ok(PrivateEntryKey(Arc::new(key)))
+        }
+        .boxed()
+        .into()
+    }
+
+    /// Encrypt the data under a random nonce, returning the nonce followed by
+    /// the ciphertext
+    pub fn encrypt(&self, data: &[u8]) -> KeystoreApiResult<Vec<u8>> {
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let ciphertext = self
+            .cipher()
+            .encrypt(GenericArray::from_slice(&nonce), data)
+            .map_err(|_| KeystoreError::Other("Could not encrypt a private entry".into()))?;
+        let mut encrypted = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        encrypted.extend_from_slice(&nonce);
+        encrypted.extend(ciphertext);
+        Ok(encrypted)
+    }
+
+    /// The inverse of [PrivateEntryKey::encrypt].
+    /// Fails if the data was not encrypted with this key or has been
+    /// tampered with.
+    pub fn decrypt(&self, encrypted: &[u8]) -> KeystoreApiResult<Vec<u8>> {
+        if encrypted.len() < NONCE_LEN {
+            return Err(KeystoreError::DecryptionFailed);
+        }
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_LEN);
+        self.cipher()
+            .decrypt(GenericArray::from_slice(nonce), ciphertext)
+            .map_err(|_| KeystoreError::DecryptionFailed)
+    }
+
+    fn cipher(&self) -> XSalsa20Poly1305 {
+        XSalsa20Poly1305::new(GenericArray::from_slice(&self.0[..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_keystore::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn private_entry_key_round_trip() {
+        let keystore = spawn_test_keystore().await.unwrap();
+        let alice = AgentPubKey::new_from_pure_entropy(&keystore).await.unwrap();
+        let bob = AgentPubKey::new_from_pure_entropy(&keystore).await.unwrap();
+        let alice_key = PrivateEntryKey::for_agent(&keystore, &alice).await.unwrap();
+        let bob_key = PrivateEntryKey::for_agent(&keystore, &bob).await.unwrap();
+
+        let data = b"my diary";
+        let encrypted = alice_key.encrypt(data).unwrap();
+        assert!(!encrypted.windows(data.len()).any(|w| w == &data[..]));
+        assert_eq!(alice_key.decrypt(&encrypted).unwrap(), data.to_vec());
+
+        // The nonce is random, so the same data never encrypts the same way
+        assert_ne!(alice_key.encrypt(data).unwrap(), encrypted);
+
+        // Only alice's key can decrypt it
+        assert_eq!(
+            bob_key.decrypt(&encrypted),
+            Err(KeystoreError::DecryptionFailed)
+        );
+        let mut tampered = encrypted;
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            alice_key.decrypt(&tampered),
+            Err(KeystoreError::DecryptionFailed)
+        );
+    }
+}
@@ -68,6 +68,15 @@ pub enum DbName {
     ValidationLimbo,
     /// KVV store to accumulate validation receipts for a published EntryHash
     ValidationReceipts,
+    /// KV store of the number of zome calls each use-limited capability grant
+    /// has given access to, keyed by the HeaderHash of the grant
+    CapGrantUses,
+    /// Single-value KV store holding the countersigning session, if any,
+    /// which the source chain is locked for
+    ChainLock,
+    /// KV store of the forks of source chains this cell has detected,
+    /// keyed by the author of the forked chain
+    ChainForks,
     /// Single store for all known agents on the network
     Agent,
 }
@@ -99,6 +108,9 @@ impl DbName {
             IntegrationLimbo => Single,
             ValidationLimbo => Single,
             ValidationReceipts => Multi,
+            CapGrantUses => Single,
+            ChainLock => Single,
+            ChainForks => Single,
             Agent => Single,
         }
     }
@@ -170,6 +182,12 @@ lazy_static! {
     pub static ref VALIDATION_LIMBO: DbKey<SingleStore> = DbKey::new(DbName::ValidationLimbo);
     /// The key to access the ValidationReceipts database
     pub static ref VALIDATION_RECEIPTS: DbKey<MultiStore> = DbKey::new(DbName::ValidationReceipts);
+    /// The key to access the CapGrantUses database
+    pub static ref CAP_GRANT_USES: DbKey<SingleStore> = DbKey::new(DbName::CapGrantUses);
+    /// The key to access the ChainLock database
+    pub static ref CHAIN_LOCK: DbKey<SingleStore> = DbKey::new(DbName::ChainLock);
+    /// The key to access the ChainForks database
+    pub static ref CHAIN_FORKS: DbKey<SingleStore> = DbKey::new(DbName::ChainForks);
     /// The key to access the Agent database
     pub static ref AGENT: DbKey<SingleStore> = DbKey::new(DbName::Agent);
 }
@@ -179,8 +197,14 @@ lazy_static! {
 }
 
 /// Get access to the singleton database manager ([GetDb]),
-/// in order to access individual LMDB databases
-pub(super) fn initialize_databases(rkv: &Rkv, kind: &EnvironmentKind) -> DatabaseResult<()> {
+/// in order to access individual LMDB databases.
+/// If `create` is false the databases must already exist, so that a
+/// read-only environment can be opened.
+pub(super) fn initialize_databases(
+    rkv: &Rkv,
+    kind: &EnvironmentKind,
+    create: bool,
+) -> DatabaseResult<()> {
     let mut dbmap = DB_MAP_MAP.write();
     let path = rkv.path().to_owned();
     match dbmap.entry(path.clone()) {
@@ -193,7 +217,7 @@ pub(super) fn initialize_databases(rkv: &Rkv, kind: &EnvironmentKind) -> Databas
         hash_map::Entry::Vacant(e) => {
             e.insert({
                 let mut um = UniversalMap::new();
-                register_databases(&rkv, kind, &mut um)?;
+                register_databases(&rkv, kind, create, &mut um)?;
                 um
             });
         }
@@ -215,39 +239,47 @@ pub(super) fn get_db<V: 'static + Copy + Send + Sync>(
     Ok(db)
 }
 
-fn register_databases(env: &Rkv, kind: &EnvironmentKind, um: &mut DbMap) -> DatabaseResult<()> {
+fn register_databases(
+    env: &Rkv,
+    kind: &EnvironmentKind,
+    create: bool,
+    um: &mut DbMap,
+) -> DatabaseResult<()> {
     match kind {
         EnvironmentKind::Cell(_) => {
-            register_db(env, um, &*ELEMENT_VAULT_PUBLIC_ENTRIES)?;
-            register_db(env, um, &*ELEMENT_VAULT_PRIVATE_ENTRIES)?;
-            register_db(env, um, &*ELEMENT_VAULT_HEADERS)?;
-            register_db(env, um, &*META_VAULT_SYS)?;
-            register_db(env, um, &*META_VAULT_LINKS)?;
-            register_db(env, um, &*META_VAULT_MISC)?;
-            register_db(env, um, &*CHAIN_SEQUENCE)?;
-            register_db(env, um, &*ELEMENT_CACHE_ENTRIES)?;
-            register_db(env, um, &*ELEMENT_CACHE_HEADERS)?;
-            register_db(env, um, &*CACHE_SYSTEM_META)?;
-            register_db(env, um, &*CACHE_LINKS_META)?;
-            register_db(env, um, &*CACHE_STATUS_META)?;
-            register_db(env, um, &*AUTHORED_DHT_OPS)?;
-            register_db(env, um, &*INTEGRATED_DHT_OPS)?;
-            register_db(env, um, &*INTEGRATION_LIMBO)?;
-            register_db(env, um, &*VALIDATION_LIMBO)?;
-            register_db(env, um, &*VALIDATION_RECEIPTS)?;
+            register_db(env, create, um, &*ELEMENT_VAULT_PUBLIC_ENTRIES)?;
+            register_db(env, create, um, &*ELEMENT_VAULT_PRIVATE_ENTRIES)?;
+            register_db(env, create, um, &*ELEMENT_VAULT_HEADERS)?;
+            register_db(env, create, um, &*META_VAULT_SYS)?;
+            register_db(env, create, um, &*META_VAULT_LINKS)?;
+            register_db(env, create, um, &*META_VAULT_MISC)?;
+            register_db(env, create, um, &*CHAIN_SEQUENCE)?;
+            register_db(env, create, um, &*ELEMENT_CACHE_ENTRIES)?;
+            register_db(env, create, um, &*ELEMENT_CACHE_HEADERS)?;
+            register_db(env, create, um, &*CACHE_SYSTEM_META)?;
+            register_db(env, create, um, &*CACHE_LINKS_META)?;
+            register_db(env, create, um, &*CACHE_STATUS_META)?;
+            register_db(env, create, um, &*AUTHORED_DHT_OPS)?;
+            register_db(env, create, um, &*INTEGRATED_DHT_OPS)?;
+            register_db(env, create, um, &*INTEGRATION_LIMBO)?;
+            register_db(env, create, um, &*VALIDATION_LIMBO)?;
+            register_db(env, create, um, &*VALIDATION_RECEIPTS)?;
+            register_db(env, create, um, &*CAP_GRANT_USES)?;
+            register_db(env, create, um, &*CHAIN_LOCK)?;
+            register_db(env, create, um, &*CHAIN_FORKS)?;
         }
         EnvironmentKind::Conductor => {
-            register_db(env, um, &*CONDUCTOR_STATE)?;
+            register_db(env, create, um, &*CONDUCTOR_STATE)?;
         }
         EnvironmentKind::Wasm => {
-            register_db(env, um, &*WASM)?;
-            register_db(env, um, &*DNA_DEF)?;
-            register_db(env, um, &*ENTRY_DEF)?;
+            register_db(env, create, um, &*WASM)?;
+            register_db(env, create, um, &*DNA_DEF)?;
+            register_db(env, create, um, &*ENTRY_DEF)?;
         }
         EnvironmentKind::P2p => {
-            register_db(env, um, &*AGENT)?;
+            register_db(env, create, um, &*AGENT)?;
             // @todo health metrics for the space
-            // register_db(env, um, &*HEALTH)?;
+            // register_db(env, create, um, &*HEALTH)?;
         }
     }
     Ok(())
@@ -255,22 +287,27 @@ fn register_databases(env: &Rkv, kind: &EnvironmentKind, um: &mut DbMap) -> Data
 
 fn register_db<V: 'static + Send + Sync>(
     env: &Rkv,
+    create: bool,
     um: &mut DbMap,
     key: &DbKey<V>,
 ) -> DatabaseResult<()> {
     let db_name = key.key();
     let db_str = format!("{}", db_name);
+    let options = || StoreOptions {
+        create,
+        ..StoreOptions::create()
+    };
     let _ = match db_name.kind() {
         DbKind::Single => um.insert(
             key.with_value_type(),
-            env.open_single(db_str.as_str(), StoreOptions::create())?,
+            env.open_single(db_str.as_str(), options())?,
         ),
         DbKind::SingleInt => um.insert(
             key.with_value_type(),
-            env.open_integer::<&str, IntKey>(db_str.as_str(), StoreOptions::create())?,
+            env.open_integer::<&str, IntKey>(db_str.as_str(), options())?,
         ),
         DbKind::Multi => {
-            let mut opts = StoreOptions::create();
+            let mut opts = options();
 
             // This is needed for the optional put flag NO_DUP_DATA on KvvBufUsed.
             // As far as I can tell, if we are not using NO_DUP_DATA, it will
@@ -9,9 +9,11 @@ use crate::error::DatabaseResult;
 use crate::transaction::Reader;
 use crate::transaction::Writer;
 use derive_more::Into;
+use holochain_keystore::private_entry_key::PrivateEntryKey;
 use holochain_keystore::KeystoreSender;
 use holochain_zome_types::cell::CellId;
 use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
 use parking_lot::RwLock;
 use parking_lot::RwLockReadGuard;
 use rkv::EnvironmentFlags;
@@ -95,6 +97,7 @@ pub struct EnvironmentRead {
     kind: EnvironmentKind,
     path: PathBuf,
     keystore: KeystoreSender,
+    private_entry_key: Arc<OnceCell<PrivateEntryKey>>,
 }
 
 impl EnvironmentRead {
@@ -122,6 +125,36 @@ impl EnvironmentRead {
     pub fn path(&self) -> &PathBuf {
         &self.path
     }
+
+    /// Open an existing environment which can only be read from, e.g. to
+    /// inspect the databases of a conductor which isn't running.
+    /// Unlike [EnvironmentWrite::new] nothing is created, so the environment
+    /// and all of its databases must already exist.
+    pub fn open_read_only(
+        path_prefix: &Path,
+        kind: EnvironmentKind,
+        keystore: KeystoreSender,
+    ) -> DatabaseResult<EnvironmentRead> {
+        let path = path_prefix.join(kind.path());
+        if !path.is_dir() {
+            return Err(DatabaseError::EnvironmentMissing(path));
+        }
+        let rkv = rkv_builder(None, Some(EnvironmentFlags::READ_ONLY))(&path)?;
+        initialize_databases(&rkv, &kind, false)?;
+        Ok(EnvironmentRead {
+            arc: Arc::new(RwLock::new(rkv)),
+            kind,
+            keystore,
+            path,
+            private_entry_key: Arc::new(OnceCell::new()),
+        })
+    }
+
+    /// The key private entries in this environment are encrypted with,
+    /// if the cell it belongs to has been given one
+    pub fn private_entry_key(&self) -> Option<&PrivateEntryKey> {
+        self.private_entry_key.get()
+    }
 }
 
 impl GetDb for EnvironmentWrite {
@@ -169,12 +202,13 @@ impl EnvironmentWrite {
                 .insert({
                     let rkv = rkv_builder(None, None)(&path)?;
                     tracing::debug!("Initializing databases for path {:?}", path);
-                    initialize_databases(&rkv, &kind)?;
+                    initialize_databases(&rkv, &kind, true)?;
                     EnvironmentWrite(EnvironmentRead {
                         arc: Arc::new(RwLock::new(rkv)),
                         kind,
                         keystore,
                         path,
+                        private_entry_key: Arc::new(OnceCell::new()),
                     })
                 })
                 .clone(),
@@ -191,6 +225,14 @@ impl EnvironmentWrite {
         Self::new(path_prefix, EnvironmentKind::Cell(cell_id), keystore)
     }
 
+    /// Give this environment the key its private entries are encrypted with.
+    /// The key is derived from the keystore once, when the cell is created,
+    /// so that reading and writing private entries never waits on it.
+    /// An environment keeps the first key it is given.
+    pub fn set_private_entry_key(&self, key: PrivateEntryKey) {
+        let _ = self.0.private_entry_key.set(key);
+    }
+
     /// Get a read-only lock guard on the environment.
     /// This reference can create read-write transactions.
     pub fn guard(&self) -> EnvironmentWriteRef<'_> {
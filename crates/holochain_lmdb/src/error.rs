@@ -31,6 +31,12 @@ pub enum DatabaseError {
     )]
     NoPrivateDb(String),
 
+    #[error("The environment at {0} hasn't been given a key to encrypt its private entries with")]
+    NoPrivateEntryKey(PathBuf),
+
+    #[error("A private entry is stored in an unknown format, version {0}")]
+    UnknownPrivateEntryVersion(u8),
+
     // TODO: the following is necessary for actual backtraces, and would be ideal,
     // but requires the unstable "backtrace" feature, so we are doing without for now.
     //
@@ -3,6 +3,7 @@
 use crate::env::EnvironmentKind;
 use crate::env::EnvironmentWrite;
 use crate::prelude::BufKey;
+use holochain_keystore::private_entry_key::PrivateEntryKey;
 use holochain_keystore::KeystoreSender;
 use holochain_zome_types::test_utils::fake_cell_id;
 use shrinkwraprs::Shrinkwrap;
@@ -59,11 +60,20 @@ pub fn test_keystore() -> holochain_keystore::KeystoreSender {
 
 fn test_env(kind: EnvironmentKind) -> TestEnvironment {
     let tmpdir = Arc::new(TempDir::new("holochain-test-environments").unwrap());
-    TestEnvironment {
-        env: EnvironmentWrite::new(tmpdir.path(), kind, test_keystore())
-            .expect("Couldn't create test LMDB environment"),
-        tmpdir,
+    let keystore = test_keystore();
+    let env = EnvironmentWrite::new(tmpdir.path(), kind.clone(), keystore.clone())
+        .expect("Couldn't create test LMDB environment");
+    // Cells are given the key for their private entries as they are created
+    if let EnvironmentKind::Cell(cell_id) = kind {
+        let key = tokio_helper::block_on(
+            PrivateEntryKey::for_agent(&keystore, cell_id.agent_pubkey()),
+            std::time::Duration::from_secs(1),
+        )
+        .expect("timeout elapsed")
+        .expect("Couldn't derive the private entry key");
+        env.set_private_entry_key(key);
     }
+    TestEnvironment { env, tmpdir }
 }
 
 /// Create a fresh set of test environments with a new TempDir
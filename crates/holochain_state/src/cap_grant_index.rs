@@ -0,0 +1,80 @@
+//! The capability index, which counts the zome calls each use-limited
+//! capability grant on the source chain has given access to.
+//!
+//! Unlike the rest of the source chain, uses aren't kept in scratch space
+//! until the workspace is flushed. A use is checked against the grant's limit
+//! and counted in a single write transaction with [`CapGrantIndex::use_grant`],
+//! so two calls can never both be let through on the last remaining use.
+
+use holo_hash::HeaderHash;
+use holochain_lmdb::buffer::KvStore;
+use holochain_lmdb::buffer::KvStoreT;
+use holochain_lmdb::db::GetDb;
+use holochain_lmdb::db::CAP_GRANT_USES;
+use holochain_lmdb::error::DatabaseResult;
+use holochain_lmdb::prelude::*;
+
+/// A store counting the uses of capability grants
+pub struct CapGrantIndex {
+    store: KvStore<HeaderHash, u32>,
+}
+
+impl CapGrantIndex {
+    /// Create a new instance
+    pub fn new(env: EnvironmentRead) -> DatabaseResult<Self> {
+        Ok(Self {
+            store: KvStore::new(env.get_db(&*CAP_GRANT_USES)?),
+        })
+    }
+
+    /// The number of zome calls the grant committed by this header has given
+    /// access to
+    pub fn uses<R: Readable>(&self, r: &R, grant_header: &HeaderHash) -> DatabaseResult<u32> {
+        Ok(self.store.get(r, grant_header)?.unwrap_or(0))
+    }
+
+    /// Count one more use of the grant committed by this header in its own
+    /// transaction, unless it has already given access to `max_uses` zome calls.
+    /// Returns whether the use was counted.
+    pub fn use_grant(
+        env: &EnvironmentWrite,
+        grant_header: &HeaderHash,
+        max_uses: u32,
+    ) -> DatabaseResult<bool> {
+        let store: KvStore<HeaderHash, u32> = KvStore::new(env.get_db(&*CAP_GRANT_USES)?);
+        env.guard().with_commit(|writer| {
+            let uses = store.get(writer, grant_header)?.unwrap_or(0);
+            if uses >= max_uses {
+                return Ok(false);
+            }
+            store.put(writer, grant_header, &(uses + 1))?;
+            Ok(true)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_lmdb::test_utils::test_cell_env;
+    use holochain_types::test_utils::fake_header_hash;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn uses_stop_at_the_limit() -> DatabaseResult<()> {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let grant = fake_header_hash(1);
+
+        assert!(CapGrantIndex::use_grant(&env, &grant, 2)?);
+        assert!(CapGrantIndex::use_grant(&env, &grant, 2)?);
+        // The last use has been taken
+        assert!(!CapGrantIndex::use_grant(&env, &grant, 2)?);
+
+        let index = CapGrantIndex::new(env.clone().into())?;
+        let env_ref = env.guard();
+        let reader = env_ref.reader()?;
+        assert_eq!(index.uses(&reader, &grant)?, 2);
+        assert_eq!(index.uses(&reader, &fake_header_hash(2))?, 0);
+        Ok(())
+    }
+}
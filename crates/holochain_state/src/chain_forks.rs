@@ -0,0 +1,209 @@
+//! The forks of source chains which this cell has detected, either as an
+//! authority holding the author's agent activity, or as the author checking
+//! its own chain against the authorities.
+//!
+//! Only the earliest fork of each author's chain is kept, as everything after
+//! it is in question anyway. Each fork remembers whether it has been signalled
+//! to the author's conductor, so that it is only signalled once.
+//!
+//! Forks are kept in scratch space until the workspace is flushed. Flushing
+//! keeps the earliest of the persisted fork and the flushed one, so two
+//! workspaces detecting forks at the same time can't undo each other.
+
+use fallible_iterator::FallibleIterator;
+use holo_hash::AgentPubKey;
+use holochain_lmdb::buffer::BufferedStore;
+use holochain_lmdb::buffer::KvStore;
+use holochain_lmdb::buffer::KvStoreT;
+use holochain_lmdb::db::GetDb;
+use holochain_lmdb::db::CHAIN_FORKS;
+use holochain_lmdb::error::DatabaseError;
+use holochain_lmdb::error::DatabaseResult;
+use holochain_lmdb::prelude::*;
+use holochain_types::prelude::*;
+use std::collections::HashMap;
+
+/// A fork of an author's source chain which this cell has detected
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DetectedFork {
+    /// The author of the forked chain
+    pub author: AgentPubKey,
+    /// Where the chain forked
+    pub fork: ChainFork,
+    /// When this cell first detected the fork
+    pub detected_at: Timestamp,
+    /// Whether the fork has been signalled to the author's conductor
+    pub signalled: bool,
+}
+
+/// A BufferedStore of the earliest detected fork of each author's chain
+pub struct ChainForks {
+    store: KvStore<AgentPubKey, DetectedFork>,
+    scratch: HashMap<AgentPubKey, DetectedFork>,
+}
+
+impl ChainForks {
+    /// Create a new instance
+    pub fn new(env: EnvironmentRead) -> DatabaseResult<Self> {
+        Ok(Self {
+            store: KvStore::new(env.get_db(&*CHAIN_FORKS)?),
+            scratch: HashMap::new(),
+        })
+    }
+
+    /// The earliest detected fork of this author's chain, if any,
+    /// including changes not yet flushed
+    pub fn get<R: Readable>(
+        &self,
+        r: &R,
+        author: &AgentPubKey,
+    ) -> DatabaseResult<Option<DetectedFork>> {
+        match self.scratch.get(author) {
+            Some(fork) => Ok(Some(fork.clone())),
+            None => self.store.get(r, author),
+        }
+    }
+
+    /// Every detected fork, including changes not yet flushed
+    pub fn all<R: Readable>(&self, r: &R) -> DatabaseResult<Vec<DetectedFork>> {
+        let mut forks = self
+            .store
+            .iter(r)?
+            .map(|(_, fork)| Ok(fork))
+            .filter(|fork| Ok(!self.scratch.contains_key(&fork.author)))
+            .collect::<Vec<_>>()?;
+        forks.extend(self.scratch.values().cloned());
+        Ok(forks)
+    }
+
+    /// Record a fork of this author's chain.
+    /// Returns true if it is the earliest fork detected so far,
+    /// false if an earlier or equally early fork is already known.
+    pub fn record<R: Readable>(
+        &mut self,
+        r: &R,
+        author: AgentPubKey,
+        fork: ChainFork,
+    ) -> DatabaseResult<bool> {
+        if let Some(known) = self.get(r, &author)? {
+            if known.fork.fork_seq <= fork.fork_seq {
+                return Ok(false);
+            }
+        }
+        self.scratch.insert(
+            author.clone(),
+            DetectedFork {
+                author,
+                fork,
+                detected_at: timestamp::now(),
+                signalled: false,
+            },
+        );
+        Ok(true)
+    }
+
+    /// Note that the fork of this author's chain has been signalled
+    pub fn mark_signalled<R: Readable>(
+        &mut self,
+        r: &R,
+        author: &AgentPubKey,
+    ) -> DatabaseResult<()> {
+        if let Some(mut fork) = self.get(r, author)? {
+            fork.signalled = true;
+            self.scratch.insert(author.clone(), fork);
+        }
+        Ok(())
+    }
+}
+
+impl BufferedStore for ChainForks {
+    type Error = DatabaseError;
+
+    fn is_clean(&self) -> bool {
+        self.scratch.is_empty()
+    }
+
+    fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> DatabaseResult<()> {
+        for (author, mut fork) in self.scratch.drain() {
+            match self.store.get(writer, &author)? {
+                // An earlier fork was flushed in the meantime
+                Some(persisted) if persisted.fork.fork_seq < fork.fork.fork_seq => continue,
+                Some(persisted) if persisted.fork == fork.fork => {
+                    fork.detected_at = persisted.detected_at;
+                    fork.signalled |= persisted.signalled;
+                }
+                _ => (),
+            }
+            self.store.put(writer, &author, &fork)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_lmdb::test_utils::test_cell_env;
+    use holochain_types::test_utils::fake_agent_pubkey_1;
+    use holochain_types::test_utils::fake_header_hash;
+
+    fn fork(fork_seq: u32) -> ChainFork {
+        ChainFork {
+            fork_seq,
+            first_header: fake_header_hash(fork_seq as u8),
+            second_header: fake_header_hash(fork_seq as u8 + 100),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn keeps_the_earliest_fork() -> DatabaseResult<()> {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let author = fake_agent_pubkey_1();
+
+        let mut forks = ChainForks::new(env.clone().into())?;
+        {
+            let env_ref = env.guard();
+            let reader = env_ref.reader()?;
+            assert!(forks.record(&reader, author.clone(), fork(5))?);
+            assert!(!forks.record(&reader, author.clone(), fork(7))?);
+            forks.mark_signalled(&reader, &author)?;
+        }
+        env.guard()
+            .with_commit(|writer| forks.flush_to_txn(writer))?;
+
+        // Another workspace detects a later fork and then an earlier one,
+        // while one that doesn't know about the earlier fork flushes a later one
+        let mut forks1 = ChainForks::new(env.clone().into())?;
+        let mut forks2 = ChainForks::new(env.clone().into())?;
+        {
+            let env_ref = env.guard();
+            let reader = env_ref.reader()?;
+            assert!(!forks1.record(&reader, author.clone(), fork(6))?);
+            assert!(forks1.record(&reader, author.clone(), fork(3))?);
+            forks2.scratch.insert(
+                author.clone(),
+                DetectedFork {
+                    author: author.clone(),
+                    fork: fork(4),
+                    detected_at: timestamp::now(),
+                    signalled: false,
+                },
+            );
+        }
+        env.guard()
+            .with_commit(|writer| forks1.flush_to_txn(writer))?;
+        env.guard()
+            .with_commit(|writer| forks2.flush_to_txn(writer))?;
+
+        let forks = ChainForks::new(env.clone().into())?;
+        let env_ref = env.guard();
+        let reader = env_ref.reader()?;
+        let detected = forks.get(&reader, &author)?.unwrap();
+        assert_eq!(detected.fork, fork(3));
+        // The earlier fork hasn't been signalled yet
+        assert!(!detected.signalled);
+        assert_eq!(forks.all(&reader)?, vec![detected]);
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+//! The chain lock, which holds the countersigning session a source chain has
+//! been locked for, if any.
+//!
+//! While the chain is locked, the only thing that can be committed to it is the
+//! entry countersigned in that session, which releases the lock. The lock
+//! lapses by itself when the session ends.
+//!
+//! Like the rest of the source chain, locking and unlocking are kept in
+//! scratch space until the workspace is flushed. A zome call which accepts a
+//! session has signed the chain's state for it whether or not the call goes on
+//! to succeed, so the lock is also persisted straight away with
+//! [`ChainLock::persist`].
+
+use holochain_lmdb::buffer::BufferedStore;
+use holochain_lmdb::buffer::KvStore;
+use holochain_lmdb::buffer::KvStoreT;
+use holochain_lmdb::db::GetDb;
+use holochain_lmdb::db::CHAIN_LOCK;
+use holochain_lmdb::error::DatabaseError;
+use holochain_lmdb::error::DatabaseResult;
+use holochain_lmdb::prelude::*;
+use holochain_types::prelude::*;
+
+/// A BufferedStore holding the countersigning session the chain is locked for
+pub struct ChainLock {
+    store: KvStore<UnitDbKey, PreflightRequest>,
+    /// None if the lock hasn't changed since the last flush,
+    /// otherwise the new state of the lock
+    scratch: Option<Option<PreflightRequest>>,
+}
+
+impl ChainLock {
+    /// Create a new instance
+    pub fn new(env: EnvironmentRead) -> DatabaseResult<Self> {
+        Ok(Self {
+            store: KvStore::new(env.get_db(&*CHAIN_LOCK)?),
+            scratch: None,
+        })
+    }
+
+    /// The request for the session the chain is locked for, if any,
+    /// including changes not yet flushed.
+    /// This is returned even if the session has ended.
+    pub fn get<R: Readable>(&self, r: &R) -> DatabaseResult<Option<PreflightRequest>> {
+        match &self.scratch {
+            Some(lock) => Ok(lock.clone()),
+            None => self.store.get(r, &UnitDbKey),
+        }
+    }
+
+    /// Lock the chain for this session
+    pub fn lock(&mut self, request: PreflightRequest) {
+        self.scratch = Some(Some(request));
+    }
+
+    /// Persist the lock for this session in its own transaction,
+    /// regardless of any scratch space which hasn't been flushed yet
+    pub fn persist(env: &EnvironmentWrite, request: &PreflightRequest) -> DatabaseResult<()> {
+        let store: KvStore<UnitDbKey, PreflightRequest> = KvStore::new(env.get_db(&*CHAIN_LOCK)?);
+        env.guard()
+            .with_commit(|writer| store.put(writer, &UnitDbKey, request))
+    }
+
+    /// Release the lock
+    pub fn unlock(&mut self) {
+        self.scratch = Some(None);
+    }
+}
+
+impl BufferedStore for ChainLock {
+    type Error = DatabaseError;
+
+    fn is_clean(&self) -> bool {
+        self.scratch.is_none()
+    }
+
+    fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> DatabaseResult<()> {
+        match self.scratch.take() {
+            Some(Some(request)) => self.store.put(writer, &UnitDbKey, &request),
+            // The chain may have been locked and unlocked without a flush
+            // in between, in which case there is nothing to delete
+            Some(None) if self.store.get(writer, &UnitDbKey)?.is_some() => {
+                self.store.delete(writer, &UnitDbKey)
+            }
+            Some(None) => Ok(()),
+            None => Ok(()),
+        }
+    }
+}
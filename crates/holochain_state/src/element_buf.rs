@@ -7,6 +7,8 @@
 /// using the ElementBuf for caching non-authored data, or for situations where
 /// it is known that private entries should be protected, such as when handling
 /// a get_entry request from the network.
+///
+/// Private entries are encrypted at rest, see [PrivateEntryBuf].
 use crate::source_chain::SourceChainResult;
 use holo_hash::hash_type::AnyDht;
 use holo_hash::AnyDhtHash;
@@ -27,6 +29,10 @@ use holochain_lmdb::prelude::*;
 use holochain_types::prelude::*;
 use tracing::*;
 
+pub use private_entries::*;
+
+mod private_entries;
+
 /// A CasBufFresh with Entries for values
 pub type EntryCas<P> = CasBufFreshSync<Entry, P>;
 /// A CasBufFresh with SignedHeaders for values
@@ -39,7 +45,7 @@ where
     P: PrefixType,
 {
     public_entries: EntryCas<P>,
-    private_entries: Option<EntryCas<P>>,
+    private_entries: Option<PrivateEntryBuf<P>>,
     headers: HeaderCas<P>,
 }
 
@@ -96,7 +102,7 @@ where
         headers_store: SingleStore,
     ) -> DatabaseResult<Self> {
         let private_entries = if let Some(store) = private_entries_store {
-            Some(CasBufFreshSync::new(env.clone(), store))
+            Some(PrivateEntryBuf::new(env.clone(), store))
         } else {
             None
         };
@@ -243,7 +249,7 @@ where
                     EntryVisibility::Public => self.public_entries.put(entry),
                     EntryVisibility::Private => {
                         if let Some(db) = self.private_entries.as_mut() {
-                            db.put(entry);
+                            db.put(entry)?;
                         } else {
                             error!(
                                 "Attempted ElementBuf::put on a private entry with a disabled private DB: {}",
@@ -273,7 +279,7 @@ where
             EntryVisibility::Public => self.public_entries.put(entry),
             EntryVisibility::Private => {
                 if let Some(db) = self.private_entries.as_mut() {
-                    db.put(entry);
+                    db.put(entry)?;
                 } else {
                     error!(
                         "Attempted ElementBuf::put on a private entry with a disabled private DB: {}",
@@ -314,7 +320,7 @@ where
         &self.public_entries
     }
 
-    pub fn private_entries(&self) -> Option<&EntryCas<P>> {
+    pub fn private_entries(&self) -> Option<&PrivateEntryBuf<P>> {
         self.private_entries.as_ref()
     }
 
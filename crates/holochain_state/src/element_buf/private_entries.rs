@@ -0,0 +1,260 @@
+//! A CAS of private entries which are encrypted at rest.
+//!
+//! Entries are encrypted with the [PrivateEntryKey] of the agent whose cell
+//! the environment belongs to as they are put, and decrypted as they are read,
+//! so the LMDB file alone doesn't reveal them. The cell gives its environment
+//! the key when it is created.
+//!
+//! Encrypted entries start with a byte giving the version of their format.
+//! Entries put before private entries were encrypted are still read, in the
+//! clear, and are encrypted if they are put again.
+
+use fallible_iterator::FallibleIterator;
+use holo_hash::HasHash;
+use holo_hash::HoloHashed;
+use holochain_keystore::private_entry_key::PrivateEntryKey;
+use holochain_lmdb::buffer::KvBufUsed;
+use holochain_lmdb::error::DatabaseError;
+use holochain_lmdb::error::DatabaseResult;
+use holochain_lmdb::exports::SingleStore;
+use holochain_lmdb::fatal_db_hash_integrity_check;
+use holochain_lmdb::fresh_reader;
+use holochain_lmdb::prelude::*;
+use holochain_types::prelude::*;
+
+/// The version of the format private entries are encrypted in
+const ENCRYPTED_ENTRY_V1: u8 = 1;
+
+/// A private entry as it is stored
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum StoredPrivateEntry {
+    /// Serialized and then encrypted, after a byte giving the version of the
+    /// format
+    Encrypted(#[serde(with = "serde_bytes")] Vec<u8>),
+    /// Stored in the clear, before private entries were encrypted
+    Plaintext(Entry),
+}
+
+/// A CasBufFresh of private entries, which encrypts them before they reach
+/// the database
+pub struct PrivateEntryBuf<P = IntegratedPrefix>
+where
+    P: PrefixType,
+{
+    env: EnvironmentRead,
+    inner: KvBufUsed<PrefixHashKey<P>, StoredPrivateEntry>,
+}
+
+impl<P> PrivateEntryBuf<P>
+where
+    P: PrefixType,
+{
+    /// Create a new PrivateEntryBuf
+    pub fn new(env: EnvironmentRead, db: SingleStore) -> Self {
+        Self {
+            env,
+            inner: KvBufUsed::new(db),
+        }
+    }
+
+    /// The key the cell this environment belongs to was given
+    fn key(&self) -> DatabaseResult<&PrivateEntryKey> {
+        self.env
+            .private_entry_key()
+            .ok_or_else(|| DatabaseError::NoPrivateEntryKey(self.env.path().clone()))
+    }
+
+    fn encrypt(&self, entry: &Entry) -> DatabaseResult<StoredPrivateEntry> {
+        let bytes = holochain_serialized_bytes::encode(entry)?;
+        let mut encrypted = vec![ENCRYPTED_ENTRY_V1];
+        encrypted.extend(self.key()?.encrypt(&bytes)?);
+        Ok(StoredPrivateEntry::Encrypted(encrypted))
+    }
+
+    fn decrypt(
+        &self,
+        hash_bytes: &[u8],
+        stored: StoredPrivateEntry,
+    ) -> DatabaseResult<EntryHashed> {
+        let entry = match stored {
+            StoredPrivateEntry::Encrypted(encrypted) => match encrypted.split_first() {
+                Some((&ENCRYPTED_ENTRY_V1, encrypted)) => {
+                    holochain_serialized_bytes::decode(&self.key()?.decrypt(encrypted)?)?
+                }
+                Some((&version, _)) => {
+                    return Err(DatabaseError::UnknownPrivateEntryVersion(version))
+                }
+                None => return Err(DatabaseError::InvalidValue),
+            },
+            StoredPrivateEntry::Plaintext(entry) => entry,
+        };
+        let data = EntryHashed::from_content_sync(entry);
+        fatal_db_hash_integrity_check!(
+            "PrivateEntryBuf::get",
+            hash_bytes,
+            data.as_hash().get_raw_39(),
+            data.as_content(),
+        );
+        Ok(data)
+    }
+
+    /// Encrypt an entry and put it into the scratch space
+    pub fn put(&mut self, h: HoloHashed<Entry>) -> DatabaseResult<()> {
+        let key = PrefixHashKey::new(h.as_hash());
+        let encrypted = self.encrypt(h.as_content())?;
+        self.inner.put(key, encrypted)
+    }
+
+    /// Delete an entry
+    pub fn delete(&mut self, k: EntryHash) {
+        let k = PrefixHashKey::new(k.as_hash());
+        // These expects seem valid as it means the hashing is broken
+        self.inner.delete(k).expect("Hash key is empty");
+    }
+
+    /// Remove a delete from the scratch space
+    pub fn cancel_delete(&mut self, k: EntryHash) {
+        let k = PrefixHashKey::new(k.as_hash());
+        // These expects seem valid as it means the hashing is broken
+        self.inner.cancel_delete(k).expect("Hash key is empty");
+    }
+
+    /// Get and decrypt an entry
+    pub fn get(&self, hash: &EntryHash) -> DatabaseResult<Option<EntryHashed>> {
+        let k = PrefixHashKey::new(hash.as_hash());
+        match fresh_reader!(self.env, |r| self.inner.get(&r, &k))? {
+            Some(encrypted) => Ok(Some(self.decrypt(hash.as_ref(), encrypted)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Check if an entry is stored at this key
+    pub fn contains(&self, k: &EntryHash) -> DatabaseResult<bool> {
+        let k = PrefixHashKey::new(k.as_hash());
+        fresh_reader!(self.env, |r| self.inner.contains(&r, &k))
+    }
+
+    /// Check if an entry is in the scratch space
+    pub fn contains_in_scratch(&self, k: &EntryHash) -> DatabaseResult<bool> {
+        let k = PrefixHashKey::new(k.as_hash());
+        self.inner.contains_in_scratch(&k)
+    }
+
+    /// Iterate over the decrypted entries, taking the scratch space into
+    /// consideration
+    pub fn iter_fail<'r, R: Readable>(
+        &'r self,
+        r: &'r R,
+    ) -> DatabaseResult<impl FallibleIterator<Item = EntryHashed, Error = DatabaseError> + 'r> {
+        Ok(Box::new(self.inner.iter(r)?.map(move |(h, c)| {
+            let k: PrefixHashKey<P> = PrefixHashKey::from_key_bytes_or_friendly_panic(h);
+            self.decrypt(k.as_hash_bytes(), c)
+        })))
+    }
+
+    /// Clear all scratch and db, useful for tests
+    pub fn clear_all(&mut self, writer: &mut Writer) -> DatabaseResult<()> {
+        self.inner.clear_all(writer)
+    }
+}
+
+impl<P: PrefixType> BufferedStore for PrivateEntryBuf<P> {
+    type Error = DatabaseError;
+
+    fn is_clean(&self) -> bool {
+        self.inner.is_clean()
+    }
+
+    fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> DatabaseResult<()> {
+        self.inner.flush_to_txn_ref(writer)
+    }
+}
+
+/// Create a PrivateEntryBuf with a clone of the scratch
+/// from another PrivateEntryBuf
+impl<P: PrefixType> From<&PrivateEntryBuf<P>> for PrivateEntryBuf<P> {
+    fn from(other: &PrivateEntryBuf<P>) -> Self {
+        Self {
+            env: other.env.clone(),
+            inner: (&other.inner).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::element_buf::ElementBuf;
+    use holochain_lmdb::buffer::KvStore;
+    use holochain_lmdb::buffer::KvStoreT;
+    use holochain_lmdb::db::ELEMENT_VAULT_PRIVATE_ENTRIES;
+    use holochain_lmdb::test_utils::test_cell_env;
+    use holochain_types::test_utils::fake_agent_pubkey_1;
+    use holochain_types::test_utils::fake_unique_element;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn private_entries_are_encrypted_at_rest() -> anyhow::Result<()> {
+        let test_env = test_cell_env();
+        let arc = test_env.env();
+        let env = arc.guard();
+        let keystore = arc.keystore().clone();
+
+        let (header, entry) =
+            fake_unique_element(&keystore, fake_agent_pubkey_1(), EntryVisibility::Private).await?;
+        env.with_commit(|txn| {
+            let mut store = ElementBuf::vault(arc.clone().into(), true)?;
+            store.put(header, Some(entry.clone()))?;
+            store.flush_to_txn(txn)
+        })?;
+
+        // The entry isn't in the database in the clear
+        let plaintext = holochain_serialized_bytes::encode(entry.as_content())?;
+        let raw: KvStore<PrefixHashKey, StoredPrivateEntry> =
+            KvStore::new(arc.get_db(&*ELEMENT_VAULT_PRIVATE_ENTRIES)?);
+        let stored = env
+            .with_reader::<DatabaseError, _, _>(|r| {
+                raw.get(&r, &PrefixHashKey::new(entry.as_hash()))
+            })?
+            .expect("The private entry was stored");
+        let encrypted = match stored {
+            StoredPrivateEntry::Encrypted(encrypted) => encrypted,
+            StoredPrivateEntry::Plaintext(_) => panic!("The private entry was stored in the clear"),
+        };
+        assert_eq!(encrypted[0], ENCRYPTED_ENTRY_V1);
+        assert!(!encrypted
+            .windows(plaintext.len())
+            .any(|window| window == &plaintext[..]));
+
+        // But it is decrypted transparently
+        let store = ElementBuf::vault(arc.clone().into(), true)?;
+        assert_eq!(store.get_entry(entry.as_hash()), Ok(Some(entry)));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn private_entries_stored_in_the_clear_can_still_be_read() -> anyhow::Result<()> {
+        let test_env = test_cell_env();
+        let arc = test_env.env();
+        let env = arc.guard();
+        let keystore = arc.keystore().clone();
+
+        let (_, entry) =
+            fake_unique_element(&keystore, fake_agent_pubkey_1(), EntryVisibility::Private).await?;
+        let raw: KvStore<PrefixHashKey, StoredPrivateEntry> =
+            KvStore::new(arc.get_db(&*ELEMENT_VAULT_PRIVATE_ENTRIES)?);
+        env.with_commit(|txn| {
+            raw.put(
+                txn,
+                &PrefixHashKey::new(entry.as_hash()),
+                &StoredPrivateEntry::Plaintext(entry.as_content().clone()),
+            )
+        })?;
+
+        let store = ElementBuf::vault(arc.clone().into(), true)?;
+        assert_eq!(store.get_entry(entry.as_hash()), Ok(Some(entry)));
+
+        Ok(())
+    }
+}
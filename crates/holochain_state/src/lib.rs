@@ -32,6 +32,9 @@
 //! source: https://textik.com/#d7907793784e17e9
 //! ```
 
+pub mod cap_grant_index;
+pub mod chain_forks;
+pub mod chain_lock;
 #[allow(missing_docs)]
 pub mod chain_sequence;
 pub mod dht_op_integration;
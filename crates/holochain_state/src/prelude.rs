@@ -1,3 +1,5 @@
+pub use crate::cap_grant_index::*;
+pub use crate::chain_forks::*;
 pub use crate::chain_sequence::*;
 pub use crate::dht_op_integration::*;
 pub use crate::element_buf::*;
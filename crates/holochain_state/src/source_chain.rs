@@ -13,6 +13,7 @@ use holochain_lmdb::prelude::*;
 use holochain_types::prelude::*;
 use shrinkwraprs::Shrinkwrap;
 pub use source_chain_buffer::*;
+use std::collections::HashMap;
 use std::collections::HashSet;
 
 mod error;
@@ -62,9 +63,93 @@ impl SourceChain {
             prev_header: self.chain_head()?.to_owned(),
         };
         let header = header_builder.build(common).into();
+        self.check_chain_lock(&header, maybe_entry.as_ref())?;
         self.put_raw(header, maybe_entry).await
     }
 
+    /// Lock the chain for a countersigning session, so that nothing but the entry
+    /// countersigned in it can be committed until the session ends.
+    /// Accepting the same request again is allowed, as long as nothing has been
+    /// committed since.
+    ///
+    /// Returns the state of the chain that the author signs to accept the request.
+    /// The lock is kept in scratch space until the source chain is flushed; callers
+    /// which sign the returned state must also persist it with [`crate::chain_lock::ChainLock::persist`].
+    pub fn lock_for_countersigning(
+        &mut self,
+        request: PreflightRequest,
+    ) -> SourceChainResult<CounterSigningAgentState> {
+        request.check()?;
+        let agent_index = request.agent_index(&self.agent_pubkey()?).ok_or_else(|| {
+            SourceChainError::CounterSigningWriteError(
+                "The author is not one of the signing agents".into(),
+            )
+        })?;
+        let now = timestamp::now();
+        if let Some(locked) = fresh_reader!(self.env(), |r| self.0.chain_lock().get(&r))? {
+            if locked != request && now <= locked.session_times.end {
+                return Err(SourceChainError::ChainLocked(locked.session_times.end));
+            }
+        }
+        let chain_top = self.chain_head()?.to_owned();
+        let header_seq = self.len() as u32 - 1;
+        self.0.chain_lock_mut().lock(request);
+        Ok(CounterSigningAgentState::new(
+            agent_index,
+            chain_top,
+            header_seq,
+        ))
+    }
+
+    /// While the chain is locked for a countersigning session, only the entry
+    /// countersigned in that session can be committed, at the chain position the
+    /// author signed for, which releases the lock.
+    /// A countersigned entry can't be committed unless the chain is locked for it.
+    fn check_chain_lock(
+        &mut self,
+        header: &Header,
+        maybe_entry: Option<&Entry>,
+    ) -> SourceChainResult<()> {
+        let lock = fresh_reader!(self.env(), |r| self.0.chain_lock().get(&r))?
+            // the lock lapses when the session ends
+            .filter(|request| header.timestamp() <= request.session_times.end);
+        let session_data = maybe_entry.and_then(Entry::as_countersigning_session_data);
+        match (lock, session_data) {
+            (None, None) => Ok(()),
+            (Some(request), None) => Err(SourceChainError::ChainLocked(request.session_times.end)),
+            (None, Some(_)) => Err(SourceChainError::CounterSigningWriteError(
+                "The chain is not locked for this countersigning session".into(),
+            )),
+            (Some(request), Some(session_data)) => {
+                if session_data.preflight_request != request {
+                    return Err(SourceChainError::CounterSigningWriteError(
+                        "The chain is locked for a different countersigning session".into(),
+                    ));
+                }
+                if !request.session_times.contains(&header.timestamp()) {
+                    return Err(SourceChainError::CounterSigningWriteError(
+                        "The countersigning session has not started yet".into(),
+                    ));
+                }
+                let at_signed_position =
+                    session_data
+                        .agent_state_for(header.author())
+                        .map_or(false, |agent_state| {
+                            header.prev_header() == Some(&agent_state.chain_top)
+                                && agent_state.header_seq.checked_add(1)
+                                    == Some(header.header_seq())
+                        });
+                if !at_signed_position {
+                    return Err(SourceChainError::CounterSigningWriteError(
+                        "The header isn't at the chain position the author signed for".into(),
+                    ));
+                }
+                self.0.chain_lock_mut().unlock();
+                Ok(())
+            }
+        }
+    }
+
     /// Add a CapClaimEntry to the source chain
     pub async fn put_cap_claim(
         &mut self,
@@ -85,6 +170,9 @@ impl SourceChain {
     ///
     /// Else the secret and assignees of a grant will be checked and may be returned.
     ///
+    /// Grants which have expired, or which have given access to as many zome calls as they
+    /// allow, are never returned.
+    ///
     /// @todo this is not particularly fast, there are several ways to speed this up in the future
     /// such as indexing secrets and prefixing cap grants in lmdb for direct lookup
     ///
@@ -97,17 +185,38 @@ impl SourceChain {
         check_agent: &AgentPubKey,
         check_secret: Option<&CapSecret>,
     ) -> SourceChainResult<Option<CapGrant>> {
+        Ok(self
+            .valid_cap_grants_with_headers(check_function, check_agent, check_secret)?
+            .into_iter()
+            .next()
+            .map(|(grant, _)| grant))
+    }
+
+    /// Fetch every CapGrant which [SourceChain::valid_cap_grant] could return, most specific
+    /// first, along with the header which committed each, unless it is the chain author's grant,
+    /// which is not committed.
+    ///
+    /// The header is what the uses of a use-limited grant are counted against, with
+    /// [CapGrantIndex::use_grant](crate::cap_grant_index::CapGrantIndex::use_grant).
+    pub fn valid_cap_grants_with_headers(
+        &self,
+        check_function: &GrantedFunction,
+        check_agent: &AgentPubKey,
+        check_secret: Option<&CapSecret>,
+    ) -> SourceChainResult<Vec<(CapGrant, Option<HeaderHash>)>> {
+        let now = timestamp::now();
+
         // most calls for most apps are going to be the local agent calling itself locally
         // for this case we want to short circuit without iterating the whole source chain
         let author_grant = CapGrant::from(self.agent_pubkey()?);
-        if author_grant.is_valid(check_function, check_agent, check_secret) {
-            return Ok(Some(author_grant));
+        if author_grant.is_valid(check_function, check_agent, check_secret, &now, 0) {
+            return Ok(vec![(author_grant, None)]);
         }
 
         // if we are here then the caller is not the current agent so we need to search the source
         // chain to see if there is a local grant that is valid for the provided secret/agent
         // combination
-        let committed_valid_grant = fresh_reader!(self.env(), |r| {
+        let mut committed_valid_grants: Vec<_> = fresh_reader!(self.env(), |r| {
             let (references, headers): (
                 HashSet<HeaderHash>,
                 Vec<HoloHashed<holochain_zome_types::element::SignedHeader>>,
@@ -160,12 +269,18 @@ impl SourceChain {
 
             // second pass over the headers to make sure that all referenced headers are removed
             // this makes the process reliable even if the iterators don't follow the chain order
-            let live_cap_grants: HashSet<_> = headers
+            // the header of each live grant is kept as it is what the grant's uses are counted
+            // against
+            let live_cap_grants: HashMap<_, _> = headers
                 .iter()
                 .filter(|header| !references.contains(header.as_hash()))
                 .filter_map(|header| match header.as_content().header() {
-                    Header::Create(create) => Some(create.entry_hash.clone()),
-                    Header::Update(update) => Some(update.entry_hash.clone()),
+                    Header::Create(create) => {
+                        Some((create.entry_hash.clone(), header.as_hash().clone()))
+                    }
+                    Header::Update(update) => {
+                        Some((update.entry_hash.clone(), header.as_hash().clone()))
+                    }
                     _ => None,
                 })
                 .collect();
@@ -179,61 +294,34 @@ impl SourceChain {
             )
             .iter_fail(&r)?
             // ensure we respect the header filtering we already did above
-            .filter(|entry| {
-                Ok(live_cap_grants.contains(entry.as_hash()))
+            .filter_map(|entry| {
+                Ok(live_cap_grants
+                    .get(entry.as_hash())
+                    .cloned()
+                    .and_then(|header_hash| Some((entry.as_cap_grant()?, header_hash))))
             })
-            .filter_map(|entry| Ok(entry.as_cap_grant()))
-            // filter down to only the grants for this function
-            .filter(|grant| {
-                Ok(grant.is_valid(check_function, check_agent, check_secret))
-            })
-            // if there are still multiple grants, fold them down based on specificity
-            // authorship > assigned > transferable > unrestricted
-            .fold(None, |mut acc, grant| {
-                acc = match &grant {
-                    CapGrant::RemoteAgent(zome_call_cap_grant) => {
-                        match &zome_call_cap_grant.access {
-                            CapAccess::Assigned { .. } => match &acc {
-                                Some(CapGrant::RemoteAgent(acc_zome_call_cap_grant)) => {
-                                    match acc_zome_call_cap_grant.access {
-                                        // an assigned acc takes precedence
-                                        CapAccess::Assigned { .. } => acc,
-                                        // current grant takes precedence over all other accs
-                                        _ => Some(grant),
-                                    }
-                                }
-                                None => Some(grant),
-                                // authorship should be short circuit and filtered
-                                _ => unreachable!(),
-                            },
-                            CapAccess::Transferable { .. } => match &acc {
-                                Some(CapGrant::RemoteAgent(acc_zome_call_cap_grant)) => {
-                                    match acc_zome_call_cap_grant.access {
-                                        // an assigned acc takes precedence
-                                        CapAccess::Assigned { .. } => acc,
-                                        // transferable acc takes precedence
-                                        CapAccess::Transferable { .. } => acc,
-                                        // current grant takes preference over other accs
-                                        _ => Some(grant),
-                                    }
-                                }
-                                None => Some(grant),
-                                // authorship should be short circuited and filtered by now
-                                _ => unreachable!(),
-                            }
-                            CapAccess::Unrestricted => match acc {
-                                Some(_) => acc,
-                                None => Some(grant),
-                            }
-                        }
-                    },
-                    // ChainAuthor should have short circuited and be filtered out already
-                    _ => unreachable!(),
-                };
-                Ok(acc)
+            // filter down to only the live grants for this function
+            .filter(|(grant, header_hash)| {
+                let uses = self.0.cap_grant_index().uses(&r, header_hash)?;
+                Ok(grant.is_valid(check_function, check_agent, check_secret, &now, uses))
             })
+            .collect()
         })?;
-        Ok(committed_valid_grant)
+        // if there are multiple grants, order them by specificity
+        // authorship > assigned > transferable > unrestricted
+        committed_valid_grants.sort_by_key(|(grant, _)| match grant {
+            CapGrant::RemoteAgent(zome_call_cap_grant) => match zome_call_cap_grant.access {
+                CapAccess::Assigned { .. } => 0,
+                CapAccess::Transferable { .. } => 1,
+                CapAccess::Unrestricted => 2,
+            },
+            // ChainAuthor should have short circuited and be filtered out already
+            _ => unreachable!(),
+        });
+        Ok(committed_valid_grants
+            .into_iter()
+            .map(|(grant, header_hash)| (grant, Some(header_hash)))
+            .collect())
     }
 
     // @todo bring all this back when we want to administer cap claims better
@@ -318,6 +406,7 @@ impl BufferedStore for SourceChain {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::cap_grant_index::CapGrantIndex;
     use ::fixt::prelude::*;
     use hdk::prelude::*;
     use holochain_lmdb::test_utils::test_cell_env;
@@ -326,6 +415,120 @@ pub mod tests {
     use holochain_zome_types::capability::ZomeCallCapGrant;
     use std::collections::BTreeSet;
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_countersigning_chain_lock() -> SourceChainResult<()> {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let mut agents = AgentPubKeyFixturator::new(Predictable);
+        let alice = agents.next().unwrap();
+        let bob = agents.next().unwrap();
+
+        let app_entry = AppEntryBytesFixturator::new(Unpredictable).next().unwrap();
+        let now = timestamp::now();
+        let request = PreflightRequest::new(
+            EntryHash::with_data_sync(&Entry::App(app_entry.clone())),
+            vec![alice.clone(), bob.clone()],
+            CounterSigningSessionTimes::new(
+                (now - std::time::Duration::from_secs(1)).unwrap(),
+                (now + std::time::Duration::from_secs(60)).unwrap(),
+            ),
+            PreflightBytes(vec![]),
+        );
+        let mut other_request = request.clone();
+        other_request.preflight_bytes = PreflightBytes(vec![1]);
+
+        let create = |entry: Entry| {
+            let (entry, entry_hash) = EntryHashed::from_content_sync(entry).into_inner();
+            let header_builder = builder::Create {
+                entry_type: EntryType::App(fixt!(AppEntryType)),
+                entry_hash,
+            };
+            (header_builder, Some(entry))
+        };
+        let claim = || {
+            CapClaim::new(
+                "tag".into(),
+                bob.clone(),
+                CapSecretFixturator::new(Unpredictable).next().unwrap(),
+            )
+        };
+
+        {
+            let mut store = SourceChainBuf::new(env.clone().into())?;
+            store.genesis(fake_dna_hash(1), alice.clone(), None).await?;
+            env.guard()
+                .with_commit(|writer| store.flush_to_txn(writer))?;
+        }
+
+        let alice_state = {
+            let mut chain = SourceChain::new(env.clone().into())?;
+            let alice_state = chain.lock_for_countersigning(request.clone())?;
+            assert_eq!(alice_state.agent_index, 0);
+            assert_eq!(&alice_state.chain_top, chain.chain_head()?);
+            env.guard()
+                .with_commit(|writer| chain.flush_to_txn(writer))?;
+            alice_state
+        };
+        let session_data = CounterSigningSessionData {
+            preflight_request: request.clone(),
+            responses: vec![
+                (alice_state.clone(), Signature([0; 64])),
+                (
+                    CounterSigningAgentState::new(1, fixt!(HeaderHash), 7),
+                    Signature([1; 64]),
+                ),
+            ],
+        };
+        let countersigned = Entry::CounterSign(Box::new(session_data.clone()), app_entry);
+
+        {
+            let mut chain = SourceChain::new(env.clone().into())?;
+            // nothing else can be committed while the chain is locked
+            matches::assert_matches!(
+                chain.put_cap_claim(claim()).await,
+                Err(SourceChainError::ChainLocked(_))
+            );
+            // and it can't be locked for another session
+            matches::assert_matches!(
+                chain.lock_for_countersigning(other_request.clone()),
+                Err(SourceChainError::ChainLocked(_))
+            );
+            // but the same request can be accepted again
+            assert_eq!(chain.lock_for_countersigning(request.clone())?, alice_state);
+
+            // committing the countersigned entry releases the lock
+            let (header_builder, entry) = create(countersigned.clone());
+            chain.put(header_builder, entry).await?;
+            chain.put_cap_claim(claim()).await?;
+            env.guard()
+                .with_commit(|writer| chain.flush_to_txn(writer))?;
+        }
+
+        {
+            let mut chain = SourceChain::new(env.clone().into())?;
+            // a countersigned entry can't be committed without the lock
+            let (header_builder, entry) = create(countersigned);
+            matches::assert_matches!(
+                chain.put(header_builder, entry).await,
+                Err(SourceChainError::CounterSigningWriteError(_))
+            );
+            // and the chain can be locked for another session
+            chain.lock_for_countersigning(other_request.clone())?;
+            // which holds even though this chain is never flushed
+            crate::chain_lock::ChainLock::persist(&env, &other_request)?;
+        }
+
+        {
+            let mut chain = SourceChain::new(env.clone().into())?;
+            matches::assert_matches!(
+                chain.put_cap_claim(claim()).await,
+                Err(SourceChainError::ChainLocked(_))
+            );
+        }
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_get_cap_grant() -> SourceChainResult<()> {
         let test_env = test_cell_env();
@@ -483,6 +686,97 @@ pub mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cap_grant_expiry_and_uses() -> SourceChainResult<()> {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let function: GrantedFunction = ("foo".into(), "bar".into());
+        let mut functions: GrantedFunctions = BTreeSet::new();
+        functions.insert(function.clone());
+        let mut agents = AgentPubKeyFixturator::new(Predictable);
+        let alice = agents.next().unwrap();
+        let bob = agents.next().unwrap();
+
+        let expired_secret = Some(CapSecretFixturator::new(Unpredictable).next().unwrap());
+        let expired_grant = ZomeCallCapGrant::new(
+            "expired".into(),
+            CapAccess::from(expired_secret.unwrap()),
+            functions.clone(),
+        )
+        .expiring_at((timestamp::now() - std::time::Duration::from_secs(60)).unwrap());
+
+        let limited_secret = Some(CapSecretFixturator::new(Unpredictable).next().unwrap());
+        let limited_grant = ZomeCallCapGrant::new(
+            "limited".into(),
+            CapAccess::from(limited_secret.unwrap()),
+            functions,
+        )
+        .expiring_at((timestamp::now() + std::time::Duration::from_secs(3600)).unwrap())
+        .with_max_uses(2);
+
+        {
+            let mut store = SourceChainBuf::new(env.clone().into())?;
+            store.genesis(fake_dna_hash(1), alice.clone(), None).await?;
+            env.guard()
+                .with_commit(|writer| store.flush_to_txn(writer))?;
+        }
+
+        {
+            let mut chain = SourceChain::new(env.clone().into())?;
+            for grant in vec![expired_grant, limited_grant.clone()] {
+                let (entry, entry_hash) =
+                    EntryHashed::from_content_sync(Entry::CapGrant(grant)).into_inner();
+                let header_builder = builder::Create {
+                    entry_type: EntryType::CapGrant,
+                    entry_hash,
+                };
+                chain.put(header_builder, Some(entry)).await?;
+            }
+            env.guard()
+                .with_commit(|writer| chain.flush_to_txn(writer))?;
+        }
+
+        {
+            let chain = SourceChain::new(env.clone().into())?;
+            // an expired grant gives no access
+            assert_eq!(
+                chain.valid_cap_grant(&function, &bob, expired_secret.as_ref())?,
+                None
+            );
+
+            // the chain author's grant is never committed, so it is never limited
+            assert_eq!(
+                chain.valid_cap_grants_with_headers(&function, &alice, limited_secret.as_ref())?,
+                vec![(CapGrant::ChainAuthor(alice.clone()), None)],
+            );
+
+            // checking a grant doesn't use it
+            let (grant, grant_header) = chain
+                .valid_cap_grants_with_headers(&function, &bob, limited_secret.as_ref())?
+                .pop()
+                .unwrap();
+            assert_eq!(grant, limited_grant.clone().into());
+            let grant_header = grant_header.unwrap();
+
+            // uses are persisted straight away, and stop at the limit
+            assert!(CapGrantIndex::use_grant(&env, &grant_header, 2)?);
+            assert_eq!(
+                chain.valid_cap_grant(&function, &bob, limited_secret.as_ref())?,
+                Some(limited_grant.into())
+            );
+            assert!(CapGrantIndex::use_grant(&env, &grant_header, 2)?);
+            assert!(!CapGrantIndex::use_grant(&env, &grant_header, 2)?);
+
+            // the grant is used up
+            assert_eq!(
+                chain.valid_cap_grant(&function, &bob, limited_secret.as_ref())?,
+                None
+            );
+        }
+
+        Ok(())
+    }
+
     // @todo bring all this back when we want to administer cap claims better
     // #[tokio::test(flavor = "multi_thread")]
     // async fn test_get_cap_claim() -> SourceChainResult<()> {
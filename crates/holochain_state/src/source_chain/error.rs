@@ -44,6 +44,9 @@ pub enum SourceChainError {
     #[error("Element previous header reference is invalid: {0}")]
     InvalidPreviousHeader(String),
 
+    #[error("The source chain archive can't be imported: {0}")]
+    InvalidArchive(String),
+
     #[error("InvalidCommit error: {0}")]
     InvalidCommit(String),
 
@@ -65,6 +68,15 @@ pub enum SourceChainError {
 
     #[error(transparent)]
     ElementGroupError(#[from] ElementGroupError),
+
+    #[error("The source chain is locked for a countersigning session until {0}")]
+    ChainLocked(Timestamp),
+
+    #[error(transparent)]
+    CounterSigningError(#[from] CounterSigningError),
+
+    #[error("CounterSigningWriteError: {0}")]
+    CounterSigningWriteError(String),
 }
 
 // serde_json::Error does not implement PartialEq - why is that a requirement??
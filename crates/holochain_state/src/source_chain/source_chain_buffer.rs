@@ -1,4 +1,6 @@
 use super::ChainInvalidReason;
+use crate::cap_grant_index::CapGrantIndex;
+use crate::chain_lock::ChainLock;
 use crate::chain_sequence::ChainSequenceBuf;
 use crate::element_buf::ElementBuf;
 use crate::element_buf::HeaderCas;
@@ -15,6 +17,8 @@ use tracing::*;
 pub struct SourceChainBuf {
     elements: ElementBuf<AuthoredPrefix>,
     sequence: ChainSequenceBuf,
+    cap_grant_index: CapGrantIndex,
+    chain_lock: ChainLock,
     keystore: KeystoreSender,
 
     env: EnvironmentRead,
@@ -28,7 +32,7 @@ pub struct SourceChainJsonDump {
     pub published_ops_count: usize,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SourceChainJsonElement {
     pub signature: Signature,
     pub header_address: HeaderHash,
@@ -36,11 +40,41 @@ pub struct SourceChainJsonElement {
     pub entry: Option<Entry>,
 }
 
+/// Every element of a source chain from genesis to the head, private entries
+/// included, for backing the chain up and restoring it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct SourceChainArchive {
+    pub dna_hash: DnaHash,
+    pub agent_pubkey: AgentPubKey,
+    pub elements: Vec<SourceChainJsonElement>,
+}
+
+/// A [SourceChainArchive] signed by the agent whose chain it is
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct SignedSourceChainArchive {
+    pub archive: SourceChainArchive,
+    pub signature: Signature,
+}
+
+impl SignedSourceChainArchive {
+    /// Encode the archive, for writing it to a file
+    pub fn to_bytes(&self) -> SourceChainResult<Vec<u8>> {
+        Ok(holochain_serialized_bytes::encode(self)?)
+    }
+
+    /// Decode an archive written with [SignedSourceChainArchive::to_bytes]
+    pub fn from_bytes(bytes: &[u8]) -> SourceChainResult<Self> {
+        Ok(holochain_serialized_bytes::decode(bytes)?)
+    }
+}
+
 impl SourceChainBuf {
     pub fn new(env: EnvironmentRead) -> DatabaseResult<Self> {
         Ok(Self {
             elements: ElementBuf::authored(env.clone(), true)?,
             sequence: ChainSequenceBuf::new(env.clone())?,
+            cap_grant_index: CapGrantIndex::new(env.clone())?,
+            chain_lock: ChainLock::new(env.clone())?,
             keystore: env.keystore().clone(),
             env,
         })
@@ -50,6 +84,8 @@ impl SourceChainBuf {
         Ok(Self {
             elements: ElementBuf::authored(env.clone(), false)?,
             sequence: ChainSequenceBuf::new(env.clone())?,
+            cap_grant_index: CapGrantIndex::new(env.clone())?,
+            chain_lock: ChainLock::new(env.clone())?,
             keystore: env.keystore().clone(),
             env,
         })
@@ -131,6 +167,18 @@ impl SourceChainBuf {
         &self.sequence
     }
 
+    pub fn cap_grant_index(&self) -> &CapGrantIndex {
+        &self.cap_grant_index
+    }
+
+    pub fn chain_lock(&self) -> &ChainLock {
+        &self.chain_lock
+    }
+
+    pub fn chain_lock_mut(&mut self) -> &mut ChainLock {
+        &mut self.chain_lock
+    }
+
     /// Add a Element to the source chain, using a fully-formed Header
     pub async fn put_raw(
         &mut self,
@@ -234,6 +282,132 @@ impl SourceChainBuf {
         })
     }
 
+    /// Export the entire source chain, signed by the agent whose chain it is.
+    /// Private entries are only included if this buffer was created with
+    /// [SourceChainBuf::new].
+    pub async fn export_archive(&self) -> SourceChainResult<SignedSourceChainArchive> {
+        let agent_pubkey = self.agent_pubkey()?.ok_or(SourceChainError::ChainEmpty)?;
+        let mut iter = self.iter_back();
+        let mut elements = Vec::with_capacity(self.len());
+
+        while let Some(h) = iter.next()? {
+            let element = self
+                .get_element(h.header_address())?
+                .ok_or_else(|| SourceChainError::ElementMissing(h.header_address().to_string()))?;
+            let (signed, entry) = element.into_inner();
+            let (header, signature) = signed.into_header_and_signature();
+            let (header, header_address) = header.into_inner();
+            elements.push(SourceChainJsonElement {
+                signature,
+                header_address,
+                header,
+                entry: entry.into_option(),
+            });
+        }
+        elements.reverse();
+
+        let dna_hash = match elements.first().map(|e| &e.header) {
+            Some(Header::Dna(dna)) => dna.hash.clone(),
+            _ => {
+                return Err(SourceChainError::InvalidStructure(
+                    ChainInvalidReason::GenesisDataMissing,
+                ))
+            }
+        };
+        let archive = SourceChainArchive {
+            dna_hash,
+            agent_pubkey,
+            elements,
+        };
+        let signature = archive.agent_pubkey.sign(&self.keystore, &archive).await?;
+        Ok(SignedSourceChainArchive { archive, signature })
+    }
+
+    /// Import an archive made by [SourceChainBuf::export_archive] into this
+    /// source chain, which must be empty.
+    /// Every element is checked against the archive's agent before anything
+    /// is put, and keeps its original signature. The elements are put as not
+    /// yet transformed into DhtOps, so their ops will be produced and
+    /// published again.
+    pub async fn import_archive(
+        &mut self,
+        signed_archive: SignedSourceChainArchive,
+    ) -> SourceChainResult<()> {
+        if !self.is_empty() {
+            return Err(SourceChainError::InvalidArchive(
+                "Archives can only be imported into an empty source chain".into(),
+            ));
+        }
+        let SignedSourceChainArchive { archive, signature } = signed_archive;
+        let author = archive.agent_pubkey.clone();
+        if !author.verify_signature(&signature, archive.clone()).await? {
+            return Err(SourceChainError::InvalidSignature);
+        }
+        match archive.elements.first().map(|e| &e.header) {
+            Some(Header::Dna(dna)) if dna.hash == archive.dna_hash => (),
+            _ => {
+                return Err(SourceChainError::InvalidStructure(
+                    ChainInvalidReason::GenesisDataMissing,
+                ))
+            }
+        }
+
+        let mut prev_header: Option<HeaderHash> = None;
+        let mut elements = Vec::with_capacity(archive.elements.len());
+        for (seq, element) in archive.elements.into_iter().enumerate() {
+            let SourceChainJsonElement {
+                signature,
+                header_address,
+                header,
+                entry,
+            } = element;
+            let header = HeaderHashed::from_content_sync(header);
+            if *header.as_hash() != header_address {
+                return Err(SourceChainError::InvalidArchive(format!(
+                    "The header at {} does not match its address {}",
+                    seq, header_address
+                )));
+            }
+            if *header.as_content().author() != author {
+                return Err(SourceChainError::InvalidArchive(format!(
+                    "The header at {} was not authored by {}",
+                    seq, author
+                )));
+            }
+            if header.as_content().header_seq() as usize != seq
+                || header.as_content().prev_header() != prev_header.as_ref()
+            {
+                return Err(SourceChainError::InvalidPreviousHeader(format!(
+                    "The header at {} is out of sequence",
+                    seq
+                )));
+            }
+            if !author
+                .verify_signature(&signature, header.as_content().clone())
+                .await?
+            {
+                return Err(SourceChainError::InvalidSignature);
+            }
+            let entry = entry.map(EntryHashed::from_content_sync);
+            if let Some(entry) = &entry {
+                if header.as_content().entry_hash() != Some(entry.as_hash()) {
+                    return Err(SourceChainError::InvalidStructure(
+                        ChainInvalidReason::HeaderAndEntryMismatch(entry.as_hash().clone()),
+                    ));
+                }
+            }
+            prev_header = Some(header_address);
+            elements.push((SignedHeaderHashed::with_presigned(header, signature), entry));
+        }
+
+        for (signed_header, entry) in elements {
+            self.sequence
+                .put_header(signed_header.header_address().clone())?;
+            self.elements.put(signed_header, entry)?;
+        }
+        Ok(())
+    }
+
     /// Commit the genesis entries to this source chain, making the chain ready
     /// to use as a `SourceChain`
     pub async fn genesis(
@@ -282,6 +456,7 @@ impl BufferedStore for SourceChainBuf {
     fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> Result<(), Self::Error> {
         self.elements.flush_to_txn_ref(writer)?;
         self.sequence.flush_to_txn_ref(writer)?;
+        self.chain_lock.flush_to_txn_ref(writer)?;
         Ok(())
     }
 }
@@ -325,6 +500,7 @@ impl<'a> FallibleIterator for SourceChainBackwardIterator<'a> {
 #[cfg(test)]
 pub mod tests {
     use super::SourceChainBuf;
+    use crate::source_chain::SourceChainError;
     use crate::source_chain::SourceChainResult;
     use fallible_iterator::FallibleIterator;
     use holochain_lmdb::prelude::*;
@@ -491,6 +667,68 @@ pub mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn source_chain_buffer_archive_round_trip() -> SourceChainResult<()> {
+        let exported_test_env = test_cell_env();
+        let exported_env = exported_test_env.env();
+        let imported_test_env = test_cell_env();
+        let imported_env = imported_test_env.env();
+
+        let dna = fake_dna_file("a");
+        let agent_pubkey = fake_agent_pubkey_1();
+
+        {
+            let mut store = SourceChainBuf::new(exported_env.clone().into()).unwrap();
+            store
+                .genesis(dna.dna_hash().clone(), agent_pubkey.clone(), None)
+                .await?;
+            exported_env
+                .guard()
+                .with_commit(|writer| store.flush_to_txn(writer))?;
+        }
+        let archive = SourceChainBuf::new(exported_env.clone().into())
+            .unwrap()
+            .export_archive()
+            .await?;
+        assert_eq!(archive.archive.elements.len(), 3);
+        assert_eq!(&archive.archive.dna_hash, dna.dna_hash());
+
+        // A tampered archive is rejected
+        {
+            let mut tampered = archive.clone();
+            tampered.archive.elements.pop();
+            let mut store = SourceChainBuf::new(imported_env.clone().into()).unwrap();
+            assert!(matches!(
+                store.import_archive(tampered).await,
+                Err(SourceChainError::InvalidSignature)
+            ));
+            assert!(store.is_empty());
+        }
+
+        {
+            let mut store = SourceChainBuf::new(imported_env.clone().into()).unwrap();
+            store.import_archive(archive.clone()).await?;
+            imported_env
+                .guard()
+                .with_commit(|writer| store.flush_to_txn(writer))?;
+        }
+
+        let mut store = SourceChainBuf::new(imported_env.clone().into()).unwrap();
+        assert_eq!(store.agent_pubkey()?, Some(agent_pubkey));
+        // The original signatures are kept
+        assert_eq!(store.export_archive().await?.archive, archive.archive);
+        // And every element still needs its ops produced
+        assert_eq!(store.get_incomplete_dht_ops().await?.len(), 3);
+
+        // Only an empty chain can be imported into
+        assert!(matches!(
+            store.import_archive(archive).await,
+            Err(SourceChainError::InvalidArchive(_))
+        ));
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_header_cas_roundtrip() {
         let test_env = test_cell_env();
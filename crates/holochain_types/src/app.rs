@@ -133,8 +133,14 @@ pub enum AppBundleSource {
     Bundle(AppBundle),
     /// A local file path
     Path(PathBuf),
-    // /// A URL
-    // Url(String),
+    /// A URL to fetch the bundle from, along with the hash the fetched bundle
+    /// must have, since nothing else vouches for what the URL serves
+    Url {
+        /// Where to fetch the bundle from
+        url: String,
+        /// The expected [`mr_bundle::ResourceHash`] of the bundle's bytes
+        hash: mr_bundle::ResourceHash,
+    },
 }
 
 impl AppBundleSource {
@@ -143,7 +149,9 @@ impl AppBundleSource {
         Ok(match self {
             Self::Bundle(bundle) => bundle,
             Self::Path(path) => AppBundle::decode(&ffs::read(&path).await?)?,
-            // Self::Url(url) => todo!("reqwest::get"),
+            Self::Url { url, hash } => {
+                AppBundle::decode(&mr_bundle::resolve_remote(&url, Some(&hash), None).await?)?
+            }
         })
     }
 }
@@ -353,6 +361,11 @@ impl InactiveApp {
         }
     }
 
+    /// The reason this app is inactive
+    pub fn reason(&self) -> &DeactivationReason {
+        &self.reason
+    }
+
     /// Convert to a ActiveApp
     pub fn into_active(self) -> ActiveApp {
         ActiveApp(self.app)
@@ -392,6 +405,12 @@ pub enum DeactivationReason {
         /// The error which necessitated the quarantine
         error: String,
     },
+    /// The agent key the app's Cells run as was rotated, so they can no
+    /// longer author anything valid
+    AgentKeySuperseded {
+        /// The key which replaced it
+        by: AgentPubKey,
+    },
 }
 
 /// The common data between apps of any status
@@ -69,3 +69,41 @@ async fn provisioning_1_create() {
     };
     assert_eq!(resolution, expected);
 }
+
+/// Test that an AppBundle can be fetched from a URL, as long as it has the
+/// expected hash
+#[tokio::test(flavor = "multi_thread")]
+async fn resolve_from_url() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let (bundle, _) = app_bundle_fixture().await;
+    let bytes = bundle.encode().unwrap();
+    let hash = mr_bundle::ResourceHash::of(&bytes);
+
+    // Serve the bundle from a minimal local file server
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/app.happ", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        for _ in 0..2 {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).await;
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                bytes.len()
+            );
+            stream.write_all(header.as_bytes()).await.unwrap();
+            stream.write_all(&bytes).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+    });
+
+    let wrong_hash = AppBundleSource::Url {
+        url: url.clone(),
+        hash: mr_bundle::ResourceHash::of(&[1, 2, 3]),
+    };
+    assert!(wrong_hash.resolve().await.is_err());
+
+    let resolved = AppBundleSource::Url { url, hash }.resolve().await.unwrap();
+    assert_eq!(resolved.manifest(), bundle.manifest());
+    assert_eq!(resolved.bundled_resources(), bundle.bundled_resources());
+}
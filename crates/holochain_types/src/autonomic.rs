@@ -1,7 +1,11 @@
 //! Holochain autonomic type helpers.
 
+use crate::Timestamp;
+use holo_hash::DhtOpHash;
+use holochain_serialized_bytes::prelude::*;
+
 /// The various processes which run "autonomically", aka subconsciously.
-/// These are currently not used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum AutonomicProcess {
     /// Validation / Correction may propagate much slower.
     SlowHeal,
@@ -24,3 +28,27 @@ impl From<AutonomicCue> for AutonomicProcess {
         match cue {}
     }
 }
+
+/// The outcome of a [`AutonomicProcess::HealthCheck`] of a single cell:
+/// how well the ops authored by the cell are held by the network, judging by
+/// the validation receipts it has received for them.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, SerializedBytes)]
+pub struct CellHealth {
+    /// The number of validators which should hold each op
+    pub redundancy_target: u32,
+    /// The number of published ops authored by this cell
+    pub authored_ops: usize,
+    /// The ops which are held by fewer validators than the target,
+    /// paired with the number of validators which do hold them.
+    /// These are the ops which a [`AutonomicProcess::SlowHeal`] republishes.
+    pub ops_below_target: Vec<(DhtOpHash, u32)>,
+    /// When the check was made
+    pub checked_at: Timestamp,
+}
+
+impl CellHealth {
+    /// Whether every authored op is held by enough validators
+    pub fn is_healthy(&self) -> bool {
+        self.ops_below_target.is_empty()
+    }
+}
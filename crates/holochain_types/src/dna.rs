@@ -8,6 +8,7 @@ mod dna_def;
 mod dna_file;
 mod dna_manifest;
 mod dna_store;
+mod limits;
 
 pub mod error;
 pub mod wasm;
@@ -19,4 +20,5 @@ pub use dna_manifest::*;
 pub use dna_store::MockDnaStore;
 pub use dna_store::*;
 pub use error::DnaError;
+pub use limits::*;
 pub use holo_hash::*;
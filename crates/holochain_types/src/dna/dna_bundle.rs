@@ -61,6 +61,7 @@ impl DnaBundle {
                     (
                         z.name.clone(),
                         z.hash.clone().map(WasmHash::from),
+                        z.host_fn_access,
                         DnaWasm::from(bytes),
                     )
                 })
@@ -68,14 +69,14 @@ impl DnaBundle {
         };
 
         let data = futures::future::join_all(intermediate.into_iter().map(
-            |(zome_name, expected_hash, wasm)| async {
+            |(zome_name, expected_hash, host_fn_access, wasm)| async move {
                 let hash = WasmHash::with_data(&wasm).await;
                 if let Some(expected) = expected_hash {
                     if hash != expected {
                         return Err(DnaError::WasmHashMismatch(expected, hash));
                     }
                 }
-                DnaResult::Ok((zome_name, hash, wasm))
+                DnaResult::Ok((zome_name, hash, host_fn_access, wasm))
             },
         ))
         .await
@@ -84,17 +85,18 @@ impl DnaBundle {
 
         let zomes: Zomes = data
             .iter()
-            .map(|(zome_name, hash, _)| {
-                (
-                    zome_name.clone(),
-                    ZomeDef::Wasm(WasmZome::new(hash.clone())),
-                )
+            .map(|(zome_name, hash, host_fn_access, _)| {
+                let zome = WasmZome {
+                    wasm_hash: hash.clone(),
+                    host_fn_access: *host_fn_access,
+                };
+                (zome_name.clone(), ZomeDef::Wasm(zome))
             })
             .collect();
 
         let code: BTreeMap<_, _> = data
             .into_iter()
-            .map(|(_, hash, wasm)| (hash, wasm))
+            .map(|(_, hash, _, wasm)| (hash, wasm))
             .into_iter()
             .collect();
         let wasms = WasmMap::from(code);
@@ -118,6 +120,9 @@ impl DnaBundle {
                         manifest.properties.clone().unwrap_or_default(),
                     )?,
                     zomes,
+                    redundancy_target: manifest.redundancy_target,
+                    zome_call_limits: manifest.zome_call_limits.clone(),
+                    integrity: manifest.integrity.clone(),
                 };
 
                 if uid.is_none() && properties.is_none() {
@@ -163,16 +168,17 @@ impl DnaBundle {
             .into_iter()
             .filter_map(|(name, zome)| {
                 match zome {
-                    ZomeDef::Wasm(wz) => Some(wz.wasm_hash),
+                    ZomeDef::Wasm(wz) => Some(wz),
                     ZomeDef::Inline(_) => None,
                 }
-                .map(|hash| {
-                    let hash = WasmHashB64::from(hash);
+                .map(|wz| {
+                    let hash = WasmHashB64::from(wz.wasm_hash);
                     let filename = format!("{}", hash);
                     ZomeManifest {
                         name,
                         hash: Some(hash),
                         location: Location::Bundled(PathBuf::from(filename)),
+                        host_fn_access: wz.host_fn_access,
                     }
                 })
             })
@@ -187,6 +193,9 @@ impl DnaBundle {
                 ))
             })?),
             zomes,
+            redundancy_target: dna_def.redundancy_target,
+            zome_call_limits: dna_def.zome_call_limits.clone(),
+            integrity: dna_def.integrity.clone(),
         }
         .into())
     }
@@ -216,14 +225,19 @@ mod tests {
                     name: "zome1".into(),
                     hash: None,
                     location: mr_bundle::Location::Bundled(path1.clone()),
+                    host_fn_access: None,
                 },
                 ZomeManifest {
                     name: "zome2".into(),
                     // Intentional wrong hash
                     hash: Some(hash1.clone().into()),
                     location: mr_bundle::Location::Bundled(path2.clone()),
+                    host_fn_access: None,
                 },
             ],
+            redundancy_target: None,
+            zome_call_limits: None,
+            integrity: None,
         };
         let resources = vec![(path1, wasm1), (path2, wasm2)];
 
@@ -264,4 +278,44 @@ mod tests {
             SerializedBytes::try_from(properties).unwrap()
         );
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn host_fn_access_from_manifest() {
+        let manifest: DnaManifest = serde_yaml::from_str(
+            r#"
+manifest_version: "1"
+name: test
+uid: ~
+properties: ~
+zomes:
+  - name: ui
+    bundled: ui.wasm
+    host_fn_access:
+      read_workspace: allow
+      write_workspace: allow
+  - name: backend
+    bundled: backend.wasm
+"#,
+        )
+        .unwrap();
+        let resources = vec![
+            (PathBuf::from("ui.wasm"), vec![1, 2, 3]),
+            (PathBuf::from("backend.wasm"), vec![4, 5, 6]),
+        ];
+        let bundle: DnaBundle = mr_bundle::Bundle::new_unchecked(manifest, resources)
+            .unwrap()
+            .into();
+        let dna_file: DnaFile = bundle.into_dna_file(None, None).await.unwrap().0;
+        let zomes = &dna_file.dna_def().zomes;
+
+        // Only the declared permissions are allowed
+        let ui = zomes[0].1.host_fn_access();
+        assert_eq!(ui.read_workspace, Permission::Allow);
+        assert_eq!(ui.write_workspace, Permission::Allow);
+        assert_eq!(ui.write_network, Permission::Deny);
+        assert_eq!(ui.keystore, Permission::Deny);
+
+        // Undeclared zomes are unrestricted
+        assert_eq!(zomes[1].1.host_fn_access(), HostFnAccess::all());
+    }
 }
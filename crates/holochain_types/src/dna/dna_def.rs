@@ -7,6 +7,10 @@ use holochain_zome_types::ZomeName;
 /// Zomes need to be an ordered map from ZomeName to a Zome
 pub type Zomes = Vec<(ZomeName, zome::ZomeDef)>;
 
+/// The number of authorities which should hold each authored op, if the DNA
+/// does not specify its own target.
+pub const DEFAULT_REDUNDANCY_TARGET: u32 = 5;
+
 /// A type to allow json values to be used as [SerializedBytes]
 #[derive(
     Debug,
@@ -58,6 +62,9 @@ impl<'a> arbitrary::Arbitrary<'a> for YamlProperties {
 /// It is included as part of a `DnaFile`. There is still a lot of code that uses
 /// this type, but in function, it has mainly been superseded by `DnaManifest`.
 ///
+/// The optional settings are skipped when None, so that DNAs which don't set
+/// them hash the same as they did before those fields existed.
+///
 /// TODO: after removing the `InstallApp` admin method, we can remove the Serialize
 ///       impl on this type, and document it/rename it to show that it is
 ///       basically a fully validated, normalized DnaManifest
@@ -80,6 +87,24 @@ pub struct DnaDef {
 
     /// An array of zomes associated with your DNA.
     pub zomes: Zomes,
+
+    /// The number of authorities which should hold each authored op.
+    /// If None, [`DEFAULT_REDUNDANCY_TARGET`] is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub redundancy_target: Option<u32>,
+
+    /// Limits on the resources used by each call into a zome of this DNA.
+    /// If None, zome calls are unlimited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub zome_call_limits: Option<ZomeCallLimits>,
+
+    /// Limits on the data which agents may publish in this DNA.
+    /// If None, the defaults of [`IntegrityLimits`] are used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub integrity: Option<IntegrityLimits>,
 }
 
 #[cfg(feature = "test_utils")]
@@ -122,6 +147,21 @@ impl DnaDef {
             })
     }
 
+    /// The number of authorities which should hold each op authored in this DNA
+    pub fn redundancy_target(&self) -> u32 {
+        self.redundancy_target.unwrap_or(DEFAULT_REDUNDANCY_TARGET)
+    }
+
+    /// The limits on the resources used by each call into a zome of this DNA
+    pub fn zome_call_limits(&self) -> ZomeCallLimits {
+        self.zome_call_limits.clone().unwrap_or_default()
+    }
+
+    /// The limits on the data which agents may publish in this DNA
+    pub fn integrity_limits(&self) -> IntegrityLimits {
+        self.integrity.clone().unwrap_or_default()
+    }
+
     /// Change the "phenotype" of this DNA -- the UID and properties -- while
     /// leaving the "genotype" of actual DNA code intact
     pub fn modify_phenotype(&self, uid: Uid, properties: YamlProperties) -> DnaResult<Self> {
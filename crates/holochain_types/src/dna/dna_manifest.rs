@@ -7,7 +7,9 @@ pub use dna_manifest_v1::{
     DnaManifestV1 as DnaManifestCurrent, DnaManifestV1Builder as DnaManifestCurrentBuilder, *,
 };
 
+use super::IntegrityLimits;
 use super::YamlProperties;
+use super::ZomeCallLimits;
 
 /// The enum which encompasses all versions of the DNA manifest, past and present.
 #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, derive_more::From)]
@@ -43,7 +45,16 @@ impl DnaManifest {
         properties: Option<YamlProperties>,
         zomes: Vec<ZomeManifest>,
     ) -> Self {
-        DnaManifestCurrent::new(name, uid, properties, zomes).into()
+        DnaManifestCurrent {
+            name,
+            uid,
+            properties,
+            zomes,
+            redundancy_target: None,
+            zome_call_limits: None,
+            integrity: None,
+        }
+        .into()
     }
 
     /// Getter for properties
@@ -66,4 +77,25 @@ impl DnaManifest {
             DnaManifest::V1(manifest) => manifest.name.clone(),
         }
     }
+
+    /// Getter for redundancy_target
+    pub fn redundancy_target(&self) -> Option<u32> {
+        match self {
+            DnaManifest::V1(manifest) => manifest.redundancy_target,
+        }
+    }
+
+    /// Getter for zome_call_limits
+    pub fn zome_call_limits(&self) -> Option<ZomeCallLimits> {
+        match self {
+            DnaManifest::V1(manifest) => manifest.zome_call_limits.clone(),
+        }
+    }
+
+    /// Getter for integrity
+    pub fn integrity(&self) -> Option<IntegrityLimits> {
+        match self {
+            DnaManifest::V1(manifest) => manifest.integrity.clone(),
+        }
+    }
 }
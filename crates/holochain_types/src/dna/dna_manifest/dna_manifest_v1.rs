@@ -29,6 +29,26 @@ pub struct DnaManifestV1 {
     /// An array of zomes associated with your DNA.
     /// The order is significant: it determines initialization order.
     pub(crate) zomes: Vec<ZomeManifest>,
+
+    /// The number of authorities which should hold each op authored in this
+    /// DNA. Ops which are held by fewer validators are re-published.
+    /// Defaults to [`DEFAULT_REDUNDANCY_TARGET`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub(crate) redundancy_target: Option<u32>,
+
+    /// Limits on the wall-clock time, wasm fuel and memory which each call
+    /// into a zome of this DNA may use. Unlimited by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub(crate) zome_call_limits: Option<ZomeCallLimits>,
+
+    /// Limits on the entries, links and headers which agents may publish,
+    /// which every node enforces in sys validation.
+    /// See [`IntegrityLimits`] for the defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub(crate) integrity: Option<IntegrityLimits>,
 }
 
 /// Manifest for an individual Zome
@@ -39,6 +59,12 @@ pub struct ZomeManifest {
     pub(crate) hash: Option<WasmHashB64>,
     #[serde(flatten)]
     pub(crate) location: ZomeLocation,
+
+    /// The host functions this zome is allowed to call.
+    /// If omitted, the zome may call any host function; otherwise, any
+    /// permission not declared here is denied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) host_fn_access: Option<HostFnAccess>,
 }
 
 /// Alias for a suitable representation of zome location
@@ -49,4 +75,9 @@ impl ZomeManifest {
     pub fn location(&self) -> &ZomeLocation {
         &self.location
     }
+
+    /// Accessor
+    pub fn host_fn_access(&self) -> Option<&HostFnAccess> {
+        self.host_fn_access.as_ref()
+    }
 }
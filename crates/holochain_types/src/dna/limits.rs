@@ -0,0 +1,214 @@
+//! Limits on the resources a DNA's zomes may use, and on the data its agents
+//! may publish
+
+use crate::prelude::*;
+
+/// Limits on the resources which a single call into a wasm zome of a DNA may
+/// use, whether it is a zome function or a callback. A call which exceeds
+/// any of these limits is aborted.
+///
+/// Every limit is optional, and a limit which is not set is not enforced.
+/// Calls to inline zomes are never limited.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(default, rename_all = "snake_case")]
+pub struct ZomeCallLimits {
+    /// The maximum wall-clock time, in milliseconds, which the guest may
+    /// spend executing. Time spent in host functions counts towards this, but
+    /// the call can only be aborted once control returns to the guest.
+    ///
+    /// Validation callbacks are not timed, as whether they finish in time
+    /// depends on the node running them, and every node must reach the same
+    /// validation outcome. They are still limited by `max_fuel`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+
+    /// The maximum amount of fuel which the guest may burn. Roughly one unit
+    /// of fuel is burned for every wasm instruction executed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fuel: Option<u64>,
+
+    /// The maximum number of 64KiB pages of linear memory which the guest may
+    /// grow its memory to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_memory_pages: Option<u32>,
+}
+
+impl ZomeCallLimits {
+    /// The wall-clock timeout, if any
+    pub fn timeout(&self) -> Option<std::time::Duration> {
+        self.timeout_ms.map(std::time::Duration::from_millis)
+    }
+
+    /// The same limits, but with no wall-clock timeout
+    pub fn without_timeout(&self) -> Self {
+        Self {
+            timeout_ms: None,
+            ..self.clone()
+        }
+    }
+
+    /// Whether any limit is set which requires the guest to be metered
+    /// as it executes
+    pub fn is_metered(&self) -> bool {
+        self.timeout_ms.is_some() || self.max_fuel.is_some()
+    }
+
+    /// Whether no limit is set at all
+    pub fn is_unlimited(&self) -> bool {
+        !self.is_metered() && self.max_memory_pages.is_none()
+    }
+}
+
+/// Limits the rate at which each agent may author headers in a DNA, so that a
+/// single agent can't flood the DHT of a public DNA.
+///
+/// Authorities hold the activity of each agent whose chain they validate, and
+/// reject any header which would make its author exceed `max_headers` headers
+/// within a `window_ms` window ending at the header's timestamp.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct PublishRateLimit {
+    /// The most headers an agent may author within the window.
+    /// Must be at least 1, as every agent has to author its genesis headers.
+    #[serde(deserialize_with = "deserialize_max_headers")]
+    pub max_headers: u32,
+
+    /// The length of the window, in milliseconds
+    pub window_ms: u64,
+}
+
+/// Reject a limit which no agent could ever meet
+fn deserialize_max_headers<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let max_headers = u32::deserialize(deserializer)?;
+    if max_headers == 0 {
+        return Err(serde::de::Error::custom(
+            "max_headers must be at least 1, or every header would exceed it",
+        ));
+    }
+    Ok(max_headers)
+}
+
+impl PublishRateLimit {
+    /// The length of the window
+    pub fn window(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.window_ms)
+    }
+}
+
+/// The default limit on the size of an app entry, in bytes.
+/// 16mb, due to websocket limits.
+/// Consider splitting large entries up.
+pub const DEFAULT_MAX_ENTRY_SIZE: u32 = 16_000_000;
+
+/// The default limit on the size of a link tag, in bytes.
+/// Tags are used as keys to the database to allow
+/// fast lookup so they need to be small.
+pub const DEFAULT_MAX_TAG_SIZE: u32 = 400;
+
+/// Limits on the data which the agents of a DNA may publish, enforced by
+/// every node in sys validation. They are part of the hashed [`DnaDef`], so
+/// that all nodes in the network agree on them.
+///
+/// The entry and tag sizes default to [`DEFAULT_MAX_ENTRY_SIZE`] and
+/// [`DEFAULT_MAX_TAG_SIZE`]. The other limits are not enforced unless set.
+///
+/// [`DnaDef`]: crate::dna::DnaDef
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(default, rename_all = "snake_case")]
+pub struct IntegrityLimits {
+    /// The maximum size of an app entry, in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_entry_size: Option<u32>,
+
+    /// The maximum size of a link tag, in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tag_size: Option<u32>,
+
+    /// The maximum number of links which may be made on a single base.
+    /// Links which have since been deleted still count towards this.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_links_per_base: Option<u32>,
+
+    /// The most headers each agent may author within a time window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish_rate_limit: Option<PublishRateLimit>,
+}
+
+impl IntegrityLimits {
+    /// The maximum size of an app entry, in bytes
+    pub fn max_entry_size(&self) -> usize {
+        self.max_entry_size.unwrap_or(DEFAULT_MAX_ENTRY_SIZE) as usize
+    }
+
+    /// The maximum size of a link tag, in bytes
+    pub fn max_tag_size(&self) -> usize {
+        self.max_tag_size.unwrap_or(DEFAULT_MAX_TAG_SIZE) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_from_yaml() {
+        let limits: ZomeCallLimits = serde_yaml::from_str("timeout_ms: 500").unwrap();
+        assert_eq!(
+            limits.timeout(),
+            Some(std::time::Duration::from_millis(500))
+        );
+        assert!(limits.is_metered());
+
+        let limits = ZomeCallLimits {
+            max_fuel: Some(1000),
+            ..limits
+        }
+        .without_timeout();
+        assert_eq!(limits.timeout(), None);
+        assert_eq!(limits.max_fuel, Some(1000));
+
+        let limits: ZomeCallLimits = serde_yaml::from_str("max_memory_pages: 16").unwrap();
+        assert!(!limits.is_metered());
+        assert!(!limits.is_unlimited());
+
+        // Unset limits are not serialized
+        assert_eq!(
+            serde_yaml::to_string(&ZomeCallLimits::default()).unwrap(),
+            serde_yaml::to_string(&serde_yaml::Mapping::new()).unwrap()
+        );
+    }
+
+    #[test]
+    fn integrity_limits_from_yaml() {
+        let limits: IntegrityLimits = serde_yaml::from_str(
+            "
+max_tag_size: 100
+max_links_per_base: 1000
+publish_rate_limit:
+  max_headers: 10
+  window_ms: 1000
+",
+        )
+        .unwrap();
+        assert_eq!(limits.max_tag_size(), 100);
+        assert_eq!(limits.max_entry_size(), DEFAULT_MAX_ENTRY_SIZE as usize);
+        assert_eq!(limits.max_links_per_base, Some(1000));
+        assert_eq!(
+            limits.publish_rate_limit.map(|limit| limit.window()),
+            Some(std::time::Duration::from_millis(1000))
+        );
+
+        // A rate limit that even genesis would exceed is rejected
+        assert!(serde_yaml::from_str::<IntegrityLimits>(
+            "
+publish_rate_limit:
+  max_headers: 0
+  window_ms: 1000
+",
+        )
+        .is_err());
+    }
+}
@@ -8,6 +8,7 @@
 
 use derive_more::Constructor;
 use holochain_serialized_bytes::prelude::*;
+use holochain_zome_types::zome::FunctionName;
 use holochain_zome_types::zome::ZomeName;
 use std::sync::Arc;
 
@@ -136,33 +137,91 @@ impl ZomeDef {
     /// If not, return an error with the provided zome name
     pub fn wasm_hash(&self, zome_name: &ZomeName) -> DnaResult<holo_hash::WasmHash> {
         match self {
-            ZomeDef::Wasm(WasmZome { wasm_hash }) => Ok(wasm_hash.clone()),
+            ZomeDef::Wasm(WasmZome { wasm_hash, .. }) => Ok(wasm_hash.clone()),
             _ => Err(DnaError::NonWasmZome(zome_name.clone())),
         }
     }
+
+    /// The host functions this zome is allowed to call, as declared in the
+    /// DNA manifest. Zomes which declare nothing may call any host function.
+    pub fn host_fn_access(&self) -> HostFnAccess {
+        match self {
+            ZomeDef::Wasm(WasmZome {
+                host_fn_access: Some(access),
+                ..
+            }) => *access,
+            _ => HostFnAccess::all(),
+        }
+    }
 }
 
 /// A zome defined by Wasm bytecode
 #[derive(
-    Serialize,
-    Deserialize,
-    Hash,
-    Clone,
-    Debug,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    SerializedBytes,
-    derive_more::Constructor,
+    Serialize, Deserialize, Hash, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, SerializedBytes,
 )]
 pub struct WasmZome {
     /// The WasmHash representing the WASM byte code for this zome.
     pub wasm_hash: holo_hash::WasmHash,
+    /// The host functions this zome is allowed to call, if restricted.
+    /// This is part of the DnaDef, so skipped when unset to keep the
+    /// hashes of unrestricted DNAs unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_fn_access: Option<HostFnAccess>,
+}
+
+impl WasmZome {
+    /// Constructor for a zome with unrestricted access to host functions
+    pub fn new(wasm_hash: holo_hash::WasmHash) -> Self {
+        Self {
+            wasm_hash,
+            host_fn_access: None,
+        }
+    }
+
+    /// Restrict the host functions this zome is allowed to call
+    pub fn with_host_fn_access(mut self, host_fn_access: HostFnAccess) -> Self {
+        self.host_fn_access = Some(host_fn_access);
+        self
+    }
 }
 
-/// Access a call has to host functions
-#[derive(Debug, Copy, Clone, Constructor, PartialEq)]
+/// The functions a zome exports, split into the zome functions which can be
+/// called from outside, and the callbacks which are called by Holochain.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, SerializedBytes)]
+pub struct ZomeExports {
+    /// Functions which can be called with a zome call
+    pub zome_fns: Vec<FunctionName>,
+    /// Callbacks such as `init` or `validate_create_entry`
+    pub callbacks: Vec<FunctionName>,
+}
+
+/// Access a call has to host functions.
+///
+/// As declared for a zome in the DNA manifest, any permission which is not
+/// listed is denied, e.g. a zome declaring only
+///
+/// ```yaml
+/// host_fn_access:
+///   read_workspace: allow
+///   write_workspace: allow
+/// ```
+///
+/// cannot make calls over the network or use the keystore.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Constructor,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+#[serde(default, rename_all = "snake_case")]
 pub struct HostFnAccess {
     /// Can access agent information
     pub agent_info: Permission,
@@ -180,7 +239,8 @@ pub struct HostFnAccess {
     pub keystore: Permission,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 /// Permission granted to a call
 pub enum Permission {
     /// Host functions with this access will be included
@@ -189,10 +249,16 @@ pub enum Permission {
     Deny,
 }
 
+impl Default for Permission {
+    fn default() -> Self {
+        Permission::Deny
+    }
+}
+
 impl ZomeDef {
     /// create a Zome from a holo_hash WasmHash instead of a holo_hash one
     pub fn from_hash(wasm_hash: holo_hash::WasmHash) -> Self {
-        WasmZome { wasm_hash }.into()
+        WasmZome::new(wasm_hash).into()
     }
 }
 
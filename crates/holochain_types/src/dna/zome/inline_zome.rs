@@ -63,6 +63,13 @@ impl InlineZome {
         self
     }
 
+    /// The names of all the functions and callbacks defined for this zome
+    pub fn function_names(&self) -> Vec<FunctionName> {
+        let mut names: Vec<_> = self.callbacks.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
     /// Make a call to an inline zome callback.
     /// If the callback doesn't exist, return None.
     pub fn maybe_call(
@@ -85,7 +85,7 @@ fixturator!(
 
 fn new_entry_element(entry: Entry, header_type: HeaderType, index: usize) -> Element {
     let et = match entry {
-        Entry::App(_) => EntryType::App(
+        Entry::App(_) | Entry::CounterSign(_, _) => EntryType::App(
             AppEntryTypeFixturator::new_indexed(Unpredictable, index)
                 .next()
                 .unwrap(),
@@ -127,7 +127,7 @@ fixturator!(
     };
     curve Entry {
         let et = match get_fixt_curve!() {
-            Entry::App(_) => EntryType::App(AppEntryTypeFixturator::new_indexed(Unpredictable, get_fixt_index!()).next().unwrap()),
+            Entry::App(_) | Entry::CounterSign(_, _) => EntryType::App(AppEntryTypeFixturator::new_indexed(Unpredictable, get_fixt_index!()).next().unwrap()),
             Entry::Agent(_) => EntryType::AgentPubKey,
             Entry::CapClaim(_) => EntryType::CapClaim,
             Entry::CapGrant(_) => EntryType::CapGrant,
@@ -156,6 +156,9 @@ fixturator!(
         zomes: ZomesFixturator::new_indexed(Empty, get_fixt_index!())
             .next()
             .unwrap(),
+        redundancy_target: None,
+        zome_call_limits: None,
+        integrity: None,
     };
 
     curve Unpredictable DnaDef {
@@ -171,6 +174,9 @@ fixturator!(
         zomes: ZomesFixturator::new_indexed(Unpredictable, get_fixt_index!())
             .next()
             .unwrap(),
+        redundancy_target: None,
+        zome_call_limits: None,
+        integrity: None,
     };
 
     curve Predictable DnaDef {
@@ -186,5 +192,8 @@ fixturator!(
         zomes: ZomesFixturator::new_indexed(Predictable, get_fixt_index!())
             .next()
             .unwrap(),
+        redundancy_target: None,
+        zome_call_limits: None,
+        integrity: None,
     };
 );
@@ -27,6 +27,28 @@ pub enum SystemSignal {
     /// Since we have no real system signals, we use a test signal for testing
     /// TODO: replace instances of this with something real
     Test(String),
+    /// The `post_commit` callback of a zome failed, after the zome call
+    /// which committed these headers had already returned
+    PostCommitFailed {
+        /// The cell which committed the headers
+        cell_id: CellId,
+        /// The zome whose callback failed
+        zome_name: ZomeName,
+        /// The headers which were committed
+        headers: Vec<HeaderHash>,
+        /// Why the callback failed
+        error: String,
+    },
+    /// A fork of a source chain has been detected, either by the cell which
+    /// authored the chain or by a cell holding the author's agent activity
+    ChainForkDetected {
+        /// The cell which detected the fork
+        cell_id: CellId,
+        /// The author of the forked chain
+        author: AgentPubKey,
+        /// Where the chain forked
+        fork: ChainFork,
+    },
 }
 
 /// Create a test signal
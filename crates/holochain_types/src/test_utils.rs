@@ -32,13 +32,16 @@ pub fn fake_dna_zomes(uid: &str, zomes: Vec<(ZomeName, DnaWasm)>) -> DnaFile {
             .unwrap(),
         uid: uid.to_string(),
         zomes: Vec::new(),
+        redundancy_target: None,
+        zome_call_limits: None,
+        integrity: None,
     };
     tokio_helper::block_forever_on(async move {
         let mut wasm_code = Vec::new();
         for (zome_name, wasm) in zomes {
             let wasm = crate::dna::wasm::DnaWasmHashed::from_content(wasm).await;
             let (wasm, wasm_hash) = wasm.into_inner();
-            dna.zomes.push((zome_name, WasmZome { wasm_hash }.into()));
+            dna.zomes.push((zome_name, WasmZome::new(wasm_hash).into()));
             wasm_code.push(wasm);
         }
         DnaFile::new(dna, wasm_code).await
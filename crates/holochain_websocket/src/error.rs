@@ -10,6 +10,8 @@ pub enum WebsocketError {
     #[error(transparent)]
     Infallible(#[from] std::convert::Infallible),
     #[error(transparent)]
+    Tls(#[from] native_tls::Error),
+    #[error(transparent)]
     SerializedBytesError(#[from] SerializedBytesError),
     #[error("Failed to receive response to request")]
     FailedToRecvResp,
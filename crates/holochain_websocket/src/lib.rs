@@ -85,6 +85,7 @@ use stream_cancel::Valve;
 use tracing::instrument;
 use url2::Url2;
 use util::url_to_addr;
+use util::Socket;
 use websocket::Websocket;
 
 mod websocket_config;
@@ -119,6 +120,13 @@ pub async fn connect(
     // socket.set_keepalive(Some(std::time::Duration::from_secs(
     //     config.tcp_keepalive_s as u64,
     // )))?;
+    let socket = if config.scheme == "wss" {
+        let domain = url.host_str().unwrap_or_default();
+        let connector = tokio_native_tls::TlsConnector::from(native_tls::TlsConnector::new()?);
+        Socket::Tls(connector.connect(domain, socket).await?)
+    } else {
+        Socket::Tcp(socket)
+    };
     let (socket, _) = tokio_tungstenite::client_async_with_config(
         url.as_str(),
         socket,
@@ -135,6 +143,30 @@ pub async fn connect(
     Websocket::create_ends(config, socket, valve)
 }
 
+#[cfg(unix)]
+#[instrument(skip(config))]
+/// Create a new websocket connection over the unix domain socket at `path`.
+/// Messages are framed exactly as they are over tcp.
+pub async fn connect_unix(
+    path: std::path::PathBuf,
+    config: Arc<WebsocketConfig>,
+) -> WebsocketResult<(WebsocketSender, WebsocketReceiver)> {
+    let socket = Socket::Unix(tokio::net::UnixStream::connect(&path).await?);
+    // The handshake still needs a request url, but there is no host to name.
+    let (socket, _) = tokio_tungstenite::client_async_with_config(
+        "ws://localhost/",
+        socket,
+        Some(config.to_tungstenite()),
+    )
+    .await
+    .map_err(|e| Error::new(ErrorKind::Other, e))?;
+    tracing::debug!("Client connected");
+
+    let (exit, valve) = Valve::new();
+    exit.disable();
+    Websocket::create_ends(config, socket, valve)
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, SerializedBytes)]
 #[serde(tag = "type")]
 /// The messages actually sent over the wire by this library.
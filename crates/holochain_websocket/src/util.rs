@@ -5,8 +5,85 @@ use std::net::SocketAddr;
 use url2::{url2, Url2};
 
 use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
-pub(crate) type ToFromSocket = tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>;
+/// The underlying stream of a websocket: a tcp stream, which may be wrapped
+/// in tls depending on the scheme, or a unix domain socket.
+pub(crate) enum Socket {
+    Tcp(tokio::net::TcpStream),
+    Tls(tokio_native_tls::TlsStream<tokio::net::TcpStream>),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixStream),
+}
+
+pub(crate) type ToFromSocket = tokio_tungstenite::WebSocketStream<Socket>;
+
+/// internal helper to get the url of the remote end of a socket
+pub(crate) fn remote_url(socket: &Socket, scheme: &str) -> Result<Url2> {
+    match socket {
+        Socket::Tcp(s) => Ok(addr_to_url(s.peer_addr()?, scheme)),
+        Socket::Tls(s) => Ok(addr_to_url(
+            s.get_ref().get_ref().get_ref().peer_addr()?,
+            scheme,
+        )),
+        // The connecting end of a unix socket is usually unnamed,
+        // so the path of the listening end is used instead.
+        #[cfg(unix)]
+        Socket::Unix(s) => {
+            let local = s.local_addr()?;
+            let addr = match local.as_pathname() {
+                Some(_) => local,
+                None => s.peer_addr()?,
+            };
+            match addr.as_pathname() {
+                Some(path) => Ok(unix_path_to_url(path)),
+                None => Ok(url2!("unix://")),
+            }
+        }
+    }
+}
+
+/// internal helper to convert a unix socket path to a url
+#[cfg(unix)]
+pub(crate) fn unix_path_to_url(path: &std::path::Path) -> Url2 {
+    url2!("unix://{}", path.display())
+}
+
+macro_rules! delegate_socket {
+    ($self:ident, $s:ident => $e:expr) => {
+        match $self.get_mut() {
+            Socket::Tcp($s) => $e,
+            Socket::Tls($s) => $e,
+            #[cfg(unix)]
+            Socket::Unix($s) => $e,
+        }
+    };
+}
+
+impl tokio::io::AsyncRead for Socket {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<Result<()>> {
+        delegate_socket!(self, s => Pin::new(s).poll_read(cx, buf))
+    }
+}
+
+impl tokio::io::AsyncWrite for Socket {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        delegate_socket!(self, s => Pin::new(s).poll_write(cx, buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        delegate_socket!(self, s => Pin::new(s).poll_flush(cx))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        delegate_socket!(self, s => Pin::new(s).poll_shutdown(cx))
+    }
+}
 
 /// Amount of time to spend waiting for channels to empty before forcing them to close.
 pub(crate) const CLOSE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
@@ -16,7 +16,7 @@ use ghost_actor::*;
 use tungstenite::protocol::frame::coding::CloseCode;
 use tungstenite::protocol::CloseFrame;
 
-use crate::util::addr_to_url;
+use crate::util::remote_url;
 use crate::util::ToFromSocket;
 use crate::util::CLOSE_TIMEOUT;
 use crate::CancelResponse;
@@ -128,7 +128,7 @@ impl Websocket {
     ) -> WebsocketResult<(WebsocketSender, WebsocketReceiver)> {
         let remote_addr = url2::url2!(
             "{}#{}",
-            addr_to_url(socket.get_ref().peer_addr()?, config.scheme),
+            remote_url(socket.get_ref(), config.scheme)?,
             nanoid::nanoid!(),
         );
 
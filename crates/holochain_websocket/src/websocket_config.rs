@@ -1,5 +1,7 @@
 //! defines a builder-style config struct for setting up websockets
 
+use std::path::PathBuf;
+
 /// A builder-style config struct for setting up websockets.
 #[derive(Debug)]
 pub struct WebsocketConfig {
@@ -26,6 +28,26 @@ pub struct WebsocketConfig {
 
     /// Maximum number of pending new incoming connections. [default = 255]
     pub max_pending_connections: usize,
+
+    /// If set, a listener will only accept connections whose `Origin` header
+    /// matches one of these origins, or `"*"` to match any origin.
+    /// Connections which send no `Origin` header, i.e. those not made from
+    /// a browser, are always accepted. [default = None]
+    pub allowed_origins: Option<Vec<String>>,
+
+    /// If set, a listener will accept tls connections using this certificate
+    /// and private key, and a client will connect over tls.
+    /// The scheme should be set to "wss" when using tls. [default = None]
+    pub tls: Option<WebsocketTlsConfig>,
+}
+
+/// The certificate and private key a listener uses to accept tls connections.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebsocketTlsConfig {
+    /// Path to the PEM encoded certificate chain
+    pub cert_path: PathBuf,
+    /// Path to the PEM encoded PKCS #8 private key
+    pub key_path: PathBuf,
 }
 
 impl Default for WebsocketConfig {
@@ -38,6 +60,8 @@ impl Default for WebsocketConfig {
             max_message_size: 64 << 20,
             max_frame_size: 16 << 20,
             max_pending_connections: 255,
+            allowed_origins: None,
+            tls: None,
         }
     }
 }
@@ -78,6 +102,36 @@ impl WebsocketConfig {
         self.max_frame_size = max;
         self
     }
+
+    /// Builder-style setter.
+    pub fn max_pending_connections(mut self, max: usize) -> Self {
+        self.max_pending_connections = max;
+        self
+    }
+
+    /// Builder-style setter.
+    pub fn allowed_origins(mut self, origins: Option<Vec<String>>) -> Self {
+        self.allowed_origins = origins;
+        self
+    }
+
+    /// Builder-style setter.
+    /// Also sets the scheme to "wss" if tls is set.
+    pub fn tls(mut self, tls: Option<WebsocketTlsConfig>) -> Self {
+        if tls.is_some() {
+            self.scheme = "wss";
+        }
+        self.tls = tls;
+        self
+    }
+
+    /// Whether a connection with the given `Origin` header may be accepted
+    pub fn is_origin_allowed(&self, origin: Option<&str>) -> bool {
+        match (&self.allowed_origins, origin) {
+            (None, _) | (_, None) => true,
+            (Some(allowed), Some(origin)) => allowed.iter().any(|a| a == "*" || a == origin),
+        }
+    }
 }
 
 /// internal helper to convert our configs into tungstenite configs
@@ -8,12 +8,21 @@ use std::sync::Arc;
 use stream_cancel::Trigger;
 use stream_cancel::Valve;
 use tracing::instrument;
+use tungstenite::handshake::server::ErrorResponse;
+use tungstenite::handshake::server::Request;
+use tungstenite::handshake::server::Response;
+use tungstenite::http::header::ORIGIN;
+use tungstenite::http::StatusCode;
 
 use url2::Url2;
 
 use crate::util::addr_to_url;
+#[cfg(unix)]
+use crate::util::unix_path_to_url;
 use crate::util::url_to_addr;
+use crate::util::Socket;
 use crate::websocket::Websocket;
+use crate::TungsteniteConfigExt;
 use crate::WebsocketConfig;
 use crate::WebsocketError;
 use crate::WebsocketReceiver;
@@ -106,6 +115,41 @@ impl WebsocketListener {
     )> {
         websocket_bind(addr, config).await
     }
+
+    /// Bind to a unix domain socket at the given path to accept incoming
+    /// connections. The socket file is given the permissions `mode`, so only
+    /// users which can write to the file are able to connect. The socket only
+    /// appears at the path once it has these permissions.
+    ///
+    /// A stale socket file left behind at this path is replaced, but binding
+    /// fails if another listener is still accepting connections on it.
+    #[cfg(unix)]
+    pub async fn bind_unix(
+        path: std::path::PathBuf,
+        mode: u32,
+        config: Arc<WebsocketConfig>,
+    ) -> WebsocketResult<Self> {
+        let (handle, stream) = Self::bind_unix_with_handle(path, mode, config).await?;
+        Ok(Self {
+            handle,
+            stream: stream.boxed(),
+        })
+    }
+
+    #[cfg(unix)]
+    #[instrument(skip(config, path))]
+    /// Same as [`WebsocketListener::bind_unix`] but gives you a [`ListenerHandle`] to shutdown
+    /// the listener and any open connections.
+    pub async fn bind_unix_with_handle(
+        path: std::path::PathBuf,
+        mode: u32,
+        config: Arc<WebsocketConfig>,
+    ) -> WebsocketResult<(
+        ListenerHandle,
+        impl futures::stream::Stream<Item = ListenerItem>,
+    )> {
+        unix_bind(path, mode, config).await
+    }
     /// Shutdown the listener stream.
     pub fn close(self) {
         self.handle.close()
@@ -202,6 +246,17 @@ async fn websocket_bind(
     socket.set_nonblocking(true)?;
     let local_addr = addr_to_url(socket.local_addr()?, config.scheme);
     let listener = tokio::net::TcpListener::from_std(socket)?;
+    let tls_acceptor = match &config.tls {
+        Some(tls) => {
+            let cert = std::fs::read(&tls.cert_path)?;
+            let key = std::fs::read(&tls.key_path)?;
+            let identity = native_tls::Identity::from_pkcs8(&cert, &key)?;
+            Some(tokio_native_tls::TlsAcceptor::from(
+                native_tls::TlsAcceptor::new(identity)?,
+            ))
+        }
+        None => None,
+    };
     let listener_stream = tokio_stream::wrappers::TcpListenerStream::new(listener);
 
     // Setup proper shutdown
@@ -212,7 +267,106 @@ async fn websocket_bind(
         .map_ok({
             let config = config.clone();
             let valve = valve.clone();
-            move |socket_result| connect(config.clone(), socket_result, valve.clone())
+            move |socket_result| {
+                connect(
+                    config.clone(),
+                    socket_result,
+                    tls_acceptor.clone(),
+                    valve.clone(),
+                )
+            }
+        })
+        .try_buffer_unordered(config.max_pending_connections);
+    tracing::debug!(sever_listening_on = ?local_addr);
+
+    let stream = valve.wrap(buffered_listener);
+
+    let listener_handle = ListenerHandle {
+        shutdown,
+        config,
+        local_addr,
+    };
+    Ok((listener_handle, stream))
+}
+
+#[cfg(unix)]
+async fn unix_bind(
+    path: std::path::PathBuf,
+    mode: u32,
+    config: Arc<WebsocketConfig>,
+) -> WebsocketResult<(
+    ListenerHandle,
+    impl futures::stream::Stream<Item = ListenerItem>,
+)> {
+    use std::os::unix::fs::DirBuilderExt;
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Ok(metadata) = std::fs::symlink_metadata(&path) {
+        if !metadata.file_type().is_socket() {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            )
+            .into());
+        }
+        if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+            return Err(Error::new(
+                ErrorKind::AddrInUse,
+                format!("{} is already being listened on", path.display()),
+            )
+            .into());
+        }
+        std::fs::remove_file(&path)?;
+    }
+    // Bind in a directory which only we can enter, and only move the socket
+    // to its path once it has its permissions, so nobody else can connect
+    // to it in between.
+    let file_name = path.file_name().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} is not a path to a file", path.display()),
+        )
+    })?;
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| std::path::Path::new("."));
+    let private_dir = parent.join(format!(
+        ".{}.{}",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+    if private_dir.exists() {
+        std::fs::remove_dir_all(&private_dir)?;
+    }
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)?;
+    let private_path = private_dir.join("socket");
+    let bound = (|| -> std::io::Result<_> {
+        let listener = tokio::net::UnixListener::bind(&private_path)?;
+        std::fs::set_permissions(&private_path, std::fs::Permissions::from_mode(mode))?;
+        std::fs::rename(&private_path, &path)?;
+        Ok(listener)
+    })();
+    std::fs::remove_dir_all(&private_dir)?;
+    let listener = bound?;
+    let local_addr = unix_path_to_url(&path);
+    let listener_stream = tokio_stream::wrappers::UnixListenerStream::new(listener);
+
+    // Setup proper shutdown
+    let (shutdown, valve) = Valve::new();
+
+    let buffered_listener = listener_stream
+        .map_err(WebsocketError::from)
+        .map_ok({
+            let config = config.clone();
+            let valve = valve.clone();
+            move |socket| {
+                tracing::debug!(message = "accepted incoming unix socket");
+                accept(config.clone(), Socket::Unix(socket), valve.clone())
+            }
         })
         .try_buffer_unordered(config.max_pending_connections);
     tracing::debug!(sever_listening_on = ?local_addr);
@@ -227,10 +381,11 @@ async fn websocket_bind(
     Ok((listener_handle, stream))
 }
 
-#[instrument(skip(config, socket, valve))]
+#[instrument(skip(config, socket, tls_acceptor, valve))]
 async fn connect(
     config: Arc<WebsocketConfig>,
     socket: tokio::net::TcpStream,
+    tls_acceptor: Option<tokio_native_tls::TlsAcceptor>,
     valve: Valve,
 ) -> WebsocketResult<Pair> {
     // TODO: find alternative to set the keepalive
@@ -241,14 +396,40 @@ async fn connect(
         message = "accepted incoming raw socket",
         remote_addr = %socket.peer_addr()?,
     );
-    let socket = tokio_tungstenite::accept_async_with_config(
+    let socket = match tls_acceptor {
+        Some(acceptor) => Socket::Tls(acceptor.accept(socket).await?),
+        None => Socket::Tcp(socket),
+    };
+    accept(config, socket, valve).await
+}
+
+/// Perform the websocket handshake on an accepted socket.
+async fn accept(
+    config: Arc<WebsocketConfig>,
+    socket: Socket,
+    valve: Valve,
+) -> WebsocketResult<Pair> {
+    let origin_check = {
+        let config = config.clone();
+        move |request: &Request, response: Response| {
+            let origin = request
+                .headers()
+                .get(ORIGIN)
+                .map(|origin| origin.to_str().unwrap_or_default());
+            if config.is_origin_allowed(origin) {
+                Ok(response)
+            } else {
+                tracing::warn!(?origin, "rejected connection from a disallowed origin");
+                let mut response = ErrorResponse::new(Some("Origin not allowed".to_string()));
+                *response.status_mut() = StatusCode::FORBIDDEN;
+                Err(response)
+            }
+        }
+    };
+    let socket = tokio_tungstenite::accept_hdr_async_with_config(
         socket,
-        Some(tungstenite::protocol::WebSocketConfig {
-            max_send_queue: Some(config.max_send_queue),
-            max_message_size: Some(config.max_message_size),
-            max_frame_size: Some(config.max_frame_size),
-            ..Default::default()
-        }),
+        origin_check,
+        Some(config.to_tungstenite()),
     )
     .await
     .map_err(|e| Error::new(ErrorKind::Other, e))?;
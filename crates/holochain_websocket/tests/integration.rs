@@ -452,3 +452,93 @@ async fn cancel_response() {
     c_jh.await.unwrap();
     s_jh.await.unwrap();
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_disallowed_origins() {
+    use tungstenite::client::IntoClientRequest;
+    observability::test_run().ok();
+    let (handle, mut listener) = WebsocketListener::bind_with_handle(
+        url2!("ws://127.0.0.1:0"),
+        Arc::new(
+            WebsocketConfig::default()
+                .allowed_origins(Some(vec!["http://localhost:8888".to_string()])),
+        ),
+    )
+    .await
+    .unwrap();
+    tokio::task::spawn(async move { while let Some(_) = listener.next().await {} });
+    let binding = handle.local_addr().clone();
+
+    let with_origin = |origin: &'static str| {
+        let mut request = binding.as_str().into_client_request().unwrap();
+        request
+            .headers_mut()
+            .insert("Origin", origin.parse().unwrap());
+        request
+    };
+
+    // A browser on a disallowed origin is refused
+    let r = tokio_tungstenite::connect_async(with_origin("http://example.com")).await;
+    assert!(r.is_err());
+
+    // A browser on an allowed origin is accepted
+    tokio_tungstenite::connect_async(with_origin("http://localhost:8888"))
+        .await
+        .expect("Failed to connect from allowed origin");
+
+    // A client which is not a browser sends no origin and is accepted
+    connect(binding.clone(), Arc::new(WebsocketConfig::default()))
+        .await
+        .expect("Failed to connect without origin");
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn unix_socket_round_trip() {
+    use holochain_websocket::connect_unix;
+    use std::os::unix::fs::PermissionsExt;
+    observability::test_run().ok();
+    let dir = tempdir::TempDir::new("unix_socket").unwrap();
+    let path = dir.path().join("admin.sock");
+    let (_handle, mut listener) = WebsocketListener::bind_unix_with_handle(
+        path.clone(),
+        0o600,
+        Arc::new(WebsocketConfig::default()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        std::fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+        0o600
+    );
+    // Nothing is left behind from binding the socket
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    tokio::task::spawn(async move {
+        while let Some(Ok((_, mut recv))) = listener.next().await {
+            tokio::task::spawn(async move {
+                while let Some((msg, resp)) = recv.next().await {
+                    let msg: TestString = msg.try_into().unwrap();
+                    resp.respond(TestString(format!("echo: {}", msg.0)).try_into().unwrap())
+                        .await
+                        .unwrap();
+                }
+            });
+        }
+    });
+
+    let (mut send, _recv) = connect_unix(path.clone(), Arc::new(WebsocketConfig::default()))
+        .await
+        .unwrap();
+    let rsp: TestString = send.request(TestString("hi".into())).await.unwrap();
+    assert_eq!(rsp.0, "echo: hi");
+
+    // The socket is still in use, so it can't be taken over
+    assert!(WebsocketListener::bind_unix(
+        path.clone(),
+        0o600,
+        Arc::new(WebsocketConfig::default())
+    )
+    .await
+    .is_err());
+}
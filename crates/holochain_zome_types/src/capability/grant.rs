@@ -1,4 +1,5 @@
 use super::CapSecret;
+use crate::timestamp::Timestamp;
 use crate::zome::FunctionName;
 use crate::zome::ZomeName;
 use holo_hash::*;
@@ -14,7 +15,8 @@ use std::collections::BTreeSet;
 /// The CapGrant is not always a dedicated entry in the chain.
 /// Notably AgentPubKey entries in the current chain act like root access to local zome calls.
 ///
-/// A `CapGrant` is valid if it matches the function, agent and secret for a given zome call.
+/// A `CapGrant` is valid if it matches the function, agent and secret for a given zome call,
+/// and, for grants to remote agents, it has neither expired nor been used up.
 ///
 /// See `.is_valid()`
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -39,9 +41,26 @@ impl From<holo_hash::AgentPubKey> for CapGrant {
 }
 
 #[derive(Default, PartialEq, Eq, Debug, Clone, serde::Serialize, serde::Deserialize)]
-/// @todo Ability to forcibly curry payloads into functions that are called with a claim.
+/// Payloads which are forcibly curried into functions that are called with a grant.
+///
+/// Each payload pins some or all of the fields of the function's input, and is usually the
+/// serialization of a struct with just those fields. A call with the grant may omit the pinned
+/// fields, in which case they are filled in, but if it gives them they must match. A payload which
+/// is not a map pins the whole input.
 pub struct CurryPayloads(pub BTreeMap<GrantedFunction, SerializedBytes>);
 
+impl CurryPayloads {
+    /// Are there no curried payloads?
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The payload curried into a function, if any
+    pub fn get(&self, function: &GrantedFunction) -> Option<&SerializedBytes> {
+        self.0.get(function)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 /// The entry for the ZomeCall capability grant.
 /// This data is committed to the callee's source chain as a private entry.
@@ -55,25 +74,64 @@ pub struct ZomeCallCapGrant {
     pub access: CapAccess,
     /// Set of functions to which this capability grants ZomeCall access
     pub functions: GrantedFunctions,
-    // @todo the payloads to curry to the functions
-    // pub curry_payloads: CurryPayloads,
+    /// The time after which this grant no longer gives access.
+    /// If `None` the grant is valid until it is deleted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<Timestamp>,
+    /// The number of zome calls this grant gives access to.
+    /// If `None` the grant may be used any number of times.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<u32>,
+    /// The payloads to curry into the functions
+    #[serde(default, skip_serializing_if = "CurryPayloads::is_empty")]
+    pub curry_payloads: CurryPayloads,
 }
 
 impl ZomeCallCapGrant {
     /// Constructor
-    pub fn new(
-        tag: String,
-        access: CapAccess,
-        functions: GrantedFunctions,
-        // @todo curry_payloads: CurryPayloads,
-    ) -> Self {
+    pub fn new(tag: String, access: CapAccess, functions: GrantedFunctions) -> Self {
         Self {
             tag,
             access,
             functions,
-            // @todo curry_payloads,
+            expires_at: None,
+            max_uses: None,
+            curry_payloads: CurryPayloads::default(),
         }
     }
+
+    /// Curry payloads into the granted functions
+    pub fn with_curry_payloads(mut self, curry_payloads: CurryPayloads) -> Self {
+        self.curry_payloads = curry_payloads;
+        self
+    }
+
+    /// Make the grant give access only until the given time
+    pub fn expiring_at(mut self, expires_at: Timestamp) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Make the grant give access to at most the given number of zome calls
+    pub fn with_max_uses(mut self, max_uses: u32) -> Self {
+        self.max_uses = Some(max_uses);
+        self
+    }
+
+    /// Has the grant expired at the given time?
+    pub fn is_expired(&self, now: &Timestamp) -> bool {
+        self.expires_at
+            .as_ref()
+            .map(|expires_at| now >= expires_at)
+            .unwrap_or(false)
+    }
+
+    /// Has the grant been used up, given the number of times it has been used?
+    pub fn is_used_up(&self, uses: u32) -> bool {
+        self.max_uses
+            .map(|max_uses| uses >= max_uses)
+            .unwrap_or(false)
+    }
 }
 
 impl From<ZomeCallCapGrant> for CapGrant {
@@ -87,21 +145,30 @@ impl CapGrant {
     /// Given a grant, is it valid in isolation?
     /// In a world of CRUD, some new entry might update or delete an existing one, but we can check
     /// if a grant is valid in a standalone way.
+    ///
+    /// `now` is the time of the call and `uses` is the number of calls the grant has already
+    /// given access to. Neither matter for the chain author.
     pub fn is_valid(
         &self,
         check_function: &GrantedFunction,
         check_agent: &AgentPubKey,
         check_secret: Option<&CapSecret>,
+        now: &Timestamp,
+        uses: u32,
     ) -> bool {
         match self {
             // Grant is always valid if the author matches the check agent.
             CapGrant::ChainAuthor(author) => author == check_agent,
             // Otherwise we need to do more work…
-            CapGrant::RemoteAgent(ZomeCallCapGrant {
-                access, functions, ..
-            }) => {
+            CapGrant::RemoteAgent(grant) => {
+                let ZomeCallCapGrant {
+                    access, functions, ..
+                } = grant;
+                // The grant needs to be live…
+                !grant.is_expired(now)
+                && !grant.is_used_up(uses)
                 // The checked function needs to be in the grant…
-                functions.contains(check_function)
+                && functions.contains(check_function)
                 // The agent needs to be valid…
                 && match access {
                     // The grant is assigned so the agent needs to match…
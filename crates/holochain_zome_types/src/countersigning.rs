@@ -0,0 +1,404 @@
+//! Countersigning of entries, so that several agents can commit the same
+//! entry atomically, e.g. both parties of a mutual-credit transaction.
+//!
+//! A countersigning session goes as follows:
+//! - One agent builds a [PreflightRequest] naming the entry to be committed,
+//!   the agents who have to sign it, and the session window, and sends it to
+//!   every other agent.
+//! - Each agent accepts the request, which locks their source chain until the
+//!   session ends and signs their [CounterSigningAgentState], i.e. the position
+//!   on their chain that the entry will be committed at.
+//! - The [PreflightResponse]s are collected into a [CounterSigningSessionData]
+//!   and sent back to every agent, who then commits an `Entry::CounterSign`
+//!   carrying the session data alongside the app entry. That commit releases
+//!   the lock on their chain.
+//!
+//! If a session isn't completed before it ends, each chain unlocks itself and
+//! the countersigned entry can no longer be committed.
+
+use crate::signature::Signature;
+use crate::timestamp::Timestamp;
+use holo_hash::AgentPubKey;
+use holo_hash::EntryHash;
+use holo_hash::HeaderHash;
+use holochain_serialized_bytes::prelude::*;
+
+/// The longest a countersigning session may last, and so the longest any
+/// source chain can be locked for by one.
+pub const SESSION_MAX_DURATION: core::time::Duration = core::time::Duration::from_secs(5 * 60);
+
+/// How far in the future a countersigning session may start for an agent to
+/// accept taking part in it.
+pub const SESSION_START_FUTURE_MAX: core::time::Duration = core::time::Duration::from_secs(60);
+
+/// Errors in the data of a countersigning session
+#[derive(Debug, Clone, thiserror::Error, PartialEq)]
+pub enum CounterSigningError {
+    /// The session ends before it starts, or lasts too long
+    #[error(
+        "The countersigning session window from {0} to {1} is invalid. Sessions must end after they start and last no longer than {:?}",
+        SESSION_MAX_DURATION
+    )]
+    InvalidSessionTimes(Timestamp, Timestamp),
+
+    /// The request doesn't name a single agent more than once, or names too
+    /// many agents to index
+    #[error(
+        "The countersigning session must have between 1 and {} distinct signing agents",
+        u8::MAX
+    )]
+    InvalidSigningAgents,
+
+    /// There isn't exactly one response from each signing agent
+    #[error("The countersigning session has {0} responses for {1} signing agents")]
+    ResponseCount(usize, usize),
+
+    /// A response is not for the same request as the others
+    #[error("A countersigning response was made to a different preflight request")]
+    MismatchedRequest,
+
+    /// A response is not from the agent at its own index
+    #[error("The countersigning response at index {0} gives the agent index {1}")]
+    MismatchedAgentIndex(usize, u8),
+}
+
+/// The window of time a countersigning session is open for.
+/// The countersigned entry must be committed within it, and the chains of the
+/// signing agents are locked until it ends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, SerializedBytes)]
+pub struct CounterSigningSessionTimes {
+    /// When the session opens
+    pub start: Timestamp,
+    /// When the session closes
+    pub end: Timestamp,
+}
+
+impl CounterSigningSessionTimes {
+    /// Constructor
+    pub fn new(start: Timestamp, end: Timestamp) -> Self {
+        Self { start, end }
+    }
+
+    /// Check the session ends after it starts and isn't longer than
+    /// [SESSION_MAX_DURATION]
+    pub fn check(&self) -> Result<(), CounterSigningError> {
+        let too_long = match self.start + SESSION_MAX_DURATION {
+            Ok(latest_end) => self.end > latest_end,
+            Err(_) => true,
+        };
+        if self.end <= self.start || too_long {
+            Err(CounterSigningError::InvalidSessionTimes(
+                self.start, self.end,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether the time falls within the session
+    pub fn contains(&self, time: &Timestamp) -> bool {
+        *time >= self.start && *time <= self.end
+    }
+}
+
+/// Arbitrary bytes the app can attach to a [PreflightRequest], e.g. to say
+/// why the session was started.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreflightBytes(#[serde(with = "serde_bytes")] pub Vec<u8>);
+
+/// The request to take part in a countersigning session, which every agent
+/// accepts before the countersigned entry can be committed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, SerializedBytes)]
+pub struct PreflightRequest {
+    /// The hash of the app entry, i.e. `Entry::App`, to be countersigned
+    pub app_entry_hash: EntryHash,
+    /// The agents who must all sign the entry, in order.
+    /// Each agent's position is their agent index in the session.
+    pub signing_agents: Vec<AgentPubKey>,
+    /// When the session is open
+    pub session_times: CounterSigningSessionTimes,
+    /// Bytes the app can use as it likes
+    pub preflight_bytes: PreflightBytes,
+}
+
+impl PreflightRequest {
+    /// Constructor
+    pub fn new(
+        app_entry_hash: EntryHash,
+        signing_agents: Vec<AgentPubKey>,
+        session_times: CounterSigningSessionTimes,
+        preflight_bytes: PreflightBytes,
+    ) -> Self {
+        Self {
+            app_entry_hash,
+            signing_agents,
+            session_times,
+            preflight_bytes,
+        }
+    }
+
+    /// Check the request is well formed
+    pub fn check(&self) -> Result<(), CounterSigningError> {
+        self.session_times.check()?;
+        let agents = &self.signing_agents;
+        if agents.is_empty()
+            || agents.len() > u8::MAX as usize
+            || agents
+                .iter()
+                .enumerate()
+                .any(|(i, agent)| agents[..i].contains(agent))
+        {
+            return Err(CounterSigningError::InvalidSigningAgents);
+        }
+        Ok(())
+    }
+
+    /// The agent index of this agent in the session, if they are a signing
+    /// agent
+    pub fn agent_index(&self, agent: &AgentPubKey) -> Option<u8> {
+        self.signing_agents
+            .iter()
+            .position(|signing_agent| signing_agent == agent)
+            .map(|i| i as u8)
+    }
+}
+
+/// The position on their source chain at which an agent will commit the
+/// countersigned entry.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, SerializedBytes)]
+pub struct CounterSigningAgentState {
+    /// The agent's position in [PreflightRequest::signing_agents]
+    pub agent_index: u8,
+    /// The header at the top of the agent's chain, which the countersigned
+    /// entry's header will follow
+    pub chain_top: HeaderHash,
+    /// The sequence number of the chain top
+    pub header_seq: u32,
+}
+
+impl CounterSigningAgentState {
+    /// Constructor
+    pub fn new(agent_index: u8, chain_top: HeaderHash, header_seq: u32) -> Self {
+        Self {
+            agent_index,
+            chain_top,
+            header_seq,
+        }
+    }
+}
+
+/// An agent's acceptance of a [PreflightRequest], signing their state
+/// for the session.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, SerializedBytes)]
+pub struct PreflightResponse {
+    /// The request being accepted
+    pub request: PreflightRequest,
+    /// The accepting agent's state
+    pub agent_state: CounterSigningAgentState,
+    /// The accepting agent's signature of the request and their state,
+    /// as encoded by [PreflightResponse::encode_for_signature]
+    pub signature: Signature,
+}
+
+impl PreflightResponse {
+    /// The bytes an agent signs to accept a request
+    pub fn encode_for_signature(
+        request: &PreflightRequest,
+        agent_state: &CounterSigningAgentState,
+    ) -> Result<Vec<u8>, SerializedBytesError> {
+        holochain_serialized_bytes::encode(&(request, agent_state))
+    }
+}
+
+/// The outcome of asking the host to accept a [PreflightRequest]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, SerializedBytes)]
+pub enum PreflightRequestAcceptance {
+    /// The request was accepted and the chain is now locked for the session
+    Accepted(PreflightResponse),
+    /// The session starts further in the future than
+    /// [SESSION_START_FUTURE_MAX]
+    UnacceptableFutureStart,
+    /// The agent isn't one of the signing agents
+    UnacceptableAgentNotFound,
+    /// The request is malformed, or the chain is already locked for another
+    /// session
+    Invalid(String),
+}
+
+/// Everything needed to check the countersigning of an entry: the request
+/// and the signed state of every agent in the session, in agent index order.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, SerializedBytes)]
+pub struct CounterSigningSessionData {
+    /// The request every agent accepted
+    pub preflight_request: PreflightRequest,
+    /// Each agent's state and their signature of it
+    pub responses: Vec<(CounterSigningAgentState, Signature)>,
+}
+
+impl CounterSigningSessionData {
+    /// Build the session data from one response from each signing agent,
+    /// in any order.
+    pub fn try_from_responses(
+        mut responses: Vec<PreflightResponse>,
+    ) -> Result<Self, CounterSigningError> {
+        let preflight_request = match responses.first() {
+            Some(response) => response.request.clone(),
+            None => return Err(CounterSigningError::ResponseCount(0, 0)),
+        };
+        if responses
+            .iter()
+            .any(|response| response.request != preflight_request)
+        {
+            return Err(CounterSigningError::MismatchedRequest);
+        }
+        responses.sort_by_key(|response| response.agent_state.agent_index);
+        let session_data = Self {
+            preflight_request,
+            responses: responses
+                .into_iter()
+                .map(|response| (response.agent_state, response.signature))
+                .collect(),
+        };
+        session_data.check()?;
+        Ok(session_data)
+    }
+
+    /// Check the request is well formed and has exactly one response from
+    /// each signing agent, in order.
+    /// This doesn't check the signatures.
+    pub fn check(&self) -> Result<(), CounterSigningError> {
+        self.preflight_request.check()?;
+        let agent_count = self.preflight_request.signing_agents.len();
+        if self.responses.len() != agent_count {
+            return Err(CounterSigningError::ResponseCount(
+                self.responses.len(),
+                agent_count,
+            ));
+        }
+        for (i, (agent_state, _)) in self.responses.iter().enumerate() {
+            if agent_state.agent_index as usize != i {
+                return Err(CounterSigningError::MismatchedAgentIndex(
+                    i,
+                    agent_state.agent_index,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The signing agents, along with their states and signatures
+    pub fn signers(
+        &self,
+    ) -> impl Iterator<Item = (&AgentPubKey, &CounterSigningAgentState, &Signature)> {
+        self.preflight_request
+            .signing_agents
+            .iter()
+            .zip(self.responses.iter())
+            .map(|(agent, (agent_state, signature))| (agent, agent_state, signature))
+    }
+
+    /// The state of this agent in the session, if they are a signing agent
+    pub fn agent_state_for(&self, agent: &AgentPubKey) -> Option<&CounterSigningAgentState> {
+        self.signers()
+            .find(|(signing_agent, _, _)| *signing_agent == agent)
+            .map(|(_, agent_state, _)| agent_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holo_hash::hash_type;
+    use holo_hash::HoloHash;
+
+    fn agent(i: u8) -> AgentPubKey {
+        HoloHash::from_raw_36_and_type(vec![i; 36], hash_type::Agent)
+    }
+
+    fn request(agents: u8) -> PreflightRequest {
+        PreflightRequest::new(
+            HoloHash::from_raw_36_and_type(vec![0; 36], hash_type::Entry),
+            (0..agents).map(agent).collect(),
+            CounterSigningSessionTimes::new(Timestamp(0, 0), Timestamp(60, 0)),
+            PreflightBytes(vec![]),
+        )
+    }
+
+    fn response(request: &PreflightRequest, agent_index: u8) -> PreflightResponse {
+        PreflightResponse {
+            request: request.clone(),
+            agent_state: CounterSigningAgentState::new(
+                agent_index,
+                HoloHash::from_raw_36_and_type(vec![agent_index; 36], hash_type::Header),
+                3,
+            ),
+            signature: Signature([agent_index; 64]),
+        }
+    }
+
+    #[test]
+    fn session_times_are_checked() {
+        let times =
+            |start, end| CounterSigningSessionTimes::new(Timestamp(start, 0), Timestamp(end, 0));
+        assert_eq!(times(0, 300).check(), Ok(()));
+        assert!(times(0, 301).check().is_err());
+        assert!(times(10, 10).check().is_err());
+        assert!(times(10, 0).check().is_err());
+    }
+
+    #[test]
+    fn session_data_from_responses() {
+        let request = request(3);
+        let session_data = CounterSigningSessionData::try_from_responses(vec![
+            response(&request, 2),
+            response(&request, 0),
+            response(&request, 1),
+        ])
+        .unwrap();
+        assert_eq!(
+            session_data.agent_state_for(&agent(2)),
+            Some(&response(&request, 2).agent_state)
+        );
+        assert_eq!(session_data.agent_state_for(&agent(3)), None);
+
+        // Every agent has to respond
+        assert_eq!(
+            CounterSigningSessionData::try_from_responses(vec![
+                response(&request, 0),
+                response(&request, 1),
+            ]),
+            Err(CounterSigningError::ResponseCount(2, 3))
+        );
+        assert_eq!(
+            CounterSigningSessionData::try_from_responses(vec![
+                response(&request, 0),
+                response(&request, 1),
+                response(&request, 1),
+            ]),
+            Err(CounterSigningError::MismatchedAgentIndex(2, 1))
+        );
+
+        // To the same request
+        let mut other = response(&request, 2);
+        other.request.preflight_bytes = PreflightBytes(vec![1]);
+        assert_eq!(
+            CounterSigningSessionData::try_from_responses(vec![
+                response(&request, 0),
+                response(&request, 1),
+                other,
+            ]),
+            Err(CounterSigningError::MismatchedRequest)
+        );
+    }
+
+    #[test]
+    fn signing_agents_must_be_distinct() {
+        let mut request = request(2);
+        assert_eq!(request.check(), Ok(()));
+        request.signing_agents.push(agent(0));
+        assert_eq!(
+            request.check(),
+            Err(CounterSigningError::InvalidSigningAgents)
+        );
+    }
+}
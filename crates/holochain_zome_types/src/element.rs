@@ -136,9 +136,9 @@ impl ElementEntry {
     pub fn to_app_option<A: TryFrom<SerializedBytes, Error = SerializedBytesError>>(
         &self,
     ) -> Result<Option<A>, SerializedBytesError> {
-        match self.as_option() {
-            Some(Entry::App(eb)) => Ok(Some(A::try_from(SerializedBytes::from(eb.to_owned()))?)),
-            _ => Ok(None),
+        match self.as_option().and_then(Entry::as_app_entry) {
+            Some(eb) => Ok(Some(A::try_from(SerializedBytes::from(eb.to_owned()))?)),
+            None => Ok(None),
         }
     }
 
@@ -8,6 +8,7 @@
 use crate::capability::CapClaim;
 use crate::capability::CapGrant;
 use crate::capability::ZomeCallCapGrant;
+use crate::countersigning::CounterSigningSessionData;
 use holo_hash::hash_type;
 use holo_hash::AgentPubKey;
 use holo_hash::HashableContent;
@@ -106,6 +107,9 @@ pub enum Entry {
     /// The capability grant system entry which allows granting of application defined
     /// capabilities
     CapGrant(CapGrantEntry),
+    /// An application entry countersigned by every agent in a countersigning
+    /// session, who each commit it to their own source chain
+    CounterSign(Box<CounterSigningSessionData>, AppEntryBytes),
 }
 
 impl Entry {
@@ -126,6 +130,23 @@ impl Entry {
         }
     }
 
+    /// The application entry data, if this is an app entry, countersigned
+    /// or not
+    pub fn as_app_entry(&self) -> Option<&AppEntryBytes> {
+        match self {
+            Entry::App(bytes) | Entry::CounterSign(_, bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// If this entry is countersigned, return the countersigning session data
+    pub fn as_countersigning_session_data(&self) -> Option<&CounterSigningSessionData> {
+        match self {
+            Entry::CounterSign(session_data, _) => Some(session_data.as_ref()),
+            _ => None,
+        }
+    }
+
     /// Create an Entry::App from SerializedBytes
     pub fn app(sb: SerializedBytes) -> Result<Self, EntryError> {
         Ok(Entry::App(AppEntryBytes::try_from(sb)?))
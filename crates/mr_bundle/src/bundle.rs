@@ -1,6 +1,8 @@
 use crate::{
+    cache::ResourceCache,
     error::{BundleError, MrBundleResult},
     location::Location,
+    lockfile::Lockfile,
     manifest::Manifest,
     resource::ResourceBytes,
 };
@@ -33,6 +35,11 @@ where
     /// are always relative paths (relative to the root_dir).
     resources: ResourceMap,
 
+    /// The expected hashes of any resources referenced by URL.
+    /// Bundles created before lockfiles existed simply have no pins.
+    #[serde(default, skip_serializing_if = "Lockfile::is_empty")]
+    lock: Lockfile,
+
     /// Since the Manifest may contain local paths referencing unbundled files,
     /// on the local filesystem, we must have an absolute path at runtime for
     /// normalizing those locations.
@@ -45,6 +52,11 @@ where
     //       struct into two versions for each case.
     #[serde(skip)]
     root_dir: Option<PathBuf>,
+
+    /// Where to look up and store remote resources. If None, remote resources
+    /// are always fetched.
+    #[serde(skip)]
+    cache: Option<ResourceCache>,
 }
 
 impl<M> Bundle<M>
@@ -102,7 +114,9 @@ where
         Ok(Self {
             manifest,
             resources,
+            lock: Lockfile::default(),
             root_dir,
+            cache: None,
         })
     }
 
@@ -113,7 +127,47 @@ where
     /// Return a new Bundle with an updated manifest, subject to the same
     /// validation constraints as creating a new Bundle from scratch.
     pub fn update_manifest(self, manifest: M) -> MrBundleResult<Self> {
-        Self::from_parts(manifest, self.resources, self.root_dir)
+        let mut lock = self.lock;
+        lock.retain_locations(&manifest.locations());
+        Ok(Self::from_parts(manifest, self.resources, self.root_dir)?
+            .with_lock(lock)
+            .with_cache_opt(self.cache))
+    }
+
+    /// The pinned hashes of this bundle's remote resources
+    pub fn lock(&self) -> &Lockfile {
+        &self.lock
+    }
+
+    /// Replace the pinned hashes of this bundle's remote resources
+    pub fn with_lock(mut self, lock: Lockfile) -> Self {
+        self.lock = lock;
+        self
+    }
+
+    /// Use a local cache when resolving remote resources
+    pub fn with_cache(self, cache: ResourceCache) -> Self {
+        self.with_cache_opt(Some(cache))
+    }
+
+    fn with_cache_opt(mut self, cache: Option<ResourceCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Fetch every remote resource which is not yet pinned, and pin its hash.
+    /// Already pinned resources are left as they are.
+    pub async fn lock_remote_resources(&mut self) -> MrBundleResult<()> {
+        for location in self.manifest.locations() {
+            if let Location::Url(url) = location {
+                if self.lock.get(&url).is_none() {
+                    let bytes =
+                        crate::location::resolve_remote(&url, None, self.cache.as_ref()).await?;
+                    self.lock.pin(url, crate::cache::ResourceHash::of(&bytes));
+                }
+            }
+        }
+        Ok(())
     }
 
     pub async fn read_from_file(path: &Path) -> MrBundleResult<Self> {
@@ -132,7 +186,10 @@ where
                     .ok_or_else(|| BundleError::BundledResourceMissing(path.clone()))?,
             ),
             Location::Path(path) => Cow::Owned(crate::location::resolve_local(path).await?),
-            Location::Url(url) => Cow::Owned(crate::location::resolve_remote(url).await?),
+            Location::Url(url) => Cow::Owned(
+                crate::location::resolve_remote(url, self.lock.get(url), self.cache.as_ref())
+                    .await?,
+            ),
         };
         Ok(bytes)
     }
@@ -0,0 +1,172 @@
+use crate::{
+    error::{BundleError, MrBundleResult},
+    ResourceBytes,
+};
+use std::{
+    convert::TryFrom,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The hex-encoded Blake2b-256 hash of a resource's bytes.
+///
+/// Used to pin the expected content of remote resources, and as the key under
+/// which resources are stored in a [`ResourceCache`]. Since it names a file,
+/// only 64 lowercase hex digits are accepted when parsing or deserializing one.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+    derive_more::Display,
+    derive_more::Into,
+)]
+#[serde(try_from = "String", into = "String")]
+pub struct ResourceHash(String);
+
+impl ResourceHash {
+    /// Compute the hash of some resource bytes
+    pub fn of(bytes: &[u8]) -> Self {
+        Self(
+            blake2b_simd::Params::new()
+                .hash_length(32)
+                .hash(bytes)
+                .to_hex()
+                .to_string(),
+        )
+    }
+}
+
+impl TryFrom<String> for ResourceHash {
+    type Error = BundleError;
+
+    fn try_from(hash: String) -> Result<Self, Self::Error> {
+        if hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+            Ok(Self(hash))
+        } else {
+            Err(BundleError::InvalidResourceHash(hash))
+        }
+    }
+}
+
+impl FromStr for ResourceHash {
+    type Err = BundleError;
+
+    fn from_str(hash: &str) -> Result<Self, Self::Err> {
+        Self::try_from(hash.to_owned())
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ResourceHash {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::of(u.arbitrary()?))
+    }
+}
+
+/// A directory on the local filesystem which stores resources by their hash.
+///
+/// Remote resources only need to be fetched once: after that, as long as the
+/// expected hash is known (see [`crate::Lockfile`]), the resource can be
+/// resolved from the cache without any network access.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ResourceCache {
+    dir: PathBuf,
+}
+
+impl ResourceCache {
+    /// Use the given directory as a cache. It will be created on first write
+    /// if it does not yet exist.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The directory backing this cache
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path_for(&self, hash: &ResourceHash) -> PathBuf {
+        self.dir.join(&hash.0)
+    }
+
+    /// Get a resource by hash, if it is in the cache.
+    ///
+    /// If the cached file has been tampered with or corrupted, it is removed
+    /// and treated as missing.
+    pub async fn get(&self, hash: &ResourceHash) -> MrBundleResult<Option<ResourceBytes>> {
+        let path = self.path_for(hash);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let bytes = ffs::read(&path).await?;
+        if ResourceHash::of(&bytes) == *hash {
+            Ok(Some(bytes))
+        } else {
+            ffs::remove_file(&path).await?;
+            Ok(None)
+        }
+    }
+
+    /// Add a resource to the cache, returning its hash
+    pub async fn put(&self, bytes: &[u8]) -> MrBundleResult<ResourceHash> {
+        let hash = ResourceHash::of(bytes);
+        let path = self.path_for(&hash);
+        if !path.is_file() {
+            ffs::create_dir_all(&self.dir).await?;
+            // Write to a temporary file first, so that a partially written
+            // resource is never visible under its hash.
+            let tmp = path.with_extension("partial");
+            ffs::write(&tmp, bytes).await?;
+            ffs::rename(&tmp, &path).await?;
+        }
+        Ok(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cache_round_trip() {
+        let dir = tempdir::TempDir::new("resource-cache").unwrap();
+        let cache = ResourceCache::new(dir.path().join("cache"));
+        let hash = ResourceHash::of(&[1, 2, 3]);
+        assert_eq!(cache.get(&hash).await.unwrap(), None);
+        assert_eq!(cache.put(&[1, 2, 3]).await.unwrap(), hash);
+        assert_eq!(cache.get(&hash).await.unwrap(), Some(vec![1, 2, 3]));
+
+        // A corrupted entry is discarded
+        std::fs::write(dir.path().join("cache").join(hash.to_string()), [4]).unwrap();
+        assert_eq!(cache.get(&hash).await.unwrap(), None);
+    }
+
+    #[test]
+    fn only_digests_are_valid_hashes() {
+        let hash = ResourceHash::of(&[1, 2, 3]);
+        assert_eq!(hash.to_string().parse::<ResourceHash>().unwrap(), hash);
+        let yaml = serde_yaml::to_string(&hash).unwrap();
+        assert_eq!(serde_yaml::from_str::<ResourceHash>(&yaml).unwrap(), hash);
+
+        for invalid in &[
+            "../../x".to_string(),
+            "/etc/passwd".to_string(),
+            hash.to_string().to_uppercase(),
+            hash.to_string()[1..].to_string(),
+            format!("{}/", &hash.to_string()[1..]),
+        ] {
+            assert_eq!(
+                invalid.parse::<ResourceHash>(),
+                Err(BundleError::InvalidResourceHash(invalid.clone()))
+            );
+            assert!(serde_yaml::from_str::<ResourceHash>(&format!("{:?}", invalid)).is_err());
+        }
+    }
+}
@@ -45,6 +45,18 @@ pub enum BundleError {
         "Cannot use relative paths for local locations. The following local path is relative: {0}"
     )]
     RelativeLocalPath(std::path::PathBuf),
+
+    #[error(
+        "The resource fetched from '{url}' has hash {actual}, but the lockfile expects {expected}"
+    )]
+    ResourceHashMismatch {
+        url: String,
+        expected: crate::ResourceHash,
+        actual: crate::ResourceHash,
+    },
+
+    #[error("'{0}' is not a resource hash: expected 64 lowercase hex digits")]
+    InvalidResourceHash(String),
 }
 pub type BundleResult<T> = Result<T, BundleError>;
 
@@ -1,7 +1,9 @@
 mod bundle;
+mod cache;
 mod encoding;
 pub mod error;
 mod location;
+mod lockfile;
 mod manifest;
 mod resource;
 pub(crate) mod util;
@@ -10,7 +12,9 @@ pub(crate) mod util;
 mod packing;
 
 pub use bundle::Bundle;
+pub use cache::{ResourceCache, ResourceHash};
 pub use encoding::{decode, encode};
-pub use location::Location;
+pub use location::{resolve_remote, Location};
+pub use lockfile::Lockfile;
 pub use manifest::Manifest;
 pub use resource::ResourceBytes;
@@ -1,6 +1,6 @@
 use crate::{
     error::{BundleError, MrBundleResult},
-    ResourceBytes,
+    ResourceBytes, ResourceCache, ResourceHash,
 };
 use std::path::{Path, PathBuf};
 
@@ -46,13 +46,43 @@ pub(crate) async fn resolve_local(path: &Path) -> MrBundleResult<ResourceBytes>
     Ok(ffs::read(path).await?)
 }
 
-pub(crate) async fn resolve_remote(url: &str) -> MrBundleResult<ResourceBytes> {
-    Ok(reqwest::get(url)
+/// Fetch a resource from a URL.
+///
+/// If an `expected` hash is given, the fetched bytes must match it, and if a
+/// `cache` is given, a resource with that hash will be taken from the cache
+/// rather than fetched. Anything fetched is added to the cache.
+pub async fn resolve_remote(
+    url: &str,
+    expected: Option<&ResourceHash>,
+    cache: Option<&ResourceCache>,
+) -> MrBundleResult<ResourceBytes> {
+    if let (Some(hash), Some(cache)) = (expected, cache) {
+        if let Some(bytes) = cache.get(hash).await? {
+            return Ok(bytes);
+        }
+    }
+    let bytes: ResourceBytes = reqwest::get(url)
         .await?
+        .error_for_status()?
         .bytes()
         .await?
         .into_iter()
-        .collect())
+        .collect();
+    let actual = ResourceHash::of(&bytes);
+    if let Some(expected) = expected {
+        if *expected != actual {
+            return Err(BundleError::ResourceHashMismatch {
+                url: url.to_owned(),
+                expected: expected.clone(),
+                actual,
+            }
+            .into());
+        }
+    }
+    if let Some(cache) = cache {
+        cache.put(&bytes).await?;
+    }
+    Ok(bytes)
 }
 
 #[cfg(test)]
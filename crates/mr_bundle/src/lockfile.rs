@@ -0,0 +1,42 @@
+use crate::{cache::ResourceHash, Location};
+use std::collections::BTreeMap;
+
+/// Pins the expected hash of each remote resource referenced by a manifest.
+///
+/// A Lockfile is stored inside a Bundle, and alongside the manifest in an
+/// unpacked bundle directory. When a `Location::Url` is resolved, the fetched
+/// bytes must match the pinned hash, and a pinned resource can be resolved
+/// from a [`crate::ResourceCache`] without touching the network.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Lockfile {
+    /// The expected hash of each remote resource, keyed by URL
+    #[serde(default)]
+    pub resources: BTreeMap<String, ResourceHash>,
+}
+
+impl Lockfile {
+    /// Whether any resources are pinned
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// Get the pinned hash of a URL, if any
+    pub fn get(&self, url: &str) -> Option<&ResourceHash> {
+        self.resources.get(url)
+    }
+
+    /// Pin the hash of a URL
+    pub fn pin(&mut self, url: String, hash: ResourceHash) {
+        self.resources.insert(url, hash);
+    }
+
+    /// Drop pins for any URLs not among the given locations
+    pub fn retain_locations(&mut self, locations: &[Location]) {
+        self.resources.retain(|url, _| {
+            locations
+                .iter()
+                .any(|loc| matches!(loc, Location::Url(u) if u == url))
+        });
+    }
+}
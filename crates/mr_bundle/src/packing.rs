@@ -2,11 +2,17 @@ use super::Bundle;
 use crate::{
     error::{MrBundleResult, PackingError, UnpackingError, UnpackingResult},
     util::prune_path,
-    Manifest,
+    Lockfile, Manifest,
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 impl<M: Manifest> Bundle<M> {
+    /// The path of the lockfile relative to the unpacked bundle directory,
+    /// which sits next to the manifest file.
+    pub fn lockfile_path() -> PathBuf {
+        M::path().with_extension("lock")
+    }
+
     /// Create a directory which contains the manifest as a YAML file,
     /// and each resource written to its own file (as raw bytes)
     /// The paths of the resources are specified by the paths of the bundle,
@@ -36,12 +42,19 @@ impl<M: Manifest> Bundle<M> {
         let yaml_str = serde_yaml::to_string(self.manifest())?;
         let manifest_path = base_path.join(M::path());
         ffs::write(&manifest_path, yaml_str.as_bytes()).await?;
+        if !self.lock().is_empty() {
+            let lock_str = serde_yaml::to_string(self.lock())?;
+            ffs::write(&base_path.join(Self::lockfile_path()), lock_str.as_bytes()).await?;
+        }
         Ok(())
     }
 
     /// Reconstruct a `Bundle<M>` from a previously unpacked directory.
     /// The manifest file itself must be specified, since it may have an arbitrary
     /// path relative to the unpacked directory root.
+    ///
+    /// If a lockfile is present next to the manifest, its pins are included
+    /// in the bundle.
     pub async fn pack_yaml(manifest_path: &Path) -> MrBundleResult<Self> {
         let manifest_path = ffs::canonicalize(manifest_path).await?;
         let manifest_yaml = ffs::read_to_string(&manifest_path).await.map_err(|err| {
@@ -61,7 +74,14 @@ impl<M: Manifest> Bundle<M> {
         .await
         .into_iter()
         .collect::<Result<Vec<_>, _>>()?;
-        Ok(Bundle::new(manifest, resources, base_path)?)
+        let lock_path = base_path.join(Self::lockfile_path());
+        let lock: Lockfile = if lock_path.is_file() {
+            serde_yaml::from_str(&ffs::read_to_string(&lock_path).await?)
+                .map_err(UnpackingError::from)?
+        } else {
+            Lockfile::default()
+        };
+        Ok(Bundle::new(manifest, resources, base_path)?.with_lock(lock))
     }
 }
 
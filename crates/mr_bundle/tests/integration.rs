@@ -1,4 +1,7 @@
-use mr_bundle::{Bundle, Location, Manifest};
+use mr_bundle::{
+    error::{BundleError, MrBundleError},
+    Bundle, Location, Lockfile, Manifest, ResourceCache, ResourceHash,
+};
 use std::{collections::HashSet, path::PathBuf};
 
 #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -180,3 +183,91 @@ async fn unpack_roundtrip() {
 
     assert_eq!(bundle, reconstructed);
 }
+
+/// Serve a single resource over HTTP on a local port, returning its URL.
+/// The server stops when the returned handle is aborted.
+async fn serve_resource(bytes: Vec<u8>) -> (String, tokio::task::JoinHandle<()>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/thing", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).await;
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                bytes.len()
+            );
+            let _ = stream.write_all(header.as_bytes()).await;
+            let _ = stream.write_all(&bytes).await;
+            let _ = stream.shutdown().await;
+        }
+    });
+    (url, handle)
+}
+
+#[tokio::test]
+async fn remote_resolution_with_lock_and_cache() {
+    let dir = tempdir::TempDir::new("mr_bundle").unwrap();
+    let remote_thing_encoded = mr_bundle::encode(&Thing("remote".into())).unwrap();
+    let (url, server) = serve_resource(remote_thing_encoded.clone()).await;
+    let remote_location = Location::Url(url.clone());
+    let manifest = TestManifest::V1(ManifestV1 {
+        name: "name".to_string(),
+        things: vec![ThingManifest {
+            location: remote_location.clone(),
+        }],
+    });
+    let cache = ResourceCache::new(dir.path().join("cache"));
+
+    // Pin the remote resource, which also populates the cache
+    let mut bundle = Bundle::new_unchecked(manifest, vec![])
+        .unwrap()
+        .with_cache(cache.clone());
+    bundle.lock_remote_resources().await.unwrap();
+    let hash = ResourceHash::of(&remote_thing_encoded);
+    assert_eq!(bundle.lock().get(&url), Some(&hash));
+    assert_eq!(
+        bundle.resolve(&remote_location).await.unwrap().into_owned(),
+        remote_thing_encoded
+    );
+
+    // The lock survives encoding
+    let decoded: Bundle<TestManifest> = Bundle::decode(&bundle.encode().unwrap()).unwrap();
+    assert_eq!(decoded.lock(), bundle.lock());
+
+    // With the server gone, the pinned resource still resolves from the cache
+    server.abort();
+    let _ = server.await;
+    let offline = decoded.with_cache(cache);
+    assert_eq!(
+        offline
+            .resolve(&remote_location)
+            .await
+            .unwrap()
+            .into_owned(),
+        remote_thing_encoded
+    );
+
+    // A resource which doesn't match its pin is rejected
+    let (url, _server) = serve_resource(vec![1, 2, 3]).await;
+    let mut lock = Lockfile::default();
+    lock.pin(url.clone(), hash.clone());
+    let location = Location::Url(url.clone());
+    let bundle = Bundle::new_unchecked(
+        TestManifest::V1(ManifestV1 {
+            name: "name".to_string(),
+            things: vec![ThingManifest {
+                location: location.clone(),
+            }],
+        }),
+        vec![],
+    )
+    .unwrap()
+    .with_lock(lock);
+    matches::assert_matches!(
+        bundle.resolve(&location).await,
+        Err(MrBundleError::BundleError(BundleError::ResourceHashMismatch { expected, actual, .. }))
+        if expected == hash && actual == ResourceHash::of(&[1, 2, 3])
+    );
+}
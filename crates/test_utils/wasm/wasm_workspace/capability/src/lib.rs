@@ -14,6 +14,9 @@ fn init(_: ()) -> ExternResult<InitCallbackResult> {
         // empty access converts to unrestricted
         access: ().into(),
         functions,
+        expires_at: None,
+        max_uses: None,
+        curry_payloads: CurryPayloads::default(),
     })?;
 
     Ok(InitCallbackResult::Pass)
@@ -32,6 +35,9 @@ fn cap_grant_entry(secret: CapSecret) -> ExternResult<CapGrantEntry> {
         tag: "".into(),
         access: secret.into(),
         functions,
+        expires_at: None,
+        max_uses: None,
+        curry_payloads: CurryPayloads::default(),
     })
 }
 
@@ -94,6 +100,9 @@ fn send_assigned_cap_claim(agent: AgentPubKey) -> ExternResult<()> {
         access: (secret, agent.clone()).into(),
         functions,
         tag: tag.clone(),
+        expires_at: None,
+        max_uses: None,
+        curry_payloads: CurryPayloads::default(),
     })?;
 
     // send the assigned cap token